@@ -0,0 +1,253 @@
+/*!
+Command-line arguments for a known setup: sources to connect and a store to load before the GUI
+opens, or a headless run behind the HTTP control endpoint instead of the GUI.
+
+Without arguments the GUI starts empty, as before.
+*/
+
+use std::{
+    net::{SocketAddr, ToSocketAddrs},
+    path::PathBuf,
+    str::FromStr,
+};
+
+use clap::Parser;
+
+use crate::{
+    data_aquisition::{
+        snmp::DEFAULT_MAX_REPETITIONS,
+        ssh::{self, HostKeyPolicy},
+    },
+    gui::{
+        autopoll::{ProtocolKind, SourceSpec},
+        credentials::Credentials,
+    },
+    topology::store::TopologyStore,
+};
+
+pub const DEFAULT_SERVE_ADDR: &str = "127.0.0.1:8080";
+
+#[derive(Debug, Parser)]
+#[command(version, about = "OSPF and IS-IS topology visualization")]
+pub struct Cli {
+    /// OSPF source polled over SNMPv2c, as `host:port:community`, with an IPv6 host in brackets.
+    /// Repeatable.
+    #[arg(long, value_name = "HOST:PORT:COMMUNITY")]
+    pub snmp: Vec<SnmpTarget>,
+
+    /// OID map (TOML) for the `--snmp` sources, for agents that don't serve the standard
+    /// OSPF-MIB. Only the OIDs that differ need to be listed.
+    #[arg(long, value_name = "PATH")]
+    pub snmp_oids: Option<PathBuf>,
+
+    /// IS-IS source polled over SSH, as `user@host[:port]`, with an IPv6 host in brackets.
+    /// Repeatable. The password comes from the secrets file or `OSPFVIZ_SSH_PASSWORD`.
+    #[arg(long, value_name = "USER@HOST[:PORT]")]
+    pub ssh: Vec<SshTarget>,
+
+    /// Add `--ssh` hosts missing from ~/.ssh/known_hosts on first connect instead of refusing
+    /// them. A changed host key is still refused.
+    #[arg(long)]
+    pub ssh_trust_on_first_use: bool,
+
+    /// Topology store JSON to load before connecting the sources.
+    #[arg(long, value_name = "PATH")]
+    pub load_store: Option<PathBuf>,
+
+    /// Skip the GUI and serve the HTTP control endpoint instead (needs the `serve` feature).
+    #[arg(long)]
+    pub headless: bool,
+
+    /// Address of the HTTP control endpoint. Implies `--headless`.
+    #[arg(long, value_name = "ADDR", num_args = 0..=1, default_missing_value = DEFAULT_SERVE_ADDR)]
+    pub serve: Option<SocketAddr>,
+}
+
+/// What to set up before the GUI or the control endpoint starts.
+#[derive(Default)]
+pub struct Startup {
+    pub store: Option<TopologyStore>,
+    pub sources: Vec<SourceSpec>,
+}
+
+impl Cli {
+    /// Address to serve on, `None` to open the GUI.
+    pub fn serve_addr(&self) -> Option<SocketAddr> {
+        match self.serve {
+            Some(addr) => Some(addr),
+            None if self.headless => Some(DEFAULT_SERVE_ADDR.parse().unwrap()),
+            None => None,
+        }
+    }
+
+    /// Load the store and build the source specs. `Err` if the store can't be read.
+    pub fn startup(&self) -> Result<Startup, String> {
+        let store = match &self.load_store {
+            Some(path) => {
+                let json = std::fs::read_to_string(path)
+                    .map_err(|e| format!("can't read {}: {}", path.display(), e))?;
+                let store = TopologyStore::from_json(&json)
+                    .map_err(|e| format!("can't load {}: {}", path.display(), e))?;
+                Some(store)
+            }
+            None => None,
+        };
+
+        let mut sources: Vec<SourceSpec> = self
+            .snmp
+            .iter()
+            .map(|target| {
+                SourceSpec::new_snmp(
+                    target.address,
+                    target.community.clone(),
+                    snmp2::Version::V2C,
+                    None,
+                    DEFAULT_MAX_REPETITIONS,
+                    ProtocolKind::Ospf,
+                )
+                .with_oid_file(self.snmp_oids.clone())
+            })
+            .collect();
+        if !self.ssh.is_empty() {
+            let password = Credentials::load().ssh_password;
+            let host_key_policy = if self.ssh_trust_on_first_use {
+                HostKeyPolicy::TrustOnFirstUse
+            } else {
+                HostKeyPolicy::Verify
+            };
+            sources.extend(self.ssh.iter().map(|target| {
+                SourceSpec::new_ssh(
+                    target.host.clone(),
+                    target.port,
+                    target.username.clone(),
+                    password.clone(),
+                    ssh::DEFAULT_CONNECT_TIMEOUT,
+                    ProtocolKind::Isis,
+                )
+                .with_host_key_policy(host_key_policy.clone())
+            }));
+        }
+        Ok(Startup { store, sources })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SnmpTarget {
+    pub address: SocketAddr,
+    pub community: String,
+}
+
+impl FromStr for SnmpTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (host, rest) = split_host(s)?;
+        let rest = rest.ok_or("expected host:port:community")?;
+        let (port, community) = rest.split_once(':').ok_or("expected host:port:community")?;
+        let port: u16 = port.parse().map_err(|_| format!("invalid port '{}'", port))?;
+        if community.is_empty() {
+            return Err("community is empty".to_string());
+        }
+        let address = (host, port)
+            .to_socket_addrs()
+            .map_err(|e| format!("can't resolve '{}': {}", host, e))?
+            .next()
+            .ok_or_else(|| format!("no address for '{}'", host))?;
+        Ok(Self {
+            address,
+            community: community.to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SshTarget {
+    pub username: String,
+    pub host: String,
+    pub port: u16,
+}
+
+impl FromStr for SshTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (username, host) = s.split_once('@').ok_or("expected user@host[:port]")?;
+        let (host, port) = match split_host(host)? {
+            (host, Some(port)) => (host, port.parse().map_err(|_| format!("invalid port '{}'", port))?),
+            (host, None) => (host, 22),
+        };
+        if username.is_empty() || host.is_empty() {
+            return Err("expected user@host[:port]".to_string());
+        }
+        Ok(Self {
+            username: username.to_string(),
+            host: host.to_string(),
+            port,
+        })
+    }
+}
+
+/// Splits a leading host off `s` at the first `:`, returning it and whatever follows. An IPv6
+/// address goes in brackets, as `[addr]:rest`.
+fn split_host(s: &str) -> Result<(&str, Option<&str>), String> {
+    let Some(bracketed) = s.strip_prefix('[') else {
+        return Ok(match s.split_once(':') {
+            Some((host, rest)) => (host, Some(rest)),
+            None => (s, None),
+        });
+    };
+    let (host, after) = bracketed
+        .split_once(']')
+        .ok_or_else(|| format!("unclosed '[' in '{}'", s))?;
+    match after {
+        "" => Ok((host, None)),
+        _ => match after.strip_prefix(':') {
+            Some(rest) => Ok((host, Some(rest))),
+            None => Err(format!("expected ':' after ']' in '{}'", s)),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cli_parsing() {
+        let cli = Cli::try_parse_from([
+            "ospf-visualization",
+            "--snmp",
+            "127.0.0.1:1161:public",
+            "--ssh",
+            "admin@10.0.0.2",
+            "--ssh",
+            "admin@10.0.0.3:2221",
+            "--snmp",
+            "[::1]:1161:public",
+            "--ssh",
+            "admin@[fd00::2]:2221",
+            "--ssh",
+            "admin@[fd00::3]",
+        ])
+        .unwrap();
+        assert_eq!(cli.snmp[0].address, "127.0.0.1:1161".parse().unwrap());
+        assert_eq!(cli.snmp[0].community, "public");
+        assert_eq!((cli.ssh[0].host.as_str(), cli.ssh[0].port), ("10.0.0.2", 22));
+        assert_eq!((cli.ssh[1].host.as_str(), cli.ssh[1].port), ("10.0.0.3", 2221));
+        assert_eq!(cli.snmp[1].address, "[::1]:1161".parse().unwrap());
+        assert_eq!(cli.snmp[1].community, "public");
+        assert_eq!((cli.ssh[2].host.as_str(), cli.ssh[2].port), ("fd00::2", 2221));
+        assert_eq!((cli.ssh[3].host.as_str(), cli.ssh[3].port), ("fd00::3", 22));
+        assert_eq!(cli.serve_addr(), None);
+
+        let cli = Cli::try_parse_from(["ospf-visualization", "--headless"]).unwrap();
+        assert_eq!(cli.serve_addr(), Some(DEFAULT_SERVE_ADDR.parse().unwrap()));
+        let cli = Cli::try_parse_from(["ospf-visualization", "--serve", "0.0.0.0:9000"]).unwrap();
+        assert_eq!(cli.serve_addr(), Some("0.0.0.0:9000".parse().unwrap()));
+
+        assert!(Cli::try_parse_from(["ospf-visualization", "--snmp", "127.0.0.1:public"]).is_err());
+        assert!(Cli::try_parse_from(["ospf-visualization", "--ssh", "10.0.0.2"]).is_err());
+        assert!(Cli::try_parse_from(["ospf-visualization", "--ssh", "admin@[fd00::2"]).is_err());
+        assert!(Cli::try_parse_from(["ospf-visualization", "--ssh", "admin@[fd00::2]22"]).is_err());
+    }
+}
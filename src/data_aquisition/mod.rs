@@ -5,5 +5,6 @@
  */
 
 pub mod core;
+pub mod netconf;
 pub mod snmp;
 pub mod ssh;
\ No newline at end of file
@@ -0,0 +1,312 @@
+/*!
+Minimal NETCONF client (RFC 6241) over the `netconf` SSH subsystem.
+
+Only what reading operational state needs: the hello exchange and a subtree-filtered `<get>`.
+Messages use the base:1.0 end-of-message framing (RFC 6242), which every server still speaks
+when the client doesn't advertise base:1.1. Replies are parsed into a small `XmlElement` tree
+keyed by local names, so callers don't deal with namespaces.
+*/
+
+use std::io::{Read, Write};
+
+use quick_xml::events::Event;
+use thiserror::Error;
+
+use crate::data_aquisition::ssh::{SshClient, SshError};
+
+/// IANA port for NETCONF over SSH (RFC 6242).
+pub const DEFAULT_PORT: u16 = 830;
+
+const END_OF_MESSAGE: &str = "]]>]]>";
+
+const HELLO: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<hello xmlns="urn:ietf:params:xml:ns:netconf:base:1.0">
+  <capabilities>
+    <capability>urn:ietf:params:netconf:base:1.0</capability>
+  </capabilities>
+</hello>"#;
+
+const CLOSE_SESSION: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rpc message-id="close" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0">
+  <close-session/>
+</rpc>"#;
+
+#[derive(Debug, Error)]
+pub enum NetconfError {
+    #[error(transparent)]
+    Ssh(#[from] SshError),
+    #[error("NETCONF transport error: {0}")]
+    Transport(String),
+    #[error("Malformed XML: {0}")]
+    Xml(String),
+    #[error("Server returned rpc-error: {0}")]
+    Rpc(String),
+}
+
+pub struct NetconfClient {
+    ssh: SshClient,
+}
+
+impl NetconfClient {
+    pub fn new(ssh: SshClient) -> Self {
+        Self { ssh }
+    }
+
+    pub fn ssh(&self) -> &SshClient {
+        &self.ssh
+    }
+
+    pub fn ssh_mut(&mut self) -> &mut SshClient {
+        &mut self.ssh
+    }
+
+    /// `<get>` with the given subtree filter. Returns the `<data>` element of the reply.
+    pub async fn get(&self, filter: &str) -> Result<XmlElement, NetconfError> {
+        let rpc = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<rpc message-id="1" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0">
+  <get>
+    <filter type="subtree">{}</filter>
+  </get>
+</rpc>"#,
+            filter
+        );
+        let reply = self
+            .ssh
+            .with_session(move |session| Self::exchange_sync(session, &rpc))
+            .await?;
+        let reply = XmlElement::parse(&reply)?;
+        if let Some(error) = reply.child("rpc-error") {
+            let message = error
+                .child_text("error-message")
+                .or_else(|| error.child_text("error-tag"))
+                .unwrap_or("unknown error");
+            return Err(NetconfError::Rpc(message.to_string()));
+        }
+        reply
+            .child("data")
+            .cloned()
+            .ok_or_else(|| NetconfError::Xml("rpc-reply without <data>".to_string()))
+    }
+
+    /// Open a session on a fresh channel, send `rpc` and return the raw reply.
+    fn exchange_sync(session: &mut ssh2::Session, rpc: &str) -> Result<String, NetconfError> {
+        let transport = |e: std::io::Error| NetconfError::Transport(e.to_string());
+        let mut channel = session
+            .channel_session()
+            .map_err(|e| SshError::SshError(e.to_string()))?;
+        channel
+            .subsystem("netconf")
+            .map_err(|e| NetconfError::Transport(format!("netconf subsystem unavailable: {}", e)))?;
+
+        let mut channel = MessageReader::new(channel);
+        write_message(channel.get_mut(), HELLO).map_err(transport)?;
+        let _server_hello = channel.read_message().map_err(transport)?;
+        write_message(channel.get_mut(), rpc).map_err(transport)?;
+        let reply = channel.read_message().map_err(transport)?;
+
+        // Best effort, the reply is already in hand
+        let _ = write_message(channel.get_mut(), CLOSE_SESSION).and_then(|_| channel.read_message());
+        let _ = channel.get_mut().close();
+        Ok(reply)
+    }
+}
+
+fn write_message(writer: &mut impl Write, message: &str) -> std::io::Result<()> {
+    writer.write_all(message.as_bytes())?;
+    writer.write_all(END_OF_MESSAGE.as_bytes())?;
+    writer.flush()
+}
+
+/// Reads messages framed by the end-of-message marker. Whatever arrives after a marker is kept
+/// for the next message.
+struct MessageReader<R> {
+    reader: R,
+    pending: Vec<u8>,
+}
+
+impl<R: Read> MessageReader<R> {
+    fn new(reader: R) -> Self {
+        Self { reader, pending: Vec::new() }
+    }
+
+    fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Read up to the next end-of-message marker, which is stripped.
+    fn read_message(&mut self) -> std::io::Result<String> {
+        let mut buf = [0u8; 8192];
+        // Bytes before this were already searched, so a multi-MB reply is scanned once
+        let mut searched = 0;
+        loop {
+            if let Some(end) = find_end_of_message(&self.pending, searched) {
+                let rest = self.pending.split_off(end + END_OF_MESSAGE.len());
+                let mut message = std::mem::replace(&mut self.pending, rest);
+                message.truncate(end);
+                return String::from_utf8(message)
+                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e));
+            }
+            // A marker may be split across reads, so its start is searched again
+            searched = self.pending.len().saturating_sub(END_OF_MESSAGE.len() - 1);
+            let n = self.reader.read(&mut buf)?;
+            if n == 0 {
+                return Err(std::io::ErrorKind::UnexpectedEof.into());
+            }
+            self.pending.extend_from_slice(&buf[..n]);
+        }
+    }
+}
+
+/// Start of the first end-of-message marker at or after `from`.
+fn find_end_of_message(bytes: &[u8], from: usize) -> Option<usize> {
+    bytes[from..]
+        .windows(END_OF_MESSAGE.len())
+        .position(|window| window == END_OF_MESSAGE.as_bytes())
+        .map(|position| from + position)
+}
+
+/// Element of a parsed XML document, by local name. Attributes are dropped.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct XmlElement {
+    pub name: String,
+    pub text: String,
+    pub children: Vec<XmlElement>,
+}
+
+impl XmlElement {
+    /// Parse a document into its root element.
+    pub fn parse(xml: &str) -> Result<Self, NetconfError> {
+        let mut reader = quick_xml::Reader::from_str(xml);
+        reader.config_mut().trim_text(true);
+        let xml_error = |e: quick_xml::Error| NetconfError::Xml(e.to_string());
+        let local_name = |name: &[u8]| String::from_utf8_lossy(name).into_owned();
+
+        // Open elements, innermost last
+        let mut stack: Vec<XmlElement> = Vec::new();
+        loop {
+            match reader.read_event().map_err(xml_error)? {
+                Event::Start(start) => stack.push(XmlElement {
+                    name: local_name(start.local_name().as_ref()),
+                    ..Default::default()
+                }),
+                Event::Empty(empty) => {
+                    let element = XmlElement {
+                        name: local_name(empty.local_name().as_ref()),
+                        ..Default::default()
+                    };
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(element),
+                        None => return Ok(element),
+                    }
+                }
+                Event::Text(text) => {
+                    if let Some(current) = stack.last_mut() {
+                        current.text.push_str(&text.unescape().map_err(xml_error)?);
+                    }
+                }
+                Event::CData(data) => {
+                    if let Some(current) = stack.last_mut() {
+                        current.text.push_str(&String::from_utf8_lossy(&data));
+                    }
+                }
+                Event::End(_) => {
+                    let element = stack
+                        .pop()
+                        .ok_or_else(|| NetconfError::Xml("unbalanced end tag".to_string()))?;
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(element),
+                        None => return Ok(element),
+                    }
+                }
+                Event::Eof => return Err(NetconfError::Xml("document ended early".to_string())),
+                _ => {}
+            }
+        }
+    }
+
+    pub fn child(&self, name: &str) -> Option<&XmlElement> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    pub fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a XmlElement> {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+
+    /// Trimmed text of the first child called `name`.
+    pub fn child_text(&self, name: &str) -> Option<&str> {
+        self.child(name).map(|child| child.text.trim())
+    }
+
+    /// Follow `path` one child name at a time.
+    pub fn find(&self, path: &[&str]) -> Option<&XmlElement> {
+        path.iter().try_fold(self, |element, name| element.child(name))
+    }
+
+    /// First element called `name` at any depth, this one included.
+    pub fn find_descendant(&self, name: &str) -> Option<&XmlElement> {
+        if self.name == name {
+            return Some(self);
+        }
+        self.children.iter().find_map(|child| child.find_descendant(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xml_element_parse() {
+        let reply = r#"<?xml version="1.0"?>
+<rpc-reply message-id="1" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0">
+  <data>
+    <interfaces xmlns="urn:ietf:params:xml:ns:yang:ietf-interfaces">
+      <interface><name>eth0</name><enabled/></interface>
+      <interface><name>a &amp; b</name></interface>
+    </interfaces>
+  </data>
+</rpc-reply>"#;
+        let root = XmlElement::parse(reply).unwrap();
+        assert_eq!(root.name, "rpc-reply");
+        let interfaces = root.find(&["data", "interfaces"]).unwrap();
+        let names: Vec<&str> = interfaces
+            .children_named("interface")
+            .filter_map(|i| i.child_text("name"))
+            .collect();
+        assert_eq!(names, ["eth0", "a & b"]);
+        assert!(interfaces.children[0].child("enabled").is_some());
+        assert_eq!(root.find_descendant("name").unwrap().text, "eth0");
+
+        assert!(XmlElement::parse("<a><b></a>").is_err());
+    }
+
+    #[test]
+    fn test_read_message_strips_marker() {
+        let mut reader = MessageReader::new("<hello/>]]>]]><rpc-reply/>]]>]]>".as_bytes());
+        assert_eq!(reader.read_message().unwrap(), "<hello/>");
+        assert_eq!(reader.read_message().unwrap(), "<rpc-reply/>");
+        assert_eq!(reader.read_message().unwrap_err().kind(), std::io::ErrorKind::UnexpectedEof);
+        assert!(find_end_of_message(b"<a/>]]>", 0).is_none());
+        assert_eq!(find_end_of_message(b"]]>]]><a/>]]>]]>", 1), Some(10));
+    }
+
+    /// Hands out at most 4 bytes per read, so markers end up split across reads.
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = self.0.len().min(buf.len()).min(4);
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn test_read_message_split_marker() {
+        let mut reader = MessageReader::new(Trickle(b"<hello/>]]>]]><rpc-reply>]]</rpc-reply>]]>]]>"));
+        assert_eq!(reader.read_message().unwrap(), "<hello/>");
+        assert_eq!(reader.read_message().unwrap(), "<rpc-reply>]]</rpc-reply>");
+    }
+}
@@ -18,6 +18,7 @@ pub enum MessageType {
     GetBulkRequest,
     SetRequest,
     WalkRequest,
+    BulkWalkRequest,
 }
 
 impl Display for MessageType {
@@ -28,10 +29,14 @@ impl Display for MessageType {
             MessageType::GetBulkRequest => write!(f, "GetBulkRequest"),
             MessageType::SetRequest => write!(f, "SetRequest"),
             MessageType::WalkRequest => write!(f, "WalkRequest"),
+            MessageType::BulkWalkRequest => write!(f, "BulkWalkRequest"),
         }
     }
 }
 
+/// Rows requested per GETBULK when walking tables, unless overridden with `with_max_repetitions`.
+pub const DEFAULT_MAX_REPETITIONS: u32 = 10;
+
 /// SNMP client for retrieving data from a network device.
 pub struct SnmpClient {
     address: SocketAddr,
@@ -39,6 +44,7 @@ pub struct SnmpClient {
     snmp_version: Version,
     session: Option<Arc<Mutex<AsyncSession>>>,
     security: Option<Security>,
+    max_repetitions: u32,
 }
 
 impl Default for SnmpClient {
@@ -66,10 +72,22 @@ impl SnmpClient {
             snmp_version,
             session: None,
             security,
+            max_repetitions: DEFAULT_MAX_REPETITIONS,
         }
     }
 
-    /// Retrieves an SNMP session for the client.
+    /// Max-repetitions used by bulk walks. Lower it for agents that choke on large GETBULK responses.
+    pub fn with_max_repetitions(mut self, max_repetitions: u32) -> Self {
+        self.max_repetitions = max_repetitions.max(1);
+        self
+    }
+
+    pub fn max_repetitions(&self) -> u32 {
+        self.max_repetitions
+    }
+
+    /// Retrieves an SNMP session for the client. The session and its UDP socket are opened by the
+    /// first call and shared by every later query; drop the client to start over.
     pub async fn get_session(&mut self) -> Result<Arc<Mutex<AsyncSession>>, SnmpClientError> {
         if self.session.is_none() {
             let session = match self.snmp_version {
@@ -107,7 +125,7 @@ impl SnmpClient {
             oids: Vec::new(),
             operation: None,
             timeout: None,
-            max_repetitions: None,
+            max_repetitions: Some(self.max_repetitions),
             non_repeaters: None,
         })
     }
@@ -138,6 +156,12 @@ impl<'a> QueryBuilder<'a> {
         self
     }
 
+    /// Walk every requested column subtree with repeated GETBULKs, using the client's max-repetitions.
+    pub fn bulk_walk(mut self) -> Self {
+        self.operation = Some(MessageType::BulkWalkRequest);
+        self
+    }
+
     pub fn get_bulk(mut self, non_repeaters: u32, max_repetitions: u32) -> Self {
         self.operation = Some(MessageType::GetBulkRequest);
         self.non_repeaters = Some(non_repeaters);
@@ -293,6 +317,22 @@ impl<'a> QueryBuilder<'a> {
 
                 results
             }
+            MessageType::BulkWalkRequest => {
+                let mut walk = BulkWalk::new(self.oids.clone());
+                let mut results: Vec<RawRouterData> = Vec::new();
+                let max_repetitions = max_repetitions.max(1);
+
+                while let Some((active, request)) = walk.next_request() {
+                    let request_refs: Vec<&Oid> = request.iter().collect();
+                    let resp = session
+                        .getbulk(&request_refs, 0, max_repetitions)
+                        .await
+                        .map_err(SnmpClientError::Snmp2Error)?;
+                    results.extend(walk.consume(&active, resp.varbinds));
+                }
+
+                results
+            }
             _ => return Err(SnmpClientError::UnsupportedSnmpOperation),
         };
 
@@ -300,6 +340,70 @@ impl<'a> QueryBuilder<'a> {
     }
 }
 
+/// Per-column cursors of a bulk walk. Every requested column advances on its own, so a short
+/// column ending early doesn't cut off the others.
+struct BulkWalk<'a> {
+    roots: Vec<Oid<'a>>,
+    // Next OID to request per column, None once the column has left its subtree
+    cursors: Vec<Option<Oid<'a>>>,
+}
+
+impl<'a> BulkWalk<'a> {
+    fn new(roots: Vec<Oid<'a>>) -> Self {
+        let cursors = roots.iter().cloned().map(Some).collect();
+        Self { roots, cursors }
+    }
+
+    /// Indices of the columns still being walked and the OIDs to request for them, or None once
+    /// every column is done.
+    fn next_request(&self) -> Option<(Vec<usize>, Vec<Oid<'a>>)> {
+        let (active, request): (Vec<usize>, Vec<Oid<'a>>) = self
+            .cursors
+            .iter()
+            .enumerate()
+            .filter_map(|(i, cursor)| Some((i, cursor.clone()?)))
+            .unzip();
+        (!active.is_empty()).then_some((active, request))
+    }
+
+    /// Keep the varbinds of one GETBULK response for `active` that are still inside their column
+    /// and move the cursors past them.
+    fn consume<'v>(
+        &mut self,
+        active: &[usize],
+        varbinds: impl IntoIterator<Item = (Oid<'v>, snmp2::Value<'v>)>,
+    ) -> Vec<RawRouterData<'a>> {
+        let mut results = Vec::new();
+        // Varbinds are interleaved: one per requested column, per repetition
+        let mut last: Vec<Option<Oid<'static>>> = vec![None; active.len()];
+        let mut done = vec![false; active.len()];
+        for (pos, (oid, value)) in varbinds.into_iter().enumerate() {
+            let k = pos % active.len();
+            if done[k] {
+                continue;
+            }
+            if !oid.starts_with(&self.roots[active[k]]) || matches!(value, snmp2::Value::EndOfMibView) {
+                done[k] = true;
+                continue;
+            }
+            results.push(RawRouterData::Snmp {
+                oid: oid.to_owned(),
+                value: LinkStateValue::from(&value),
+            });
+            last[k] = Some(oid.to_owned());
+        }
+
+        for (k, &col) in active.iter().enumerate() {
+            self.cursors[col] = match last[k].take() {
+                // Guard against agents that don't advance
+                Some(oid) if !done[k] && self.cursors[col].as_ref() != Some(&oid) => Some(oid),
+                _ => None,
+            };
+        }
+        results
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum SnmpClientError {
     #[error("Failed to parse OID")]
@@ -515,4 +619,66 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_bulk_walk_cursors() {
+        let oid = |s: &str| Oid::from_str(s).unwrap();
+        let varbinds = |oids: &[&str]| -> Vec<(Oid<'static>, snmp2::Value<'static>)> {
+            oids.iter()
+                .map(|s| match *s {
+                    "end" => (oid("1.3.6.1.9.9"), snmp2::Value::EndOfMibView),
+                    s => (oid(s), snmp2::Value::Integer(1)),
+                })
+                .collect()
+        };
+        let walked = |results: Vec<RawRouterData>| -> Vec<String> {
+            results
+                .into_iter()
+                .map(|r| match r {
+                    RawRouterData::Snmp { oid, .. } => oid.to_id_string(),
+                    _ => unreachable!(),
+                })
+                .collect()
+        };
+        let mut walk = BulkWalk::new(vec![oid("1.3.6.1.9.1"), oid("1.3.6.1.9.2")]);
+
+        let (active, request) = walk.next_request().unwrap();
+        assert_eq!(active, vec![0, 1]);
+        assert_eq!(request, vec![oid("1.3.6.1.9.1"), oid("1.3.6.1.9.2")]);
+        let response = varbinds(&[
+            "1.3.6.1.9.1.1",
+            "1.3.6.1.9.2.1",
+            "1.3.6.1.9.1.2",
+            "1.3.6.1.9.2.2",
+        ]);
+        let results = walk.consume(&active, response);
+        assert_eq!(walked(results).len(), 4);
+
+        // The first column runs into the second one's subtree and stops, the second goes on
+        let (active, request) = walk.next_request().unwrap();
+        assert_eq!(request, vec![oid("1.3.6.1.9.1.2"), oid("1.3.6.1.9.2.2")]);
+        let response = varbinds(&[
+            "1.3.6.1.9.1.3",
+            "1.3.6.1.9.2.3",
+            "1.3.6.1.9.2.1",
+            "1.3.6.1.9.2.4",
+        ]);
+        let results = walk.consume(&active, response);
+        assert_eq!(walked(results), vec!["1.3.6.1.9.1.3", "1.3.6.1.9.2.3", "1.3.6.1.9.2.4"]);
+
+        let (active, request) = walk.next_request().unwrap();
+        assert_eq!(active, vec![1]);
+        assert_eq!(request, vec![oid("1.3.6.1.9.2.4")]);
+        let results = walk.consume(&active, varbinds(&["1.3.6.1.9.2.5", "end"]));
+        assert_eq!(walked(results), vec!["1.3.6.1.9.2.5"]);
+        assert!(walk.next_request().is_none());
+
+        // An agent answering with the cursor itself ends the column instead of looping
+        let mut walk = BulkWalk::new(vec![oid("1.3.6.1.9.1")]);
+        let (active, _) = walk.next_request().unwrap();
+        walk.consume(&active, varbinds(&["1.3.6.1.9.1.1"]));
+        let (active, _) = walk.next_request().unwrap();
+        walk.consume(&active, varbinds(&["1.3.6.1.9.1.1"]));
+        assert!(walk.next_request().is_none());
+    }
 }
@@ -1,23 +1,120 @@
-use std::net::TcpStream;
-use std::io::Read;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use ssh2::Session;
+use std::time::Duration;
+use base64::Engine;
+use ssh2::{CheckResult, HashType, KnownHostFileKind, Session};
 use tokio::sync::Mutex;
 
 use thiserror::Error;
 
+use crate::logging::info_log;
+
 pub struct SshClient {
     username: String,
     host: String,
     password: Option<String>,
     port: u16,
+    connect_timeout: Duration,
+    host_key_policy: HostKeyPolicy,
+    known_hosts: PathBuf,
     session: Option<Arc<Mutex<ssh2::Session>>>
 }
 
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How the server's host key is checked against the known_hosts file before authenticating.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum HostKeyPolicy {
+    /// Only hosts already in known_hosts with the same key are accepted
+    #[default]
+    Verify,
+    /// Hosts not in known_hosts yet are added on first connect. A changed key is still rejected
+    TrustOnFirstUse,
+    /// Like `Verify`, but the key with this fingerprint is accepted and recorded, replacing any
+    /// old entry. Used once the user has confirmed a rejected key.
+    Accept(String),
+    /// Any key is accepted, nothing is checked
+    Off,
+}
+
+/// A host key that failed verification, with what the user needs to decide whether to trust it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostKeyRejection {
+    pub host: String,
+    pub port: u16,
+    /// SHA-256 fingerprint in OpenSSH notation (`SHA256:...`), as `ssh-keygen -l` prints it
+    pub fingerprint: String,
+    /// The host is in known_hosts with a different key, rather than not there at all
+    pub changed: bool,
+}
+
+impl std::fmt::Display for HostKeyRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.changed {
+            write!(
+                f,
+                "Host key of {} has CHANGED, the server now presents {}. Someone may be intercepting the connection",
+                known_hosts_name(&self.host, self.port),
+                self.fingerprint
+            )
+        } else {
+            write!(
+                f,
+                "Unknown host key for {}: {}",
+                known_hosts_name(&self.host, self.port),
+                self.fingerprint
+            )
+        }
+    }
+}
+
+/// `~/.ssh/known_hosts`, shared with OpenSSH.
+pub fn default_known_hosts_path() -> PathBuf {
+    let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"));
+    match home {
+        Some(home) => PathBuf::from(home).join(".ssh").join("known_hosts"),
+        None => PathBuf::from("known_hosts"),
+    }
+}
+
+/// Append one known_hosts `line`, leaving the rest of the file as it is.
+fn append_known_host(path: &Path, line: &str) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut file = std::fs::OpenOptions::new().create(true).read(true).append(true).open(path)?;
+    let mut text = String::new();
+    // A last line without a newline would otherwise run into ours
+    if file.metadata()?.len() > 0 {
+        let mut last = [0u8; 1];
+        file.seek(SeekFrom::End(-1))?;
+        file.read_exact(&mut last)?;
+        if last[0] != b'\n' {
+            text.push('\n');
+        }
+    }
+    text.push_str(line.trim_end());
+    text.push('\n');
+    file.write_all(text.as_bytes())
+}
+
+/// Host as known_hosts names it, with the port only when it isn't 22.
+fn known_hosts_name(host: &str, port: u16) -> String {
+    if port == 22 {
+        host.to_string()
+    } else {
+        format!("[{}]:{}", host, port)
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum SshError {
     #[error("TCP error: {0}")]
     TcpError(String),
+    #[error("Connection timed out: {0}")]
+    Timeout(String),
     #[error("SSH error: {0}")]
     SshError(String),
     #[error("SSH authentication error: {0}")]
@@ -26,6 +123,8 @@ pub enum SshError {
     CommandError(String),
     #[error("Async error: {0}")]
     AsyncError(String),
+    #[error("{0}")]
+    HostKey(HostKeyRejection),
 }
 
 impl SshClient {
@@ -35,22 +134,148 @@ impl SshClient {
             host,
             password: Some(password),
             port,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            host_key_policy: HostKeyPolicy::default(),
+            known_hosts: default_known_hosts_path(),
             session: None
         }
     }
-    
+
+    /// Timeout applied to the TCP connect and the SSH handshake/auth.
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    pub fn with_host_key_policy(mut self, policy: HostKeyPolicy) -> Self {
+        self.host_key_policy = policy;
+        self
+    }
+
+    /// Check the key the server presented during the handshake. Runs before authentication, so
+    /// the password is never sent to a server that failed the check.
+    fn verify_host_key(session: &Session, host: &str, port: u16, policy: &HostKeyPolicy, path: &Path) -> Result<(), SshError> {
+        if *policy == HostKeyPolicy::Off {
+            return Ok(());
+        }
+        let (key, key_type) = session
+            .host_key()
+            .ok_or_else(|| SshError::SshError("Server sent no host key".to_string()))?;
+        let hash = session
+            .host_key_hash(HashType::Sha256)
+            .ok_or_else(|| SshError::SshError("Couldn't hash the host key".to_string()))?;
+        let fingerprint = format!("SHA256:{}", base64::engine::general_purpose::STANDARD_NO_PAD.encode(hash));
+
+        let mut known_hosts = session.known_hosts().map_err(|e| SshError::SshError(e.to_string()))?;
+        if path.exists() {
+            known_hosts
+                .read_file(path, KnownHostFileKind::OpenSSH)
+                .map_err(|e| SshError::SshError(format!("Couldn't read {}: {}", path.display(), e)))?;
+        }
+        let rejection = |changed| {
+            SshError::HostKey(HostKeyRejection {
+                host: host.to_string(),
+                port,
+                fingerprint: fingerprint.clone(),
+                changed,
+            })
+        };
+        match (known_hosts.check_port(host, port, key), policy) {
+            (CheckResult::Match, _) => return Ok(()),
+            (CheckResult::Failure, _) => {
+                return Err(SshError::SshError(format!("Couldn't check the host key against {}", path.display())));
+            }
+            (_, HostKeyPolicy::Accept(accepted)) if *accepted == fingerprint => {}
+            (CheckResult::NotFound, HostKeyPolicy::TrustOnFirstUse) => {}
+            (CheckResult::NotFound, _) => return Err(rejection(false)),
+            (CheckResult::Mismatch, _) => return Err(rejection(true)),
+        }
+
+        Self::record_host_key(session, path, host, port, key, key_type)?;
+        info_log!("[ssh] Trusted host key {} for {}, added to {}", fingerprint, known_hosts_name(host, port), path.display());
+        Ok(())
+    }
+
+    /// Add `key` to the known_hosts file at `path`. Lines that give this host another key are
+    /// commented out, so an accepted changed key replaces the old one instead of trusting both.
+    /// Every other line is kept as it is, rewriting the file through libssh2 would drop the
+    /// comments, markers and key types it doesn't understand.
+    fn record_host_key(session: &Session, path: &Path, host: &str, port: u16, key: &[u8], key_type: ssh2::HostKeyType) -> Result<(), SshError> {
+        let name = known_hosts_name(host, port);
+        let mut known_hosts = session.known_hosts().map_err(|e| SshError::SshError(e.to_string()))?;
+        known_hosts
+            .add(&name, key, "", key_type.into())
+            .map_err(|e| SshError::SshError(e.to_string()))?;
+        let hosts = known_hosts.hosts().map_err(|e| SshError::SshError(e.to_string()))?;
+        let entry = hosts
+            .first()
+            .ok_or_else(|| SshError::SshError(format!("Couldn't add an entry for {}", name)))?;
+        let line = known_hosts
+            .write_string(entry, KnownHostFileKind::OpenSSH)
+            .map_err(|e| SshError::SshError(e.to_string()))?;
+
+        let write_error = |e: std::io::Error| SshError::SshError(format!("Couldn't write {}: {}", path.display(), e));
+        let existing = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(SshError::SshError(format!("Couldn't read {}: {}", path.display(), e))),
+        };
+        let (mut kept, retired) = Self::retire_host_lines(session, &existing, host, port, key)?;
+        if retired == 0 {
+            return append_known_host(path, &line).map_err(write_error);
+        }
+        kept.push_str(line.trim_end());
+        kept.push('\n');
+        std::fs::write(path, kept).map_err(write_error)
+    }
+
+    /// `text` with every line that names this host with another key than `key` commented out,
+    /// and how many were. Each line is checked by libssh2 on its own, so hashed names and
+    /// patterns match the way the host key check matches them. Lines it can't read stay as they are.
+    fn retire_host_lines(session: &Session, text: &str, host: &str, port: u16, key: &[u8]) -> Result<(String, usize), SshError> {
+        let mut kept = String::with_capacity(text.len());
+        let mut retired = 0;
+        for line in text.lines() {
+            let trimmed = line.trim_start();
+            let mut single = session.known_hosts().map_err(|e| SshError::SshError(e.to_string()))?;
+            let stale = !trimmed.is_empty()
+                && !trimmed.starts_with('#')
+                && single.read_str(line, KnownHostFileKind::OpenSSH).is_ok()
+                && matches!(single.check_port(host, port, key), CheckResult::Mismatch);
+            if stale {
+                kept.push_str("# ");
+                retired += 1;
+            }
+            kept.push_str(line);
+            kept.push('\n');
+        }
+        Ok((kept, retired))
+    }
+
     // Move your sync logic here:
-    fn connect_sync_inner(username: String, host: String, password: Option<String>, port: u16) -> Result<Session, SshError> {
-        let tcp = TcpStream::connect(format!("{}:{}", host, port)).map_err(|e| SshError::TcpError(e.to_string()))?;
+    fn connect_sync_inner(username: String, host: String, password: Option<String>, port: u16, timeout: Duration, host_key_policy: HostKeyPolicy, known_hosts: PathBuf) -> Result<Session, SshError> {
+        let addr = (host.as_str(), port)
+            .to_socket_addrs()
+            .map_err(|e| SshError::TcpError(format!("Couldn't resolve {}:{}: {}", host, port, e)))?
+            .next()
+            .ok_or_else(|| SshError::TcpError(format!("No addresses found for {}:{}", host, port)))?;
+        let tcp = TcpStream::connect_timeout(&addr, timeout).map_err(|e| match e.kind() {
+            std::io::ErrorKind::TimedOut => SshError::Timeout(format!("{} after {:?}", addr, timeout)),
+            _ => SshError::TcpError(e.to_string()),
+        })?;
         let mut session = ssh2::Session::new().map_err(|e| SshError::SshError(e.to_string()))?;
         session.set_tcp_stream(tcp);
+        // Bound handshake/auth so an unresponsive server can't hang the connect; reset afterwards.
+        session.set_timeout(timeout.as_millis() as u32);
         session.handshake().map_err(|e| SshError::SshError(e.to_string()))?;
+        Self::verify_host_key(&session, &host, port, &host_key_policy, &known_hosts)?;
         if let Some(password) = password {
             session.userauth_password(&username, &password).map_err(|e| SshError::SshAuthError(e.to_string()))?;
         }
         if !session.authenticated() {
             return Err(SshError::SshAuthError("Authentication failed".to_string()));
         }
+        session.set_timeout(0);
         Ok(session)
     }
     
@@ -62,8 +287,11 @@ impl SshClient {
         let host = self.host.clone();
         let password = self.password.clone();
         let port = self.port;
+        let timeout = self.connect_timeout;
+        let host_key_policy = self.host_key_policy.clone();
+        let known_hosts = self.known_hosts.clone();
         let session = tokio::task::spawn_blocking(move || {
-            SshClient::connect_sync_inner(username, host, password, port)
+            SshClient::connect_sync_inner(username, host, password, port, timeout, host_key_policy, known_hosts)
         })
         .await
         .map_err(|e| SshError::AsyncError(e.to_string()))?
@@ -71,6 +299,29 @@ impl SshClient {
         self.session = Some(Arc::new(Mutex::new(session)));
         Ok(())
     }
+
+    /// Connect, retrying transport failures with exponential backoff. Authentication and host
+    /// key errors are returned immediately since retrying them won't help.
+    pub async fn connect_with_retry(&mut self, attempts: u32, initial_backoff: Duration) -> Result<(), SshError> {
+        let mut backoff = initial_backoff;
+        let mut attempt = 1;
+        loop {
+            match self.connect().await {
+                Ok(()) => return Ok(()),
+                Err(e @ (SshError::SshAuthError(_) | SshError::HostKey(_))) => return Err(e),
+                Err(e) if attempt >= attempts => return Err(e),
+                Err(e) => {
+                    eprintln!(
+                        "[ssh] connect to {}:{} failed (attempt {}/{}): {}; retrying in {:?}",
+                        self.host, self.port, attempt, attempts, e, backoff
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                    attempt += 1;
+                }
+            }
+        }
+    }
     
     fn execute_command_sync(session: &mut ssh2::Session, command: &str) -> Result<String, SshError> {
         let mut channel = session.channel_session().map_err(|e| SshError::SshError(e.to_string()))?;
@@ -83,17 +334,28 @@ impl SshClient {
     
     pub async fn execute_command(&self, command: &str) -> Result<String, SshError> {
         let command = command.to_string();
+        self.with_session(move |session| Self::execute_command_sync(session, &command))
+            .await
+    }
+
+    /// Run blocking work against the session on the blocking pool, e.g. opening a subsystem
+    /// channel. `E` must absorb `SshError` for the session and join failures.
+    pub async fn with_session<T, E, F>(&self, f: F) -> Result<T, E>
+    where
+        T: Send + 'static,
+        E: From<SshError> + Send + 'static,
+        F: FnOnce(&mut ssh2::Session) -> Result<T, E> + Send + 'static,
+    {
         let session_mutex = match &self.session {
             Some(s) => s.clone(),
-            None => return Err(SshError::SshError("Session not initialized".to_string())),
+            None => return Err(SshError::SshError("Session not initialized".to_string()).into()),
         };
-        let result: Result<String, SshError> = tokio::task::spawn_blocking(move || {
+        tokio::task::spawn_blocking(move || {
             let mut session = session_mutex.blocking_lock();
-            Self::execute_command_sync(&mut session, &command)
+            f(&mut session)
         })
         .await
-        .map_err(|e| SshError::AsyncError(e.to_string()))?;
-        result
+        .map_err(|e| SshError::AsyncError(e.to_string()))?
     }
     
     pub fn is_connected(&self) -> bool {
@@ -101,11 +363,16 @@ impl SshClient {
     }
     
     pub async fn close(self) -> Result<(), SshError> {
-        if let Some(session) = self.session {
-            let session = session.lock().await;
-            session.disconnect(Some(ssh2::DisconnectCode::ByApplication), "", None).map_err(|e| SshError::SshError(e.to_string()))?;
+        if !self.is_connected() {
+            return Ok(());
         }
-        Ok(())
+        // disconnect() writes to the socket, keep it off the async workers like every other call
+        self.with_session(|session| {
+            session
+                .disconnect(Some(ssh2::DisconnectCode::ByApplication), "", None)
+                .map_err(|e| SshError::SshError(e.to_string()))
+        })
+        .await
     }
 }
 
@@ -118,6 +385,58 @@ mod tests {
             "localhost".to_string(),
             "password".to_string(),
             2221)
+            // The lab containers get a new host key every time they are rebuilt
+            .with_host_key_policy(HostKeyPolicy::Off)
+    }
+
+    #[test]
+    fn test_host_key_rejection_message() {
+        let mut rejection = HostKeyRejection {
+            host: "r1.lab".to_string(),
+            port: 2221,
+            fingerprint: "SHA256:abc".to_string(),
+            changed: false,
+        };
+        assert_eq!(rejection.to_string(), "Unknown host key for [r1.lab]:2221: SHA256:abc");
+        rejection.port = 22;
+        rejection.changed = true;
+        assert!(rejection.to_string().starts_with("Host key of r1.lab has CHANGED"));
+    }
+
+    #[test]
+    fn test_accepted_changed_key_replaces_old() {
+        let path = std::env::temp_dir().join(format!("known_hosts_changed_{}", std::process::id()));
+        let encode = |key: &[u8]| base64::engine::general_purpose::STANDARD.encode(key);
+        let (old_key, new_key) = (b"old host key".as_slice(), b"new host key".as_slice());
+        let existing = format!(
+            "# lab routers\n[r1.lab]:2221 ssh-ed25519 {}\nr2.lab ssh-ed25519 {}\n",
+            encode(old_key),
+            encode(old_key)
+        );
+        std::fs::write(&path, &existing).unwrap();
+        let session = Session::new().unwrap();
+        SshClient::record_host_key(&session, &path, "r1.lab", 2221, new_key, ssh2::HostKeyType::Ed25519).unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let mut known_hosts = session.known_hosts().unwrap();
+        known_hosts.read_file(&path, KnownHostFileKind::OpenSSH).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(contents.starts_with(&format!("# lab routers\n# [r1.lab]:2221 ssh-ed25519 {}\n", encode(old_key))));
+        assert!(matches!(known_hosts.check_port("r1.lab", 2221, old_key), CheckResult::Mismatch));
+        assert!(matches!(known_hosts.check_port("r1.lab", 2221, new_key), CheckResult::Match));
+        // Other hosts keep their key
+        assert!(matches!(known_hosts.check_port("r2.lab", 22, old_key), CheckResult::Match));
+    }
+
+    #[test]
+    fn test_append_known_host_keeps_file() {
+        let path = std::env::temp_dir().join(format!("known_hosts_test_{}", std::process::id()));
+        let existing = "# lab routers\n@cert-authority *.lab ssh-ed25519 AAAA\nr1.lab ssh-ed25519 BBBB";
+        std::fs::write(&path, existing).unwrap();
+        append_known_host(&path, "[r2.lab]:2221 ssh-ed25519 CCCC\n").unwrap();
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(contents, format!("{}\n[r2.lab]:2221 ssh-ed25519 CCCC\n", existing));
     }
     
     #[tokio::test]
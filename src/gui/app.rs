@@ -1,51 +1,94 @@
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use std::hash::{DefaultHasher, Hash};
+use std::net::Ipv4Addr;
 use std::sync::Arc;
-use std::time::Duration;
+use std::task::Poll;
+use std::time::{Duration, SystemTime};
 
 use std::hash::Hasher;
 
-use crate::data_aquisition::ssh::SshClient;
-use crate::gui::autopoll::SourceSpec;
+use crate::data_aquisition::snmp::DEFAULT_MAX_REPETITIONS;
+use crate::data_aquisition::netconf::{self, NetconfClient};
+use crate::data_aquisition::ssh::{self, HostKeyPolicy, HostKeyRejection, SshClient};
+use crate::gui::autopoll::{FetchGuard, FetchesInFlight, SourceSpec};
+use crate::gui::area_hulls;
+use crate::gui::background_task::{BackgroundTask, TaskPhase};
+use crate::gui::poll_error::PollError;
+use crate::gui::credentials::Credentials;
+use crate::cli::Startup;
+use crate::gui::saved_layout::SavedLayout;
+use crate::gui::saved_overlay::SavedOverlay;
+use crate::gui::session::{SavedSource, Session};
 use crate::gui::edge_anim;
 use crate::gui::edge_shape::{self, NetworkGraphEdgeShape};
 use crate::gui::node_panel::{
-    FloatingNodePanel, bullet_list, collapsible_section, protocol_data_section
+    self, FloatingNodePanel, anonymized, bullet_list, collapsible_section, copyable_label,
+    protocol_data_section,
 };
-use crate::gui::node_shape::{self, clear_path_highlight};
-use crate::network::edge::EdgeKind;
-use crate::network::node::NodeInfo;
+use crate::gui::node_shape::{self, NodeSizing, clear_path_highlight};
+use crate::gui::palette::{self, NodeRole, Palette};
+use crate::logging::{self, LogLevel, debug_log, info_log};
+use crate::network::edge::{
+    DEFAULT_UNMETERED_COST, EdgeKind, EdgeMetric, ReverseMetricPolicy, UndirectedEdgeKey,
+};
+use crate::network::network_graph::{
+    AreaKey, InterfaceMapping, PathHop, interface_traffic_weights,
+};
+use crate::network::node::{NodeInfo, source_scoped_uuid};
 
-use crate::network::router::InterfaceStats;
-use crate::parsers::isis_parser::topology::IsIsTopology;
+use crate::network::router::{InterfaceRates, InterfaceStats};
+use crate::network::router::RouterId;
+use crate::parsers::isis_parser::core_lsp::MtId;
+use crate::parsers::isis_parser::protocol::{
+    PrefixResolutionReport, UnresolvedPrefixPolicy, isis_areas, isis_topologies, retain_isis_area,
+    retain_isis_topology,
+};
+use crate::parsers::isis_parser::topology::{IsIsNetconfTopology, IsIsTopology};
+use crate::parsers::ospf_parser::snmp_oids::SnmpOidMap;
 use crate::topology::protocol::FederationError;
-use crate::topology::source::SnapshotSource;
-use crate::topology::store::{MergeConfig, SourceId, SourceState, TopologyStore};
+use crate::topology::source::{ParseWarning, Snapshot, SnapshotSource, TopologyError, TopologySource};
+use crate::topology::store::{
+    AcquisitionMetrics, AreaType, MergeConfig, MetricConflict, SourceHealth, SourceId, SourceState, TopologyStore,
+};
 use crate::{
     gui::node_shape::{
         LabelOverlay, NetworkGraphNodeShape, clear_area_highlight, clear_label_overlays,
         partition_highlight_enabled, set_partition_highlight_enabled, take_label_overlays,
     },
-    network::{network_graph::NetworkGraph, node::Node},
-    topology::OspfSnmpTopology,
+    network::{
+        label_template::{LABEL_PLACEHOLDERS, LabelTemplate},
+        network_graph::{GraphStats, MetricHistogram, NetworkGraph},
+        node::Node,
+    },
+    topology::{OspfSnmpTopology, ospf_protocol::ConsolidationPolicy},
 };
 use catppuccin_egui::Theme;
 use eframe::egui;
-use egui::{Button, CentralPanel, Checkbox, CollapsingHeader, Context, Id, SidePanel, Ui};
+use egui::{Button, CentralPanel, Checkbox, CollapsingHeader, Context, Id, Rect, SidePanel, Ui};
 use egui_extras::{Column, TableBuilder};
 use egui_graphs::{
     FruchtermanReingoldWithCenterGravity, FruchtermanReingoldWithCenterGravityState,
-    LayoutForceDirected, SettingsInteraction, SettingsNavigation,
+    Layout as GraphLayout, LayoutForceDirected, SettingsInteraction, SettingsNavigation,
 };
 use ipnetwork::IpNetwork;
 use petgraph::{Directed, csr::DefaultIx, graph::NodeIndex};
 use ssh2::DisconnectCode::ProtocolError;
 use tokio::runtime::Runtime;
-use tokio::sync::watch;
+use tokio::sync::{Semaphore, watch};
 use uuid::Uuid;
 
+/// Default cap on snapshot fetches running at once during autopoll.
+const DEFAULT_AUTOPOLL_MAX_CONCURRENT: usize = 8;
+
+/// Default screen space kept free around the fitted graph, clears the node labels.
+const DEFAULT_FIT_PADDING: f32 = 40.0;
+/// Node movement per frame below which the layout counts as still
+const SETTLE_THRESHOLD: f32 = 0.1;
+/// Still frames in a row before the layout is considered settled
+const SETTLE_FRAMES: u32 = 30;
+
 thread_local! {
     static THEME: RefCell<Theme> = RefCell::new(catppuccin_egui::MACCHIATO);
 }
@@ -54,13 +97,13 @@ pub fn get_theme() -> Theme {
     THEME.with(|theme| theme.borrow().clone())
 }
 
-pub fn main(rt: Arc<Runtime>) {
+pub fn main(rt: Arc<Runtime>, startup: Startup) {
     let native_options = eframe::NativeOptions::default();
     let result = eframe::run_native(
         "My egui App",
         native_options,
         Box::new(|cc| {
-            let app = rt.block_on(App::new(cc, rt.clone()));
+            let app = rt.block_on(App::new(cc, rt.clone(), startup));
 
             match app {
                 Ok(app) => {
@@ -109,19 +152,135 @@ enum EditTool {
     Draw,
 }
 
-pub type PollResult = Result<(SourceId, Vec<Node>, Vec<InterfaceStats>), String>;
+/// Shortest time an autopoll fetch gets before it's abandoned, see `poll_timeout()`.
+const MIN_POLL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long an autopoll fetch may run: a few intervals, so a hung agent doesn't hold its fetch
+/// permit forever, but never less than `MIN_POLL_TIMEOUT` for short intervals.
+fn poll_timeout(interval: Duration) -> Duration {
+    interval.saturating_mul(3).max(MIN_POLL_TIMEOUT)
+}
+
+/// File written by the "Export GraphML" button, relative to the working directory.
+const GRAPHML_EXPORT_FILE: &str = "topology.graphml";
+
+/// File written by the "Export merge trace" button, relative to the working directory.
+const MERGE_TRACE_EXPORT_FILE: &str = "merge_trace.json";
+
+/// Snapshot with the time it took to fetch.
+pub type TimedSnapshot = (Snapshot, Duration);
+
+pub type PollResult = Result<TimedSnapshot, PollError>;
+
+/// Snapshot taken by a Connect button, with the spec to re-poll the source later.
+pub type ConnectOutput = (TimedSnapshot, SourceSpec);
+
+/// One row of the Sources table, copied out of the store so the table can mutate `App`.
+struct SourceRow {
+    src_id: SourceId,
+    health: SourceHealth,
+    nodes_count: usize,
+    last_snapshot: SystemTime,
+    if_stats: Vec<(InterfaceStats, Option<InterfaceRates>)>,
+    warnings: Vec<ParseWarning>,
+    acquisition: AcquisitionMetrics,
+}
+
+impl SourceRow {
+    fn new(src_id: &SourceId, state: &SourceState) -> Self {
+        Self {
+            src_id: src_id.clone(),
+            health: state.health.clone(),
+            nodes_count: state.partition.nodes.len(),
+            last_snapshot: state.last_snapshot,
+            if_stats: state
+                .interface_stats
+                .iter()
+                .map(|stats| (stats.clone(), state.interface_rates(stats.ip_address)))
+                .collect(),
+            warnings: state.parse_warnings.clone(),
+            acquisition: state.acquisition.clone(),
+        }
+    }
+}
+
+/// Last fetch duration with a node count trend arrow; the averages and history are in the tooltip.
+fn render_acquisition_metrics(ui: &mut Ui, metrics: &AcquisitionMetrics) {
+    let Some(last) = metrics.last_fetch() else {
+        ui.label("–").on_hover_text("Not timed yet");
+        return;
+    };
+    let trend = match metrics.node_count_trend() {
+        0 => "",
+        t if t > 0 => " ↑",
+        _ => " ↓",
+    };
+    ui.label(format!("{} ms{}", last.as_millis(), trend))
+        .on_hover_ui(|ui| {
+            ui.label(format!("Last fetch: {} ms", last.as_millis()));
+            if let Some(average) = metrics.average_fetch() {
+                ui.label(format!(
+                    "Average of last {}: {} ms",
+                    metrics.fetch_durations.len(),
+                    average.as_millis()
+                ));
+            }
+            let counts: Vec<String> = metrics.node_counts.iter().map(|c| c.to_string()).collect();
+            ui.label(format!("Node counts, oldest first: {}", counts.join(" → ")));
+            let trend = metrics.node_count_trend();
+            if trend != 0 {
+                ui.label(format!("Topology {} by {} node(s)", if trend > 0 { "grew" } else { "shrank" }, trend.abs()));
+            }
+        });
+}
+
+/// `SnapshotSource::fetch_snapshot`, reporting each step into `phase`.
+async fn fetch_snapshot_with_phases<S: SnapshotSource + ?Sized>(
+    source: &mut S,
+    phase: &TaskPhase,
+) -> Result<Snapshot, TopologyError> {
+    phase.set("Fetching router ID…");
+    let source_id = source.fetch_source_id().await?;
+    phase.set("Fetching and parsing LSDB…");
+    let nodes = source.fetch_nodes().await?;
+    let warnings = source.take_parse_warnings();
+    phase.set("Fetching interface stats…");
+    let stats = source.fetch_stats().await?;
+    Ok((source_id, nodes, stats, warnings))
+}
 
 struct App {
+    // Source driven by the legacy switch_*_target() helpers, none until one of them runs
     #[allow(unused)]
-    topo: Box<dyn SnapshotSource>,
+    topo: Option<Box<dyn SnapshotSource>>,
     store: TopologyStore,
 
     graph: NetworkGraph,
 
     selected_node: Option<NodeIndex>,
-    #[allow(unused)]
+    // Full selection (shift-click to extend); selected_node is only set when exactly one is selected
+    selected_nodes: Vec<NodeIndex>,
     runtime: Arc<Runtime>,
     layout_state: LayoutState,
+    // Extra simulation steps run before the GraphView's own step each frame
+    layout_iterations_per_frame: usize,
+    // Screen pixels kept free around the graph when fitting it to the canvas
+    fit_padding: f32,
+    // Zoom relative to the fitted view, 1.0 shows the whole graph
+    initial_zoom: f32,
+    // Pull OSPF areas towards separate anchors while the layout runs
+    area_clustering: bool,
+    area_clustering_strength: f32,
+    // Retarget center gravity at the selected node instead of the graph center
+    gravity_to_selected: bool,
+    // Run a single layout step next frame, then stay paused
+    layout_step_once: bool,
+    // Stop stepping the layout, and with it the continuous repaints, once nodes stop moving
+    idle_when_settled: bool,
+    // Node positions after the previous layout step, to detect when the layout has settled
+    last_positions: HashMap<Uuid, egui::Pos2>,
+    // Consecutive frames in which no node moved more than `SETTLE_THRESHOLD`
+    settled_frames: u32,
     theme: Theme,
 
     pending_destroy: Vec<(Uuid, Uuid, EdgeKind, bool)>,
@@ -129,31 +288,78 @@ struct App {
     path_mode: bool,
     path_start: Option<NodeIndex>,
     path_end: Option<NodeIndex>,
+    // Nodes the path must visit between start and end, in order
+    path_waypoints: Vec<NodeIndex>,
+    // Node the right-click menu was opened on
+    context_menu_node: Option<NodeIndex>,
+    // Endpoints of the edge under the pointer, highlighted in node_shape
+    hovered_edge: Option<(Uuid, Uuid)>,
+    // Hops of the last computed path, kept for the hop list and "Copy path"
+    path_hops: Vec<PathHop>,
+    // Hide everything that isn't on the computed path
+    path_only: bool,
+    // What-if simulation: elements treated as down for pathfinding, the real topology is untouched
+    failed_nodes: HashSet<Uuid>,
+    failed_edges: HashSet<UndirectedEdgeKey>,
+    // Node whose point of view the blast radius is computed from
+    blast_root: Option<Uuid>,
+    // Node whose failure the blast radius shows, and the nodes the root loses with it
+    blast_failed: Option<Uuid>,
+    blast_radius: HashSet<Uuid>,
+    // Pathfinding cost of edges without a metric
+    unmetered_cost: u32,
+    // Links whose cost differs by direction, refreshed on every reload
+    asymmetric_links: Vec<(Uuid, Uuid, u32, u32)>,
+    highlight_asymmetric: bool,
+    // Interface-to-edge matches from the last traffic weighting, per source
+    interface_mappings: Vec<(SourceId, Vec<InterfaceMapping>)>,
+    // Draw the membership edge between each OSPF transit network and its DR thicker
+    highlight_designated_routers: bool,
 
     edit_tool: EditTool,
     draw_first: Option<NodeIndex>,
     selected_edge: Option<(Uuid, Uuid, EdgeKind)>,
     previous_manual_metric: Option<u32>,
+    // Saved manual edges whose endpoints aren't in the topology yet, applied on reload
+    pending_overlay: SavedOverlay,
     
     source_specs: HashMap<SourceId, SourceSpec>,
+    // Reconnect the sources saved in the session file on the next launch
+    reconnect_on_launch: bool,
     autopoll_enabled: bool,
     autopoll_interval: Duration,
     autopoll_interval_tx: Option<tokio::sync::watch::Sender<Duration>>,
-    poll_tx: Option<std::sync::mpsc::Sender<PollResult>>,
-    poll_rx: Option<std::sync::mpsc::Receiver<PollResult>>,
-    autopoll_handles: Vec<tokio::task::JoinHandle<()>>,
+    poll_tx: Option<std::sync::mpsc::Sender<(SourceId, PollResult)>>,
+    poll_rx: Option<std::sync::mpsc::Receiver<(SourceId, PollResult)>>,
+    autopoll_handles: HashMap<SourceId, tokio::task::JoinHandle<()>>,
+    // Sources with a poll, reconnect or refresh running
+    fetches_in_flight: FetchesInFlight,
+    // One-shot reconnect attempts for Lost sources, keyed by the source being retried
+    reconnect_tasks: Vec<(SourceId, BackgroundTask<TimedSnapshot>)>,
+    // Startup-connect tasks, aborted on shutdown; finished ones are pruned on spawn
+    oneshot_fetches: Vec<tokio::task::JoinHandle<()>>,
+    // First snapshots of the sources given on the command line
+    startup_rx: std::sync::mpsc::Receiver<Result<ConnectOutput, PollError>>,
+    // Random-ish per-source delay window applied before the first poll
+    autopoll_stagger_window: Duration,
+    // Spread first polls evenly across the interval (on top of the stagger window)
+    autopoll_stagger_ramp: bool,
+    // Most snapshot fetches allowed in flight at once across all autopoll tasks
+    autopoll_max_concurrent: usize,
     
     // SNMP source switching state
     snmp_host: String,
     snmp_port: u16,
     snmp_community: String,
+    // Rows per GETBULK when walking the LSDB
+    snmp_max_repetitions: u32,
+    // OID map file for non-standard agents, empty for the standard OSPF-MIB
+    snmp_oid_file: String,
+    // Keep OSPF summary networks as their own nodes instead of folding them
+    snmp_keep_summaries: bool,
     clear_sources_on_switch: bool,
-    // Quick & dirty: shared result storage for background SNMP connect -> snapshot result
-    snmp_connect_res: std::sync::Arc<
-        std::sync::Mutex<Option<Result<(SourceId, Vec<Node>, Vec<InterfaceStats>, SourceSpec), String>>>,
-    >,
-    // Quick & dirty: flag indicating SNMP connect in progress
-    snmp_connect_pending: bool,
+    // In-flight SNMP connect + first snapshot, if any
+    snmp_connect_task: Option<BackgroundTask<ConnectOutput>>,
 
     // SSH source switching state
     ssh_host: String,
@@ -161,19 +367,80 @@ struct App {
     ssh_username: String,
     ssh_password: String,
     ssh_clear_sources_on_switch: bool,
-    // Quick & dirty: shared result storage for background SSH connect -> snapshot result
-    ssh_connect_res: std::sync::Arc<
-        std::sync::Mutex<Option<Result<(SourceId, Vec<Node>, Vec<InterfaceStats>, SourceSpec), String>>>,
-    >,
-    // Quick & dirty: flag indicating SSH connect in progress
-    ssh_connect_pending: bool,
+    // In-flight SSH connect + first snapshot, if any
+    ssh_connect_task: Option<BackgroundTask<ConnectOutput>>,
+    ssh_connect_timeout: Duration,
+    // Last SSH connect failure, shown under the Connect button
+    ssh_connect_error: Option<PollError>,
+    // Host key checking, shared with NETCONF
+    ssh_verify_host_keys: bool,
+    ssh_trust_on_first_use: bool,
+    // Fingerprint the user confirmed in the host key prompt, trusted by the next connect
+    ssh_accepted_host_key: Option<String>,
+
+    // NETCONF source switching state, same shape as SSH
+    netconf_host: String,
+    netconf_port: u16,
+    netconf_username: String,
+    netconf_password: String,
+    netconf_clear_sources_on_switch: bool,
+    netconf_connect_task: Option<BackgroundTask<ConnectOutput>>,
+    netconf_connect_error: Option<PollError>,
 
     merge_config: MergeConfig,
+    // Area override text field of the node panel, and the node it was filled in for
+    area_override_input: String,
+    area_override_node: Option<Uuid>,
+    area_override_error: Option<String>,
+
+    // Latest IS-IS pseudonode prefix resolution diagnostics, per source
+    isis_prefix_reports: HashMap<SourceId, PrefixResolutionReport>,
+    // Prefixes seen via both OSPF and IS-IS, refreshed on reload_graph()
+    prefix_conflicts: Vec<IpNetwork>,
+    // Links whose metric fused router facets disagree on
+    metric_conflicts: Vec<MetricConflict>,
+    // Containing/contained prefix pairs from different sources, refreshed on reload_graph()
+    overlapping_prefixes: Vec<(IpNetwork, IpNetwork)>,
+
+    // View filters applied on top of the merged view in reload_graph()
+    hide_stub_networks: bool,
+    node_sizing: NodeSizing,
+    // OSPF area types inferred from the LSDBs, refreshed on reload
+    area_types: HashMap<Ipv4Addr, AreaType>,
+    // Screen position where a box selection drag started
+    box_select_origin: Option<egui::Pos2>,
+    // Leaf networks dropped by MergeConfig in the last reload_graph()
+    hidden_leaf_networks: usize,
+    hidden_lost_sources: usize,
+    collapsed_unresolved_networks: usize,
+    // IS-IS multi-topology shown in the view (None shows all), and the topologies seen in the last reload
+    isis_topology_filter: Option<MtId>,
+    isis_topologies: Vec<MtId>,
+    isis_area_filter: Option<String>,
+    isis_areas: Vec<String>,
+    hidden_nodes: HashSet<Uuid>,
+    // Show every area as a single node, except the ones clicked open
+    collapse_areas: bool,
+    expanded_areas: HashSet<AreaKey>,
+    // Neighborhood focus: when set, only these nodes are shown
+    focus_hops: usize,
+    focus_set: Option<HashSet<Uuid>>,
+
+    // Cached graph stats, keyed by (node count, edge count); cleared on reload_graph()
+    graph_stats: Option<((usize, usize), GraphStats)>,
+    // (node count, edge count) the edge bundles were last computed for
+    edge_bundles_key: Option<(usize, usize)>,
+
+    // Default node labels, edited in the side panel and pushed to the graph on change
+    label_template: LabelTemplate,
+
+    search_query: String,
+    search_miss: bool,
 }
 
 impl Drop for App {
     fn drop(&mut self) {
-        self.stop_autopoll();
+        self.shutdown();
     }
 }
 
@@ -181,35 +448,41 @@ impl App {
     async fn new(
         cc: &eframe::CreationContext<'_>,
         runtime: Arc<Runtime>,
+        startup: Startup,
     ) -> Result<Self, RuntimeError> {
         let _ = cc; // silence unused variable warning for now
 
-        //let snmp_client = crate::data_aquisition::snmp::SnmpClient::default();
-        let ssh_client = SshClient::new_with_password(
-            "client".to_string(),
-            "localhost".to_string(),
-            "password".to_string(),
-            2221,
-        );
-        let topo = IsIsTopology::new_from_ssh_client(ssh_client).await.unwrap();
-        let topo: Box<dyn SnapshotSource> =
-            //Box::new(OspfSnmpTopology::from_snmp_client(snmp_client));
-            Box::new(topo);
-        let store = TopologyStore::default();
+        let creds = Credentials::load();
+        let session = Session::load();
+        palette::set_palette(session.palette);
+        let loaded_store = startup.store.is_some();
+        let store = startup.store.unwrap_or_default();
 
         let merge_config = MergeConfig::default();
+        let (startup_tx, startup_rx) = std::sync::mpsc::channel();
 
         let mut layout_state = LayoutState::default();
         layout_state.base.k_scale = 0.2;
 
-        let app = Self {
-            topo,
+        let mut app = Self {
+            topo: None,
             store,
             graph: NetworkGraph::default(),
 
             selected_node: Option::default(),
+            selected_nodes: Vec::new(),
             runtime,
             layout_state,
+            layout_iterations_per_frame: 1,
+            fit_padding: DEFAULT_FIT_PADDING,
+            initial_zoom: 1.0,
+            area_clustering: false,
+            area_clustering_strength: 0.05,
+            gravity_to_selected: false,
+            layout_step_once: false,
+            idle_when_settled: true,
+            last_positions: HashMap::new(),
+            settled_frames: 0,
             selected_edge: None,
             pending_destroy: Vec::new(),
             theme: THEME.with(|theme| theme.borrow().clone()),
@@ -217,7 +490,23 @@ impl App {
             path_mode: false,
             path_start: None,
             path_end: None,
+            path_waypoints: Vec::new(),
+            context_menu_node: None,
+            hovered_edge: None,
+            path_hops: Vec::new(),
+            path_only: false,
+            failed_nodes: HashSet::new(),
+            failed_edges: HashSet::new(),
+            blast_root: None,
+            blast_failed: None,
+            blast_radius: HashSet::new(),
+            unmetered_cost: DEFAULT_UNMETERED_COST,
+            asymmetric_links: Vec::new(),
+            highlight_asymmetric: false,
+            interface_mappings: Vec::new(),
+            highlight_designated_routers: false,
             previous_manual_metric: None,
+            pending_overlay: SavedOverlay::load().unwrap_or_default(),
 
             edit_tool: EditTool::None,
             draw_first: None,
@@ -228,28 +517,199 @@ impl App {
             autopoll_interval_tx: None,
             poll_rx: None,
             poll_tx: None,
-            autopoll_handles: Vec::new(),
+            autopoll_handles: HashMap::new(),
+            fetches_in_flight: FetchesInFlight::default(),
+            reconnect_tasks: Vec::new(),
+            oneshot_fetches: Vec::new(),
+            startup_rx,
+            autopoll_stagger_window: Duration::from_millis(250),
+            autopoll_stagger_ramp: false,
+            autopoll_max_concurrent: DEFAULT_AUTOPOLL_MAX_CONCURRENT,
 
-            snmp_host: "127.0.0.1".to_string(),
-            snmp_port: 1161,
-            snmp_community: "public".to_string(),
+            snmp_host: creds.snmp_host.clone(),
+            snmp_port: creds.snmp_port,
+            snmp_community: creds.snmp_community.clone(),
+            snmp_max_repetitions: DEFAULT_MAX_REPETITIONS,
+            snmp_oid_file: String::new(),
+            snmp_keep_summaries: false,
             clear_sources_on_switch: true,
 
-            ssh_host: "127.0.0.1".to_string(),
-            ssh_port: 2221,
-            ssh_username: "client".to_string(),
-            ssh_password: "password".to_string(),
+            netconf_host: creds.ssh_host.clone(),
+            netconf_port: netconf::DEFAULT_PORT,
+            netconf_username: creds.ssh_username.clone(),
+            netconf_password: creds.ssh_password.clone(),
+            netconf_clear_sources_on_switch: true,
+            netconf_connect_task: None,
+            netconf_connect_error: None,
+
+            ssh_host: creds.ssh_host.clone(),
+            ssh_port: creds.ssh_port,
+            ssh_username: creds.ssh_username.clone(),
+            ssh_password: creds.ssh_password.clone(),
             ssh_clear_sources_on_switch: true,
-            snmp_connect_res: std::sync::Arc::new(std::sync::Mutex::new(None)),
-            snmp_connect_pending: false,
-            ssh_connect_res: std::sync::Arc::new(std::sync::Mutex::new(None)),
-            ssh_connect_pending: false,
+            snmp_connect_task: None,
+            ssh_connect_task: None,
+            ssh_connect_timeout: ssh::DEFAULT_CONNECT_TIMEOUT,
+            ssh_connect_error: None,
+            ssh_verify_host_keys: true,
+            ssh_trust_on_first_use: false,
+            ssh_accepted_host_key: None,
 
             merge_config,
+            area_override_input: String::new(),
+            area_override_node: None,
+            area_override_error: None,
+
+            isis_prefix_reports: HashMap::new(),
+            prefix_conflicts: Vec::new(),
+            metric_conflicts: Vec::new(),
+            overlapping_prefixes: Vec::new(),
+
+            hide_stub_networks: false,
+            node_sizing: NodeSizing::None,
+            area_types: HashMap::new(),
+            box_select_origin: None,
+            hidden_leaf_networks: 0,
+            hidden_lost_sources: 0,
+            collapsed_unresolved_networks: 0,
+            isis_topology_filter: None,
+            isis_topologies: Vec::new(),
+            isis_area_filter: None,
+            isis_areas: Vec::new(),
+            hidden_nodes: HashSet::new(),
+            collapse_areas: false,
+            expanded_areas: HashSet::new(),
+            focus_hops: 1,
+            focus_set: None,
+
+            graph_stats: None,
+            edge_bundles_key: None,
+
+            label_template: LabelTemplate::default(),
+
+            search_query: String::new(),
+            search_miss: false,
+
+            reconnect_on_launch: session.reconnect_on_launch,
         };
 
+        // Nodes from the first snapshots start where they were last saved
+        if let Some(layout) = SavedLayout::load() {
+            app.graph.restore_layout(layout.to_positions());
+        }
+        // The saved overlay is applied by the reloads, as the nodes it needs show up
+        if loaded_store {
+            app.reload_graph_logged("loading the store");
+        }
+        for spec in startup.sources {
+            app.spawn_startup_connect(spec, startup_tx.clone());
+        }
+        if session.reconnect_on_launch {
+            app.restore_session_sources(&session, &creds);
+        }
+
         Ok(app)
     }
+
+    /// Reconnect every source saved in `session` in the background. Sources that can't be
+    /// rebuilt or fail to connect show up as Lost instead of holding up startup.
+    fn restore_session_sources(&mut self, session: &Session, creds: &Credentials) {
+        let now = std::time::SystemTime::now();
+        for saved in &session.sources {
+            match saved.to_spec(creds) {
+                Ok(spec) => {
+                    info_log!("[app] Reconnecting saved source {}", saved.source_id);
+                    self.source_specs.insert(saved.source_id.clone(), spec.clone());
+                    self.spawn_reconnect(saved.source_id.clone(), spec);
+                }
+                Err(e) => {
+                    eprintln!("[app] Can't restore saved source {}: {}", saved.source_id, e);
+                    self.store.mark_lost(&saved.source_id, now);
+                }
+            }
+        }
+    }
+
+    /// Connect a source given on the command line in the background, reported to
+    /// `handle_startup_connects()`.
+    fn spawn_startup_connect(
+        &mut self,
+        spec: SourceSpec,
+        tx: std::sync::mpsc::Sender<Result<ConnectOutput, PollError>>,
+    ) {
+        let handle = self.runtime.spawn(async move {
+            let result = match spec.build_topology().await {
+                Ok(mut source) => {
+                    let started = std::time::Instant::now();
+                    source
+                        .fetch_snapshot()
+                        .await
+                        .map(|snapshot| ((snapshot, started.elapsed()), spec))
+                        .map_err(|e| PollError::Fetch(e.to_string()))
+                }
+                Err(e) => Err(PollError::from_build(e, PollError::Init)),
+            };
+            let _ = tx.send(result);
+        });
+        self.track_oneshot_fetch(handle);
+    }
+
+    fn track_oneshot_fetch(&mut self, handle: tokio::task::JoinHandle<()>) {
+        self.oneshot_fetches.retain(|handle| !handle.is_finished());
+        self.oneshot_fetches.push(handle);
+    }
+
+    /// Stop all background acquisition: autopolling, the Connect buttons' tasks and one-shot
+    /// reconnects. Aborting drops their SNMP sockets and SSH sessions at the next await point.
+    fn shutdown(&mut self) {
+        self.stop_autopoll();
+        self.ssh_connect_task = None;
+        self.netconf_connect_task = None;
+        self.snmp_connect_task = None;
+        self.reconnect_tasks.clear();
+        for handle in self.oneshot_fetches.drain(..) {
+            handle.abort();
+        }
+    }
+
+    fn handle_startup_connects(&mut self, ctx: &egui::Context) {
+        let mut reload_needed = false;
+        while let Ok(result) = self.startup_rx.try_recv() {
+            match result {
+                Ok((((src_id, nodes, stats, warnings), elapsed), spec)) => {
+                    info_log!("[app] Connected startup source {}", src_id);
+                    self.source_specs.insert(src_id.clone(), spec);
+                    let now = std::time::SystemTime::now();
+                    self.store
+                        .replace_partition_with_warnings(&src_id, nodes, stats, warnings, now);
+                    self.store.record_fetch(&src_id, elapsed);
+                    reload_needed = true;
+                }
+                Err(e) => eprintln!("[app] Startup source failed: {}", e),
+            }
+            ctx.request_repaint();
+        }
+        if reload_needed {
+            self.save_session();
+            self.reload_graph_logged("connecting startup sources");
+        }
+    }
+
+    /// Write the known sources and the reconnect setting to the session file.
+    fn save_session(&self) {
+        let mut sources: Vec<SavedSource> = self
+            .source_specs
+            .iter()
+            .filter_map(|(src_id, spec)| SavedSource::from_spec(src_id, spec))
+            .collect();
+        sources.sort_by_key(|saved| saved.source_id.to_string());
+        Session {
+            reconnect_on_launch: self.reconnect_on_launch,
+            palette: palette::palette(),
+            sources,
+        }
+        .save();
+    }
     
     fn start_autopoll(&mut self) {
         
@@ -271,27 +731,44 @@ impl App {
         );
         
         self.autopoll_interval_tx = Some(interval_tx.clone());
-        
-        for (src_id, spec) in self.source_specs.iter() {
+
+        // Deterministic ordering so the ramp slots stay stable across restarts
+        let mut specs: Vec<(&SourceId, &SourceSpec)> = self.source_specs.iter().collect();
+        specs.sort_by_key(|(src_id, _)| src_id.to_string());
+        let source_count = specs.len();
+        let initial_interval = *interval_rx.borrow();
+        let fetch_permits = Arc::new(Semaphore::new(self.autopoll_max_concurrent.max(1)));
+
+        for (slot, (src_id, spec)) in specs.into_iter().enumerate() {
             let poll_tx = poll_tx.clone();
             let src_id = src_id.clone();
+            let src_id_for_handle = src_id.clone();
             let spec = spec.clone();
+            let fetch_permits = fetch_permits.clone();
+            let fetches_in_flight = self.fetches_in_flight.clone();
             let mut interval_rx = interval_rx.clone();
+            let initial_delay = initial_poll_delay(
+                &src_id,
+                slot,
+                source_count,
+                initial_interval,
+                self.autopoll_stagger_window,
+                self.autopoll_stagger_ramp,
+            );
             let handle = self.runtime.spawn(async move {
-                let mut hasher = DefaultHasher::new();
-                src_id.hash(&mut hasher);
-                let jitter = Duration::from_millis(hasher.finish() % 250);
-                tokio::time::sleep(jitter).await;
+                tokio::time::sleep(initial_delay).await;
                 
                 let mut source = match spec.build_topology().await {
                     Ok(topology) => {
                         Some(topology)
                     }
                     Err(e) => {
-                        let _ = poll_tx.send(Err(format!("Init failed: {}", e)));
+                        let _ = poll_tx.send((src_id.clone(), Err(PollError::from_build(e, PollError::Init))));
                         None
                     }
                 };
+                // Whether the next fetch is the first on a freshly built source, for the latency log
+                let mut fresh_source = true;
                 let mut current_interval = *interval_rx.borrow();
                 let mut ticker = tokio::time::interval(current_interval);
                 loop {
@@ -299,22 +776,41 @@ impl App {
                         _ = ticker.tick() => {
                             if source.is_none() {
                                 match spec.build_topology().await {
-                                    Ok(s) => source = Some(s),
+                                    Ok(s) => {
+                                        source = Some(s);
+                                        fresh_source = true;
+                                    }
                                     Err(e) => {
-                                        let _ = poll_tx.send(Err(format!("reinit failed: {}", e)));
+                                        let _ = poll_tx.send((src_id.clone(), Err(PollError::from_build(e, PollError::Reinit))));
                                         continue;
                                     }
                                 }
                             }
-                            match source.as_mut().unwrap().fetch_snapshot().await {
-                                Ok((id, nodes, stats)) => {
-                                    let _ = poll_tx.send(Ok((id, nodes, stats)));
-                                }
-                                Err(e) => {
-                                    source = None; // force rebuild next tick
-                                    let _ = poll_tx.send(Err(format!("poll failed: {}", e)));
+                            let Some(_in_flight) = FetchGuard::try_acquire(&fetches_in_flight, &src_id) else {
+                                debug_log!("[autopoll] {} skipped, a refresh is running", src_id);
+                                continue;
+                            };
+                            // Held until the fetch finishes; the semaphore is never closed
+                            let Ok(_permit) = fetch_permits.acquire().await else {
+                                break;
+                            };
+                            let started = std::time::Instant::now();
+                            let timeout = poll_timeout(current_interval);
+                            let fetch = source.as_mut().unwrap().fetch_snapshot();
+                            let result = match tokio::time::timeout(timeout, fetch).await {
+                                Ok(Ok(snapshot)) => Ok((snapshot, started.elapsed())),
+                                Ok(Err(e)) => Err(PollError::Fetch(e.to_string())),
+                                Err(_) => Err(PollError::Timeout(timeout)),
+                            };
+                            match &result {
+                                Ok((_, elapsed)) => {
+                                    let session = if fresh_source { "new" } else { "reused" };
+                                    debug_log!("[autopoll] {} polled in {:?} ({} session)", src_id, elapsed, session);
                                 }
+                                Err(_) => source = None, // force rebuild next tick
                             }
+                            fresh_source = false;
+                            let _ = poll_tx.send((src_id.clone(), result));
                         }
                         // Interval change notification
                         changed = interval_rx.changed() => {
@@ -331,12 +827,245 @@ impl App {
                     }
                 }
             });
-            self.autopoll_handles.push(handle);
+            self.autopoll_handles.insert(src_id_for_handle, handle);
         }
     }
     
+    /// Spawn a background rebuild + fetch for every Lost source with a known `SourceSpec`.
+    /// Results are applied in `handle_reconnect_results()`.
+    fn retry_lost_sources(&mut self) {
+        let lost: Vec<(SourceId, SourceSpec)> = self
+            .store
+            .sources_iter()
+            .filter(|(_, state)| state.health == SourceHealth::Lost)
+            .filter_map(|(src_id, _)| {
+                self.source_specs
+                    .get(src_id)
+                    .map(|spec| (src_id.clone(), spec.clone()))
+            })
+            .collect();
+
+        for (src_id, spec) in lost {
+            info_log!("[app] Retrying lost source {}", src_id);
+            self.spawn_reconnect(src_id, spec);
+        }
+    }
+
+    /// Rebuild `spec` and fetch a snapshot in the background, reported to `handle_reconnect_results()`.
+    /// Does nothing if a fetch for the source is already running.
+    fn spawn_reconnect(&mut self, src_id: SourceId, spec: SourceSpec) {
+        let Some(guard) = FetchGuard::try_acquire(&self.fetches_in_flight, &src_id) else {
+            info_log!("[app] {} is already being fetched", src_id);
+            return;
+        };
+        let task = BackgroundTask::spawn(&self.runtime, |_| async move {
+            let _guard = guard;
+            let mut source = spec.build_topology().await.map_err(|e| PollError::from_build(e, PollError::Reinit))?;
+            let started = std::time::Instant::now();
+            source
+                .fetch_snapshot()
+                .await
+                .map(|snapshot| (snapshot, started.elapsed()))
+                .map_err(|e| PollError::Fetch(e.to_string()))
+        });
+        self.reconnect_tasks.push((src_id, task));
+    }
+
+    /// Forget every known source, and the keyring entries of those `keep` doesn't share.
+    fn clear_source_specs(&mut self, keep: &SourceSpec) {
+        for spec in self.source_specs.values() {
+            if spec.secret() != keep.secret() {
+                spec.secret().forget();
+            }
+        }
+        self.source_specs.clear();
+    }
+
+    /// Remove every Lost source: its autopoll task, connection details and partition.
+    /// The graph is reloaded once afterwards.
+    fn remove_lost_sources(&mut self) {
+        let lost: Vec<SourceId> = self
+            .store
+            .sources_iter()
+            .filter(|(_, state)| state.health == SourceHealth::Lost)
+            .map(|(src_id, _)| src_id.clone())
+            .collect();
+        if lost.is_empty() {
+            return;
+        }
+
+        for src_id in &lost {
+            info_log!("[app] Removing lost source {}", src_id);
+            self.remove_source(src_id);
+        }
+        if let Err(e) = self.reload_graph() {
+            eprintln!("[app] Error reloading graph after removing lost sources: {:?}", e);
+        }
+        self.save_session();
+    }
+
+    /// Forget a source: its autopoll task, pending reconnect, connection details, prefix report
+    /// and partition. The graph isn't reloaded.
+    fn remove_source(&mut self, src_id: &SourceId) {
+        // Abort first so an in-flight poll can't re-add the partition
+        if let Some(handle) = self.autopoll_handles.remove(src_id) {
+            handle.abort();
+        }
+        self.reconnect_tasks.retain(|(id, _)| id != src_id);
+        if let Some(spec) = self.source_specs.remove(src_id) {
+            spec.secret().forget();
+        }
+        self.isis_prefix_reports.remove(src_id);
+        if let Err(e) = self.store.remove_partition(src_id) {
+            eprintln!("[app] Failed to remove partition {}: {}", src_id, e);
+        }
+    }
+
+    fn handle_reconnect_results(&mut self, ctx: &egui::Context) {
+        let mut reload_needed = false;
+        let mut finished = Vec::new();
+        self.reconnect_tasks.retain_mut(|(src_id, task)| match task.poll() {
+            Poll::Ready(result) => {
+                finished.push((src_id.clone(), result));
+                false
+            }
+            Poll::Pending => true,
+        });
+        for (src_id, result) in finished {
+            if !self.source_specs.contains_key(&src_id) {
+                continue;
+            }
+            let now = std::time::SystemTime::now();
+            match result {
+                Ok(((id, nodes, stats, warnings), elapsed)) => {
+                    info_log!("[app] Reconnected source {}", id);
+                    self.store
+                        .replace_partition_with_warnings(&id, nodes, stats, warnings, now);
+                    self.store.record_fetch(&id, elapsed);
+                    reload_needed = true;
+                }
+                Err(e) => {
+                    eprintln!("[app] Reconnect of source {} failed: {}", src_id, e);
+                    if e.marks_lost() {
+                        self.store.mark_lost(&src_id, now);
+                    }
+                }
+            }
+            ctx.request_repaint();
+        }
+        if reload_needed
+            && let Err(e) = self.reload_graph()
+        {
+            eprintln!("[app] Error reloading graph after reconnect: {:?}", e);
+        }
+    }
+
+    fn snmp_summary_policy(&self) -> ConsolidationPolicy {
+        if self.snmp_keep_summaries {
+            ConsolidationPolicy::KeepSummaries
+        } else {
+            ConsolidationPolicy::FoldSummaries
+        }
+    }
+
+    /// Host key policy set by the checkboxes, also stored with the sources for their reconnects.
+    fn ssh_host_key_policy(&self) -> HostKeyPolicy {
+        match (self.ssh_verify_host_keys, self.ssh_trust_on_first_use) {
+            (false, _) => HostKeyPolicy::Off,
+            (true, true) => HostKeyPolicy::TrustOnFirstUse,
+            (true, false) => HostKeyPolicy::Verify,
+        }
+    }
+
+    /// Connect and take the first snapshot on the shared runtime; the result is picked up by
+    /// render() once the task finishes.
+    fn start_ssh_connect(&mut self) {
+        let host = self.ssh_host.clone();
+        let port = self.ssh_port;
+        let username = self.ssh_username.clone();
+        let password = self.ssh_password.clone();
+        let connect_timeout = self.ssh_connect_timeout;
+        let host_key_policy = self.ssh_host_key_policy();
+        // A key the user just confirmed is only trusted for this connect, after that it's in
+        // known_hosts
+        let connect_policy = match self.ssh_accepted_host_key.take() {
+            Some(fingerprint) => HostKeyPolicy::Accept(fingerprint),
+            None => host_key_policy.clone(),
+        };
+        self.ssh_connect_error = None;
+
+        self.ssh_connect_task = Some(BackgroundTask::spawn(&self.runtime, |phase| async move {
+            phase.set("Connecting…");
+            let client =
+                SshClient::new_with_password(username.clone(), host.clone(), password.clone(), port)
+                    .with_connect_timeout(connect_timeout)
+                    .with_host_key_policy(connect_policy);
+            let mut topo = IsIsTopology::new_from_ssh_client(client)
+                .await
+                .map_err(PollError::from)?;
+            let started = std::time::Instant::now();
+            let snapshot = fetch_snapshot_with_phases(&mut topo, &phase)
+                .await
+                .map_err(|e| PollError::Fetch(e.to_string()))?;
+            let elapsed = started.elapsed();
+            println!("[bg-ssh] snapshot fetch succeeded, src_id={:?}, nodes_count={}", snapshot.0, snapshot.1.len());
+            let source_spec = SourceSpec::new_ssh(
+                host,
+                port,
+                username,
+                password,
+                connect_timeout,
+                crate::gui::autopoll::ProtocolKind::Isis
+            )
+            .with_host_key_policy(host_key_policy);
+            Ok(((snapshot, elapsed), source_spec))
+        }));
+    }
+
+    fn start_netconf_connect(&mut self) {
+        let host = self.netconf_host.clone();
+        let port = self.netconf_port;
+        let username = self.netconf_username.clone();
+        let password = self.netconf_password.clone();
+        // Shares the SSH connect timeout, it's the same transport
+        let connect_timeout = self.ssh_connect_timeout;
+        let host_key_policy = self.ssh_host_key_policy();
+        let connect_policy = match self.ssh_accepted_host_key.take() {
+            Some(fingerprint) => HostKeyPolicy::Accept(fingerprint),
+            None => host_key_policy.clone(),
+        };
+        self.netconf_connect_error = None;
+
+        self.netconf_connect_task = Some(BackgroundTask::spawn(&self.runtime, |phase| async move {
+            phase.set("Connecting…");
+            let client =
+                SshClient::new_with_password(username.clone(), host.clone(), password.clone(), port)
+                    .with_connect_timeout(connect_timeout)
+                    .with_host_key_policy(connect_policy);
+            let mut topo = IsIsNetconfTopology::new_from_netconf_client(NetconfClient::new(client))
+                .await
+                .map_err(PollError::from)?;
+            let started = std::time::Instant::now();
+            let snapshot = fetch_snapshot_with_phases(&mut topo, &phase)
+                .await
+                .map_err(|e| PollError::Fetch(e.to_string()))?;
+            let elapsed = started.elapsed();
+            debug_log!("[bg-netconf] snapshot fetch succeeded, src_id={:?}, nodes_count={}", snapshot.0, snapshot.1.len());
+            let source_spec = SourceSpec::new_netconf(
+                host,
+                port,
+                username,
+                password,
+                connect_timeout,
+                crate::gui::autopoll::ProtocolKind::Isis
+            )
+            .with_host_key_policy(host_key_policy);
+            Ok(((snapshot, elapsed), source_spec))
+        }));
+    }
+
     fn stop_autopoll(&mut self) {
-        for h in self.autopoll_handles.drain(..) {
+        for (_, h) in self.autopoll_handles.drain() {
             h.abort();
         }
         self.poll_tx = None;
@@ -345,121 +1074,447 @@ impl App {
     }
 
     fn read_data(&mut self) {
-        if let Some(node_index) = self.graph.graph.selected_nodes().first() {
-            self.selected_node = Some(*node_index);
+        self.selected_nodes = self.graph.graph.selected_nodes().to_vec();
+        match self.selected_nodes.as_slice() {
+            [node_index] => self.selected_node = Some(*node_index),
+            [_, _, ..] => self.selected_node = None,
+            [] => {}
         }
     }
 
-    fn apply_edge_traffic_weights(&mut self) {
-        for (src_id, state) in self.store.sources_iter() {
-            let src_uuid = src_id.to_uuidv5();
-            let src_node_idx = self.graph.node_id_to_index_map.get(&src_uuid);
-            let src_node_idx = if let Some(idx) = src_node_idx {
-                idx.clone()
-            } else {
-                continue;
-            };
-
-            let edges: Vec<_> = self
+    /// Ring the nodes reported by the same source as the single selected node.
+    fn refresh_source_highlight(&self) {
+        let source_id = match self.selected_nodes.as_slice() {
+            [idx] => self
                 .graph
                 .graph
-                .edges_directed(src_node_idx, petgraph::Direction::Outgoing)
-                .collect();
-            
-            if edges.len() < 2 {
-                continue;
-            }
-            
-            let mut prefix_to_dst_uuid: HashMap<IpNetwork, Uuid> = edges.iter()
-                .filter_map(|edge| {
-                    let dst_uuid = edge.weight().payload().destination_id;
-                    let dst_node_idx = self
-                        .graph
-                        .node_id_to_index_map
-                        .get(&dst_uuid)
-                        .unwrap()
-                        .clone();
-                    let dst_node = self.graph.graph.node(dst_node_idx).unwrap();
-                    if let NodeInfo::Network(net) = &dst_node.payload().info {
-                        Some((net.ip_address, edge.weight().payload().destination_id))
-                    } else {
-                        None
+                .node(*idx)
+                .and_then(|node| node.payload().source_id.clone()),
+            _ => None,
+        };
+        node_shape::highlight_source(source_id);
+    }
+
+    /// Make `idx` the only selected node.
+    fn select_only(&mut self, idx: NodeIndex) {
+        self.clear_selection();
+        if let Some(node) = self.graph.graph.node_mut(idx) {
+            node.set_selected(true);
+            self.selected_nodes = vec![idx];
+            self.selected_node = Some(idx);
+        }
+    }
+
+    /// While drawing an edge, ring the nodes the first pick can connect to and grey out the rest.
+    fn update_draw_preview(&self) {
+        let first = self
+            .draw_first
+            .filter(|_| matches!(self.edit_tool, EditTool::Draw))
+            .and_then(|idx| self.graph.graph.node(idx));
+        let Some(first) = first else {
+            node_shape::set_draw_preview(None);
+            return;
+        };
+        let targets = self
+            .graph
+            .graph
+            .nodes_iter()
+            .filter(|(_, n)| drawable_edge_kind(&first.payload().info, &n.payload().info).is_some())
+            .map(|(_, n)| n.payload().id)
+            .collect();
+        node_shape::set_draw_preview(Some((first.payload().id, targets)));
+    }
+
+    /// Select the next (or previous) node in the graph's stable order, wrapping around.
+    fn cycle_selected_node(&mut self, backwards: bool) {
+        let order = self.graph.ordered_node_indices();
+        if order.is_empty() {
+            return;
+        }
+        let current = self
+            .selected_node
+            .and_then(|sel| order.iter().position(|idx| *idx == sel));
+        let next = match (current, backwards) {
+            (None, false) => 0,
+            (None, true) => order.len() - 1,
+            (Some(i), false) => (i + 1) % order.len(),
+            (Some(i), true) => (i + order.len() - 1) % order.len(),
+        };
+        self.select_only(order[next]);
+    }
+
+    fn render_search_controls(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.search_query)
+                    .hint_text("IP, prefix, label or router id"),
+            );
+            let submitted =
+                response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            if submitted || ui.button("Find").clicked() {
+                match self.graph.search(&self.search_query) {
+                    Some(idx) => {
+                        self.search_miss = false;
+                        self.select_only(idx);
                     }
-                })
-                .collect();
+                    None => self.search_miss = true,
+                }
+            }
+        });
+        if self.search_miss {
+            ui.colored_label(self.theme.red, "No matching node");
+        }
+    }
 
-            let total_weight: f32 = state
-                .interface_stats
-                .iter()
-                .map(|stats| stats.get_weight() as f32)
-                .sum();
+    /// Pick up the edge under the pointer found while drawing and highlight its endpoints. Nodes
+    /// are drawn with last frame's value, so a change asks for one more frame.
+    fn update_hovered_edge(&mut self, ctx: &Context, response: &egui::Response) {
+        let hovered_edge = edge_shape::take_hovered_edge().filter(|_| {
+            // A node under the pointer wins over the edges ending at it
+            response.hovered() && !self.graph.graph.nodes_iter().any(|(_, node)| node.hovered())
+        });
+        if hovered_edge != self.hovered_edge {
+            self.hovered_edge = hovered_edge;
+            node_shape::set_hovered_edge_endpoints(hovered_edge);
+            ctx.request_repaint();
+        }
+    }
 
-            for stats in state.interface_stats.iter() {
-                if stats.ip_address.is_loopback() {
-                    continue;
+    /// Rubber-band selection: a drag that starts on the empty canvas selects every node drawn
+    /// inside the rectangle, added to the current selection while shift is held. Drags starting
+    /// on a node, and drags in draw mode, are left to the graph view.
+    fn handle_box_select(&mut self, ui: &mut Ui, response: &egui::Response) {
+        let positions = node_shape::take_node_screen_positions();
+        if response.drag_started_by(egui::PointerButton::Primary) {
+            let on_node = self.graph.graph.nodes_iter().any(|(_, node)| node.hovered());
+            let drawing = matches!(self.edit_tool, EditTool::Draw);
+            self.box_select_origin = response.interact_pointer_pos().filter(|_| !on_node && !drawing);
+        }
+        let Some(origin) = self.box_select_origin else {
+            return;
+        };
+        let Some(current) = ui.input(|i| i.pointer.latest_pos()) else {
+            return;
+        };
+        let rect = egui::Rect::from_two_pos(origin, current);
+        let selection = ui.visuals().selection;
+        ui.painter().rect_filled(rect, 0.0, selection.bg_fill.gamma_multiply(0.15));
+        ui.painter()
+            .rect_stroke(rect, 0.0, selection.stroke, egui::StrokeKind::Inside);
+
+        if !response.drag_stopped() {
+            return;
+        }
+        self.box_select_origin = None;
+        if !ui.input(|i| i.modifiers.shift) {
+            self.clear_selection();
+        }
+        let inside: Vec<NodeIndex> = positions
+            .into_iter()
+            .filter(|(_, pos)| rect.contains(*pos))
+            .filter_map(|(id, _)| self.graph.node_id_to_index_map.get(&id).copied())
+            .collect();
+        for idx in inside {
+            if let Some(node) = self.graph.graph.node_mut(idx) {
+                node.set_selected(true);
+                if !self.selected_nodes.contains(&idx) {
+                    self.selected_nodes.push(idx);
                 }
+            }
+        }
+        self.selected_node = match self.selected_nodes.as_slice() {
+            [idx] => Some(*idx),
+            _ => None,
+        };
+    }
 
-                let prefix = prefix_to_dst_uuid.iter().find_map(|(prefix, _)| {
-                    if prefix.contains(stats.ip_address) {
-                        Some(prefix)
-                    } else {
-                        None
-                    }
+    fn clear_selection(&mut self) {
+        for idx in self.selected_nodes.drain(..) {
+            if let Some(node) = self.graph.graph.node_mut(idx) {
+                node.set_selected(false);
+            }
+        }
+        self.selected_node = None;
+    }
+
+    /// Clicking a collapsed area expands it back into its routers and networks.
+    fn expand_selected_area(&mut self) {
+        let [idx] = self.selected_nodes.as_slice() else {
+            return;
+        };
+        let Some(area) = self
+            .graph
+            .graph
+            .node(*idx)
+            .and_then(|node| self.graph.collapsed_area(&node.payload().id))
+            .cloned()
+        else {
+            return;
+        };
+        info_log!("[app] Expanding {}", area);
+        self.expanded_areas.insert(area);
+        self.clear_selection();
+        if let Err(e) = self.reload_graph() {
+            eprintln!("[app] Failed to reload graph: {}", e);
+        }
+    }
+
+    /// Hide the selected nodes from the view (reversible via "Unhide all").
+    fn hide_selected_nodes(&mut self) {
+        let uuids: Vec<Uuid> = self
+            .selected_nodes
+            .iter()
+            .filter_map(|idx| self.graph.graph.node(*idx).map(|n| n.payload().id))
+            .collect();
+        self.clear_selection();
+        self.hidden_nodes.extend(uuids);
+        if let Err(e) = self.reload_graph() {
+            eprintln!("[app] Failed to reload graph: {}", e);
+        }
+    }
+
+    fn render_multi_selection_panel(&mut self, ctx: &Context, anchor: egui::Pos2) {
+        let mut router_count = 0usize;
+        let mut network_count = 0usize;
+        let mut labels: Vec<String> = Vec::new();
+        for idx in &self.selected_nodes {
+            let Some(node) = self.graph.graph.node(*idx) else {
+                continue;
+            };
+            match node.payload().info {
+                NodeInfo::Router(_) => router_count += 1,
+                NodeInfo::Network(_) => network_count += 1,
+                NodeInfo::Area(_) => {}
+            }
+            labels.push(node.label().to_string());
+        }
+
+        let mut hide = false;
+        let mut use_as_path = false;
+        let id = Id::new("multi_selection_panel");
+        let resp = FloatingNodePanel::new(id, anchor)
+            .title(format!("{} nodes", self.selected_nodes.len()))
+            .show(ctx, |ui, _ctx| {
+                ui.label(format!(
+                    "{} routers, {} networks selected",
+                    router_count, network_count
+                ));
+                collapsible_section(ui, "Selected nodes", false, |ui| {
+                    bullet_list(ui, labels.iter());
                 });
-                let prefix = if let Some(prefix) = prefix {
-                    prefix.clone()
-                } else {
-                    dbg!("No prefix found for IP address: {}", stats.ip_address);
-                    dbg!(&prefix_to_dst_uuid);
-                    return;
-                };
+                ui.separator();
+                ui.horizontal(|ui| {
+                    hide = ui.button("Hide selected").clicked();
+                    use_as_path = ui
+                        .button("Use as path endpoints")
+                        .on_hover_text("First selected node becomes the start, last becomes the end, the rest waypoints in selection order")
+                        .clicked();
+                });
+                if ui.button("Print selected as JSON").clicked() {
+                    let nodes: Vec<&Node> = self
+                        .selected_nodes
+                        .iter()
+                        .filter_map(|idx| self.graph.graph.node(*idx).map(|n| n.payload()))
+                        .collect();
+                    match serde_json::to_string_pretty(&nodes) {
+                        Ok(json) => println!("{}", json),
+                        Err(e) => eprintln!("[app] Failed to serialize selection: {}", e),
+                    }
+                }
+            });
+        let clear = resp.close_clicked;
 
-                let weight = stats.get_weight() as f32 / total_weight;
-                let dst_uuid = prefix_to_dst_uuid.remove(&prefix).unwrap();
-                println!(
-                    "Setting weight for {} -> {} to {}",
-                    src_uuid, &prefix, weight
-                );
-                edge_shape::insert_edge_weight(src_uuid, dst_uuid, weight);
+        if use_as_path {
+            self.path_start = self.selected_nodes.first().copied();
+            self.path_end = self.selected_nodes.last().copied();
+            self.path_waypoints = match self.selected_nodes.len() {
+                0..=2 => Vec::new(),
+                n => self.selected_nodes[1..n - 1].to_vec(),
+            };
+        }
+        if hide {
+            self.hide_selected_nodes();
+        } else if clear {
+            self.clear_selection();
+        }
+    }
+
+    /// Graph node of the router a source was polled from; in the un-fused view that is the
+    /// source's own copy.
+    fn source_router_uuid(&self, src_id: &SourceId) -> Uuid {
+        if self.merge_config.unfused() {
+            source_scoped_uuid(src_id.to_uuidv5(), src_id)
+        } else {
+            src_id.to_uuidv5()
+        }
+    }
+
+    /// Recompute every edge's traffic weight from the sources' interface stats, replacing the old ones.
+    fn apply_edge_traffic_weights(&mut self) {
+        self.interface_mappings.clear();
+        let mut edge_weights: HashMap<(Uuid, Uuid), f32> = HashMap::new();
+        let mut skipped = 0;
+        for (src_id, state) in self.store.sources_iter() {
+            let src_uuid = self.source_router_uuid(src_id);
+            let Some(mappings) = self.graph.map_interfaces(src_uuid, &state.interface_stats) else {
+                continue;
+            };
+            self.interface_mappings.push((src_id.clone(), mappings.clone()));
+
+            let out_degree = self
+                .graph
+                .node_id_to_index_map
+                .get(&src_uuid)
+                .map(|idx| {
+                    self.graph
+                        .graph
+                        .edges_directed(*idx, petgraph::Direction::Outgoing)
+                        .count()
+                })
+                .unwrap_or(0);
+            if out_degree < 2 {
+                continue;
             }
+
+            let (weights, unmapped) = interface_traffic_weights(&state.interface_stats, &mappings);
+            if unmapped > 0 {
+                debug_log!("[app] {} interface(s) of {} match no edge", unmapped, src_id);
+            }
+            skipped += unmapped;
+            for (dst_uuid, weight) in weights {
+                debug_log!("[app] Setting weight for {} -> {} to {}", src_uuid, dst_uuid, weight);
+                edge_weights.insert((src_uuid, dst_uuid), weight);
+            }
+        }
+        if skipped > 0 {
+            info_log!("[app] Traffic weighting skipped {} unmapped interface(s) in total", skipped);
         }
+        edge_shape::set_edge_weights(edge_weights);
     }
 
-    fn render_sources_section(&mut self, ui: &mut Ui) {
-        CollapsingHeader::new("Sources")
+    /// Per-source list of polled interfaces and the edge each one was mapped to, unmapped ones
+    /// highlighted. Traffic weights are only drawn for mapped interfaces.
+    fn render_interface_mappings(&self, ui: &mut Ui) {
+        CollapsingHeader::new("Interface mapping")
             .default_open(false)
             .show(ui, |ui| {
-                egui::ScrollArea::vertical()
-                    .max_height(300.0)
-                    .show(ui, |ui| {
-                        if ui.button("Print store data").clicked() {
-                            println!("[app] Pressed print store data button");
-                            let json = serde_json::to_string_pretty(&self.store);
+                info_icon(
+                    ui,
+                    "Interfaces are matched to the network whose prefix contains their address. \
+                     Loopbacks are skipped.",
+                );
+                if self.interface_mappings.is_empty() {
+                    ui.label("No source router with interface stats is shown");
+                    return;
+                }
+                for (src_id, mappings) in &self.interface_mappings {
+                    let unmapped = mappings.iter().filter(|m| m.target.is_none()).count();
+                    let title = if unmapped > 0 {
+                        format!("{} ({} unmapped)", src_id, unmapped)
+                    } else {
+                        src_id.to_string()
+                    };
+                    CollapsingHeader::new(title)
+                        .id_salt(("interface_mapping", src_id.to_string()))
+                        .default_open(unmapped > 0)
+                        .show(ui, |ui| {
+                            for mapping in mappings {
+                                match mapping.target {
+                                    Some((prefix, dst_uuid)) => {
+                                        let label = self
+                                            .graph
+                                            .node_label(dst_uuid)
+                                            .unwrap_or_else(|| prefix.to_string());
+                                        ui.label(format!("{} → {}", mapping.ip_address, label));
+                                    }
+                                    None => {
+                                        ui.colored_label(
+                                            self.theme.red,
+                                            format!("{} → no matching edge", mapping.ip_address),
+                                        );
+                                    }
+                                }
+                            }
+                        });
+                }
+            });
+    }
+
+    fn render_sources_section(&mut self, ui: &mut Ui) {
+        CollapsingHeader::new("Sources")
+            .default_open(false)
+            .show(ui, |ui| {
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        let lost_with_spec = self
+                            .store
+                            .sources_iter()
+                            .filter(|(src_id, state)| {
+                                state.health == SourceHealth::Lost
+                                    && self.source_specs.contains_key(*src_id)
+                            })
+                            .count();
+                        if ui
+                            .checkbox(&mut self.reconnect_on_launch, "Reconnect on launch")
+                            .on_hover_text("Reconnect the current sources the next time the app starts")
+                            .changed()
+                        {
+                            self.save_session();
+                        }
+                        ui.horizontal(|ui| {
+                            if ui
+                                .add_enabled(
+                                    lost_with_spec > 0 && self.reconnect_tasks.is_empty(),
+                                    egui::Button::new(format!("Retry lost sources ({})", lost_with_spec)),
+                                )
+                                .on_hover_text("Reconnect and re-fetch every Lost source with known connection details")
+                                .clicked()
+                            {
+                                self.retry_lost_sources();
+                            }
+                            if !self.reconnect_tasks.is_empty() {
+                                ui.spinner();
+                            }
+                        });
+                        let lost_count = self
+                            .store
+                            .sources_iter()
+                            .filter(|(_, state)| state.health == SourceHealth::Lost)
+                            .count();
+                        if ui
+                            .add_enabled(
+                                lost_count > 0,
+                                egui::Button::new(format!("Remove all lost sources ({})", lost_count)),
+                            )
+                            .on_hover_text("Remove every Lost source and its partition from the store")
+                            .clicked()
+                        {
+                            self.remove_lost_sources();
+                        }
+
+                        if ui.button("Print store data").clicked() {
+                            println!("[app] Pressed print store data button");
+                            let json = serde_json::to_string_pretty(&self.store);
                             match json {
                                 Ok(json) => println!("{}", json),
                                 Err(err) => println!("Error serializing store data: {}", err)
                             }
                         }
 
-                        let mut rows: Vec<_> = self.store.sources_iter()
-                            .map(|(src_id, state): (&SourceId, &SourceState)| {
-                                (
-                                    src_id.clone(),
-                                    state.health.clone(),
-                                    state.partition.nodes.len(),
-                                    state.last_snapshot.clone(),
-                                    state.interface_stats.clone()
-                                )
-                            })
+                        let mut rows: Vec<SourceRow> = self.store.sources_iter()
+                            .map(|(src_id, state)| SourceRow::new(src_id, state))
                             .collect();
-                        rows.sort_by(|this, other| this.3.cmp(&other.3));
+                        rows.sort_by(|this, other| this.last_snapshot.cmp(&other.last_snapshot));
 
                         let mut sources_to_remove: Vec<SourceId> = Vec::new();
-                        let mut source_enable_states: HashMap<SourceId, bool> = rows.iter().map(|(src_id, _, _, _, _)| {
-                            let enabled = self.merge_config.is_source_enabled(src_id);
-                            (src_id.clone(), enabled)
+                        let mut sources_to_refresh: Vec<SourceId> = Vec::new();
+                        let mut source_enable_states: HashMap<SourceId, bool> = rows.iter().map(|row| {
+                            let enabled = self.merge_config.is_source_enabled(&row.src_id);
+                            (row.src_id.clone(), enabled)
                         }).collect();
 
+                        let warning_color = self.theme.yellow;
                         let table = TableBuilder::new(ui)
                             .striped(true)
                             .resizable(true)
@@ -467,6 +1522,7 @@ impl App {
                             .column(Column::auto().at_least(70.0))
                             .column(Column::auto().at_least(55.0))
                             .column(Column::auto().at_least(145.0))
+                            .column(Column::auto().at_least(55.0))
                             .column(Column::auto().at_least(40.0))
                             .column(Column::auto().at_least(55.0))
                             .column(Column::auto().at_least(20.0));
@@ -476,26 +1532,41 @@ impl App {
                                 header.col(|ui| { ui.strong("Health"); });
                                 header.col(|ui| { ui.strong("#Nodes"); });
                                 header.col(|ui| { ui.strong("Last snapshot (s)"); });
+                                header.col(|ui| { ui.strong("Fetch"); });
                                 header.col(|ui| { ui.strong("IfStats"); });
                                 header.col(|ui| { ui.strong("Actions"); });
                                 header.col(|ui| { ui.strong("Enabled"); });
                             })
                             .body(|mut body| {
-                                rows.sort_by(|(src_id_a, _, _, _, _), (src_id_b, _, _, _, _)| {
-                                    src_id_a.as_string().cmp(&src_id_b.to_string())
-                                });
-                                for (src_id, health, nodes_count, last_snapshot, if_stats) in rows {
+                                rows.sort_by(|a, b| a.src_id.as_string().cmp(&b.src_id.to_string()));
+                                for SourceRow { src_id, health, nodes_count, last_snapshot, if_stats, warnings, acquisition } in rows {
                                     body.row(22.0, |mut row| {
-                                        row.col(|ui| { ui.label(src_id.to_string()); });
+                                        row.col(|ui| { ui.label(anonymized(&src_id)); });
                                         row.col(|ui| { ui.label(health.to_string()); });
-                                        row.col(|ui| { ui.label(nodes_count.to_string()); });
+                                        row.col(|ui| {
+                                            if warnings.is_empty() {
+                                                ui.label(nodes_count.to_string());
+                                                return;
+                                            }
+                                            let text = format!("{} (⚠ {} skipped)", nodes_count, warnings.len());
+                                            ui.colored_label(warning_color, text).on_hover_ui(|ui| {
+                                                ui.label("Records skipped as malformed in the last snapshot:");
+                                                for warning in warnings.iter().take(20) {
+                                                    ui.label(warning.to_string());
+                                                }
+                                                if warnings.len() > 20 {
+                                                    ui.label(format!("… and {} more", warnings.len() - 20));
+                                                }
+                                            });
+                                        });
                                         row.col(|ui| { ui.label(humantime::format_rfc3339_seconds(last_snapshot).to_string()); });
+                                        row.col(|ui| { render_acquisition_metrics(ui, &acquisition); });
 
                                         // IfStats column
                                         row.col(|ui| {
                                             let response = ui.link("ℹ");
                                             let tooltip_closure = |ui: &mut Ui| {
-                                                ui.set_width(420.0);
+                                                ui.set_width(640.0);
                                                 ui.label("Interface Stats");
                                                 ui.separator();
 
@@ -506,7 +1577,9 @@ impl App {
                                                     .column(Column::auto().at_least(70.0))  // RX bytes
                                                     .column(Column::auto().at_least(70.0))  // TX bytes
                                                     .column(Column::auto().at_least(70.0))  // RX packets
-                                                    .column(Column::auto().at_least(70.0)); // TX packets
+                                                    .column(Column::auto().at_least(70.0))  // TX packets
+                                                    .column(Column::auto().at_least(100.0)) // RX rate
+                                                    .column(Column::auto().at_least(100.0)); // TX rate
 
                                                 stats_table
                                                     .header(18.0, |mut h| {
@@ -515,15 +1588,19 @@ impl App {
                                                         h.col(|ui| { ui.strong("TX B"); });
                                                         h.col(|ui| { ui.strong("RX Pkts"); });
                                                         h.col(|ui| { ui.strong("TX Pkts"); });
+                                                        h.col(|ui| { ui.strong("RX rate"); });
+                                                        h.col(|ui| { ui.strong("TX rate"); });
                                                     })
                                                     .body(|mut b| {
-                                                        for interface in if_stats {
+                                                        for (interface, rates) in if_stats {
                                                             b.row(18.0, |mut r| {
-                                                                r.col(|ui| { ui.label(interface.ip_address.to_string()); });
+                                                                r.col(|ui| { ui.label(anonymized(interface.ip_address)); });
                                                                 r.col(|ui| { ui.label(interface.rx_bytes.map(|v| humanize_bytes(v)).unwrap_or_else(|| "-".to_string())); });
                                                                 r.col(|ui| { ui.label(interface.tx_bytes.map(|v| humanize_bytes(v)).unwrap_or_else(|| "-".to_string())); });
                                                                 r.col(|ui| { ui.label(interface.rx_packets.map(|v| humanize_packet_count(v)).unwrap_or_else(|| "-".to_string())); });
                                                                 r.col(|ui| { ui.label(interface.tx_packets.map(|v| humanize_packet_count(v)).unwrap_or_else(|| "-".to_string())); });
+                                                                r.col(|ui| { ui.label(format_rate(rates.and_then(|r| r.rx_bps), rates.and_then(|r| r.rx_pps))); });
+                                                                r.col(|ui| { ui.label(format_rate(rates.and_then(|r| r.tx_bps), rates.and_then(|r| r.tx_pps))); });
                                                             });
                                                         }
                                                     });
@@ -535,6 +1612,17 @@ impl App {
 
                                         row.col(|ui| {
                                             ui.horizontal(|ui| {
+                                                let refreshing = self.fetches_in_flight.lock().unwrap().contains(&src_id);
+                                                if refreshing {
+                                                    ui.spinner();
+                                                } else if ui
+                                                    .add_enabled(self.source_specs.contains_key(&src_id), egui::Button::new("↻").small())
+                                                    .on_hover_text("Fetch this source now instead of waiting for the next poll")
+                                                    .on_disabled_hover_text("No connection details for this source")
+                                                    .clicked()
+                                                {
+                                                    sources_to_refresh.push(src_id.clone());
+                                                }
                                                 if ui.small_button("🗑").on_hover_text("Remove a source and its partition from the store").clicked() {
                                                     sources_to_remove.push(src_id.clone());
                                                 }
@@ -542,6 +1630,17 @@ impl App {
                                                     let state = self.store.get_source_state(&src_id).expect("Failed to get source state, this should never happen");
                                                     println!("{}", serde_json::to_string_pretty(state).unwrap_or("Couldn't serialize".to_string()))
                                                 }
+                                                if matches!(src_id, RouterId::IsIs(_))
+                                                    && ui.small_button("🔎").on_hover_text("Check IS-IS pseudonode prefix resolution for this source").clicked()
+                                                {
+                                                    let state = self.store.get_source_state(&src_id).expect("Failed to get source state, this should never happen");
+                                                    let report = PrefixResolutionReport::from_nodes(state.partition.nodes.values());
+                                                    info_log!("[app] IS-IS prefix resolution for {}: {}", src_id, report);
+                                                    for lsp_id in &report.unresolved {
+                                                        debug_log!("[app]   unresolved pseudonode {}", lsp_id);
+                                                    }
+                                                    self.isis_prefix_reports.insert(src_id.clone(), report);
+                                                }
                                             });
                                         });
                                         row.col(|ui| {
@@ -551,14 +1650,26 @@ impl App {
                                 }
                             });
 
-                        if !sources_to_remove.is_empty() {
-                            for src_id in sources_to_remove.iter() {
-                                if let Err(e) = self.store.remove_partition(src_id) {
-                                    eprintln!("Failed to remove partition: {}", e);
-                                }
+                        for (src_id, report) in self.isis_prefix_reports.iter() {
+                            let text = format!("{}: {}", src_id, report);
+                            if report.has_collisions() || report.stale_ids > 0 {
+                                ui.colored_label(self.theme.red, text);
+                            } else {
+                                ui.label(text);
+                            }
+                        }
+
+                        for src_id in sources_to_refresh {
+                            if let Some(spec) = self.source_specs.get(&src_id).cloned() {
+                                info_log!("[app] Refreshing source {}", src_id);
+                                self.spawn_reconnect(src_id, spec);
                             }
                         }
 
+                        for src_id in sources_to_remove.iter() {
+                            self.remove_source(src_id);
+                        }
+
                         let sources_enable_state_changed: Vec<_> = source_enable_states.into_iter().filter_map(|(src_id, enabled)| {
                             if enabled != self.merge_config.is_source_enabled(&src_id) {
                                 Some((src_id, enabled))
@@ -588,9 +1699,15 @@ impl App {
         ui.checkbox(&mut self.path_mode, "Enable Path Mode");
 
         if !self.path_mode || ui.button("Clear path").clicked() {
+            let was_path_view = self.path_view().is_some();
             self.path_start = None;
             self.path_end = None;
+            self.path_waypoints.clear();
+            self.path_hops.clear();
             clear_path_highlight();
+            if was_path_view {
+                self.reload_graph_logged("clearing the path");
+            }
         }
 
         if ui.button("Use Selected as start").clicked() {
@@ -605,30 +1722,92 @@ impl App {
             }
         }
 
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(self.selected_node.is_some(), Button::new("Add waypoint"))
+                .on_hover_text("Make the path pass through the selected node, after the waypoints already added")
+                .clicked()
+                && let Some(selected) = self.selected_node
+            {
+                self.path_waypoints.push(selected);
+            }
+            if ui
+                .add_enabled(!self.path_waypoints.is_empty(), Button::new("Clear waypoints"))
+                .clicked()
+            {
+                self.path_waypoints.clear();
+            }
+        });
+        self.render_path_waypoints(ui);
+
+        ui.horizontal(|ui| {
+            ui.label("Unmetered edge cost");
+            ui.add(egui::DragValue::new(&mut self.unmetered_cost).range(0..=u32::MAX))
+                .on_hover_text("Cost used for edges without a metric, 0 makes them free shortcuts");
+        });
+
         if ui.button("Compute Path").clicked() {
-            use petgraph::algo::astar;
-            if let (Some(start_id), Some(end_id)) = (self.path_start, self.path_end) {
-                let graph = self.graph.graph.g();
-                let paths = astar(
-                    &graph,
-                    start_id,
-                    |idx| idx == end_id,
-                    |e| -> u32 { (&e.weight().payload().metric).into() },
-                    |_| 0,
-                );
+            self.compute_path();
+        }
 
-                let path_uuids = if let Some((_, path)) = paths {
-                    path.iter()
-                        .filter_map(|idx| self.graph.graph.node(*idx))
-                        .map(|n| n.payload().id)
-                        .collect()
-                } else {
-                    Vec::new()
-                };
+        if ui
+            .checkbox(&mut self.path_only, "Show only path")
+            .on_hover_text("Hide every node and edge that isn't on the computed path")
+            .changed()
+            && !self.path_hops.is_empty()
+        {
+            self.reload_graph_logged("toggling the path-only view");
+        }
 
-                node_shape::set_path_highlight(path_uuids.into_iter());
+        ui.separator();
+        ui.label("What-if failures");
+        ui.horizontal(|ui| {
+            let mut changed = false;
+            if let Some(uuid) = self
+                .selected_node
+                .and_then(|idx| self.graph.graph.node(idx))
+                .map(|node| node.payload().id)
+            {
+                let failed = self.failed_nodes.contains(&uuid);
+                if ui
+                    .button(if failed { "Restore node" } else { "Fail node" })
+                    .on_hover_text("Treat the selected node as down when computing paths")
+                    .clicked()
+                {
+                    toggle_in_set(&mut self.failed_nodes, uuid);
+                    changed = true;
+                }
             }
-        }
+            if let Some((a, b, kind)) = self.selected_edge {
+                let key = UndirectedEdgeKey::new(a, b, kind);
+                let failed = self.failed_edges.contains(&key);
+                if ui
+                    .button(if failed { "Restore edge" } else { "Fail edge" })
+                    .on_hover_text("Treat the selected edge as down when computing paths")
+                    .clicked()
+                {
+                    toggle_in_set(&mut self.failed_edges, key);
+                    changed = true;
+                }
+            }
+            let failed_count = self.failed_nodes.len() + self.failed_edges.len();
+            if ui
+                .add_enabled(
+                    failed_count > 0,
+                    Button::new(format!("Clear failures ({})", failed_count)),
+                )
+                .clicked()
+            {
+                self.failed_nodes.clear();
+                self.failed_edges.clear();
+                changed = true;
+            }
+            if changed {
+                self.apply_failures();
+            }
+        });
+
+        self.render_blast_radius_controls(ui);
 
         let start_id_name = self
             .path_start
@@ -645,18 +1824,671 @@ impl App {
             .unwrap_or("None".to_string());
 
         ui.label(format!("Start: {}", start_id_name));
+        if !self.path_waypoints.is_empty() {
+            ui.label(format!("Via: {} waypoint(s)", self.path_waypoints.len()));
+        }
         ui.label(format!("End: {}", end_id_name));
+        self.render_path_hops(ui);
+    }
+
+    /// Pick the root and failed node of the blast radius from the selection.
+    fn render_blast_radius_controls(&mut self, ui: &mut Ui) {
+        ui.label("Blast radius");
+        let selected = self
+            .selected_node
+            .and_then(|idx| self.graph.graph.node(idx))
+            .map(|node| node.payload().id);
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(selected.is_some(), Button::new("Set root"))
+                .on_hover_text("See failures from the selected node's point of view")
+                .clicked()
+            {
+                self.blast_root = selected;
+                changed = true;
+            }
+            if ui
+                .add_enabled(
+                    selected.is_some() && self.blast_root.is_some(),
+                    Button::new("If selected fails"),
+                )
+                .on_hover_text("Ring what the root can no longer reach once the selected node is down")
+                .clicked()
+            {
+                self.blast_failed = selected;
+                changed = true;
+            }
+            if ui
+                .add_enabled(self.blast_root.is_some(), Button::new("Clear"))
+                .clicked()
+            {
+                self.blast_root = None;
+                self.blast_failed = None;
+                changed = true;
+            }
+        });
+        if changed {
+            self.refresh_blast_radius();
+        }
+        let label = |id: Uuid| self.graph.node_label(id).unwrap_or_else(|| id.to_string());
+        if let Some(root) = self.blast_root {
+            ui.label(format!("Root: {}", label(root)));
+        }
+        if let Some(failed) = self.blast_failed {
+            let (routers, networks) = self
+                .blast_radius
+                .iter()
+                .filter_map(|id| self.graph.node_id_to_index_map.get(id))
+                .filter_map(|idx| self.graph.graph.node(*idx))
+                .fold((0, 0), |(routers, networks), node| match node.payload().info {
+                    NodeInfo::Router(_) => (routers + 1, networks),
+                    NodeInfo::Network(_) => (routers, networks + 1),
+                    NodeInfo::Area(_) => (routers, networks),
+                });
+            ui.label(format!(
+                "If {} fails: {} router(s) and {} network(s) lost",
+                label(failed),
+                routers,
+                networks
+            ));
+        }
+    }
+
+    /// Recompute the blast radius for the current graph and push it to the shapes.
+    fn refresh_blast_radius(&mut self) {
+        self.blast_radius = match (self.blast_root, self.blast_failed) {
+            (Some(root), Some(failed)) => self.graph.unreachable_if_removed(root, failed),
+            _ => HashSet::new(),
+        };
+        node_shape::set_blast_radius(self.blast_radius.clone());
+    }
+
+    /// Push the what-if failures to the shapes and recompute the path around them.
+    fn apply_failures(&mut self) {
+        info_log!(
+            "[app] What-if failures: {} nodes, {} edges",
+            self.failed_nodes.len(),
+            self.failed_edges.len()
+        );
+        node_shape::set_failed_nodes(self.failed_nodes.clone());
+        edge_shape::set_failed_edges(self.failed_edges.clone());
+        self.compute_path();
+    }
+
+    /// Right-click menu of the node at `idx`.
+    fn render_node_context_menu(&mut self, ui: &mut Ui, idx: NodeIndex) {
+        let Some(node) = self.graph.graph.node(idx) else {
+            ui.close();
+            return;
+        };
+        let uuid = node.payload().id;
+        let ident = match &node.payload().info {
+            NodeInfo::Router(router) => router.id.to_string(),
+            NodeInfo::Network(network) => network.ip_address.to_string(),
+            NodeInfo::Area(area) => area.name.clone(),
+        };
+        ui.label(node.label().to_string());
+        ui.separator();
+
+        if ui.button("Use as path start").clicked() {
+            self.path_mode = true;
+            self.path_start = Some(idx);
+            ui.close();
+        }
+        if ui.button("Use as path end").clicked() {
+            self.path_mode = true;
+            self.path_end = Some(idx);
+            ui.close();
+        }
+        if ui.button("Add as path waypoint").clicked() {
+            self.path_mode = true;
+            self.path_waypoints.push(idx);
+            ui.close();
+        }
+        ui.separator();
+        if ui.button("Copy id").on_hover_text(&ident).clicked() {
+            ui.ctx().copy_text(ident);
+            ui.close();
+        }
+        if ui
+            .button(format!("Focus neighborhood ({} hops)", self.focus_hops))
+            .clicked()
+        {
+            self.focus_on(idx);
+            ui.close();
+        }
+        let failed = self.failed_nodes.contains(&uuid);
+        if ui
+            .button(if failed { "Restore node" } else { "Mark failed" })
+            .clicked()
+        {
+            toggle_in_set(&mut self.failed_nodes, uuid);
+            self.apply_failures();
+            ui.close();
+        }
+        if ui.button("Use as blast radius root").clicked() {
+            self.blast_root = Some(uuid);
+            self.refresh_blast_radius();
+            ui.close();
+        }
+        if self.blast_root.is_some_and(|root| root != uuid)
+            && ui.button("Show blast radius if this fails").clicked()
+        {
+            self.blast_failed = Some(uuid);
+            self.refresh_blast_radius();
+            ui.close();
+        }
+        let pinned = self.graph.is_pinned(uuid);
+        if ui
+            .button(if pinned { "Unpin position" } else { "Pin position" })
+            .clicked()
+        {
+            self.graph.toggle_pin(uuid);
+            ui.close();
+        }
+    }
+
+    /// Ordered waypoint list with buttons to move or remove each one.
+    fn render_path_waypoints(&mut self, ui: &mut Ui) {
+        let mut move_up = None;
+        let mut remove = None;
+        let count = self.path_waypoints.len();
+        for (i, idx) in self.path_waypoints.iter().enumerate() {
+            let name = self
+                .graph
+                .graph
+                .node(*idx)
+                .map(|node| node.label().to_string())
+                .unwrap_or_else(|| "(not shown)".to_string());
+            ui.horizontal(|ui| {
+                ui.label(format!("{}. {}", i + 1, name));
+                if ui.add_enabled(i > 0, Button::new("⏶").small()).clicked() {
+                    move_up = Some(i);
+                }
+                if ui.add_enabled(i + 1 < count, Button::new("⏷").small()).clicked() {
+                    move_up = Some(i + 1);
+                }
+                if ui.small_button("✖").clicked() {
+                    remove = Some(i);
+                }
+            });
+        }
+        if let Some(i) = move_up {
+            self.path_waypoints.swap(i - 1, i);
+        }
+        if let Some(i) = remove {
+            self.path_waypoints.remove(i);
+        }
+    }
+
+    /// Uuids along the computed path while the path-only view is on.
+    fn path_view(&self) -> Option<Vec<Uuid>> {
+        (self.path_only && !self.path_hops.is_empty())
+            .then(|| self.path_hops.iter().map(|hop| hop.node_id).collect())
+    }
+
+    /// Node the center gravity pulls toward when it is retargeted at the selection.
+    fn gravity_focus(&self) -> Option<NodeIndex> {
+        let extras = &self.layout_state.extras.0;
+        (self.gravity_to_selected && extras.enabled)
+            .then_some(self.selected_node)
+            .flatten()
+    }
+
+    fn reload_graph_logged(&mut self, reason: &str) {
+        if let Err(e) = self.reload_graph() {
+            eprintln!("[app] Error reloading graph after {}: {:?}", reason, e);
+        }
+    }
+
+    /// Highlight the lowest-metric path between the chosen endpoints through the waypoints in order,
+    /// avoiding what-if failures.
+    fn compute_path(&mut self) {
+        let (Some(start_id), Some(end_id)) = (self.path_start, self.path_end) else {
+            return;
+        };
+        let mut stops = vec![start_id];
+        stops.extend(self.path_waypoints.iter().copied());
+        stops.push(end_id);
+        // Search the full graph, not the path-only view of the previous path
+        if self.path_view().is_some() {
+            self.path_hops.clear();
+            self.reload_graph_logged("leaving the path-only view");
+        }
+        let view = self
+            .graph
+            .with_failed(&self.failed_nodes, &self.failed_edges)
+            .with_unmetered_cost(self.unmetered_cost);
+        self.path_hops = match view.shortest_path_via(&stops) {
+            Some((_, path)) => view.hops(&path),
+            None => {
+                println!("[app] No path between the selected nodes");
+                Vec::new()
+            }
+        };
+
+        clear_path_highlight();
+        node_shape::set_path_highlight(self.path_hops.iter().map(|hop| hop.node_id));
+        if self.path_view().is_some() {
+            self.reload_graph_logged("computing the path");
+        }
+    }
+
+    fn render_path_hops(&self, ui: &mut Ui) {
+        if self.path_hops.is_empty() {
+            return;
+        }
+        egui::Grid::new("path_hops_grid")
+            .num_columns(4)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.strong("#");
+                ui.strong("Hop");
+                ui.strong("Cost");
+                ui.strong("Total");
+                ui.end_row();
+                for (i, hop) in self.path_hops.iter().enumerate() {
+                    ui.label((i + 1).to_string());
+                    ui.label(&hop.ident);
+                    ui.label(hop.cost.to_string());
+                    ui.label(hop.total.to_string());
+                    ui.end_row();
+                }
+            });
+        if ui.button("Copy path").clicked() {
+            ui.ctx().copy_text(format_path_hops(&self.path_hops));
+        }
+    }
+
+    fn render_focus_controls(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Focus hops");
+            ui.add(egui::DragValue::new(&mut self.focus_hops).range(0..=32));
+            if ui
+                .add_enabled(self.selected_node.is_some(), Button::new("Focus selected"))
+                .on_hover_text("Hide everything further than N hops from the selected node")
+                .clicked()
+                && let Some(center) = self.selected_node
+            {
+                self.focus_on(center);
+            }
+            if ui
+                .add_enabled(self.focus_set.is_some(), Button::new("Clear focus"))
+                .clicked()
+            {
+                self.focus_set = None;
+                if let Err(e) = self.reload_graph() {
+                    eprintln!("[app] Failed to reload graph: {}", e);
+                }
+            }
+        });
+    }
+
+    /// Hide everything further than `focus_hops` from `center`.
+    fn focus_on(&mut self, center: NodeIndex) {
+        let focus = self.graph.neighborhood(center, self.focus_hops);
+        info_log!(
+            "[app] Focusing {} nodes within {} hops",
+            focus.len(),
+            self.focus_hops
+        );
+        self.focus_set = Some(focus);
+        if let Err(e) = self.reload_graph() {
+            eprintln!("[app] Failed to reload graph: {}", e);
+        }
+    }
+
+    fn layout_settled(&self) -> bool {
+        self.idle_when_settled && self.settled_frames >= SETTLE_FRAMES
+    }
+
+    /// Count frames in which no node moved noticeably; see `layout_settled`.
+    fn track_layout_settling(&mut self) {
+        let still = self
+            .graph
+            .max_displacement(&self.last_positions)
+            .is_some_and(|moved| moved < SETTLE_THRESHOLD);
+        self.settled_frames = if still { self.settled_frames.saturating_add(1) } else { 0 };
+        self.last_positions = self.graph.node_positions();
     }
 
     fn reload_graph(&mut self) -> Result<(), FederationError> {
-        let merged = self.store.build_merged_view_with(&self.merge_config)?;
+        let (merged, merge_stats) = self.store.build_merged_view_with_stats(&self.merge_config)?;
+        self.hidden_leaf_networks = merge_stats.hidden_leaf_networks;
+        self.hidden_lost_sources = merge_stats.hidden_lost_sources;
+        self.collapsed_unresolved_networks = merge_stats.collapsed_unresolved_networks;
+        self.isis_topologies = isis_topologies(&merged);
+        self.isis_areas = isis_areas(&merged);
+        let known: HashSet<Uuid> = if self.pending_overlay.is_empty() {
+            HashSet::new()
+        } else {
+            merged.iter().map(|node| node.id).collect()
+        };
+        let merged = self.apply_view_filters(merged);
 
         self.graph.reconcile(merged);
+        self.apply_pending_overlay(&known);
+        if self.collapse_areas {
+            self.graph.collapse_areas(&self.expanded_areas);
+        }
+        self.sync_panel_pseudonyms();
+        self.settled_frames = 0;
+        if let Some(path) = self.path_view() {
+            self.graph.retain_path_edges(&path);
+        }
         // Authoritatively recompute edge traffic weights after reconciling the graph
         self.apply_edge_traffic_weights();
+        self.graph_stats = None;
+        self.edge_bundles_key = None;
+        self.prefix_conflicts = self.store.cross_protocol_prefix_conflicts();
+        self.metric_conflicts = self.store.router_metric_conflicts();
+        self.overlapping_prefixes = self.store.overlapping_prefixes();
+        self.refresh_asymmetric_links();
+        self.refresh_designated_router_edges();
+        self.refresh_node_sizes();
+        self.refresh_blast_radius();
+        self.area_types = self.store.classify_areas();
+        area_hulls::set_area_types(self.area_types.clone());
         Ok(())
     }
 
+    /// Apply the saved manual edges whose endpoints are in `known`, the merged view before the
+    /// view filters, so edges to hidden nodes aren't lost.
+    fn apply_pending_overlay(&mut self, known: &HashSet<Uuid>) {
+        if self.pending_overlay.is_empty() {
+            return;
+        }
+        let applied = self.pending_overlay.apply(&mut self.graph, known);
+        if applied > 0 {
+            info_log!(
+                "[app] Reapplied {} manual edge change(s), {} waiting for their nodes",
+                applied,
+                self.pending_overlay.len()
+            );
+        }
+    }
+
+    fn refresh_node_sizes(&self) {
+        let values: HashMap<Uuid, f64> = match self.node_sizing {
+            NodeSizing::None => HashMap::new(),
+            NodeSizing::Degree => self
+                .graph
+                .node_degrees()
+                .into_iter()
+                .map(|(id, degree)| (id, degree as f64))
+                .collect(),
+            NodeSizing::Traffic => {
+                let mut values: HashMap<Uuid, f64> = self
+                    .graph
+                    .graph
+                    .nodes_iter()
+                    .map(|(_, node)| (node.payload().id, 0.0))
+                    .collect();
+                for (src_id, state) in self.store.sources_iter() {
+                    if let Some(value) = values.get_mut(&self.source_router_uuid(src_id)) {
+                        *value = state.interface_stats.iter().map(|s| s.get_weight() as f64).sum();
+                    }
+                }
+                values
+            }
+        };
+        node_shape::set_node_scales(node_shape::node_scales(&values));
+    }
+
+    fn refresh_asymmetric_links(&mut self) {
+        self.asymmetric_links = self.graph.asymmetric_links();
+        if self.highlight_asymmetric {
+            edge_shape::set_asymmetric_edges(self.graph.asymmetric_edge_keys());
+        } else {
+            edge_shape::set_asymmetric_edges(HashSet::new());
+        }
+    }
+
+    fn refresh_designated_router_edges(&self) {
+        if self.highlight_designated_routers {
+            edge_shape::set_designated_router_edges(self.graph.designated_router_edge_keys());
+        } else {
+            edge_shape::set_designated_router_edges(HashSet::new());
+        }
+    }
+
+    /// Push parallel edge groups to the edge shapes, recomputed only after a reload or when
+    /// manual edits change the node/edge count.
+    fn refresh_edge_bundles(&mut self) {
+        let key = (self.graph.graph.node_count(), self.graph.graph.edge_count());
+        if self.edge_bundles_key != Some(key) {
+            edge_shape::set_parallel_edges(self.graph.parallel_edge_groups());
+            self.edge_bundles_key = Some(key);
+        }
+    }
+
+    /// Graph stats, recomputed only after a reload or when manual edits change the node/edge count.
+    fn graph_stats(&mut self) -> &GraphStats {
+        let key = (self.graph.graph.node_count(), self.graph.graph.edge_count());
+        if self.graph_stats.as_ref().is_none_or(|(cached, _)| *cached != key) {
+            self.graph_stats = Some((key, self.graph.stats()));
+        }
+        &self.graph_stats.as_ref().unwrap().1
+    }
+
+    fn render_label_template_controls(&mut self, ui: &mut Ui) {
+        let mut changed = false;
+        egui::Grid::new("label_template_grid")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label("Routers");
+                changed |= ui
+                    .text_edit_singleline(&mut self.label_template.router)
+                    .changed();
+                ui.end_row();
+                ui.label("Networks");
+                changed |= ui
+                    .text_edit_singleline(&mut self.label_template.network)
+                    .changed();
+                ui.end_row();
+            });
+        ui.small(format!("Placeholders: {}", LABEL_PLACEHOLDERS.join(" ")));
+        if ui.button("Reset").clicked() {
+            self.label_template = LabelTemplate::default();
+            changed = true;
+        }
+        if changed {
+            self.graph.set_label_template(self.label_template.clone());
+        }
+
+        let mut anonymize = self.graph.anonymize();
+        if ui
+            .checkbox(&mut anonymize, "Anonymize")
+            .on_hover_text(
+                "Replace router IDs, prefixes and hostnames with pseudonyms (R1, N1, …) in labels \
+                 and panels, e.g. for screenshots. The topology data is left untouched.",
+            )
+            .changed()
+        {
+            self.graph.set_anonymize(anonymize);
+            self.sync_panel_pseudonyms();
+        }
+    }
+
+    /// Hand the graph's pseudonyms to the panels while anonymizing, so they show the same names.
+    fn sync_panel_pseudonyms(&self) {
+        node_panel::set_pseudonyms(self.graph.anonymize().then(|| self.graph.pseudonyms().clone()));
+    }
+
+    fn render_graph_stats(&mut self, ui: &mut Ui) {
+        let source_count = self.store.sources_iter().count();
+        let stats = self.graph_stats().clone();
+
+        egui::Grid::new("graph_stats_grid")
+            .num_columns(2)
+            .striped(true)
+            .show(ui, |ui| {
+                let rows = [
+                    ("Routers", stats.routers),
+                    ("Networks", stats.networks),
+                    ("Sources", source_count),
+                    ("OSPF areas", stats.ospf_areas),
+                    ("Collapsed areas", stats.collapsed_areas),
+                    ("Connected components", stats.connected_components),
+                ];
+                for (name, value) in rows {
+                    ui.label(name);
+                    ui.label(value.to_string());
+                    ui.end_row();
+                }
+                for kind in [
+                    EdgeKind::Membership,
+                    EdgeKind::LogicalReachability,
+                    EdgeKind::External,
+                    EdgeKind::VirtualAdjacency,
+                    EdgeKind::PointToPoint,
+                    EdgeKind::VirtualLink,
+                ] {
+                    let count = stats.edges_by_kind.get(&kind).copied().unwrap_or(0);
+                    ui.label(format!("{:?} edges", kind));
+                    ui.label(count.to_string());
+                    ui.end_row();
+                }
+            });
+
+        collapsible_section(ui, "Metric distribution", false, |ui| {
+            render_metric_histogram(ui, &stats.metric_histogram, &self.theme);
+        });
+    }
+
+    /// Drop nodes hidden by the current view toggles. Edges follow automatically since they are
+    /// derived from the remaining nodes.
+    fn apply_view_filters(&self, mut nodes: Vec<Node>) -> Vec<Node> {
+        let path = self.path_view();
+        if let Some(mt_id) = &self.isis_topology_filter {
+            retain_isis_topology(&mut nodes, mt_id);
+        }
+        if let Some(area) = &self.isis_area_filter {
+            retain_isis_area(&mut nodes, area);
+        }
+        nodes
+            .into_iter()
+            .filter(|node| !self.hidden_nodes.contains(&node.id))
+            .filter(|node| {
+                self.focus_set
+                    .as_ref()
+                    .is_none_or(|focus| focus.contains(&node.id))
+            })
+            .filter(|node| path.as_ref().is_none_or(|path| path.contains(&node.id)))
+            .filter(|node| {
+                !(self.hide_stub_networks
+                    && matches!(&node.info, NodeInfo::Network(net) if net.is_synthetic))
+            })
+            .collect()
+    }
+
+    fn render_palette_selector(&self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Palette");
+            let mut selected = palette::palette();
+            egui::ComboBox::from_id_salt("palette")
+                .selected_text(selected.name())
+                .show_ui(ui, |ui| {
+                    for option in Palette::ALL {
+                        ui.selectable_value(&mut selected, option, option.name());
+                    }
+                });
+            if selected != palette::palette() {
+                palette::set_palette(selected);
+                self.save_session();
+            }
+            info_icon(ui, "Deuteranopia-safe avoids red/green pairs. Monochrome tells node and edge kinds apart by outline and dash pattern instead of color.");
+        });
+    }
+
+    fn render_legend(&self, ui: &mut Ui) {
+        let icon_size = egui::Vec2::splat(2.0 * node_shape::NODE_RADIUS);
+        let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+        let entries = [
+            (node_shape::router_texture_id(ui.ctx()), NodeRole::Router, false, "Router"),
+            (node_shape::network_texture_id(ui.ctx()), NodeRole::Network, false, "Network"),
+            (
+                node_shape::network_texture_id(ui.ctx()),
+                NodeRole::Network,
+                true,
+                "Stub network (inferred from Router-LSA)",
+            ),
+            (
+                node_shape::network_texture_id(ui.ctx()),
+                NodeRole::External,
+                false,
+                "External route (Type-5/7)",
+            ),
+            (
+                node_shape::network_texture_id(ui.ctx()),
+                NodeRole::Unresolved,
+                false,
+                "Unresolved IS-IS pseudonode",
+            ),
+        ];
+        for (tex_id, role, synthetic, text) in entries {
+            let color = palette::node_color(&self.theme, role);
+            ui.horizontal(|ui| {
+                let (rect, _) = ui.allocate_exact_size(icon_size, egui::Sense::hover());
+                let radius = if synthetic {
+                    node_shape::STUB_NETWORK_RADIUS
+                } else {
+                    node_shape::NODE_RADIUS
+                };
+                let icon_rect = egui::Rect::from_center_size(rect.center(), egui::Vec2::splat(2.0 * radius));
+                ui.painter().image(tex_id, icon_rect, uv, color);
+                if synthetic {
+                    ui.painter().extend(node_shape::dashed_circle(
+                        rect.center(),
+                        radius * 1.15,
+                        egui::Stroke::new(1.0, color),
+                    ));
+                }
+                if palette::uses_shapes() {
+                    ui.painter().extend(node_shape::role_marker(role, rect.center(), radius, color));
+                }
+                ui.label(text);
+            });
+        }
+
+        for (tag, name) in edge_shape::PROTOCOL_TAGS {
+            let Some(color) = edge_shape::protocol_color(&self.theme, Some(tag)) else {
+                continue;
+            };
+            ui.horizontal(|ui| {
+                let (rect, _) = ui.allocate_exact_size(icon_size, egui::Sense::hover());
+                ui.painter().line_segment(
+                    [rect.left_center(), rect.right_center()],
+                    egui::Stroke::new(2.0, color),
+                );
+                ui.label(format!("{} edge", name));
+            });
+        }
+
+        if area_hulls::area_backgrounds_enabled() {
+            let mut areas: Vec<(&Ipv4Addr, &AreaType)> = self.area_types.iter().collect();
+            areas.sort_by_key(|(area_id, _)| **area_id);
+            for (area_id, area_type) in areas {
+                ui.horizontal(|ui| {
+                    let (rect, _) = ui.allocate_exact_size(icon_size, egui::Sense::hover());
+                    let color = area_hulls::area_color(&self.theme, &AreaKey::Ospf(*area_id));
+                    ui.painter().rect_filled(rect.shrink(2.0), 2.0, color.gamma_multiply(0.4));
+                    ui.label(format!("Area {} ({})", area_id, area_type.name()));
+                });
+            }
+            for name in &self.isis_areas {
+                let area = AreaKey::IsIs(name.clone());
+                ui.horizontal(|ui| {
+                    let (rect, _) = ui.allocate_exact_size(icon_size, egui::Sense::hover());
+                    let color = area_hulls::area_color(&self.theme, &area);
+                    ui.painter().rect_filled(rect.shrink(2.0), 2.0, color.gamma_multiply(0.4));
+                    ui.label(area.to_string());
+                });
+            }
+        }
+    }
+
     fn render_edit_tools(&mut self, ui: &mut Ui) {
         ui.label("Edit mode");
         ui.horizontal(|ui| {
@@ -684,11 +2516,45 @@ impl App {
         ui.add_enabled_ui(self.graph.any_manual_changes(), |ui| {
             if ui.button("Clear all manual changes").clicked() {
                 self.graph.clear_manual_changes();
+                self.pending_overlay = SavedOverlay::default();
+            }
+        });
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(self.graph.any_manual_changes(), Button::new("Save manual edges"))
+                .on_hover_text(format!(
+                    "Write manual edges and snipped edges to {}",
+                    SavedOverlay::path().display()
+                ))
+                .clicked()
+            {
+                match SavedOverlay::from_graph(&self.graph).save() {
+                    Ok(path) => info_log!("[app] Saved manual edges to {}", path.display()),
+                    Err(e) => eprintln!("[app] Failed to save manual edges: {}", e),
+                }
+            }
+            if ui
+                .button("Load manual edges")
+                .on_hover_text("Reapply saved manual edges on top of the current graph. Edges whose nodes aren't in the topology yet follow once they show up.")
+                .clicked()
+            {
+                match SavedOverlay::load() {
+                    Some(overlay) => {
+                        self.pending_overlay = overlay;
+                        self.reload_graph_logged("loading manual edges");
+                    }
+                    None => eprintln!("[app] No saved manual edges at {}", SavedOverlay::path().display()),
+                }
             }
         });
         ui.label("Hint: In Draw, click node A then node B to create an edge. Esc or click empty space cancels.");
         if let Some((a, b, kind)) = self.selected_edge {
             let is_manual = self.graph.is_manual_edge(a, b, kind);
+            if !is_manual {
+                self.render_edge_summary(ui, a, b, kind);
+                self.previous_manual_metric = None;
+                return;
+            }
             ui.separator();
             ui.label("Manual edge properties");
             let mut metric_val: i32 = if let Some(metric) = self.previous_manual_metric {
@@ -719,6 +2585,32 @@ impl App {
         }
     }
 
+    /// Read-only view of both directed halves of a protocol-derived edge.
+    fn render_edge_summary(&self, ui: &mut Ui, a: Uuid, b: Uuid, kind: EdgeKind) {
+        ui.separator();
+        ui.label(format!("Edge properties ({:?})", kind));
+        let name = |id: Uuid| self.graph.node_label(id).unwrap_or_else(|| id.to_string());
+        let (forward, reverse) = self.graph.edge_pair_metrics(a, b, kind);
+        egui::Grid::new("edge_summary_grid")
+            .num_columns(3)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.strong("Direction");
+                ui.strong("Metric");
+                ui.strong("Utilization");
+                ui.end_row();
+                for (src, dst, metric) in [(a, b, forward), (b, a, reverse)] {
+                    ui.label(format!("{} → {}", name(src), name(dst)));
+                    ui.label(metric.as_ref().map_or("-".to_string(), format_edge_metric));
+                    ui.label(
+                        edge_shape::get_edge_weight(src, dst)
+                            .map_or("-".to_string(), |w| format!("{:.1}%", w * 100.0)),
+                    );
+                    ui.end_row();
+                }
+            });
+    }
+
     fn render_autopoll_controls(&mut self, ui: &mut Ui) {
         CollapsingHeader::new("Autopoll Controls")
             .default_open(true)
@@ -736,6 +2628,25 @@ impl App {
                     }
                 });
                 
+                ui.horizontal(|ui| {
+                    ui.label("Stagger window (ms)");
+                    let mut millis = self.autopoll_stagger_window.as_millis() as u64;
+                    if ui
+                        .add(egui::DragValue::new(&mut millis).range(0..=60_000))
+                        .on_hover_text("Per-source random delay before the first poll. Applied on next start.")
+                        .changed()
+                    {
+                        self.autopoll_stagger_window = Duration::from_millis(millis);
+                    }
+                });
+                ui.checkbox(&mut self.autopoll_stagger_ramp, "Spread first polls over the interval")
+                    .on_hover_text("Offset each source's first poll by an even share of the interval. Applied on next start.");
+                ui.horizontal(|ui| {
+                    ui.label("Max concurrent polls");
+                    ui.add(egui::DragValue::new(&mut self.autopoll_max_concurrent).range(1..=64))
+                        .on_hover_text("Fetches beyond this wait for a running one to finish. Applied on next start.");
+                });
+
                 let was_enabled = self.autopoll_enabled;
                 ui.checkbox(&mut self.autopoll_enabled, "Enable periodic polling for known sources");
                 if self.autopoll_enabled && !was_enabled {
@@ -748,104 +2659,138 @@ impl App {
 
     fn render(&mut self, ctx: &Context) {
         catppuccin_egui::set_theme(ctx, self.theme);
-        // Debug: print pending/connect slot state at start of render
+        // Poll the SSH/SNMP connect tasks at start of render (non-blocking).
+        // Apply any completed snapshots to the store and reconcile the graph on the UI thread.
+        if let Some(task) = &mut self.ssh_connect_task
+            && let Poll::Ready(res) = task.poll()
         {
-            // Snapshot the mutex states briefly for logging (non-blocking relative to UI)
-            let _ = match self.ssh_connect_res.lock() {
-                Ok(g) => g.is_some(),
-                Err(_) => {
-                    eprintln!("[app] failed to lock ssh_connect_res for debug");
-                    false
+            self.ssh_connect_task = None;
+            match res {
+                Ok((((src_id, nodes, stats, warnings), elapsed), source_spec)) => {
+                    println!("[app] SSH snapshot received in UI thread");
+                    self.ssh_connect_error = None;
+                    
+                    if self.ssh_clear_sources_on_switch {
+                        self.store = TopologyStore::default();
+                        self.clear_source_specs(&source_spec);
+                    }
+                    
+                    self.source_specs.insert(src_id.clone(), source_spec);
+                    self.save_session();
+                    
+                    let now = std::time::SystemTime::now();
+                    self.store
+                        .replace_partition_with_warnings(&src_id, nodes, stats, warnings, now);
+                    self.store.record_fetch(&src_id, elapsed);
+
+                    // Rebuild graph via authoritative reload_graph()
+                    if let Err(e) = self.reload_graph() {
+                        eprintln!("[app] Error reloading graph after SSH snapshot: {:?}", e);
+                    }
                 }
-            };
-            let _ = match self.snmp_connect_res.lock() {
-                Ok(g) => g.is_some(),
-                Err(_) => {
-                    eprintln!("[app] failed to lock snmp_connect_res for debug");
-                    false
+                Err(PollError::Cancelled) => info_log!("[app] SSH connect cancelled"),
+                Err(err) => {
+                    eprintln!("[app] SSH connect/fetch failed: {}", err);
+                    self.ssh_connect_error = Some(err);
                 }
-            };
+            }
+            // Request a repaint so the updated graph is shown
+            ctx.request_repaint();
         }
 
-        // Poll shared result slots for SSH/SNMP at start of render (non-blocking).
-        // Apply any completed snapshots to the store and reconcile the graph on the UI thread.
+        if let Some(task) = &mut self.netconf_connect_task
+            && let Poll::Ready(res) = task.poll()
         {
-            let res_opt = { self.ssh_connect_res.lock().unwrap().take() };
-            if let Some(res) = res_opt {
-                match res {
-                    Ok((src_id, nodes, stats, source_spec)) => {
-                        println!("[app] SSH snapshot received in UI thread (via Arc<Mutex>)");
-                        
-                        if self.ssh_clear_sources_on_switch {
-                            self.store = TopologyStore::default();
-                            self.source_specs.clear();
-                        }
-                        
-                        self.source_specs.insert(src_id.clone(), source_spec);
-                        
-                        let now = std::time::SystemTime::now();
-                        self.store.replace_partition(&src_id, nodes, stats, now);
+            self.netconf_connect_task = None;
+            match res {
+                Ok((((src_id, nodes, stats, warnings), elapsed), source_spec)) => {
+                    info_log!("[app] NETCONF snapshot received in UI thread");
+                    self.netconf_connect_error = None;
 
-                        // Rebuild graph via authoritative reload_graph()
-                        if let Err(e) = self.reload_graph() {
-                            eprintln!("[app] Error reloading graph after SSH snapshot: {:?}", e);
-                        }
+                    if self.netconf_clear_sources_on_switch {
+                        self.store = TopologyStore::default();
+                        self.clear_source_specs(&source_spec);
                     }
-                    Err(err) => {
-                        eprintln!("[app] SSH connect/fetch failed (via Arc<Mutex>): {}", err);
+
+                    self.source_specs.insert(src_id.clone(), source_spec);
+                    self.save_session();
+
+                    let now = std::time::SystemTime::now();
+                    self.store
+                        .replace_partition_with_warnings(&src_id, nodes, stats, warnings, now);
+                    self.store.record_fetch(&src_id, elapsed);
+
+                    if let Err(e) = self.reload_graph() {
+                        eprintln!("[app] Error reloading graph after NETCONF snapshot: {:?}", e);
                     }
                 }
-                // Ensure pending flag is cleared so UI buttons re-enable
-                self.ssh_connect_pending = false;
-                // Request a repaint so the updated graph is shown
-                ctx.request_repaint();
+                Err(PollError::Cancelled) => info_log!("[app] NETCONF connect cancelled"),
+                Err(err) => {
+                    eprintln!("[app] NETCONF connect/fetch failed: {}", err);
+                    self.netconf_connect_error = Some(err);
+                }
             }
+            ctx.request_repaint();
         }
 
+        if let Some(task) = &mut self.snmp_connect_task
+            && let Poll::Ready(res) = task.poll()
         {
-            let res_opt = { self.snmp_connect_res.lock().unwrap().take() };
-            if let Some(res) = res_opt {
-                match res {
-                    Ok((src_id, nodes, stats, spec)) => {
-                        println!("[app] SNMP snapshot received in UI thread (via Arc<Mutex>)");
-                        if self.clear_sources_on_switch {
-                            self.store = TopologyStore::default();
-                            self.source_specs.clear();
-                        }
-                        
-                        self.source_specs.insert(src_id.clone(), spec);
-                        
-                        let now = std::time::SystemTime::now();
-                        self.store.replace_partition(&src_id, nodes, stats, now);
-
-                        // Rebuild graph via authoritative reload_graph()
-                        if let Err(e) = self.reload_graph() {
-                            eprintln!("[app] Error reloading graph after SNMP snapshot: {:?}", e);
-                        }
+            self.snmp_connect_task = None;
+            match res {
+                Ok((((src_id, nodes, stats, warnings), elapsed), spec)) => {
+                    println!("[app] SNMP snapshot received in UI thread");
+                    if self.clear_sources_on_switch {
+                        self.store = TopologyStore::default();
+                        self.clear_source_specs(&spec);
                     }
-                    Err(err) => {
-                        eprintln!("[app] SNMP connect/fetch failed (via Arc<Mutex>): {}", err);
+                    
+                    self.source_specs.insert(src_id.clone(), spec);
+                    self.save_session();
+                    
+                    let now = std::time::SystemTime::now();
+                    self.store
+                        .replace_partition_with_warnings(&src_id, nodes, stats, warnings, now);
+                    self.store.record_fetch(&src_id, elapsed);
+
+                    // Rebuild graph via authoritative reload_graph()
+                    if let Err(e) = self.reload_graph() {
+                        eprintln!("[app] Error reloading graph after SNMP snapshot: {:?}", e);
                     }
                 }
-                // Ensure pending flag is cleared so UI buttons re-enable
-                self.snmp_connect_pending = false;
-                // Request a repaint so the updated graph is shown
-                ctx.request_repaint();
+                Err(PollError::Cancelled) => info_log!("[app] SNMP connect cancelled"),
+                Err(err) => {
+                    eprintln!("[app] SNMP connect/fetch failed: {}", err);
+                }
             }
+            // Request a repaint so the updated graph is shown
+            ctx.request_repaint();
         }
         
+        self.handle_reconnect_results(ctx);
+        self.handle_startup_connects(ctx);
+
         {
             let mut reload_needed = false;
             if let Some(rx) = &self.poll_rx {
-                while let Ok(msg) = rx.try_recv() {
+                while let Ok((polled_id, msg)) = rx.try_recv() {
+                    // The source was removed while this poll was queued, don't re-add it
+                    if !self.source_specs.contains_key(&polled_id) {
+                        continue;
+                    }
                     match msg {
-                        Ok((src_id, nodes, stats)) => {
+                        Ok(((src_id, nodes, stats, warnings), elapsed)) => {
                             let now = std::time::SystemTime::now();
-                            self.store.replace_partition(&src_id, nodes, stats, now);
+                            self.store
+                                .replace_partition_with_warnings(&src_id, nodes, stats, warnings, now);
+                            self.store.record_fetch(&src_id, elapsed);
                             reload_needed = true;
                         }
                         Err(e) => {
-                            eprintln!("[app] autopoll failed: {:?}", e);
+                            eprintln!("[app] autopoll of {} failed: {}", polled_id, e);
+                            if e.marks_lost() {
+                                self.store.mark_lost(&polled_id, std::time::SystemTime::now());
+                            }
                         }
                     }
                 }
@@ -869,6 +2814,14 @@ impl App {
                 );
                 set_partition_highlight_enabled(highlight_enabled);
             }
+            let mut area_backgrounds = area_hulls::area_backgrounds_enabled();
+            if ui
+                .checkbox(&mut area_backgrounds, "Area backgrounds")
+                .on_hover_text("Group nodes by OSPF or IS-IS area with translucent hulls; ABRs and L2 routers appear in each of their areas")
+                .changed()
+            {
+                area_hulls::set_area_backgrounds_enabled(area_backgrounds);
+            }
             let mut edge_labels_enabled = edge_shape::edge_labels_enabled();
             if ui
                 .checkbox(&mut edge_labels_enabled, "Edge metric labels")
@@ -880,6 +2833,337 @@ impl App {
                 );
                 edge_shape::set_edge_labels_enabled(edge_labels_enabled);
             }
+            let mut show_arrows = edge_shape::show_arrows();
+            if ui
+                .checkbox(&mut show_arrows, "Edge direction arrows")
+                .on_hover_text("Every link is two directed edges, so arrows appear at both ends")
+                .changed()
+            {
+                edge_shape::set_show_arrows(show_arrows);
+            }
+            let mut bundling = edge_shape::edge_bundling_enabled();
+            if ui
+                .checkbox(&mut bundling, "Bundle parallel edges")
+                .on_hover_text("Draw edges of different kinds between the same nodes side by side")
+                .changed()
+            {
+                edge_shape::set_edge_bundling_enabled(bundling);
+            }
+            if ui
+                .checkbox(&mut self.highlight_asymmetric, "Highlight asymmetric metrics")
+                .on_hover_text("Links whose cost differs by direction")
+                .changed()
+            {
+                self.refresh_asymmetric_links();
+            }
+            if ui
+                .checkbox(&mut self.highlight_designated_routers, "Emphasize DR links")
+                .on_hover_text("Draw the link between each OSPF transit network and its designated router thicker")
+                .changed()
+            {
+                self.refresh_designated_router_edges();
+            }
+            let mut logical_reachability = edge_shape::logical_reachability_enabled();
+            if ui
+                .checkbox(&mut logical_reachability, "Show logical reachability")
+                .on_hover_text("Show ABR -> summary network edges")
+                .changed()
+            {
+                println!(
+                    "[app] Show logical reachability changed to: {}",
+                    logical_reachability
+                );
+                edge_shape::set_logical_reachability_enabled(logical_reachability);
+                if let Err(e) = self.reload_graph() {
+                    eprintln!("[app] Failed to reload graph: {}", e);
+                }
+            }
+            let mut collapse_changed = ui
+                .checkbox(&mut self.collapse_areas, "Collapse areas")
+                .on_hover_text(
+                    "Show each OSPF/IS-IS area as a single node, with the cheapest link between \
+                     areas. Click an area to expand it.",
+                )
+                .changed();
+            if self.collapse_areas
+                && !self.expanded_areas.is_empty()
+                && ui.button("Collapse expanded areas").clicked()
+            {
+                collapse_changed = true;
+            }
+            if collapse_changed {
+                info_log!("[app] Collapse areas changed to: {}", self.collapse_areas);
+                self.expanded_areas.clear();
+                if let Err(e) = self.reload_graph() {
+                    eprintln!("[app] Failed to reload graph: {}", e);
+                }
+            }
+            let mut mirror_metrics =
+                self.graph.reverse_metric_policy() == ReverseMetricPolicy::Mirror;
+            if ui
+                .checkbox(&mut mirror_metrics, "Mirror metrics onto reverse edges")
+                .on_hover_text(
+                    "Give an edge half without an advertised metric the cost of the opposite half. \
+                     Turn off to only show costs the protocol data actually provides.",
+                )
+                .changed()
+            {
+                let policy = if mirror_metrics {
+                    ReverseMetricPolicy::Mirror
+                } else {
+                    ReverseMetricPolicy::Strict
+                };
+                info_log!("[app] Reverse metric policy changed to: {:?}", policy);
+                self.graph.set_reverse_metric_policy(policy);
+                if let Err(e) = self.reload_graph() {
+                    eprintln!("[app] Failed to reload graph: {}", e);
+                }
+            }
+            ui.horizontal(|ui| {
+                ui.label("Size nodes by");
+                let previous = self.node_sizing;
+                egui::ComboBox::from_id_salt("node_sizing")
+                    .selected_text(self.node_sizing.name())
+                    .show_ui(ui, |ui| {
+                        for option in NodeSizing::ALL {
+                            ui.selectable_value(&mut self.node_sizing, option, option.name());
+                        }
+                    });
+                if self.node_sizing != previous {
+                    self.refresh_node_sizes();
+                }
+                info_icon(ui, &format!(
+                    "Degree counts distinct neighbors. Traffic sums the bytes sent and received on a router's interfaces, so only polled routers grow. Sizes stay between {}x and {}x.",
+                    node_shape::MIN_NODE_SCALE,
+                    node_shape::MAX_NODE_SCALE
+                ));
+            });
+            ui.horizontal(|ui| {
+                ui.label("Fit padding");
+                ui.add(egui::Slider::new(&mut self.fit_padding, 0.0..=200.0).suffix(" px"));
+                info_icon(ui, "Space kept free between the graph and the canvas edges");
+            });
+            ui.horizontal(|ui| {
+                ui.label("Initial zoom");
+                ui.add(
+                    egui::Slider::new(&mut self.initial_zoom, 0.25..=4.0)
+                        .logarithmic(true)
+                        .suffix("x"),
+                );
+                info_icon(ui, "Zoom relative to fitting the whole graph, 1x shows every node");
+            });
+            if ui
+                .checkbox(&mut self.hide_stub_networks, "Hide stub networks")
+                .on_hover_text("Hide networks inferred from Router-LSA stub links")
+                .changed()
+            {
+                println!(
+                    "[app] Hide stub networks changed to: {}",
+                    self.hide_stub_networks
+                );
+                if let Err(e) = self.reload_graph() {
+                    eprintln!("[app] Failed to reload graph: {}", e);
+                }
+            }
+            ui.horizontal(|ui| {
+                let mut hide_leaf_networks = self.merge_config.hide_leaf_networks();
+                if ui
+                    .checkbox(&mut hide_leaf_networks, "Hide leaf networks")
+                    .on_hover_text("Hide networks with at most one attached router")
+                    .changed()
+                {
+                    info_log!("[app] Hide leaf networks changed to: {}", hide_leaf_networks);
+                    self.merge_config.set_hide_leaf_networks(hide_leaf_networks);
+                    if let Err(e) = self.reload_graph() {
+                        eprintln!("[app] Failed to reload graph: {}", e);
+                    }
+                }
+                if self.merge_config.hide_leaf_networks() {
+                    ui.weak(format!("({} hidden)", self.hidden_leaf_networks));
+                }
+            });
+            ui.horizontal(|ui| {
+                let mut collapse = self.merge_config.unresolved_prefix_policy()
+                    == UnresolvedPrefixPolicy::Collapse;
+                if ui
+                    .checkbox(&mut collapse, "Collapse unresolved IS-IS networks")
+                    .on_hover_text("Replace pseudonode networks whose prefix couldn't be resolved with direct links between their routers")
+                    .changed()
+                {
+                    let policy = if collapse {
+                        UnresolvedPrefixPolicy::Collapse
+                    } else {
+                        UnresolvedPrefixPolicy::Keep
+                    };
+                    info_log!("[app] Unresolved prefix policy changed to: {:?}", policy);
+                    self.merge_config.set_unresolved_prefix_policy(policy);
+                    if let Err(e) = self.reload_graph() {
+                        eprintln!("[app] Failed to reload graph: {}", e);
+                    }
+                }
+                if collapse {
+                    ui.weak(format!("({} collapsed)", self.collapsed_unresolved_networks));
+                }
+            });
+            ui.horizontal(|ui| {
+                let mut connected_only = self.merge_config.connected_only();
+                if ui
+                    .checkbox(&mut connected_only, "Hide lost sources")
+                    .on_hover_text("Leave partitions of disconnected sources out of the view. They stay in the store and come back when unticked.")
+                    .changed()
+                {
+                    info_log!("[app] Hide lost sources changed to: {}", connected_only);
+                    self.merge_config.set_connected_only(connected_only);
+                    if let Err(e) = self.reload_graph() {
+                        eprintln!("[app] Failed to reload graph: {}", e);
+                    }
+                }
+                if self.merge_config.connected_only() {
+                    ui.weak(format!("({} hidden)", self.hidden_lost_sources));
+                }
+            });
+            let mut unfused = self.merge_config.unfused();
+            if ui
+                .checkbox(&mut unfused, "Show un-fused")
+                .on_hover_text("Skip router fusion and network merging: every source's nodes are drawn separately, labeled with the source, exactly as it reported them. Leaf networks aren't hidden in this mode.")
+                .changed()
+            {
+                info_log!("[app] Show un-fused changed to: {}", unfused);
+                self.merge_config.set_unfused(unfused);
+                if let Err(e) = self.reload_graph() {
+                    eprintln!("[app] Failed to reload graph: {}", e);
+                }
+            }
+            if self.isis_topologies.len() > 1 || self.isis_topology_filter.is_some() {
+                let previous = self.isis_topology_filter.clone();
+                egui::ComboBox::from_label("IS-IS topology")
+                    .selected_text(match &self.isis_topology_filter {
+                        Some(mt_id) => mt_id.to_string(),
+                        None => "All".to_string(),
+                    })
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.isis_topology_filter, None, "All");
+                        for mt_id in &self.isis_topologies {
+                            ui.selectable_value(
+                                &mut self.isis_topology_filter,
+                                Some(mt_id.clone()),
+                                mt_id.to_string(),
+                            );
+                        }
+                    });
+                if self.isis_topology_filter != previous {
+                    println!(
+                        "[app] IS-IS topology filter changed to: {:?}",
+                        self.isis_topology_filter
+                    );
+                    if let Err(e) = self.reload_graph() {
+                        eprintln!("[app] Failed to reload graph: {}", e);
+                    }
+                }
+            }
+            if self.isis_areas.len() > 1 || self.isis_area_filter.is_some() {
+                let previous = self.isis_area_filter.clone();
+                egui::ComboBox::from_label("IS-IS area")
+                    .selected_text(self.isis_area_filter.as_deref().unwrap_or("All"))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.isis_area_filter, None, "All");
+                        for area in &self.isis_areas {
+                            ui.selectable_value(&mut self.isis_area_filter, Some(area.clone()), area);
+                        }
+                    })
+                    .response
+                    .on_hover_text("Routers in several areas (L2 backbone routers) show up in each of them");
+                if self.isis_area_filter != previous {
+                    info_log!("[app] IS-IS area filter changed to: {:?}", self.isis_area_filter);
+                    if let Err(e) = self.reload_graph() {
+                        eprintln!("[app] Failed to reload graph: {}", e);
+                    }
+                }
+            }
+
+            if !self.hidden_nodes.is_empty()
+                && ui
+                    .button(format!("Unhide all ({})", self.hidden_nodes.len()))
+                    .clicked()
+            {
+                self.hidden_nodes.clear();
+                if let Err(e) = self.reload_graph() {
+                    eprintln!("[app] Failed to reload graph: {}", e);
+                }
+            }
+
+            CollapsingHeader::new("Legend")
+                .default_open(false)
+                .show(ui, |ui| {
+                    self.render_palette_selector(ui);
+                    self.render_legend(ui);
+                });
+
+            CollapsingHeader::new("Node labels")
+                .default_open(false)
+                .show(ui, |ui| {
+                    self.render_label_template_controls(ui);
+                });
+
+            // Body (and therefore the stats computation) only runs while expanded
+            CollapsingHeader::new("Graph stats")
+                .default_open(false)
+                .show(ui, |ui| {
+                    self.render_graph_stats(ui);
+                });
+
+            let warning_count = self.prefix_conflicts.len()
+                + self.overlapping_prefixes.len()
+                + self.metric_conflicts.len();
+            if warning_count > 0 {
+                CollapsingHeader::new(format!("Warnings ({})", warning_count))
+                    .id_salt("warnings")
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        if !self.prefix_conflicts.is_empty() {
+                            ui.colored_label(
+                                self.theme.yellow,
+                                "Prefixes seen via both OSPF and IS-IS are shown as separate network nodes:",
+                            );
+                            bullet_list(ui, self.prefix_conflicts.iter());
+                        }
+                        if !self.overlapping_prefixes.is_empty() {
+                            ui.colored_label(
+                                self.theme.yellow,
+                                "Overlapping prefixes from different sources (mask mismatch or aggregation?):",
+                            );
+                            bullet_list(
+                                ui,
+                                self.overlapping_prefixes
+                                    .iter()
+                                    .map(|(outer, inner)| format!("{} contains {}", outer, inner)),
+                            );
+                        }
+                        if !self.metric_conflicts.is_empty() {
+                            ui.colored_label(
+                                self.theme.yellow,
+                                "Sources disagree on link metrics of fused routers, the first one is shown:",
+                            );
+                            bullet_list(ui, self.metric_conflicts.iter());
+                        }
+                    });
+            }
+
+            if !self.asymmetric_links.is_empty() {
+                CollapsingHeader::new(format!(
+                    "Asymmetric metrics ({})",
+                    self.asymmetric_links.len()
+                ))
+                .id_salt("asymmetric_metrics")
+                .default_open(false)
+                .show(ui, |ui| {
+                    let name = |id: Uuid| self.graph.node_label(id).unwrap_or_else(|| id.to_string());
+                    let links = self.asymmetric_links.iter().map(|(a, b, ab, ba)| {
+                        format!("{} <-> {}: {} forward, {} back", name(*a), name(*b), ab, ba)
+                    });
+                    bullet_list(ui, links);
+                });
+            }
 
             ui.separator();
 
@@ -909,89 +3193,104 @@ impl App {
                         ui.label("Password");
                         ui.text_edit_singleline(&mut self.ssh_password);
                     });
+                    ui.horizontal(|ui| {
+                        ui.label("Connect timeout (s)");
+                        let mut seconds = self.ssh_connect_timeout.as_secs();
+                        if ui
+                            .add(egui::DragValue::new(&mut seconds).range(1..=120))
+                            .changed()
+                        {
+                            self.ssh_connect_timeout = Duration::from_secs(seconds);
+                        }
+                    });
                     ui.checkbox(
                         &mut self.ssh_clear_sources_on_switch,
                         "Clear previous sources on connect",
                     );
-                    if self.ssh_connect_pending {
-                        ui.add_enabled_ui(false, |ui| {
-                            _ = ui.button("Connect");
+                    ui.checkbox(&mut self.ssh_verify_host_keys, "Verify host keys")
+                        .on_hover_text(
+                            "Check the router's host key against ~/.ssh/known_hosts before \
+                             sending the password. Also applies to NETCONF.",
+                        );
+                    ui.add_enabled(
+                        self.ssh_verify_host_keys,
+                        Checkbox::new(&mut self.ssh_trust_on_first_use, "Trust new hosts on first use"),
+                    )
+                    .on_hover_text(
+                        "Add hosts that aren't in known_hosts yet without asking. A changed key is \
+                         still rejected.",
+                    );
+                    if let Some(task) = &self.ssh_connect_task {
+                        ui.horizontal(|ui| {
+                            ui.add_enabled_ui(false, |ui| {
+                                _ = ui.button("Connect");
+                            });
+                            ui.add(egui::Spinner::new());
+                            ui.label(task.phase().unwrap_or("Starting…"));
                         });
                     } else if ui.button("Connect").clicked() {
-                        // Quick & dirty: spawn a thread and create a per-thread runtime to perform SSH connect + snapshot fetch,
-                        // then send snapshot back via channel for the UI thread to apply.
-                        let res_arc = std::sync::Arc::new(std::sync::Mutex::new(None));
-                        self.ssh_connect_res = res_arc.clone();
-                        self.ssh_connect_pending = true;
-
-                        let host = self.ssh_host.clone();
-                        let port = self.ssh_port;
-                        let username = self.ssh_username.clone();
-                        let password = self.ssh_password.clone();
-                        let res_arc = res_arc.clone();
-
-                        std::thread::spawn(move || {
-                            println!("[bg-ssh] thread start - attempting to create runtime");
-                            let rt = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
-                                Ok(rt) => {
-                                    println!("[bg-ssh] runtime (current_thread) created");
-                                    rt
-                                }
-                                Err(e) => {
-                                    eprintln!("[bg-ssh] failed to create runtime: {:?}", e);
-                                    // Store the error into the shared result slot so the UI thread can observe it.
-                                    {
-                                        let mut guard = res_arc.lock().unwrap();
-                                        *guard = Some(Err(format!("Failed to create runtime: {:?}", e)));
-                                    }
-                                    return;
-                                }
-                            };
+                        self.start_ssh_connect();
+                        ui.ctx().request_repaint();
+                    }
+                    if let Some(PollError::HostKey(rejection)) = self.ssh_connect_error.clone() {
+                        if let Some(trust) = host_key_prompt(ui, &rejection, self.theme.red) {
+                            self.ssh_connect_error = None;
+                            if trust {
+                                self.ssh_accepted_host_key = Some(rejection.fingerprint);
+                                self.start_ssh_connect();
+                            }
+                        }
+                    } else if let Some(err) = &self.ssh_connect_error {
+                        ui.colored_label(self.theme.red, err.to_string());
+                    }
+                });
 
-                            println!("[bg-ssh] entering block_on to run async connect/fetch");
-                            let res = rt.block_on(async move {
-                                println!("[bg-ssh async] creating SSH client");
-                                let client =
-                                    SshClient::new_with_password(username.clone(), host.clone(), password.clone(), port);
-                                println!("[bg-ssh async] created SSH client, creating topology");
-                                match IsIsTopology::new_from_ssh_client(client).await {
-                                    Ok(mut topo) => {
-                                        println!("[bg-ssh async] topology created, fetching snapshot");
-                                        match topo.fetch_snapshot().await {
-                                            Ok((src_id, nodes, stats)) => {
-                                                println!("[bg-ssh async] snapshot fetch succeeded, src_id={:?}, nodes_count={}", src_id, nodes.len());
-                                                // Register source spec
-                                                
-                                                let source_spec = SourceSpec::new_ssh(
-                                                    host.clone(),
-                                                    port,
-                                                    username.clone(),
-                                                    password.clone(),
-                                                    crate::gui::autopoll::ProtocolKind::Isis
-                                                );
-                                                
-                                                Ok((src_id, nodes, stats, source_spec))
-                                            }
-                                            Err(e) => {
-                                                eprintln!("[bg-ssh async] snapshot fetch failed: {:?}", e);
-                                                Err(format!("Failed to fetch snapshot: {:?}", e))
-                                            }
-                                        }
-                                    }
-                                    Err(e) => {
-                                        eprintln!("[bg-ssh async] failed to create topology: {:?}", e);
-                                        Err(format!("Failed to create IsIsTopology: {:?}", e))
-                                    }
-                                }
+            // NETCONF connection management, for routers exposing ietf-isis instead of vtysh
+            CollapsingHeader::new("NETCONF Connection (IS-IS)")
+                .default_open(false)
+                .show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Host");
+                        ui.text_edit_singleline(&mut self.netconf_host);
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Port");
+                        ui.add(egui::DragValue::new(&mut self.netconf_port).range(1..=65535));
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Username");
+                        ui.text_edit_singleline(&mut self.netconf_username);
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Password");
+                        ui.add(egui::TextEdit::singleline(&mut self.netconf_password).password(true));
+                    });
+                    ui.checkbox(
+                        &mut self.netconf_clear_sources_on_switch,
+                        "Clear previous sources on connect",
+                    );
+                    if let Some(task) = &self.netconf_connect_task {
+                        ui.horizontal(|ui| {
+                            ui.add_enabled_ui(false, |ui| {
+                                _ = ui.button("Connect");
                             });
-
-                            println!("[bg-ssh] async work complete, sending result back to UI thread (ok/error)");
-                            {
-                                // store result into shared Arc<Mutex<Option<...>>> so UI thread can pick it up
-                                let mut guard = res_arc.lock().unwrap();
-                                *guard = Some(res);
+                            ui.add(egui::Spinner::new());
+                            ui.label(task.phase().unwrap_or("Starting…"));
+                        });
+                    } else if ui.button("Connect").clicked() {
+                        self.start_netconf_connect();
+                        ui.ctx().request_repaint();
+                    }
+                    if let Some(PollError::HostKey(rejection)) = self.netconf_connect_error.clone() {
+                        if let Some(trust) = host_key_prompt(ui, &rejection, self.theme.red) {
+                            self.netconf_connect_error = None;
+                            if trust {
+                                self.ssh_accepted_host_key = Some(rejection.fingerprint);
+                                self.start_netconf_connect();
                             }
-                        }); ui.ctx().request_repaint();
+                        }
+                    } else if let Some(err) = &self.netconf_connect_error {
+                        ui.colored_label(self.theme.red, err.to_string());
                     }
                 });
 
@@ -1017,98 +3316,84 @@ impl App {
                         ui.label("Community");
                         ui.text_edit_singleline(&mut self.snmp_community);
                     });
+                    ui.horizontal(|ui| {
+                        ui.label("Max repetitions");
+                        ui.add(egui::DragValue::new(&mut self.snmp_max_repetitions).range(1..=255))
+                            .on_hover_text("Rows requested per GETBULK. Lower this for agents that drop large responses");
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("OID map");
+                        ui.add(egui::TextEdit::singleline(&mut self.snmp_oid_file).hint_text("standard OSPF-MIB"))
+                            .on_hover_text("TOML file with the OIDs to walk on agents that deviate from the OSPF-MIB. Only the OIDs that differ need to be listed.");
+                    });
+                    ui.checkbox(&mut self.snmp_keep_summaries, "Keep summary networks")
+                        .on_hover_text("Show Type-3 summaries as separate inter-area nodes instead of folding them into the detailed network for the same prefix");
                     ui.checkbox(
                         &mut self.clear_sources_on_switch,
                         "Clear previous sources on connect",
                     );
-                    if self.snmp_connect_pending {
-                        ui.add_enabled_ui(false, |ui| {
-                            _ = ui.button("Connect");
+                    if let Some(task) = &self.snmp_connect_task {
+                        ui.horizontal(|ui| {
+                            ui.add_enabled_ui(false, |ui| {
+                                _ = ui.button("Connect");
+                            });
+                            ui.add(egui::Spinner::new());
+                            ui.label(task.phase().unwrap_or("Starting…"));
                         });
                     } else if ui.button("Connect").clicked() {
-                        // Quick & dirty: spawn a thread and create a per-thread runtime to perform SNMP connect + snapshot fetch,
-                        // then send snapshot back via channel for the UI thread to apply.
-                        let res_arc = std::sync::Arc::new(std::sync::Mutex::new(None));
-                        self.snmp_connect_res = res_arc.clone();
-                        self.snmp_connect_pending = true;
-
+                        // Connect and take the first snapshot on the shared runtime; the result
+                        // is picked up by render() once the task finishes.
                         let host = self.snmp_host.clone();
                         let port = self.snmp_port;
                         let community = self.snmp_community.clone();
-                        let res_arc = res_arc.clone();
-
-                        std::thread::spawn(move || {
-                            println!("[bg-snmp] thread start - attempting to create runtime");
-                            let rt = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
-                                Ok(rt) => {
-                                    println!("[bg-snmp] runtime (current_thread) created");
-                                    rt
-                                }
-                                Err(e) => {
-                                    eprintln!("[bg-snmp] failed to create runtime: {:?}", e);
-                                    // Store the error into the shared result slot so the UI thread can observe it.
-                                    {
-                                        let mut guard = res_arc.lock().unwrap();
-                                        *guard = Some(Err(format!("Failed to create runtime: {:?}", e)));
-                                    }
-                                    return;
-                                }
-                            };
+                        let max_repetitions = self.snmp_max_repetitions;
+                        let oid_file = Some(self.snmp_oid_file.trim())
+                            .filter(|path| !path.is_empty())
+                            .map(std::path::PathBuf::from);
+                        let summary_policy = self.snmp_summary_policy();
 
-                            println!("[bg-snmp] entering block_on to run async SNMP lookup/fetch");
-                            let res = rt.block_on(async move {
-                                println!("[bg-snmp async] resolving host");
-                                // Resolve host (IP or DNS)
-                                let addr = if let Ok(ip) = host.parse::<std::net::IpAddr>() {
-                                    std::net::SocketAddr::new(ip, port)
-                                } else {
-                                    match tokio::net::lookup_host((host.as_str(), port)).await {
-                                        Ok(mut addrs) => addrs.next().unwrap_or_else(|| {
-                                            std::net::SocketAddr::new(
-                                                std::net::IpAddr::V4(std::net::Ipv4Addr::LOCALHOST),
-                                                port,
-                                            )
-                                        }),
-                                        Err(e) => {
-                                            eprintln!("[bg-snmp async] DNS lookup failed: {:?}", e);
-                                            return Err(format!("DNS lookup failed: {:?}", e));
-                                        }
-                                    }
-                                };
-
-                                println!("[bg-snmp async] creating SNMP client for addr={}", addr);
-                                let client = crate::data_aquisition::snmp::SnmpClient::new(
-                                    addr.clone(),
-                                    &community,
-                                    snmp2::Version::V2C,
-                                    None,
-                                );
-                                println!("[bg-snmp async] created SNMP client, building topology");
-                                let mut topo = OspfSnmpTopology::from_snmp_client(client);
-                                println!("[bg-snmp async] fetching snapshot from SNMP topology");
-                                match topo.fetch_snapshot().await {
-                                    Ok((src_id, nodes, stats)) => {
-                                        println!("[bg-snmp async] snapshot fetch succeeded src_id={:?}, nodes_count={}", src_id, nodes.len());
-                                        
-                                        let spec = SourceSpec::new_snmp(addr, community, snmp2::Version::V2C, None, crate::gui::autopoll::ProtocolKind::Ospf);
-                                        
-                                        Ok((src_id, nodes, stats, spec))
-                                    }
-                                    Err(e) => {
-                                        eprintln!("[bg-snmp async] failed to fetch snapshot: {:?}", e);
-                                        Err(format!("Failed to fetch snapshot: {:?}", e))
-                                    }
-                                }
-                            });
+                        self.snmp_connect_task = Some(BackgroundTask::spawn(&self.runtime, |phase| async move {
+                            phase.set("Resolving host…");
+                            // Resolve host (IP or DNS)
+                            let addr = if let Ok(ip) = host.parse::<std::net::IpAddr>() {
+                                std::net::SocketAddr::new(ip, port)
+                            } else {
+                                tokio::net::lookup_host((host.as_str(), port))
+                                    .await
+                                    .map_err(|e| PollError::Init(format!("DNS lookup failed: {:?}", e)))?
+                                    .next()
+                                    .ok_or_else(|| {
+                                        PollError::Init(format!("DNS lookup for {} returned no addresses", host))
+                                    })?
+                            };
 
-                            println!("[bg-snmp] async work complete, sending result back to UI thread");
-                            {
-                                // store result into shared Arc<Mutex<Option<...>>> so UI thread can pick it up
-                                let mut guard = res_arc.lock().unwrap();
-                                *guard = Some(res);
+                            let client = crate::data_aquisition::snmp::SnmpClient::new(
+                                addr,
+                                &community,
+                                snmp2::Version::V2C,
+                                None,
+                            )
+                            .with_max_repetitions(max_repetitions);
+                            let mut topo = match &oid_file {
+                                Some(path) => OspfSnmpTopology::from_snmp_client_with_oids(
+                                    client,
+                                    SnmpOidMap::load(path).map_err(PollError::Init)?,
+                                ),
+                                None => OspfSnmpTopology::from_snmp_client(client),
                             }
-                            println!("[bg-snmp] send complete, thread exiting");
-                        }); ui.ctx().request_repaint();
+                            .with_consolidation_policy(summary_policy);
+                            let started = std::time::Instant::now();
+                            let snapshot = fetch_snapshot_with_phases(&mut topo, &phase)
+                                .await
+                                .map_err(|e| PollError::Fetch(format!("{:?}", e)))?;
+                            let elapsed = started.elapsed();
+                            println!("[bg-snmp] snapshot fetch succeeded src_id={:?}, nodes_count={}", snapshot.0, snapshot.1.len());
+                            let spec = SourceSpec::new_snmp(addr, community, snmp2::Version::V2C, None, max_repetitions, crate::gui::autopoll::ProtocolKind::Ospf)
+                                .with_oid_file(oid_file)
+                                .with_summary_policy(summary_policy);
+                            Ok(((snapshot, elapsed), spec))
+                        }));
+                        ui.ctx().request_repaint();
                     }
                 });
 
@@ -1116,6 +3401,10 @@ impl App {
 
             self.render_sources_section(ui);
 
+            ui.separator();
+
+            self.render_interface_mappings(ui);
+
             ui.separator();
             
             self.render_autopoll_controls(ui);
@@ -1156,6 +3445,66 @@ impl App {
 
             // Forces section
             CollapsingHeader::new("Forces").default_open(true).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    let mut frozen = !self.layout_state.base.is_running;
+                    if ui.checkbox(&mut frozen, "Freeze layout").changed() {
+                        self.layout_state.base.is_running = !frozen;
+                    }
+                    info_icon(ui, "Stop the simulation so arranged nodes stay put. Pin single nodes from their right-click menu.");
+                });
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::Slider::new(&mut self.layout_iterations_per_frame, 1..=20)
+                            .text("iterations/frame"),
+                    );
+                    info_icon(ui, "Simulation steps per frame. Lower keeps big graphs responsive, higher settles faster.");
+                });
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.idle_when_settled, "Idle when settled");
+                    info_icon(ui, "Pause the simulation once nodes stop moving so an idle window doesn't keep redrawing. Any input wakes it up again.");
+                    if self.layout_settled() {
+                        ui.weak("(settled)");
+                    }
+                });
+                if ui
+                    .add_enabled(!self.layout_state.base.is_running, Button::new("Step once"))
+                    .on_hover_text("Run a single layout step while the layout is frozen")
+                    .clicked()
+                {
+                    self.layout_step_once = true;
+                }
+                if self.graph.pinned_count() > 0
+                    && ui
+                        .button(format!("Unpin all ({})", self.graph.pinned_count()))
+                        .clicked()
+                {
+                    self.graph.clear_pins();
+                }
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("Save layout")
+                        .on_hover_text(format!("Write node positions to {}", SavedLayout::path().display()))
+                        .clicked()
+                    {
+                        match SavedLayout::from_positions(&self.graph.node_positions()).save() {
+                            Ok(path) => info_log!("[app] Saved layout to {}", path.display()),
+                            Err(e) => eprintln!("[app] Failed to save layout: {}", e),
+                        }
+                    }
+                    if ui
+                        .button("Load layout")
+                        .on_hover_text("Move nodes to their saved positions. Nodes not in the saved layout stay where they are.")
+                        .clicked()
+                    {
+                        match SavedLayout::load() {
+                            Some(layout) => {
+                                let moved = self.graph.restore_layout(layout.to_positions());
+                                info_log!("[app] Restored the saved position of {} node(s)", moved);
+                            }
+                            None => eprintln!("[app] No saved layout at {}", SavedLayout::path().display()),
+                        }
+                    }
+                });
                 ui.horizontal(|ui| {
                     ui.add(egui::Slider::new(&mut self.layout_state.base.k_scale, 0.2..=3.0).text("k_scale"));
                     info_icon(ui, "Scale ideal edge length k; >1 spreads the layout, <1 compacts it.");
@@ -1180,13 +3529,46 @@ impl App {
                         ui.add(egui::Slider::new(&mut self.layout_state.extras.0.params.c, 0.0..=2.0).text("center_strength"));
                         info_icon(ui, "Coefficient for pull toward viewport/graph center.");
                     });
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut self.gravity_to_selected, "gravity_to_selected");
+                        info_icon(ui, "Pull toward the selected node instead of the graph center, making it the focal point of the layout.");
+                    });
+                });
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.area_clustering, "area_clustering");
+                    info_icon(ui, "Group nodes by OSPF area: each area is pulled toward its own anchor, backbone in the middle.");
+                });
+                ui.add_enabled_ui(self.area_clustering, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.add(egui::Slider::new(&mut self.area_clustering_strength, 0.0..=0.3).text("area_strength"));
+                        info_icon(ui, "Fraction of the distance to the area anchor covered per frame.");
+                    });
                 });
             });
             ui.separator();
+            self.render_search_controls(ui);
+            ui.separator();
             self.render_path_controls(ui);
             ui.separator();
+            self.render_focus_controls(ui);
+            ui.separator();
             self.render_edit_tools(ui);
             ui.separator();
+            ui.horizontal(|ui| {
+                ui.label("Console logging");
+                let mut level = logging::level();
+                egui::ComboBox::from_id_salt("log_level")
+                    .selected_text(level.name())
+                    .show_ui(ui, |ui| {
+                        for option in LogLevel::ALL {
+                            ui.selectable_value(&mut level, option, option.name());
+                        }
+                    });
+                if level != logging::level() {
+                    logging::set_level(level);
+                }
+                info_icon(ui, "Verbosity of parsing and graph-building output on stdout. Also set by OSPFVIZ_LOG.");
+            });
             if ui.button("Print graph data").clicked() {
                 println!("[app] Pressed print graph data button");
                 println!("{}", self.graph.to_string())
@@ -1195,7 +3577,7 @@ impl App {
                 let merged = self.store.build_merged_view_with(&self.merge_config);
                 match merged {
                     Ok(merged) => {
-                        let graph = NetworkGraph::build_new(merged);
+                        let graph = NetworkGraph::build_new(merged, self.graph.reverse_metric_policy());
                         println!("[app] Pressed try build graph from store and print button");
                         println!("Fresh {}", graph.to_string())
                     }
@@ -1204,6 +3586,42 @@ impl App {
                     }
                 }
             }
+            if ui
+                .button("Copy Mermaid")
+                .on_hover_text("Copy the displayed graph as a Mermaid flowchart")
+                .clicked()
+            {
+                ui.ctx().copy_text(self.graph.to_mermaid());
+            }
+            if ui
+                .button("Export GraphML")
+                .on_hover_text(format!("Write the displayed graph to {} for yEd, Gephi and similar tools", GRAPHML_EXPORT_FILE))
+                .clicked()
+            {
+                match std::fs::write(GRAPHML_EXPORT_FILE, self.graph.to_graphml()) {
+                    Ok(()) => info_log!("[app] Exported graph to {}", GRAPHML_EXPORT_FILE),
+                    Err(e) => eprintln!("[app] Failed to export graph to {}: {}", GRAPHML_EXPORT_FILE, e),
+                }
+            }
+            if ui
+                .button("Export merge trace")
+                .on_hover_text(format!(
+                    "Write which sources every merged node came from, and whether they were fused, to {}",
+                    MERGE_TRACE_EXPORT_FILE
+                ))
+                .clicked()
+            {
+                let trace = self
+                    .store
+                    .build_merged_view_traced(&self.merge_config)
+                    .map_err(|e| e.to_string())
+                    .and_then(|(_, trace)| trace.to_json().map_err(|e| e.to_string()));
+                match trace.map(|json| std::fs::write(MERGE_TRACE_EXPORT_FILE, json)) {
+                    Ok(Ok(())) => info_log!("[app] Exported merge trace to {}", MERGE_TRACE_EXPORT_FILE),
+                    Ok(Err(e)) => eprintln!("[app] Failed to write {}: {}", MERGE_TRACE_EXPORT_FILE, e),
+                    Err(e) => eprintln!("[app] Failed to trace the merge: {}", e),
+                }
+            }
             if ui.button("Print all node uuids").clicked() {
                 println!("[app] Pressed print all node uuids button");
                 for node in self.graph.graph.nodes_iter() {
@@ -1226,12 +3644,58 @@ impl App {
         SidePanel::right("right_panel").show(ctx, render_side_panel);
 
         CentralPanel::default().show(ctx, |ui| {
-            egui_graphs::set_layout_state(ui, self.layout_state.clone(), None);
+            // Any input wakes a settled layout, e.g. dragging a node needs the others to follow
+            if ctx.input(|i| !i.events.is_empty() || i.pointer.is_moving() || i.pointer.any_down()) {
+                self.settled_frames = 0;
+            }
+            let mut layout_state = self.layout_state.clone();
+            if self.layout_settled() {
+                layout_state.base.is_running = false;
+            }
+            let layout_active = layout_state.base.is_running;
+            if let Some(focus) = self.gravity_focus() {
+                // GraphView would pull towards the view center, so step the layout here with the
+                // gravity centered on the focus node and only let the widget draw
+                let steps = if self.layout_step_once {
+                    1
+                } else if layout_active {
+                    self.layout_iterations_per_frame
+                } else {
+                    0
+                };
+                if steps > 0 {
+                    layout_state.base.is_running = true;
+                    let view_size = ctx.content_rect().size();
+                    layout_state = self.graph.step_layout_with_focus(layout_state, focus, view_size, steps);
+                }
+                layout_state.base.is_running = false;
+            } else if self.layout_step_once {
+                layout_state.base.is_running = true;
+            } else if layout_state.base.is_running {
+                // GraphView runs one step itself; drive the rest explicitly
+                for _ in 1..self.layout_iterations_per_frame {
+                    let mut layout = LayoutForceDirected::<Layout>::from_state(layout_state);
+                    layout.next(&mut self.graph.graph, ui);
+                    layout_state = layout.state();
+                }
+            }
+            egui_graphs::set_layout_state(ui, layout_state, None);
+            self.layout_step_once = false;
 
             // Reset area highlight and clear collector before drawing graph so shapes() will populate them during widget draw.
             clear_area_highlight();
             clear_label_overlays();
+            node_shape::clear_node_screen_positions();
+            area_hulls::clear_area_members();
+            // Reserve a slot below the graph; area hulls are only known once shapes() has run
+            let area_hull_slot = ui.painter().add(egui::Shape::Noop);
+            self.refresh_edge_bundles();
 
+            // GraphView only takes padding as a fraction of the graph size, so convert the pixel
+            // padding and zoom using the current bounding box
+            let fit_padding = self.graph.bounding_box().map_or(0.1, |bounds| {
+                fit_padding_fraction(bounds, ui.available_rect_before_wrap(), self.fit_padding, self.initial_zoom)
+            });
             let widget = &mut egui_graphs::GraphView::<
                 Node,
                 crate::network::edge::Edge,
@@ -1245,11 +3709,14 @@ impl App {
             .with_navigations(
                 &SettingsNavigation::default()
                     .with_zoom_and_pan_enabled(false)
-                    .with_fit_to_screen_enabled(true),
+                    .with_fit_to_screen_enabled(true)
+                    .with_fit_to_screen_padding(fit_padding),
             )
             .with_interactions(
                 &SettingsInteraction::default()
                     .with_node_selection_enabled(true)
+                    // Hold shift to add nodes to the current selection
+                    .with_node_selection_multi_enabled(ctx.input(|i| i.modifiers.shift))
                     .with_edge_clicking_enabled(true)
                     .with_edge_selection_enabled(true),
             );
@@ -1258,7 +3725,37 @@ impl App {
             edge_shape::clear_edge_events();
 
             // Add widget and obtain response so we can overlay labels afterwards.
-            let _response = ui.add(widget);
+            let response = ui.add(widget);
+            self.update_hovered_edge(ui.ctx(), &response);
+
+            if area_hulls::area_backgrounds_enabled() {
+                let (hulls, labels) = area_hulls::take_area_hulls(&self.theme, 8.0);
+                ui.painter().set(area_hull_slot, egui::Shape::Vec(hulls));
+                area_hulls::paint_area_labels(ui.painter(), labels);
+            }
+
+            self.handle_box_select(ui, &response);
+
+            // Right-click opens the context menu of the hovered node, none on the background
+            if response.secondary_clicked() {
+                self.context_menu_node = self
+                    .graph
+                    .graph
+                    .nodes_iter()
+                    .find(|(_, node)| node.hovered())
+                    .map(|(idx, _)| idx);
+            }
+            if let Some(idx) = self.context_menu_node {
+                response.context_menu(|ui| self.render_node_context_menu(ui, idx));
+            }
+            if self.area_clustering && layout_active {
+                self.graph.apply_area_gravity(self.area_clustering_strength);
+            }
+            // Undo this frame's layout step for pinned nodes
+            self.graph.apply_pins();
+            if layout_active {
+                self.track_layout_settling();
+            }
 
             for ev in crate::gui::edge_shape::take_edge_events() {
                 if matches!(self.edit_tool, EditTool::Snip) {
@@ -1318,28 +3815,30 @@ impl App {
                         }
                         Some(a) if a != idx => {
                             // Validate Router <-> Network for Membership
-                            let a_info = self.graph.graph.node(a).map(|n| n.payload().info.clone());
-                            let b_info =
-                                self.graph.graph.node(idx).map(|n| n.payload().info.clone());
-                            let valid_membership = match (a_info, b_info) {
-                                (
-                                    Some(crate::network::node::NodeInfo::Router(_)),
-                                    Some(crate::network::node::NodeInfo::Network(_)),
-                                )
-                                | (
-                                    Some(crate::network::node::NodeInfo::Network(_)),
-                                    Some(crate::network::node::NodeInfo::Router(_)),
-                                ) => true,
+                            let valid_membership = match (
+                                self.graph.graph.node(a),
+                                self.graph.graph.node(idx),
+                            ) {
+                                (Some(a), Some(b)) => {
+                                    drawable_edge_kind(&a.payload().info, &b.payload().info)
+                                        .is_some()
+                                }
                                 _ => false,
                             };
                             if valid_membership {
                                 let a_uuid = self.graph.graph.node(a).unwrap().payload().id;
                                 let b_uuid = self.graph.graph.node(idx).unwrap().payload().id;
                                 // Default metric 1; later make this user-configurable at creation
-                                self.graph
-                                    .add_manual_edge(a_uuid, b_uuid, EdgeKind::Membership, 1);
-                                edge_anim::publish_create(a_uuid, b_uuid, EdgeKind::Membership);
-                                edge_anim::publish_create(b_uuid, a_uuid, EdgeKind::Membership);
+                                match self
+                                    .graph
+                                    .add_manual_edge(a_uuid, b_uuid, EdgeKind::Membership, 1)
+                                {
+                                    Ok(()) => {
+                                        edge_anim::publish_create(a_uuid, b_uuid, EdgeKind::Membership);
+                                        edge_anim::publish_create(b_uuid, a_uuid, EdgeKind::Membership);
+                                    }
+                                    Err(e) => eprintln!("[app] Can't add manual edge: {}", e),
+                                }
                             } else {
                                 // Optional: feedback
                                 eprintln!(
@@ -1352,10 +3851,15 @@ impl App {
                     }
                 }
             }
+            self.update_draw_preview();
 
             // Take the collected overlay labels and paint them on top of the graph widget.
             let labels: Vec<LabelOverlay> = take_label_overlays();
-            if let Some(sel_idx) = self.selected_node {
+            if self.selected_nodes.len() > 1 {
+                if let Some(first_overlay) = labels.first() {
+                    self.render_multi_selection_panel(ctx, first_overlay.center);
+                }
+            } else if let Some(sel_idx) = self.selected_node {
                 // Ensure the node is still selected in the underlying graph; if not, drop selection.
                 let still_selected =
                     self.graph.graph.selected_nodes().first().map(|i| *i) == Some(sel_idx);
@@ -1370,9 +3874,56 @@ impl App {
                         .graph
                         .node(sel_idx)
                         .expect("Could not find selected node");
+                    let selected_uuid = selected_node.payload().id;
+                    let is_pinned = self.graph.is_pinned(selected_uuid);
+                    let mut toggle_pin = false;
+
+                    if self.area_override_node != Some(selected_uuid) {
+                        self.area_override_node = Some(selected_uuid);
+                        self.area_override_input = self
+                            .merge_config
+                            .area_override(&selected_uuid)
+                            .map(format_area_list)
+                            .unwrap_or_default();
+                        self.area_override_error = None;
+                    }
+                    // Only OSPF nodes inside an area can have theirs overridden
+                    let has_areas = !area_hulls::node_areas(selected_node.payload()).is_empty();
+                    let is_overridden = self.merge_config.area_override(&selected_uuid).is_some();
+                    let area_override_input = &mut self.area_override_input;
+                    let area_override_error = &self.area_override_error;
+                    let mut area_override_edit: Option<Result<Vec<Ipv4Addr>, String>> = None;
+                    let store = &self.store;
+                    let theme = &self.theme;
+                    let autopoll_interval = self.autopoll_interval;
                     let render_node_label = |ui: &mut Ui, _ctx: &Context| {
                         let node_info = &selected_node.props().payload.info;
-                        ui.label(format!("Node ID: {}", selected_node.payload().id));
+                        copyable_label(
+                            ui,
+                            format!("Node ID: {}", selected_node.payload().id),
+                            selected_node.payload().id,
+                        );
+                        if let Some(src_id) = &selected_node.payload().source_id {
+                            ui.horizontal(|ui| {
+                                copyable_label(ui, format!("Source: {}", src_id), src_id);
+                                if let Some(state) = store.get_source_state(src_id) {
+                                    let age = state.last_snapshot.elapsed().unwrap_or_default();
+                                    let color = match Freshness::from_age(age, autopoll_interval) {
+                                        Freshness::Fresh => theme.green,
+                                        Freshness::Aging => theme.yellow,
+                                        Freshness::Stale => theme.red,
+                                    };
+                                    ui.colored_label(color, format!("updated {} ago", format_age(age)))
+                                        .on_hover_text(
+                                            humantime::format_rfc3339_seconds(state.last_snapshot)
+                                                .to_string(),
+                                        );
+                                }
+                            });
+                        }
+                        toggle_pin = ui
+                            .button(if is_pinned { "Unpin position" } else { "Pin position" })
+                            .clicked();
                         if ui.button("Print serialized node data").clicked() {
                             println!(
                                 "{}",
@@ -1381,18 +3932,68 @@ impl App {
                         }
                         match node_info {
                             NodeInfo::Router(router) => {
-                                ui.label(format!("Router ID: {}", router.id));
+                                copyable_label(ui, format!("Router ID: {}", router.id), &router.id);
+                                if let Some(hostname) = &router.hostname {
+                                    copyable_label(ui, format!("Hostname: {}", hostname), hostname);
+                                }
+                                if let Some(description) = &router.description {
+                                    collapsible_section(ui, "System description", false, |ui| {
+                                        ui.label(anonymized(description));
+                                    });
+                                }
                                 protocol_data_section(ui, &router.protocol_data);
                             }
                             NodeInfo::Network(net) => {
-                                ui.label(format!("Network prefix: {}", net.ip_address));
+                                copyable_label(
+                                    ui,
+                                    format!("Network prefix: {}", net.ip_address),
+                                    net.ip_address,
+                                );
                                 ui.label(format!("Network mask: {}", net.ip_address.mask()));
+                                let designated_router = net.ospf_designated_router();
+                                if let Some((dr, interface)) = &designated_router {
+                                    copyable_label(
+                                        ui,
+                                        format!("Designated Router: {} (interface {})", dr, interface),
+                                        dr,
+                                    );
+                                }
                                 ui.separator();
                                 collapsible_section(ui, "Attached router IDs", true, |ui| {
-                                    bullet_list(ui, net.attached_routers.iter());
+                                    let routers = net.attached_routers.iter().map(|router| {
+                                        match &designated_router {
+                                            Some((dr, _)) if dr == router => format!("{} (DR)", router),
+                                            _ => router.to_string(),
+                                        }
+                                    });
+                                    bullet_list(ui, routers);
                                 });
                                 protocol_data_section(ui, &net.protocol_data);
                             }
+                            NodeInfo::Area(area) => {
+                                ui.label(format!("Collapsed {}, {} nodes", area.name, area.members));
+                                collapsible_section(ui, "Router IDs", true, |ui| {
+                                    bullet_list(ui, area.routers.iter().map(|router| router.to_string()));
+                                });
+                            }
+                        }
+                        if has_areas || is_overridden {
+                            let title = if is_overridden { "Area override (active)" } else { "Area override" };
+                            collapsible_section(ui, title, is_overridden, |ui| {
+                                ui.label("Areas, comma separated, e.g. \"0.0.0.0, 1\"");
+                                ui.text_edit_singleline(area_override_input);
+                                ui.horizontal(|ui| {
+                                    if ui.button("Apply").clicked() {
+                                        area_override_edit = Some(parse_area_list(area_override_input));
+                                    }
+                                    if is_overridden && ui.button("Clear").clicked() {
+                                        area_override_edit = Some(Ok(Vec::new()));
+                                    }
+                                });
+                                if let Some(err) = area_override_error {
+                                    ui.colored_label(theme.red, err);
+                                }
+                            });
                         }
                     };
 
@@ -1403,6 +4004,21 @@ impl App {
                             node.set_label(working_label);
                         }
                     }
+                    if toggle_pin {
+                        self.graph.toggle_pin(selected_uuid);
+                    }
+                    match area_override_edit {
+                        Some(Ok(areas)) => {
+                            if areas.is_empty() {
+                                self.area_override_input.clear();
+                            }
+                            self.area_override_error = None;
+                            self.merge_config.set_area_override(selected_uuid, areas);
+                            self.reload_graph_logged("area override");
+                        }
+                        Some(Err(err)) => self.area_override_error = Some(err),
+                        None => {}
+                    }
                     if resp.close_clicked {
                         // Deselect node when panel is closed to prevent flicker on hover of other nodes.
                         self.selected_node = None;
@@ -1416,7 +4032,10 @@ impl App {
         // If a connect request is pending, request continuous repaints so render() keeps being called
         // and the background channels are polled until the result arrives. Without this, the UI may
         // stop repainting and never observe the channel message, leaving the buttons locked.
-        if self.ssh_connect_pending || self.snmp_connect_pending {
+        if self.ssh_connect_task.is_some()
+            || self.netconf_connect_task.is_some()
+            || self.snmp_connect_task.is_some()
+        {
             ctx.request_repaint();
         }
     }
@@ -1436,7 +4055,7 @@ impl App {
             }
         };
 
-        self.topo = Box::new(topo);
+        self.topo = Some(Box::new(topo));
 
         self.refresh_from_source().await;
     }
@@ -1470,8 +4089,12 @@ impl App {
             &self.snmp_community,
             snmp2::Version::V2C,
             None,
-        );
-        self.topo = Box::new(OspfSnmpTopology::from_snmp_client(client));
+        )
+        .with_max_repetitions(self.snmp_max_repetitions);
+        self.topo = Some(Box::new(
+            OspfSnmpTopology::from_snmp_client(client)
+                .with_consolidation_policy(self.snmp_summary_policy()),
+        ));
         if self.clear_sources_on_switch {
             self.store = TopologyStore::default();
         }
@@ -1489,12 +4112,15 @@ impl App {
         let now = std::time::SystemTime::now();
 
         // Fetch SourceId first so we can mark it lost if node fetch fails.
-        let snapshot = self.topo.fetch_snapshot().await;
+        let Some(topo) = self.topo.as_mut() else {
+            return;
+        };
+        let snapshot = topo.fetch_snapshot().await;
         match snapshot {
-            Ok((src_id, nodes, stats)) => {
+            Ok((src_id, nodes, stats, warnings)) => {
                 let rollback_state = self.store.get_source_state(&src_id).cloned();
                 self.store
-                    .replace_partition(&src_id, nodes, stats.clone(), now);
+                    .replace_partition_with_warnings(&src_id, nodes, stats.clone(), warnings, now);
                 // Route through authoritative reload_graph()
                 if let Err(e) = self.reload_graph() {
                     eprintln!("Failed to build merged view: {:?}", e);
@@ -1531,11 +4157,131 @@ impl App {
 impl eframe::App for App {
     fn update(&mut self, ctx: &egui::Context, _: &mut eframe::Frame) {
         self.read_data();
+        self.expand_selected_area();
+        self.refresh_source_highlight();
+        // Tab / Shift-Tab walk the nodes, unless a text field has the keyboard
+        if !ctx.wants_keyboard_input() {
+            let (forward, backward) = ctx.input_mut(|i| {
+                (
+                    i.consume_key(egui::Modifiers::NONE, egui::Key::Tab),
+                    i.consume_key(egui::Modifiers::SHIFT, egui::Key::Tab),
+                )
+            });
+            if forward || backward {
+                self.cycle_selected_node(backward);
+            }
+        }
         self.render(ctx);
         // update_data removed (direct edit applied in panel)
     }
 }
 
+/// Delay before a source's first autopoll: a stable hash-based jitter inside `window`, plus
+/// (when `ramp` is set) an even share of `interval` so first polls are spread across it.
+fn initial_poll_delay(
+    src_id: &SourceId,
+    slot: usize,
+    source_count: usize,
+    interval: Duration,
+    window: Duration,
+    ramp: bool,
+) -> Duration {
+    let window_ms = window.as_millis() as u64;
+    let jitter = if window_ms == 0 {
+        Duration::ZERO
+    } else {
+        let mut hasher = DefaultHasher::new();
+        src_id.hash(&mut hasher);
+        Duration::from_millis(hasher.finish() % window_ms)
+    };
+    let ramp_offset = if ramp && source_count > 1 {
+        interval.mul_f64(slot as f64 / source_count as f64)
+    } else {
+        Duration::ZERO
+    };
+    ramp_offset + jitter
+}
+
+/// Kind of manual edge the Draw tool creates between `a` and `b`, or `None` if the pair can't be
+/// connected. Only Router <-> Network membership is supported.
+fn drawable_edge_kind(a: &NodeInfo, b: &NodeInfo) -> Option<EdgeKind> {
+    match (a, b) {
+        (NodeInfo::Router(_), NodeInfo::Network(_)) | (NodeInfo::Network(_), NodeInfo::Router(_)) => {
+            Some(EdgeKind::Membership)
+        }
+        _ => None,
+    }
+}
+
+/// Remove `value` if present, insert it otherwise.
+fn toggle_in_set<T: std::hash::Hash + Eq>(set: &mut HashSet<T>, value: T) {
+    if !set.remove(&value) {
+        set.insert(value);
+    }
+}
+
+/// One horizontal bar per metric bucket, scaled to the fullest bucket.
+fn render_metric_histogram(ui: &mut egui::Ui, histogram: &MetricHistogram, theme: &Theme) {
+    let (Some(min), Some(max)) = (histogram.min, histogram.max) else {
+        ui.label("No edges with a metric.");
+        return;
+    };
+    ui.horizontal(|ui| {
+        ui.label(format!("{} metrics, min {}, max {}", histogram.total(), min, max));
+        info_icon(ui, "Metrics of every edge direction, bucketed by powers of two. Network to router halves of membership edges are left out, they always cost 0. A lone bar far to the right is a link with an unusually high metric.");
+    });
+    let peak = histogram.buckets.iter().map(|b| b.count).max().unwrap_or(0).max(1);
+    let bar_width = 120.0;
+    egui::Grid::new("metric_histogram_grid")
+        .num_columns(3)
+        .show(ui, |ui| {
+            for bucket in &histogram.buckets {
+                if bucket.low == bucket.high {
+                    ui.label(bucket.low.to_string());
+                } else {
+                    ui.label(format!("{}-{}", bucket.low, bucket.high));
+                }
+                let (rect, _) = ui.allocate_exact_size(
+                    egui::vec2(bar_width, ui.spacing().interact_size.y * 0.6),
+                    egui::Sense::hover(),
+                );
+                let filled = rect.width() * bucket.count as f32 / peak as f32;
+                ui.painter().rect_filled(
+                    egui::Rect::from_min_size(rect.min, egui::vec2(filled, rect.height())),
+                    2.0,
+                    theme.blue,
+                );
+                ui.label(bucket.count.to_string());
+                ui.end_row();
+            }
+        });
+}
+
+/// Warning for a host key that failed verification, drawn so it isn't mistaken for an ordinary
+/// connect error. `Some(true)` once the user trusts the key, `Some(false)` if they dismiss it.
+fn host_key_prompt(ui: &mut egui::Ui, rejection: &HostKeyRejection, color: egui::Color32) -> Option<bool> {
+    let mut answer = None;
+    egui::Frame::group(ui.style())
+        .stroke(egui::Stroke::new(2.0, color))
+        .show(ui, |ui| {
+            let title = if rejection.changed { "⚠ Host key changed" } else { "Unknown host key" };
+            ui.label(egui::RichText::new(title).strong().size(16.0).color(color));
+            ui.label(rejection.to_string());
+            if rejection.changed {
+                ui.label("Only trust the new key if you know the router's key was replaced.");
+            }
+            ui.horizontal(|ui| {
+                if ui.button("Trust and connect").clicked() {
+                    answer = Some(true);
+                }
+                if ui.button("Cancel").clicked() {
+                    answer = Some(false);
+                }
+            });
+        });
+    answer
+}
+
 fn info_icon(ui: &mut egui::Ui, tip: &str) {
     ui.add_space(4.0);
     ui.small_button("ℹ").on_hover_text(tip);
@@ -1554,14 +4300,210 @@ fn humanize_value(value: u64) -> (f64, String) {
     (current_value, UNITS[unit_index].to_string())
 }
 
-fn humanize_bytes(bytes: u64) -> String {
-    let (value, prefix) = humanize_value(bytes);
+/// Whole numbers (including 0) print without decimals, everything else with two.
+fn format_humanized(value: u64, unit: &str) -> String {
+    let (value, prefix) = humanize_value(value);
+
+    if value.fract() == 0.0 {
+        format!("{} {}{}", value as u64, prefix, unit)
+    } else {
+        format!("{:.2} {}{}", value, prefix, unit)
+    }
+}
 
-    format!("{:.2} {}B", value, prefix)
+fn humanize_bytes(bytes: u64) -> String {
+    format_humanized(bytes, "B")
 }
 
 fn humanize_packet_count(count: u64) -> String {
-    let (value, prefix) = humanize_value(count);
+    format_humanized(count, "pkts")
+}
+
+fn format_edge_metric(metric: &EdgeMetric) -> String {
+    match metric {
+        EdgeMetric::Ospf(m) => format!("OSPF {}", m),
+        EdgeMetric::IsIs(m) => format!("IS-IS {}", m),
+        EdgeMetric::Manual(m) => format!("Manual {}", m),
+        EdgeMetric::Other => "other".to_string(),
+        EdgeMetric::None => "none".to_string(),
+    }
+}
+
+/// "bps / pps" cell for the IfStats tooltip; "-" until two samples exist.
+fn format_rate(bps: Option<f64>, pps: Option<f64>) -> String {
+    let bps = bps.map(|v| format_humanized(v.round() as u64, "bps"));
+    let pps = pps.map(|v| format_humanized(v.round() as u64, "pps"));
+    match (bps, pps) {
+        (Some(bps), Some(pps)) => format!("{} / {}", bps, pps),
+        (Some(rate), None) | (None, Some(rate)) => rate,
+        (None, None) => "-".to_string(),
+    }
+}
+
+/// Staleness of a source snapshot relative to the polling interval.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Freshness {
+    Fresh,
+    Aging,
+    Stale,
+}
+
+impl Freshness {
+    /// Fresh within two polling intervals, stale after five.
+    fn from_age(age: Duration, poll_interval: Duration) -> Self {
+        let poll_interval = poll_interval.max(Duration::from_secs(1));
+        if age <= poll_interval * 2 {
+            Freshness::Fresh
+        } else if age <= poll_interval * 5 {
+            Freshness::Aging
+        } else {
+            Freshness::Stale
+        }
+    }
+}
 
-    format!("{:.2} {}pkts", value, prefix)
+/// Age rounded to whole seconds, e.g. "2m 5s".
+fn format_age(age: Duration) -> String {
+    humantime::format_duration(Duration::from_secs(age.as_secs())).to_string()
+}
+
+/// Padding fraction for `SettingsNavigation::with_fit_to_screen_padding` that leaves
+/// `padding_px` free around `bounds` (graph coordinates) inside `canvas`, zoomed by `zoom`.
+///
+/// GraphView fits with `zoom = min(canvas / (size * (1 + padding)))` per axis, so the fraction
+/// follows from the zoom we want.
+fn fit_padding_fraction(bounds: Rect, canvas: Rect, padding_px: f32, zoom: f32) -> f32 {
+    // A single node or a straight line has no extent on some axis
+    let size = bounds.size().max(egui::vec2(1.0, 1.0));
+    let fit = (canvas.width() / size.x).min(canvas.height() / size.y);
+    let padded_fit = ((canvas.width() - 2.0 * padding_px) / size.x)
+        .min((canvas.height() - 2.0 * padding_px) / size.y)
+        .max(f32::EPSILON);
+    // Keep the fraction above -1, where GraphView's zoom would flip sign
+    (fit / (padded_fit * zoom) - 1.0).max(-0.9)
+}
+
+/// Parse OSPF area ids in dotted (`0.0.0.1`) or plain number (`1`) form, comma or space separated.
+fn parse_area_list(input: &str) -> Result<Vec<Ipv4Addr>, String> {
+    let mut areas = Vec::new();
+    for part in input.split([',', ' ']).filter(|part| !part.is_empty()) {
+        let area = part
+            .parse::<Ipv4Addr>()
+            .or_else(|_| part.parse::<u32>().map(Ipv4Addr::from))
+            .map_err(|_| format!("'{}' is not an area id", part))?;
+        if !areas.contains(&area) {
+            areas.push(area);
+        }
+    }
+    Ok(areas)
+}
+
+fn format_area_list(areas: &[Ipv4Addr]) -> String {
+    areas.iter().map(Ipv4Addr::to_string).collect::<Vec<_>>().join(", ")
+}
+
+/// Plain-text hop list, one hop per line, suitable for pasting into a ticket.
+fn format_path_hops(hops: &[PathHop]) -> String {
+    hops.iter()
+        .enumerate()
+        .map(|(i, hop)| format!("{}. {} (+{}, total {})", i + 1, hop.ident, hop.cost, hop.total))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_humanize_bytes() {
+        assert_eq!(humanize_bytes(0), "0 B");
+        assert_eq!(humanize_bytes(999), "999 B");
+        assert_eq!(humanize_bytes(1000), "1 kB");
+        assert_eq!(humanize_bytes(1536), "1.54 kB");
+        assert_eq!(humanize_bytes(5_000_000_000_000), "5 TB");
+        assert_eq!(humanize_bytes(2_345_000_000_000_000), "2.35 PB");
+    }
+
+    #[test]
+    fn test_humanize_packet_count() {
+        assert_eq!(humanize_packet_count(0), "0 pkts");
+        assert_eq!(humanize_packet_count(5), "5 pkts");
+        assert_eq!(humanize_packet_count(1536), "1.54 kpkts");
+        assert_eq!(humanize_packet_count(u64::MAX), "18.45 Epkts");
+    }
+
+    #[test]
+    fn test_format_rate_from_samples() {
+        let ip: std::net::IpAddr = "10.0.0.1".parse().unwrap();
+        let previous = InterfaceStats {
+            ip_address: ip,
+            tx_bytes: Some(1_000),
+            tx_packets: Some(10),
+            rx_bytes: Some(5_000),
+            rx_packets: None,
+        };
+        let current = InterfaceStats {
+            tx_bytes: Some(126_000),
+            tx_packets: Some(110),
+            rx_bytes: Some(4_000), // counter reset
+            ..previous.clone()
+        };
+        let rates = current.rates_since(&previous, Duration::from_secs(10));
+        assert_eq!(format_rate(rates.tx_bps, rates.tx_pps), "100 kbps / 10 pps");
+        assert_eq!(format_rate(rates.rx_bps, rates.rx_pps), "-");
+    }
+
+    #[test]
+    fn test_snapshot_freshness() {
+        let interval = Duration::from_secs(30);
+        assert_eq!(Freshness::from_age(Duration::from_secs(45), interval), Freshness::Fresh);
+        assert_eq!(Freshness::from_age(Duration::from_secs(120), interval), Freshness::Aging);
+        assert_eq!(Freshness::from_age(Duration::from_secs(151), interval), Freshness::Stale);
+        assert_eq!(format_age(Duration::from_millis(125_400)), "2m 5s");
+    }
+
+    #[test]
+    fn test_fit_padding_fraction() {
+        let canvas = Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(1000.0, 500.0));
+        let bounds = Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(100.0, 100.0));
+        let zoom = |padding: f32| (canvas.width() / 100.0).min(canvas.height() / 100.0) / (1.0 + padding);
+
+        // The height limits: 50 px on each side leaves 400 px for 100 units
+        assert!((zoom(fit_padding_fraction(bounds, canvas, 50.0, 1.0)) - 4.0).abs() < 1e-4);
+        assert!((zoom(fit_padding_fraction(bounds, canvas, 50.0, 2.0)) - 8.0).abs() < 1e-4);
+        assert!(fit_padding_fraction(bounds, canvas, 0.0, 1.0).abs() < 1e-6);
+        assert!(fit_padding_fraction(bounds, canvas, 0.0, 100.0) >= -0.9);
+    }
+
+    #[test]
+    fn test_parse_area_list() {
+        let areas = parse_area_list("0.0.0.0, 1 0.0.0.1,, 10").unwrap();
+        assert_eq!(
+            areas,
+            [Ipv4Addr::new(0, 0, 0, 0), Ipv4Addr::new(0, 0, 0, 1), Ipv4Addr::new(0, 0, 0, 10)]
+        );
+        assert_eq!(format_area_list(&areas), "0.0.0.0, 0.0.0.1, 0.0.0.10");
+        assert!(parse_area_list("").unwrap().is_empty());
+        assert!(parse_area_list("backbone").is_err());
+    }
+
+    #[test]
+    fn test_format_path_hops() {
+        let hop = |ident: &str, cost, total| PathHop {
+            node_id: Uuid::nil(),
+            ident: ident.to_string(),
+            cost,
+            total,
+        };
+        let hops = [
+            hop("10.0.0.1", 0, 0),
+            hop("172.21.14.0/24", 10, 10),
+            hop("10.0.0.2", 0, 10),
+        ];
+        assert_eq!(
+            format_path_hops(&hops),
+            "1. 10.0.0.1 (+0, total 0)\n2. 172.21.14.0/24 (+10, total 10)\n3. 10.0.0.2 (+0, total 10)"
+        );
+    }
 }
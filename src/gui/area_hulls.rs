@@ -0,0 +1,226 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::net::Ipv4Addr;
+
+use catppuccin_egui::Theme;
+use egui::{Align2, Color32, FontId, Painter, Pos2, Shape, Stroke, Vec2, epaint::PathShape};
+
+use crate::network::network_graph::AreaKey;
+use crate::network::node::{Node, NodeInfo, OspfPayload, ProtocolData};
+use crate::topology::store::AreaType;
+
+/// Screen-space footprint of one node in one OSPF or IS-IS area, recorded during `shapes()`.
+#[derive(Clone, Debug)]
+struct AreaMember {
+    area: AreaKey,
+    center: Pos2,
+    radius: f32,
+}
+
+thread_local! {
+    static AREA_MEMBERS: RefCell<Vec<AreaMember>> = const { RefCell::new(Vec::new()) };
+    static AREA_BACKGROUNDS_ENABLED: RefCell<bool> = const { RefCell::new(false) };
+    // Inferred area types, shown next to the area id in the hull labels
+    static AREA_TYPES: RefCell<HashMap<Ipv4Addr, AreaType>> = RefCell::new(HashMap::new());
+}
+
+pub fn set_area_types(types: HashMap<Ipv4Addr, AreaType>) {
+    AREA_TYPES.with(|v| *v.borrow_mut() = types);
+}
+
+fn area_label(area: &AreaKey) -> String {
+    let AreaKey::Ospf(area_id) = area else {
+        return area.to_string();
+    };
+    match AREA_TYPES.with_borrow(|v| v.get(area_id).copied()) {
+        Some(area_type) if area_type != AreaType::Normal => {
+            format!("Area {} ({})", area_id, area_type.name())
+        }
+        _ => format!("Area {}", area_id),
+    }
+}
+
+pub fn set_area_backgrounds_enabled(enabled: bool) {
+    AREA_BACKGROUNDS_ENABLED.with(|v| *v.borrow_mut() = enabled);
+}
+
+pub fn area_backgrounds_enabled() -> bool {
+    AREA_BACKGROUNDS_ENABLED.with(|v| *v.borrow())
+}
+
+pub fn clear_area_members() {
+    AREA_MEMBERS.with(|v| v.borrow_mut().clear());
+}
+
+/// Record a node for every area it belongs to, so ABRs and L2 routers end up inside each of
+/// their areas' hulls.
+pub fn record_area_member(areas: &[AreaKey], center: Pos2, radius: f32) {
+    AREA_MEMBERS.with(|v| {
+        v.borrow_mut().extend(areas.iter().map(|area| AreaMember {
+            area: area.clone(),
+            center,
+            radius,
+        }))
+    });
+}
+
+/// OSPF areas a node belongs to. External (AS-scoped) networks and non-OSPF nodes belong to none.
+pub fn node_areas(node: &Node) -> Vec<Ipv4Addr> {
+    let mut areas = match &node.info {
+        NodeInfo::Router(router) => match &router.protocol_data {
+            Some(ProtocolData::Ospf(data)) => {
+                let mut areas = vec![data.area_id];
+                if let OspfPayload::Router(payload) = &data.payload {
+                    areas.extend(payload.per_area_facets.iter().map(|f| f.area_id));
+                }
+                areas
+            }
+            _ => Vec::new(),
+        },
+        NodeInfo::Network(network) => match &network.protocol_data {
+            Some(ProtocolData::Ospf(data)) if !network.is_external() => vec![data.area_id],
+            _ => Vec::new(),
+        },
+        NodeInfo::Area(_) => Vec::new(),
+    };
+    areas.sort();
+    areas.dedup();
+    areas
+}
+
+/// Stable per-area color picked from the theme palette.
+pub fn area_color(theme: &Theme, area: &AreaKey) -> Color32 {
+    let palette = [
+        theme.blue,
+        theme.green,
+        theme.peach,
+        theme.mauve,
+        theme.teal,
+        theme.yellow,
+        theme.pink,
+        theme.lavender,
+    ];
+    let slot = match area {
+        AreaKey::Ospf(area_id) => area_id.to_bits() as usize,
+        // Same name, same color across sessions
+        AreaKey::IsIs(name) => name
+            .bytes()
+            .fold(0usize, |hash, b| hash.wrapping_mul(31).wrapping_add(b as usize)),
+    };
+    palette[slot % palette.len()]
+}
+
+/// Convex hull (Andrew's monotone chain), counter-clockwise in screen coordinates.
+pub fn convex_hull(mut points: Vec<Pos2>) -> Vec<Pos2> {
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+
+    fn cross(o: Pos2, a: Pos2, b: Pos2) -> f32 {
+        (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x)
+    }
+
+    let mut lower: Vec<Pos2> = Vec::new();
+    for p in &points {
+        while lower.len() >= 2 && cross(lower[lower.len() - 2], lower[lower.len() - 1], *p) <= 0.0 {
+            lower.pop();
+        }
+        lower.push(*p);
+    }
+    let mut upper: Vec<Pos2> = Vec::new();
+    for p in points.iter().rev() {
+        while upper.len() >= 2 && cross(upper[upper.len() - 2], upper[upper.len() - 1], *p) <= 0.0 {
+            upper.pop();
+        }
+        upper.push(*p);
+    }
+    lower.pop();
+    upper.pop();
+    lower.extend(upper);
+    lower
+}
+
+/// Consume the members recorded this frame and turn them into one translucent hull per area.
+/// Returns the hull shapes (to be placed behind the graph) and an (anchor, text, color) label per area.
+pub fn take_area_hulls(theme: &Theme, padding: f32) -> (Vec<Shape>, Vec<(Pos2, String, Color32)>) {
+    const OUTLINE_POINTS: usize = 12;
+
+    let members = AREA_MEMBERS.with(|v| std::mem::take(&mut *v.borrow_mut()));
+    let mut by_area: BTreeMap<AreaKey, Vec<Pos2>> = BTreeMap::new();
+    for member in members {
+        let outline = (0..OUTLINE_POINTS).map(|i| {
+            let angle = i as f32 / OUTLINE_POINTS as f32 * std::f32::consts::TAU;
+            member.center + Vec2::angled(angle) * (member.radius + padding)
+        });
+        by_area.entry(member.area).or_default().extend(outline);
+    }
+
+    let mut shapes = Vec::with_capacity(by_area.len());
+    let mut labels = Vec::with_capacity(by_area.len());
+    for (area, points) in by_area {
+        let hull = convex_hull(points);
+        let Some(top) = hull.iter().copied().min_by(|a, b| a.y.total_cmp(&b.y)) else {
+            continue;
+        };
+        let color = area_color(theme, &area);
+        shapes.push(Shape::Path(PathShape::convex_polygon(
+            hull,
+            color.gamma_multiply(0.08),
+            Stroke::new(1.0, color.gamma_multiply(0.4)),
+        )));
+        labels.push((top, area_label(&area), color));
+    }
+    (shapes, labels)
+}
+
+pub fn paint_area_labels(painter: &Painter, labels: Vec<(Pos2, String, Color32)>) {
+    for (anchor, text, color) in labels {
+        painter.text(
+            anchor - Vec2::new(0.0, 2.0),
+            Align2::CENTER_BOTTOM,
+            text,
+            FontId::proportional(12.0),
+            color.gamma_multiply(0.8),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convex_hull_drops_interior_points() {
+        let points = vec![
+            Pos2::new(0.0, 0.0),
+            Pos2::new(10.0, 0.0),
+            Pos2::new(10.0, 10.0),
+            Pos2::new(0.0, 10.0),
+            Pos2::new(5.0, 5.0),
+            Pos2::new(0.0, 0.0),
+        ];
+        let hull = convex_hull(points);
+        assert_eq!(hull.len(), 4);
+        assert!(!hull.contains(&Pos2::new(5.0, 5.0)));
+    }
+
+    #[test]
+    fn test_isis_area_hulls() {
+        let theme = catppuccin_egui::MACCHIATO;
+        let area = AreaKey::IsIs("49.0001".to_string());
+        clear_area_members();
+        record_area_member(std::slice::from_ref(&area), Pos2::new(0.0, 0.0), 10.0);
+        record_area_member(
+            &[AreaKey::Ospf(Ipv4Addr::UNSPECIFIED), area.clone()],
+            Pos2::new(50.0, 0.0),
+            10.0,
+        );
+        let (hulls, labels) = take_area_hulls(&theme, 8.0);
+        assert_eq!(hulls.len(), 2);
+        let (_, text, color) = labels.iter().find(|(_, text, _)| text.starts_with("IS-IS")).unwrap();
+        assert_eq!(text, "IS-IS area 49.0001");
+        assert_eq!(*color, area_color(&theme, &area));
+    }
+}
@@ -1,8 +1,25 @@
+use std::collections::HashSet;
 use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-use crate::{data_aquisition::{snmp::SnmpClient, ssh::SshClient}, parsers::{isis_parser::topology::IsIsTopology, ospf_parser::snmp_source::OspfSnmpSource}, topology::{OspfSnmpTopology, source::SnapshotSource}};
+use crate::{data_aquisition::{netconf::NetconfClient, snmp::SnmpClient, ssh::{HostKeyPolicy, SshClient}}, gui::credentials::SecretHandle, parsers::{isis_parser::topology::{IsIsConnectError, IsIsNetconfTopology, IsIsTopology}, ospf_parser::{snmp_oids::SnmpOidMap, snmp_source::OspfSnmpSource}}, topology::{OspfSnmpTopology, ospf_protocol::ConsolidationPolicy, source::SnapshotSource, store::SourceId}};
 
+use thiserror::Error;
 
+/// Why `SourceSpec::build_topology` failed. IS-IS failures keep their `IsIsConnectError`, so a
+/// rejected host key can still be told apart from a plain connect error.
+#[derive(Debug, Error)]
+pub enum BuildError {
+    /// The spec can't be built as given, e.g. no pinned secret or an unreadable OID file
+    #[error("{0}")]
+    Config(String),
+    #[error("Failed to build ISIS topology: {0}")]
+    Ssh(IsIsConnectError),
+    #[error("Failed to build ISIS topology over NETCONF: {0}")]
+    Netconf(IsIsConnectError),
+}
 
 #[derive(Clone)]
 pub enum ProtocolKind {
@@ -14,14 +31,47 @@ pub enum ProtocolKind {
 pub enum AcquisitionConfig {
     Snmp(SnmpAcquisitionConfig),
     Ssh(SshAcquisitionConfig),
+    /// NETCONF over the SSH `netconf` subsystem, same connection settings as plain SSH
+    Netconf(SshAcquisitionConfig),
 }
 
 #[derive(Clone)]
 pub struct SnmpAcquisitionConfig {
     address: SocketAddr,
-    community: String,
+    /// The community lives in the in-memory keyring, like SSH passwords.
+    community: SecretHandle,
     snmp_version: snmp2::Version,
     security: Option<snmp2::v3::Security>,
+    max_repetitions: u32,
+    /// OID map file for agents that don't serve the standard OSPF-MIB, see `SnmpOidMap`
+    oid_file: Option<PathBuf>,
+    summary_policy: ConsolidationPolicy,
+}
+
+impl SnmpAcquisitionConfig {
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    pub fn community(&self) -> &SecretHandle {
+        &self.community
+    }
+
+    pub fn max_repetitions(&self) -> u32 {
+        self.max_repetitions
+    }
+
+    pub fn security(&self) -> Option<&snmp2::v3::Security> {
+        self.security.as_ref()
+    }
+
+    pub fn oid_file(&self) -> Option<&Path> {
+        self.oid_file.as_deref()
+    }
+
+    pub fn summary_policy(&self) -> ConsolidationPolicy {
+        self.summary_policy
+    }
 }
 
 #[derive(Clone)]
@@ -29,7 +79,10 @@ pub struct SshAcquisitionConfig {
     pub host: String,
     pub port: u16,
     pub username: String,
-    pub password: String
+    /// The password lives in the in-memory keyring, never in the spec itself.
+    pub password: SecretHandle,
+    pub connect_timeout: Duration,
+    pub host_key_policy: HostKeyPolicy,
 }
 
 #[derive(Clone)]
@@ -40,49 +93,155 @@ pub struct SourceSpec {
 
 impl SourceSpec {
     
-    pub fn new_ssh(host: String, port: u16, username: String, password: String, protocol: ProtocolKind) -> Self {
+    /// SSH source spec. The password is pinned in the keyring under `ssh://username@host:port`
+    /// and resolved again when the topology is built.
+    pub fn new_ssh(host: String, port: u16, username: String, password: String, connect_timeout: Duration, protocol: ProtocolKind) -> Self {
+        let password = SecretHandle::pin(format!("ssh://{}@{}:{}", username, host, port), password);
         Self {
             protocol,
             acquisition: AcquisitionConfig::Ssh(SshAcquisitionConfig {
                 host,
                 port,
                 username,
-                password
+                password,
+                connect_timeout,
+                host_key_policy: HostKeyPolicy::default(),
+            })
+        }
+    }
+    
+    /// NETCONF source spec, the password is pinned under `netconf://username@host:port`.
+    pub fn new_netconf(host: String, port: u16, username: String, password: String, connect_timeout: Duration, protocol: ProtocolKind) -> Self {
+        let password = SecretHandle::pin(format!("netconf://{}@{}:{}", username, host, port), password);
+        Self {
+            protocol,
+            acquisition: AcquisitionConfig::Netconf(SshAcquisitionConfig {
+                host,
+                port,
+                username,
+                password,
+                connect_timeout,
+                host_key_policy: HostKeyPolicy::default(),
             })
         }
     }
     
-    pub fn new_snmp(address: SocketAddr, community: String, version: snmp2::Version, security: Option<snmp2::v3::Security>, protocol: ProtocolKind) -> Self {
+    /// SNMP source spec, the community is pinned under `snmp://address`.
+    pub fn new_snmp(address: SocketAddr, community: String, version: snmp2::Version, security: Option<snmp2::v3::Security>, max_repetitions: u32, protocol: ProtocolKind) -> Self {
+        let community = SecretHandle::pin(format!("snmp://{}", address), community);
         Self {
             protocol,
             acquisition: AcquisitionConfig::Snmp(SnmpAcquisitionConfig {
                 address,
                 community,
                 snmp_version: version,
-                security
+                security,
+                max_repetitions,
+                oid_file: None,
+                summary_policy: ConsolidationPolicy::default(),
             })
         }
     }
+
+    /// Read the SNMP OIDs from `oid_file` when the topology is built. No effect on other sources.
+    pub fn with_oid_file(mut self, oid_file: Option<PathBuf>) -> Self {
+        if let AcquisitionConfig::Snmp(config) = &mut self.acquisition {
+            config.oid_file = oid_file;
+        }
+        self
+    }
+
+    /// Fold or keep OSPF summary networks of this source. No effect on other sources.
+    pub fn with_summary_policy(mut self, policy: ConsolidationPolicy) -> Self {
+        if let AcquisitionConfig::Snmp(config) = &mut self.acquisition {
+            config.summary_policy = policy;
+        }
+        self
+    }
     
-    pub async fn build_topology(&self) -> Result<Box<dyn SnapshotSource>, String> {
+    /// The keyring entry holding this source's password or community.
+    pub fn secret(&self) -> &SecretHandle {
+        match &self.acquisition {
+            AcquisitionConfig::Ssh(config) | AcquisitionConfig::Netconf(config) => &config.password,
+            AcquisitionConfig::Snmp(config) => &config.community,
+        }
+    }
+
+    /// Check SSH and NETCONF host keys with `policy` on every (re)connect. No effect on SNMP sources.
+    pub fn with_host_key_policy(mut self, policy: HostKeyPolicy) -> Self {
+        if let AcquisitionConfig::Ssh(config) | AcquisitionConfig::Netconf(config) = &mut self.acquisition {
+            config.host_key_policy = policy;
+        }
+        self
+    }
+
+    pub async fn build_topology(&self) -> Result<Box<dyn SnapshotSource>, BuildError> {
         match (&self.protocol, &self.acquisition) {
             (ProtocolKind::Ospf, AcquisitionConfig::Snmp(config)) => {
+                let community = config.community.resolve()
+                    .ok_or_else(|| BuildError::Config(format!("No community pinned for {}", config.community.key())))?;
                 let client = SnmpClient::new(
                     config.address,
-                    &config.community,
+                    &community,
                     config.snmp_version,
                     config.security.clone()
-                );
-                let topo = OspfSnmpTopology::from_snmp_client(client);
-                Ok(Box::new(topo))
+                ).with_max_repetitions(config.max_repetitions);
+                let topo = match &config.oid_file {
+                    Some(path) => OspfSnmpTopology::from_snmp_client_with_oids(client, SnmpOidMap::load(path).map_err(BuildError::Config)?),
+                    None => OspfSnmpTopology::from_snmp_client(client),
+                };
+                Ok(Box::new(topo.with_consolidation_policy(config.summary_policy)))
             }
             (ProtocolKind::Isis, AcquisitionConfig::Ssh(config)) => {
-                let client = SshClient::new_with_password(config.username.clone(), config.host.clone(), config.password.clone(), config.port);
+                let password = config.password.resolve()
+                    .ok_or_else(|| BuildError::Config(format!("No password pinned for {}", config.password.key())))?;
+                let client = SshClient::new_with_password(config.username.clone(), config.host.clone(), password, config.port)
+                    .with_connect_timeout(config.connect_timeout)
+                    .with_host_key_policy(config.host_key_policy.clone());
                 let topo = IsIsTopology::new_from_ssh_client(client).await
-                    .map_err(|e| format!("Failed to build ISIS topology: {}", e))?;
+                    .map_err(BuildError::Ssh)?;
+                Ok(Box::new(topo))
+            }
+            (ProtocolKind::Isis, AcquisitionConfig::Netconf(config)) => {
+                let password = config.password.resolve()
+                    .ok_or_else(|| BuildError::Config(format!("No password pinned for {}", config.password.key())))?;
+                let client = SshClient::new_with_password(config.username.clone(), config.host.clone(), password, config.port)
+                    .with_connect_timeout(config.connect_timeout)
+                    .with_host_key_policy(config.host_key_policy.clone());
+                let topo = IsIsNetconfTopology::new_from_netconf_client(NetconfClient::new(client)).await
+                    .map_err(BuildError::Netconf)?;
                 Ok(Box::new(topo))
             }
-            _ => Err("Unsupported protocol or acquisition method".to_string())
+            _ => Err(BuildError::Config("Unsupported protocol or acquisition method".to_string()))
+        }
+    }
+}
+
+/// Sources with a fetch running, shared by the autopoll tasks and one-shot refreshes so the two
+/// never fetch the same source at once.
+pub type FetchesInFlight = Arc<Mutex<HashSet<SourceId>>>;
+
+/// Marks a source as being fetched until dropped, so an aborted task can't leave it marked.
+pub struct FetchGuard {
+    in_flight: FetchesInFlight,
+    src_id: SourceId,
+}
+
+impl FetchGuard {
+    /// `None` if a fetch for `src_id` is already running.
+    pub fn try_acquire(in_flight: &FetchesInFlight, src_id: &SourceId) -> Option<Self> {
+        if !in_flight.lock().unwrap().insert(src_id.clone()) {
+            return None;
         }
+        Some(Self {
+            in_flight: in_flight.clone(),
+            src_id: src_id.clone(),
+        })
+    }
+}
+
+impl Drop for FetchGuard {
+    fn drop(&mut self) {
+        self.in_flight.lock().unwrap().remove(&self.src_id);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,121 @@
+/*!
+One-shot futures run on the app's shared tokio runtime, polled from the UI thread.
+
+The UI can't `.await`, so each frame it calls `BackgroundTask::poll` instead: `Pending` while the
+future runs, then `Ready` with its result exactly once. The future can report short progress
+phases through a `TaskPhase` for the UI to show next to a spinner.
+*/
+
+use std::{
+    future::Future,
+    sync::{Arc, Mutex},
+    task::Poll,
+};
+
+use tokio::{runtime::Runtime, sync::oneshot, task::JoinHandle};
+
+use crate::{gui::poll_error::PollError, logging::debug_log};
+
+/// Progress phase shared between a running task and the UI.
+#[derive(Clone, Default)]
+pub struct TaskPhase(Arc<Mutex<Option<&'static str>>>);
+
+impl TaskPhase {
+    pub fn set(&self, phase: &'static str) {
+        debug_log!("[task] phase: {}", phase);
+        *self.0.lock().unwrap() = Some(phase);
+    }
+
+    pub fn get(&self) -> Option<&'static str> {
+        *self.0.lock().unwrap()
+    }
+}
+
+pub struct BackgroundTask<T> {
+    handle: JoinHandle<()>,
+    result: oneshot::Receiver<Result<T, PollError>>,
+    phase: TaskPhase,
+}
+
+impl<T: Send + 'static> BackgroundTask<T> {
+    /// Spawn the future built by `make` on `runtime`. It gets a `TaskPhase` to report progress into.
+    pub fn spawn<F, Fut>(runtime: &Runtime, make: F) -> Self
+    where
+        F: FnOnce(TaskPhase) -> Fut,
+        Fut: Future<Output = Result<T, PollError>> + Send + 'static,
+    {
+        let phase = TaskPhase::default();
+        let (tx, result) = oneshot::channel();
+        let future = make(phase.clone());
+        let handle = runtime.spawn(async move {
+            // The receiver is gone if the task was dropped, nobody is left to tell
+            let _ = tx.send(future.await);
+        });
+        Self {
+            handle,
+            result,
+            phase,
+        }
+    }
+
+    /// Non-blocking check for the result. Returns `Ready` once; after that the task is spent and
+    /// should be dropped.
+    pub fn poll(&mut self) -> Poll<Result<T, PollError>> {
+        match self.result.try_recv() {
+            Ok(result) => Poll::Ready(result),
+            Err(oneshot::error::TryRecvError::Empty) => Poll::Pending,
+            Err(oneshot::error::TryRecvError::Closed) => Poll::Ready(Err(PollError::Cancelled)),
+        }
+    }
+
+    /// Latest phase reported by the task, if any.
+    pub fn phase(&self) -> Option<&'static str> {
+        self.phase.get()
+    }
+
+    /// Abort the task. A following `poll` reports it as cancelled.
+    #[allow(dead_code)]
+    pub fn cancel(&self) {
+        self.handle.abort();
+    }
+}
+
+impl<T> Drop for BackgroundTask<T> {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wait<T: Send + 'static>(task: &mut BackgroundTask<T>) -> Result<T, PollError> {
+        loop {
+            if let Poll::Ready(result) = task.poll() {
+                return result;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn test_background_task_poll_and_cancel() {
+        let runtime = Runtime::new().unwrap();
+
+        let mut task = BackgroundTask::spawn(&runtime, |phase| async move {
+            phase.set("Working…");
+            Ok(42)
+        });
+        assert_eq!(wait(&mut task), Ok(42));
+        assert_eq!(task.phase(), Some("Working…"));
+
+        let mut slow: BackgroundTask<()> = BackgroundTask::spawn(&runtime, |_| async {
+            tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+            Ok(())
+        });
+        assert!(slow.poll().is_pending());
+        slow.cancel();
+        assert_eq!(wait(&mut slow), Err(PollError::Cancelled));
+    }
+}
@@ -0,0 +1,240 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+    sync::{LazyLock, Mutex},
+};
+
+use serde::{Deserialize, Serialize};
+
+pub const SECRETS_FILE_ENV: &str = "OSPFVIZ_SECRETS_FILE";
+pub const DEFAULT_SECRETS_FILE: &str = "secrets.toml";
+
+/// Connection defaults used to prefill the SNMP/SSH connect forms.
+///
+/// Resolution order (later wins): built-in lab defaults, `secrets.toml`
+/// (or the file pointed to by `OSPFVIZ_SECRETS_FILE`), then `OSPFVIZ_*` environment variables.
+#[derive(Debug, Clone)]
+pub struct Credentials {
+    pub snmp_host: String,
+    pub snmp_port: u16,
+    pub snmp_community: String,
+
+    pub ssh_host: String,
+    pub ssh_port: u16,
+    pub ssh_username: String,
+    pub ssh_password: String,
+}
+
+impl Default for Credentials {
+    fn default() -> Self {
+        Self {
+            snmp_host: "127.0.0.1".to_string(),
+            snmp_port: 1161,
+            snmp_community: "public".to_string(),
+
+            ssh_host: "127.0.0.1".to_string(),
+            ssh_port: 2221,
+            ssh_username: "client".to_string(),
+            ssh_password: "password".to_string(),
+        }
+    }
+}
+
+/// Secrets pinned for the lifetime of the process, keyed by `SecretHandle`.
+/// Shared across threads since sources are polled from background tasks.
+static KEYRING: LazyLock<Mutex<HashMap<String, String>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Reference to a secret held in the in-memory keyring.
+///
+/// Only the key is ever printed or serialized, so specs holding a handle can be dumped safely.
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SecretHandle(String);
+
+impl SecretHandle {
+    /// Pin `secret` under `key`, replacing any secret previously pinned under the same key.
+    pub fn pin(key: impl Into<String>, secret: String) -> Self {
+        let key = key.into();
+        KEYRING.lock().unwrap().insert(key.clone(), secret);
+        Self(key)
+    }
+
+    /// The pinned secret, or `None` if it was never pinned in this process or was forgotten.
+    pub fn resolve(&self) -> Option<String> {
+        KEYRING.lock().unwrap().get(&self.0).cloned()
+    }
+
+    pub fn forget(&self) {
+        KEYRING.lock().unwrap().remove(&self.0);
+    }
+
+    pub fn key(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for SecretHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretHandle({})", self.0)
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct SecretsFile {
+    #[serde(default)]
+    snmp: SnmpSecrets,
+    #[serde(default)]
+    ssh: SshSecrets,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct SnmpSecrets {
+    host: Option<String>,
+    port: Option<u16>,
+    community: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct SshSecrets {
+    host: Option<String>,
+    port: Option<u16>,
+    username: Option<String>,
+    password: Option<String>,
+}
+
+impl Credentials {
+    /// Load credentials from the secrets file and environment, falling back to lab defaults.
+    pub fn load() -> Self {
+        let mut creds = Self::default();
+
+        let path = std::env::var(SECRETS_FILE_ENV)
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from(DEFAULT_SECRETS_FILE));
+        if path.exists() {
+            match Self::read_secrets_file(&path) {
+                Ok(secrets) => creds.apply_secrets(secrets),
+                Err(e) => eprintln!("[credentials] Ignoring {}: {}", path.display(), e),
+            }
+        }
+
+        creds.apply_env(|key| std::env::var(key).ok());
+        creds
+    }
+
+    fn read_secrets_file(path: &Path) -> Result<SecretsFile, String> {
+        let contents = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        toml::from_str(&contents).map_err(|e| e.to_string())
+    }
+
+    fn apply_secrets(&mut self, secrets: SecretsFile) {
+        let SecretsFile { snmp, ssh } = secrets;
+        if let Some(host) = snmp.host {
+            self.snmp_host = host;
+        }
+        if let Some(port) = snmp.port {
+            self.snmp_port = port;
+        }
+        if let Some(community) = snmp.community {
+            self.snmp_community = community;
+        }
+        if let Some(host) = ssh.host {
+            self.ssh_host = host;
+        }
+        if let Some(port) = ssh.port {
+            self.ssh_port = port;
+        }
+        if let Some(username) = ssh.username {
+            self.ssh_username = username;
+        }
+        if let Some(password) = ssh.password {
+            self.ssh_password = password;
+        }
+    }
+
+    fn apply_env(&mut self, lookup: impl Fn(&str) -> Option<String>) {
+        let parse_port = |key: &str| {
+            lookup(key).and_then(|v| match v.parse::<u16>() {
+                Ok(port) => Some(port),
+                Err(_) => {
+                    eprintln!("[credentials] Ignoring {}={}: not a valid port", key, v);
+                    None
+                }
+            })
+        };
+
+        if let Some(host) = lookup("OSPFVIZ_SNMP_HOST") {
+            self.snmp_host = host;
+        }
+        if let Some(port) = parse_port("OSPFVIZ_SNMP_PORT") {
+            self.snmp_port = port;
+        }
+        if let Some(community) = lookup("OSPFVIZ_SNMP_COMMUNITY") {
+            self.snmp_community = community;
+        }
+        if let Some(host) = lookup("OSPFVIZ_SSH_HOST") {
+            self.ssh_host = host;
+        }
+        if let Some(port) = parse_port("OSPFVIZ_SSH_PORT") {
+            self.ssh_port = port;
+        }
+        if let Some(username) = lookup("OSPFVIZ_SSH_USERNAME") {
+            self.ssh_username = username;
+        }
+        if let Some(password) = lookup("OSPFVIZ_SSH_PASSWORD") {
+            self.ssh_password = password;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_handle_never_exposes_secret() {
+        let handle = SecretHandle::pin("ssh://test@r1:22", "hunter2".to_string());
+        assert_eq!(handle.resolve().as_deref(), Some("hunter2"));
+        assert!(!format!("{:?}", handle).contains("hunter2"));
+        assert!(!serde_json::to_string(&handle).unwrap().contains("hunter2"));
+
+        // Re-pinning under the same key replaces the secret
+        let repinned = SecretHandle::pin("ssh://test@r1:22", "changed".to_string());
+        assert_eq!(handle, repinned);
+        assert_eq!(handle.resolve().as_deref(), Some("changed"));
+
+        handle.forget();
+        assert_eq!(repinned.resolve(), None);
+    }
+
+    #[test]
+    fn test_env_overrides_secrets_file() {
+        let secrets: SecretsFile = toml::from_str(
+            r#"
+            [snmp]
+            community = "from-file"
+            port = 161
+
+            [ssh]
+            username = "admin"
+            password = "from-file"
+            "#,
+        )
+        .unwrap();
+
+        let env: HashMap<&str, &str> = HashMap::from([
+            ("OSPFVIZ_SSH_PASSWORD", "from-env"),
+            ("OSPFVIZ_SNMP_PORT", "not-a-port"),
+        ]);
+
+        let mut creds = Credentials::default();
+        creds.apply_secrets(secrets);
+        creds.apply_env(|key| env.get(key).map(|v| v.to_string()));
+
+        assert_eq!(creds.snmp_community, "from-file");
+        assert_eq!(creds.snmp_port, 161);
+        assert_eq!(creds.snmp_host, "127.0.0.1");
+        assert_eq!(creds.ssh_username, "admin");
+        assert_eq!(creds.ssh_password, "from-env");
+    }
+}
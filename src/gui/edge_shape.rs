@@ -1,5 +1,5 @@
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use egui::{Color32, Pos2, Shape, Stroke};
 use egui_graphs::{DisplayEdge, DisplayNode, DrawContext, EdgeProps};
@@ -7,9 +7,11 @@ use petgraph::csr::EdgeIndex;
 use petgraph::{EdgeType, stable_graph::IndexType};
 use uuid::Uuid;
 
-use crate::gui::app;
-use crate::gui::node_shape::NetworkGraphNodeShape;
-use crate::network::edge::{Edge as NetEdge, EdgeKind, EdgeMetric};
+use crate::gui::{app, palette};
+use crate::logging::debug_log;
+use crate::gui::node_shape::{self, NetworkGraphNodeShape};
+use crate::network::edge::{Edge as NetEdge, EdgeKind, EdgeMetric, UndirectedEdgeKey};
+use crate::network::network_graph::ParallelEdgeGroups;
 
 #[derive(Clone, Debug)]
 pub struct EdgeEvent {
@@ -20,21 +22,102 @@ pub struct EdgeEvent {
 }
 
 thread_local! {
-    static EDGE_EVENTS: RefCell<Vec<EdgeEvent>> = RefCell::new(Vec::new());
-    static ANY_GRAPH_HIT: RefCell<bool> = RefCell::new(false);
-    static EDGE_LABELS_ENABLED: RefCell<bool> = RefCell::new(false);
+    static EDGE_EVENTS: RefCell<Vec<EdgeEvent>> = const { RefCell::new(Vec::new()) };
+    static ANY_GRAPH_HIT: RefCell<bool> = const { RefCell::new(false) };
+    static EDGE_LABELS_ENABLED: RefCell<bool> = const { RefCell::new(false) };
+    static LOGICAL_REACHABILITY_ENABLED: RefCell<bool> = const { RefCell::new(true) };
+    static SHOW_ARROWS: RefCell<bool> = const { RefCell::new(false) };
     static EDGE_WEIGHTS: RefCell<HashMap<(Uuid, Uuid), f32>> = RefCell::new(HashMap::new());
+    // Edges marked failed in the what-if simulation, drawn greyed out
+    static FAILED_EDGES: RefCell<HashSet<UndirectedEdgeKey>> = RefCell::new(HashSet::new());
+    // Links whose metric differs by direction, highlighted when enabled
+    static ASYMMETRIC_EDGES: RefCell<HashSet<UndirectedEdgeKey>> = RefCell::new(HashSet::new());
+    // Membership edges from OSPF transit networks to their DR, drawn thicker when enabled
+    static DR_EDGES: RefCell<HashSet<UndirectedEdgeKey>> = RefCell::new(HashSet::new());
+    static EDGE_BUNDLING_ENABLED: RefCell<bool> = const { RefCell::new(true) };
+    // Closest edge to the pointer drawn so far this frame: (screen distance, source, destination)
+    static HOVER_CANDIDATE: RefCell<Option<(f32, Uuid, Uuid)>> = const { RefCell::new(None) };
+    // Node pairs with several distinct edges, see `NetworkGraph::parallel_edge_groups`
+    static PARALLEL_EDGES: RefCell<ParallelEdgeGroups> = RefCell::new(HashMap::new());
 }
 
-pub fn set_edge_weights(weights: HashMap<(Uuid, Uuid), f32>) {
-    EDGE_WEIGHTS.with(|w| *w.borrow_mut() = weights);
+/// Canvas-space distance between neighbouring edges of a bundle.
+const BUNDLE_SPACING: f32 = 6.0;
+
+pub fn set_parallel_edges(groups: ParallelEdgeGroups) {
+    PARALLEL_EDGES.with(|v| *v.borrow_mut() = groups);
 }
 
-pub fn insert_edge_weight(src: Uuid, dst: Uuid, weight: f32) {
-    EDGE_WEIGHTS.with(|w| {
-        let mut weights = w.borrow_mut();
-        weights.insert((src, dst), weight);
-    });
+/// Enable/disable drawing parallel edges between the same node pair side by side.
+pub fn set_edge_bundling_enabled(enabled: bool) {
+    EDGE_BUNDLING_ENABLED.with(|b| *b.borrow_mut() = enabled);
+}
+
+/// Read current edge bundling toggle.
+pub fn edge_bundling_enabled() -> bool {
+    EDGE_BUNDLING_ENABLED.with(|b| *b.borrow())
+}
+
+/// Position of an edge within its bundle as `(slot, bundle size)`, or `None` if it has no parallels.
+fn bundle_slot(src: Uuid, dst: Uuid, kind: EdgeKind, protocol_tag: Option<&str>) -> Option<(usize, usize)> {
+    if !edge_bundling_enabled() {
+        return None;
+    }
+    let pair = UndirectedEdgeKey::new(src, dst, kind).endpoints();
+    PARALLEL_EDGES.with_borrow(|groups| {
+        let group = groups.get(&pair)?;
+        let slot = group
+            .iter()
+            .position(|(k, tag)| *k == kind && tag.as_deref() == protocol_tag)?;
+        Some((slot, group.len()))
+    })
+}
+
+/// Perpendicular canvas-space offset of an edge, spreading the bundle symmetrically around the
+/// center line. The normal is taken from the ordered pair so both directed halves agree.
+fn bundle_offset(src: Uuid, dst: Uuid, a: Pos2, b: Pos2, slot: (usize, usize)) -> egui::Vec2 {
+    let (slot, count) = slot;
+    let (from, to) = if src < dst { (a, b) } else { (b, a) };
+    let dir = to - from;
+    let len = dir.length();
+    if len <= f32::EPSILON {
+        return egui::Vec2::ZERO;
+    }
+    let normal = egui::vec2(-dir.y, dir.x) / len;
+    normal * (slot as f32 - (count - 1) as f32 / 2.0) * BUNDLE_SPACING
+}
+
+/// Edges to highlight as asymmetric; pass an empty set to turn the highlight off.
+pub fn set_asymmetric_edges(keys: HashSet<UndirectedEdgeKey>) {
+    ASYMMETRIC_EDGES.with(|v| *v.borrow_mut() = keys);
+}
+
+fn is_asymmetric_edge(src: Uuid, dst: Uuid, kind: EdgeKind) -> bool {
+    ASYMMETRIC_EDGES.with_borrow(|v| v.contains(&UndirectedEdgeKey::new(src, dst, kind)))
+}
+
+/// Edges to emphasize as designated router links; pass an empty set to turn the emphasis off.
+pub fn set_designated_router_edges(keys: HashSet<UndirectedEdgeKey>) {
+    DR_EDGES.with(|v| *v.borrow_mut() = keys);
+}
+
+fn is_designated_router_edge(src: Uuid, dst: Uuid, kind: EdgeKind) -> bool {
+    DR_EDGES.with_borrow(|v| v.contains(&UndirectedEdgeKey::new(src, dst, kind)))
+}
+
+pub fn set_failed_edges(keys: HashSet<UndirectedEdgeKey>) {
+    FAILED_EDGES.with(|v| *v.borrow_mut() = keys);
+}
+
+/// An edge is shown as failed if it was failed itself or either endpoint was.
+pub fn is_failed_edge(src: Uuid, dst: Uuid, kind: EdgeKind) -> bool {
+    node_shape::is_failed_node(src)
+        || node_shape::is_failed_node(dst)
+        || FAILED_EDGES.with_borrow(|v| v.contains(&UndirectedEdgeKey::new(src, dst, kind)))
+}
+
+pub fn set_edge_weights(weights: HashMap<(Uuid, Uuid), f32>) {
+    EDGE_WEIGHTS.with(|w| *w.borrow_mut() = weights);
 }
 
 pub fn get_edge_weight(src: Uuid, dst: Uuid) -> Option<f32> {
@@ -51,6 +134,35 @@ pub fn edge_labels_enabled() -> bool {
     EDGE_LABELS_ENABLED.with(|b| *b.borrow())
 }
 
+/// Enable/disable materialization of `LogicalReachability` (ABR -> summary network) edges.
+/// Takes effect on the next graph rebuild.
+pub fn set_logical_reachability_enabled(enabled: bool) {
+    LOGICAL_REACHABILITY_ENABLED.with(|b| *b.borrow_mut() = enabled);
+}
+
+/// Read current logical reachability edge toggle.
+pub fn logical_reachability_enabled() -> bool {
+    LOGICAL_REACHABILITY_ENABLED.with(|b| *b.borrow())
+}
+
+/// Enable/disable arrowheads at the destination end of each directed edge.
+pub fn set_show_arrows(enabled: bool) {
+    SHOW_ARROWS.with(|b| *b.borrow_mut() = enabled);
+}
+
+/// Read current arrowhead toggle.
+pub fn show_arrows() -> bool {
+    SHOW_ARROWS.with(|b| *b.borrow())
+}
+
+/// Edge protocol tags shown in the legend, paired with their display names.
+pub const PROTOCOL_TAGS: [(&str, &str); 3] = [("OSPF", "OSPF"), ("ISIS", "IS-IS"), ("MANUAL", "Manual")];
+
+/// Color used for edges carrying the given protocol tag in the current palette.
+pub fn protocol_color(theme: &catppuccin_egui::Theme, protocol_tag: Option<&str>) -> Option<Color32> {
+    palette::protocol_color(theme, protocol_tag?)
+}
+
 /// Clear the per-frame edge event queue.
 pub fn clear_edge_events() {
     EDGE_EVENTS.with(|q| q.borrow_mut().clear());
@@ -76,6 +188,34 @@ pub fn any_hit() -> bool {
     ANY_GRAPH_HIT.with(|f| *f.borrow())
 }
 
+/// Screen distance within which the pointer counts as hovering an edge.
+const EDGE_HOVER_DISTANCE: f32 = 6.0;
+
+/// Keep the edge from `a` to `b` (screen space) as the hovered one if it's the closest to the
+/// pointer so far this frame.
+fn record_hover_candidate(ctx: &egui::Context, a: Pos2, b: Pos2, src: Uuid, dst: Uuid) {
+    let Some(pointer) = ctx.input(|i| i.pointer.hover_pos()) else {
+        return;
+    };
+    let dist = distance_point_to_segment(pointer, a, b);
+    if dist > EDGE_HOVER_DISTANCE {
+        return;
+    }
+    HOVER_CANDIDATE.with(|c| {
+        let mut c = c.borrow_mut();
+        if c.is_none_or(|(best, _, _)| dist < best) {
+            *c = Some((dist, src, dst));
+        }
+    });
+}
+
+/// Endpoints of the edge closest to the pointer among those drawn since the last call.
+pub fn take_hovered_edge() -> Option<(Uuid, Uuid)> {
+    HOVER_CANDIDATE
+        .with(|c| c.borrow_mut().take())
+        .map(|(_, src, dst)| (src, dst))
+}
+
 /// Custom edge shape that draws a simple line and emits click events when selection changes.
 #[derive(Clone, Debug)]
 pub struct NetworkGraphEdgeShape {
@@ -85,6 +225,7 @@ pub struct NetworkGraphEdgeShape {
     dst_uuid: Option<uuid::Uuid>,
     kind: Option<crate::network::edge::EdgeKind>,
     metric: EdgeMetric,
+    protocol_tag: Option<String>,
 }
 
 impl Default for NetworkGraphEdgeShape {
@@ -95,10 +236,17 @@ impl Default for NetworkGraphEdgeShape {
             dst_uuid: None,
             kind: None,
             metric: EdgeMetric::None,
+            protocol_tag: None,
         }
     }
 }
 
+impl NetworkGraphEdgeShape {
+    fn bundle_slot(&self) -> Option<(usize, usize)> {
+        bundle_slot(self.src_uuid?, self.dst_uuid?, self.kind?, self.protocol_tag.as_deref())
+    }
+}
+
 // Required by the trait bound: Clone + From<EdgeProps<E>>
 impl From<EdgeProps<NetEdge>> for NetworkGraphEdgeShape {
     fn from(props: EdgeProps<NetEdge>) -> Self {
@@ -108,6 +256,7 @@ impl From<EdgeProps<NetEdge>> for NetworkGraphEdgeShape {
             dst_uuid: Some(props.payload.destination_id),
             kind: Some(props.payload.kind),
             metric: props.payload.metric,
+            protocol_tag: props.payload.protocol_tag,
         }
     }
 }
@@ -143,10 +292,22 @@ impl<Ty: EdgeType, Ix: IndexType>
             Ty,
             Ix,
         >>::closest_boundary_point(end.display(), a - b);
-        let a_screen = ctx.meta.canvas_to_screen_pos(a_boundary);
-        let b_screen = ctx.meta.canvas_to_screen_pos(b_boundary);
+        let slot = self.bundle_slot();
+        let offset = match (slot, self.src_uuid, self.dst_uuid) {
+            (Some(slot), Some(src), Some(dst)) => bundle_offset(src, dst, a, b, slot),
+            _ => egui::Vec2::ZERO,
+        };
+        let a_screen = ctx.meta.canvas_to_screen_pos(a_boundary + offset);
+        let b_screen = ctx.meta.canvas_to_screen_pos(b_boundary + offset);
+        if let (Some(src), Some(dst)) = (self.src_uuid, self.dst_uuid) {
+            record_hover_candidate(ctx.ctx, a_screen, b_screen, src, dst);
+        }
 
-        let mut base = ctx.ctx.style().visuals.widgets.inactive.fg_stroke.color;
+        let theme = app::get_theme();
+        let kind_color = slot.and(self.kind).and_then(|kind| palette::edge_kind_color(&theme, kind));
+        let mut base = kind_color
+            .or_else(|| protocol_color(&theme, self.protocol_tag.as_deref()))
+            .unwrap_or(ctx.ctx.style().visuals.widgets.inactive.fg_stroke.color);
 
         // Default: no animation
         let traffic_width_modifier = 2.5;
@@ -189,6 +350,21 @@ impl<Ty: EdgeType, Ix: IndexType>
             }
         }
 
+        if let (Some(src), Some(dst), Some(kind)) = (self.src_uuid, self.dst_uuid, self.kind) {
+            if is_failed_edge(src, dst, kind) {
+                base = app::get_theme().overlay0;
+                alpha_factor *= 0.5;
+            } else if is_asymmetric_edge(src, dst, kind) {
+                base = palette::asymmetric_color(&app::get_theme());
+                if palette::uses_shapes() {
+                    width_scale *= 1.75;
+                }
+            }
+            if is_designated_router_edge(src, dst, kind) {
+                width_scale *= 2.0;
+            }
+        }
+
         let color = egui::Color32::from_rgba_unmultiplied(
             base.r(),
             base.g(),
@@ -203,12 +379,40 @@ impl<Ty: EdgeType, Ix: IndexType>
         let line_length = (b_screen - a_screen).length();
         
         let mut shapes = match self.kind {
-            Some(EdgeKind::Membership) => vec![Shape::line_segment([a_screen, b_screen], stroke)],
+            // Without protocol colors, IS-IS adjacencies get a short dash to stay apart from OSPF
+            Some(EdgeKind::Membership | EdgeKind::PointToPoint)
+                if palette::uses_shapes() && self.protocol_tag.as_deref() == Some("ISIS") =>
+            {
+                Shape::dashed_line(&[a_screen, b_screen], stroke, 3.0, 3.0)
+            }
+            Some(EdgeKind::Membership | EdgeKind::PointToPoint) => {
+                vec![Shape::line_segment([a_screen, b_screen], stroke)]
+            }
+            // Virtual links tunnel through a transit area, so bow them away from the direct path
+            Some(EdgeKind::VirtualLink) => {
+                let curve = virtual_link_curve(self.src_uuid, self.dst_uuid, a_screen, b_screen);
+                Shape::dashed_line(&curve, stroke, 6.0, 4.0)
+            }
             _ => Shape::dashed_line(&[a_screen, b_screen], stroke, line_length / 10.0, line_length / 5.0)
         };
+        // Optional arrowhead at the destination boundary:
+        if show_arrows() && line_length > 0.0 {
+            let dir = (b_screen - a_screen) / line_length;
+            let normal = egui::vec2(-dir.y, dir.x);
+            let head_length = 6.0 + 2.0 * width_scale;
+            let back = b_screen - dir * head_length;
+            shapes.push(Shape::convex_polygon(
+                vec![
+                    b_screen,
+                    back + normal * head_length * 0.5,
+                    back - normal * head_length * 0.5,
+                ],
+                color,
+                egui::Stroke::NONE,
+            ));
+        }
         // Optional metric label:
         if edge_labels_enabled() {
-            println!("Metric label enabled");
             // Midpoint in screen space:
             let mid = egui::pos2(
                 (a_screen.x + b_screen.x) * 0.5,
@@ -226,16 +430,8 @@ impl<Ty: EdgeType, Ix: IndexType>
             let label_pos = mid + offset;
 
             // Fetch a human-readable metric string from the edge payload:
-            // Adjust to your actual payload fields.
-            let metric_text = match self.metric {
-                EdgeMetric::Ospf(m) => Some(format!("OSPF: {}", m)),
-                EdgeMetric::IsIs(m) => Some(format!("IS-IS: {}", m)),
-                EdgeMetric::Manual(m) => Some(format!("Manual: {}", m)),
-                _ => None,
-            };
-
-            if let Some(metric_text) = metric_text {
-                println!("[edge_shape] Metric text: {}", metric_text);
+            if let Some(metric_text) = self.metric.label() {
+                debug_log!("[edge_shape] Metric text: {}", metric_text);
                 // Use egui font system to layout the text:
                 let base_text = ctx.ctx.style().visuals.widgets.inactive.fg_stroke.color;
                 let text_color = Color32::from_rgba_unmultiplied(
@@ -263,6 +459,7 @@ impl<Ty: EdgeType, Ix: IndexType>
         self.dst_uuid = Some(props.payload.destination_id);
         self.kind = Some(props.payload.kind);
         self.metric = props.payload.metric.clone();
+        self.protocol_tag = props.payload.protocol_tag.clone();
 
         // Emit event when selection transitions from false -> true.
         if props.selected && !self.selected_prev {
@@ -292,7 +489,6 @@ impl<Ty: EdgeType, Ix: IndexType>
         pos: Pos2,
     ) -> bool {
         // pos is in canvas coordinates. Do a simple segment distance test (in canvas space).
-        println!("Is inside triggered!");
         let a = start.props().location();
         let b = end.props().location();
         let a_boundary = <NetworkGraphNodeShape as DisplayNode<
@@ -307,7 +503,20 @@ impl<Ty: EdgeType, Ix: IndexType>
             Ty,
             Ix,
         >>::closest_boundary_point(end.display(), a - b);
-        let dist = distance_point_to_segment(pos, a_boundary, b_boundary);
+        let offset = match (self.bundle_slot(), self.src_uuid, self.dst_uuid) {
+            (Some(slot), Some(src), Some(dst)) => bundle_offset(src, dst, a, b, slot),
+            _ => egui::Vec2::ZERO,
+        };
+        let (a, b) = (a_boundary + offset, b_boundary + offset);
+        // Virtual links are drawn as a curve, so test against that rather than the chord
+        let dist = if self.kind == Some(EdgeKind::VirtualLink) {
+            virtual_link_curve(self.src_uuid, self.dst_uuid, a, b)
+                .windows(2)
+                .map(|w| distance_point_to_segment(pos, w[0], w[1]))
+                .fold(f32::INFINITY, f32::min)
+        } else {
+            distance_point_to_segment(pos, a, b)
+        };
         let inside = dist <= 6.0;
         if inside {
             mark_hit();
@@ -316,6 +525,27 @@ impl<Ty: EdgeType, Ix: IndexType>
     }
 }
 
+/// Points of a quadratic curve from `a` to `b`, bulging to the same side for both directed halves.
+fn virtual_link_curve(src: Option<Uuid>, dst: Option<Uuid>, a: Pos2, b: Pos2) -> Vec<Pos2> {
+    const SEGMENTS: usize = 16;
+    let dir = b - a;
+    let mut normal = egui::vec2(-dir.y, dir.x) * 0.2;
+    if src > dst {
+        normal = -normal;
+    }
+    let control = a + dir * 0.5 + normal;
+    (0..=SEGMENTS)
+        .map(|i| {
+            let t = i as f32 / SEGMENTS as f32;
+            let u = 1.0 - t;
+            Pos2::new(
+                u * u * a.x + 2.0 * u * t * control.x + t * t * b.x,
+                u * u * a.y + 2.0 * u * t * control.y + t * t * b.y,
+            )
+        })
+        .collect()
+}
+
 fn distance_point_to_segment(p: Pos2, a: Pos2, b: Pos2) -> f32 {
     let ap = p - a;
     let ab = b - a;
@@ -3,4 +3,12 @@ pub mod node_panel;
 pub mod node_shape;
 pub mod edge_shape;
 pub mod edge_anim;
-pub mod autopoll;
\ No newline at end of file
+pub mod autopoll;
+pub mod credentials;
+pub mod area_hulls;
+pub mod background_task;
+pub mod poll_error;
+pub mod session;
+pub mod saved_layout;
+pub mod saved_overlay;
+pub mod palette;
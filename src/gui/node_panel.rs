@@ -1,13 +1,36 @@
+use std::cell::RefCell;
+
 use egui::{
     self, CollapsingHeader, Context, Frame, Id, InnerResponse, Label, Order, Pos2, Response, Ui,
     Vec2,
 };
 
 use crate::{
-    network::node::{IsIsData, OspfData, OspfPayload, ProtocolData},
+    network::{
+        node::{IsIsData, OspfData, OspfPayload, ProtocolData},
+        pseudonyms::Pseudonyms,
+    },
     parsers::isis_parser::core_lsp::{IsLevel, Tlv},
 };
 
+thread_local! {
+    // Set while the graph is anonymized; panel text goes through it before it's shown
+    static PSEUDONYMS: RefCell<Option<Pseudonyms>> = const { RefCell::new(None) };
+}
+
+pub fn set_pseudonyms(pseudonyms: Option<Pseudonyms>) {
+    PSEUDONYMS.with(|p| *p.borrow_mut() = pseudonyms);
+}
+
+/// `text` as panels show it: with identifiers replaced by pseudonyms while anonymizing.
+pub fn anonymized(text: impl ToString) -> String {
+    let text = text.to_string();
+    PSEUDONYMS.with(|p| match &*p.borrow() {
+        Some(pseudonyms) => pseudonyms.scrub(&text),
+        None => text,
+    })
+}
+
 /// A reusable floating panel anchored near a node on the canvas.
 /// Designed to replace simple text labels with a fully interactive panel.
 ///
@@ -338,9 +361,37 @@ fn isis_protocol_data_section(ui: &mut Ui, data: &IsIsData) {
             }
         )));
         ui.add(label_no_wrap(format!("LSP ID: {}", &data.lsp_id)));
+        if let Some(sequence_number) = &data.sequence_number {
+            ui.add(label_no_wrap(format!("Sequence: {}", sequence_number)));
+        }
+        if let Some(holdtime) = data.holdtime {
+            ui.add(label_no_wrap(format!("Holdtime: {} s", holdtime)));
+        }
+        if data.near_expiry() {
+            expiry_warning(ui);
+        }
+        if !data.areas.is_empty() {
+            ui.add(label_no_wrap(format!("Areas: {}", data.areas.join(", "))));
+        }
         if let Some(net_address) = &data.net_address {
             ui.add(label_no_wrap(format!("NET Address: {}", net_address)));
         }
+        if data.flags.overload {
+            ui.horizontal(|ui| {
+                ui.colored_label(ui.visuals().error_fg_color, "OL");
+                ui.label("Overloaded").on_hover_text(
+                    "The router set the Overload bit. Other routers don't route transit traffic \
+                     through it, only to its own prefixes.",
+                );
+            });
+        }
+        let flag = |set: bool| if set { "1" } else { "0" };
+        ui.add(label_no_wrap(format!(
+            "ATT/P/OL: {}/{}/{}",
+            flag(data.flags.attached),
+            flag(data.flags.partition_repair),
+            flag(data.flags.overload)
+        )));
         if !data.tlvs.is_empty() {
             collapsible_section(ui, "TLVs", false, |ui| {
                 egui::ScrollArea::vertical().min_scrolled_height(100.0).show(ui, |ui| {
@@ -380,7 +431,7 @@ fn isis_protocol_data_section(ui: &mut Ui, data: &IsIsData) {
                                             for n in &tlv.neighbors {
                                                 body.row(18.0, |mut row| {
                                                     row.col(|ui| {
-                                                        ui.label(format!("{}", n.prefix));
+                                                        ui.label(anonymized(n.prefix));
                                                     });
                                                     row.col(|ui| {
                                                         ui.label(format!("{}", n.metric));
@@ -419,7 +470,7 @@ fn isis_protocol_data_section(ui: &mut Ui, data: &IsIsData) {
                                             for n in &tlv.neighbors {
                                                 body.row(18.0, |mut row| {
                                                     row.col(|ui| {
-                                                        ui.label(n.neighbor_id.to_string());
+                                                        ui.label(anonymized(&n.neighbor_id));
                                                     });
                                                     row.col(|ui| {
                                                         ui.label(if n.pseudonode_id == 0 {
@@ -497,9 +548,23 @@ fn ospf_protocol_data_section(ui: &mut Ui, data: &OspfData) {
         if let Some(sum) = data.checksum {
             ui.add(label_no_wrap(format!("LSA checksum: {:x}", sum)));
         }
+        ui.add(label_no_wrap(format!("Sequence: {:#010x}", data.sequence_number())));
+        ui.add(label_no_wrap(format!("LS age: {} s", data.age())));
+        if data.near_expiry() {
+            expiry_warning(ui);
+        }
         ospf_payload_section(ui, &data.payload);
     });
 }
+/// Shown for an advertisement close to the end of its lifetime, see `EXPIRY_WARNING_SECS`.
+fn expiry_warning(ui: &mut Ui) {
+    ui.colored_label(ui.visuals().warn_fg_color, "⚠ About to expire")
+        .on_hover_text(
+            "Less than a minute of lifetime is left. The originator has stopped refreshing it, \
+             so it will probably be purged soon.",
+        );
+}
+
 fn ospf_payload_section(ui: &mut Ui, payload: &OspfPayload) {
     match payload {
         OspfPayload::Router(router) => {
@@ -522,6 +587,32 @@ fn ospf_payload_section(ui: &mut Ui, payload: &OspfPayload) {
                 bullet_list(ui, metrics);
             });
         }
+        OspfPayload::Network(network) => {
+            if !network.summaries.is_empty() {
+                collapsible_section(ui, "Summaries", false, |ui| {
+                    let summaries = network
+                        .summaries
+                        .iter()
+                        .map(|s| format!("ABR {} : metric {}", s.origin_abr, s.metric));
+                    bullet_list(ui, summaries);
+                });
+            }
+            if !network.externals.is_empty() {
+                collapsible_section(ui, "External Routes", true, |ui| {
+                    let externals = network.externals.iter().map(|e| {
+                        let mut line = format!("ASBR {} : metric {}", e.origin_asbr, e.metric);
+                        if let Some(tag) = e.route_tag {
+                            line.push_str(&format!(", tag {}", tag));
+                        }
+                        if let Some(fwd) = e.forwarding_address {
+                            line.push_str(&format!(", via {}", fwd));
+                        }
+                        line
+                    });
+                    bullet_list(ui, externals);
+                });
+            }
+        }
         _ => (),
     }
 }
@@ -545,8 +636,23 @@ pub fn collapsible_section(
     });
 }
 
-pub fn label_no_wrap(text: impl Into<egui::WidgetText>) -> Label {
-    Label::new(text).wrap_mode(egui::TextWrapMode::Extend)
+/// Label followed by a small button that copies `value` to the clipboard. Both are anonymized,
+/// a copied address ends up in shared documents as easily as a screenshot.
+pub fn copyable_label(ui: &mut Ui, text: impl ToString, value: impl ToString) {
+    ui.horizontal(|ui| {
+        ui.label(anonymized(text));
+        if ui
+            .small_button("📋")
+            .on_hover_text("Copy to clipboard")
+            .clicked()
+        {
+            ui.ctx().copy_text(anonymized(value));
+        }
+    });
+}
+
+pub fn label_no_wrap(text: impl ToString) -> Label {
+    Label::new(anonymized(text)).wrap_mode(egui::TextWrapMode::Extend)
 }
 
 /// Tiny helper to render a bullet point list.
@@ -558,7 +664,7 @@ where
     for s in items {
         ui.horizontal(|ui| {
             ui.label("•");
-            ui.label(s.to_string());
+            ui.label(anonymized(s));
         });
     }
 }
@@ -1,5 +1,5 @@
 use std::cell::RefCell;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use catppuccin_egui::Theme;
 use egui::{Color32, Pos2, Shape, Stroke, Vec2, epaint::CircleShape};
@@ -13,13 +13,14 @@ use uuid::Uuid;
 
 use egui::TextureHandle;
 
-use crate::gui::app;
+use crate::gui::{app, area_hulls, palette::{self, NodeRole}};
+use crate::network::network_graph::AreaKey;
 use crate::network::node::{Node, NodeInfo};
 use crate::network::router::RouterId;
 
 thread_local! {
-    static ROUTER_TEX: RefCell<Option<TextureHandle>> = RefCell::new(None);
-    static NETWORK_TEX: RefCell<Option<TextureHandle>> = RefCell::new(None);
+    static ROUTER_TEX: RefCell<Option<TextureHandle>> = const { RefCell::new(None) };
+    static NETWORK_TEX: RefCell<Option<TextureHandle>> = const { RefCell::new(None) };
 }
 
 // Rasterize SVG bytes to a square RGBA buffer at the given target_px (keeps aspect)
@@ -83,6 +84,8 @@ pub fn network_texture_id(ctx: &Context) -> TextureId {
 enum NodeType {
     Router,
     Network,
+    /// A whole area in the collapsed view
+    Area,
 }
 
 impl From<&NodeInfo> for NodeType {
@@ -90,6 +93,7 @@ impl From<&NodeInfo> for NodeType {
         match node_info {
             NodeInfo::Router(_) => NodeType::Router,
             NodeInfo::Network(_) => NodeType::Network,
+            NodeInfo::Area(_) => NodeType::Area,
         }
     }
 }
@@ -105,11 +109,20 @@ pub struct NetworkGraphNodeShape {
     pub dragged: bool,
     pub hovered: bool,
     pub highlighted: bool,
+    /// OSPF external route (Type-5/Type-7), tinted differently from internal networks
     pub external: bool,
+    /// IS-IS pseudonode whose prefix couldn't be resolved
+    pub unresolved: bool,
     pub source_id: Option<RouterId>,
     pub node_uuid: uuid::Uuid, // stable id for animation
     pub node_router_id: Option<RouterId>,
     pub theme: Theme,
+    /// Network inferred from a Router-LSA stub link (drawn smaller with a dashed outline)
+    pub synthetic: bool,
+    /// OSPF and IS-IS areas this node belongs to, used for the area background hulls
+    pub areas: Vec<AreaKey>,
+    /// IS-IS router with the Overload bit set, drawn with an "OL" badge
+    pub overloaded: bool,
     node_type: NodeType,
 }
 
@@ -124,13 +137,101 @@ pub struct LabelOverlay {
 }
 
 thread_local! {
-    static LABEL_OVERLAY: RefCell<Vec<LabelOverlay>> = RefCell::new(Vec::new());
+    static LABEL_OVERLAY: RefCell<Vec<LabelOverlay>> = const { RefCell::new(Vec::new()) };
     // Current hovered OSPF area for the frame, set by hovered node during update()
-    static HOVERED_SOURCE_ID: RefCell<Option<RouterId>> = RefCell::new(None);
+    static HOVERED_SOURCE_ID: RefCell<Option<RouterId>> = const { RefCell::new(None) };
     // Global toggle for partition highlighting
-    static HIGHLIGHT_ENABLED: RefCell<bool> = RefCell::new(true);
+    static HIGHLIGHT_ENABLED: RefCell<bool> = const { RefCell::new(true) };
 
     static PATH_HIGHLIGHT: RefCell<HashSet<Uuid>> = RefCell::new(HashSet::new());
+    // Nodes marked failed in the what-if simulation, drawn greyed out
+    static FAILED_NODES: RefCell<HashSet<Uuid>> = RefCell::new(HashSet::new());
+    // Nodes lost from the blast radius root if the chosen node fails, ringed in red
+    static BLAST_RADIUS: RefCell<HashSet<Uuid>> = RefCell::new(HashSet::new());
+    // Endpoints of the edge under the pointer, highlighted like a hovered node
+    static HOVERED_EDGE_ENDPOINTS: RefCell<Option<(Uuid, Uuid)>> = const { RefCell::new(None) };
+    // Source of the selected node; every node it reported gets a dashed ring
+    static SELECTED_SOURCE_ID: RefCell<Option<RouterId>> = const { RefCell::new(None) };
+    // Draw-mode preview: the first picked node and the nodes it can be connected to
+    static DRAW_PREVIEW: RefCell<Option<(Uuid, HashSet<Uuid>)>> = const { RefCell::new(None) };
+    // Radius multiplier per node from the "Size nodes by" option; missing nodes keep 1.0
+    static NODE_SCALES: RefCell<HashMap<Uuid, f32>> = RefCell::new(HashMap::new());
+    // Screen position of every node drawn this frame, for box selection
+    static NODE_SCREEN_POSITIONS: RefCell<HashMap<Uuid, Pos2>> = RefCell::new(HashMap::new());
+}
+
+pub fn clear_node_screen_positions() {
+    NODE_SCREEN_POSITIONS.with(|v| v.borrow_mut().clear());
+}
+
+/// Where each node was drawn this frame, in screen coordinates.
+pub fn take_node_screen_positions() -> HashMap<Uuid, Pos2> {
+    NODE_SCREEN_POSITIONS.with(|v| std::mem::take(&mut *v.borrow_mut()))
+}
+
+/// What the node radius is scaled by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NodeSizing {
+    #[default]
+    None,
+    /// Number of distinct neighbors
+    Degree,
+    /// Bytes sent and received on the router's interfaces, for polled routers
+    Traffic,
+}
+
+impl NodeSizing {
+    pub const ALL: [NodeSizing; 3] = [NodeSizing::None, NodeSizing::Degree, NodeSizing::Traffic];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            NodeSizing::None => "None",
+            NodeSizing::Degree => "Degree",
+            NodeSizing::Traffic => "Traffic",
+        }
+    }
+}
+
+/// Bounds of the radius multiplier, so outliers neither vanish nor cover their neighbors.
+pub const MIN_NODE_SCALE: f32 = 0.7;
+pub const MAX_NODE_SCALE: f32 = 2.0;
+
+pub fn set_node_scales(scales: HashMap<Uuid, f32>) {
+    NODE_SCALES.with(|v| *v.borrow_mut() = scales);
+}
+
+fn node_scale(uuid: Uuid) -> f32 {
+    NODE_SCALES.with_borrow(|v| v.get(&uuid).copied().unwrap_or(1.0))
+}
+
+/// Radius multipliers for the given per-node sizes. The square root keeps a hub with ten times
+/// the traffic from dwarfing everything else. All-zero input leaves every node at 1.0.
+pub fn node_scales(values: &HashMap<Uuid, f64>) -> HashMap<Uuid, f32> {
+    let max = values.values().copied().fold(0.0, f64::max);
+    if max <= 0.0 {
+        return HashMap::new();
+    }
+    values
+        .iter()
+        .map(|(id, value)| {
+            let t = (value.max(0.0) / max).sqrt() as f32;
+            (*id, MIN_NODE_SCALE + (MAX_NODE_SCALE - MIN_NODE_SCALE) * t)
+        })
+        .collect()
+}
+
+/// Show which nodes `source` can be connected to; `None` ends the preview.
+pub fn set_draw_preview(preview: Option<(Uuid, HashSet<Uuid>)>) {
+    DRAW_PREVIEW.with(|v| *v.borrow_mut() = preview);
+}
+
+/// `Some(true)` for a valid draw target, `Some(false)` for an invalid one, `None` if the node
+/// is the preview's source or no preview is active.
+fn draw_target_state(uuid: Uuid) -> Option<bool> {
+    DRAW_PREVIEW.with_borrow(|v| {
+        let (source, targets) = v.as_ref()?;
+        (*source != uuid).then(|| targets.contains(&uuid))
+    })
 }
 
 pub fn clear_path_highlight() {
@@ -141,6 +242,40 @@ pub fn set_path_highlight(uuids: impl Iterator<Item = Uuid>) {
     PATH_HIGHLIGHT.with(|v| v.borrow_mut().extend(uuids))
 }
 
+/// Ring every node reported by `source_id`; `None` clears it. Unlike the hover-based partition
+/// highlight this stays while the node is selected.
+pub fn highlight_source(source_id: Option<RouterId>) {
+    SELECTED_SOURCE_ID.with(|v| *v.borrow_mut() = source_id);
+}
+
+fn is_from_selected_source(source_id: Option<&RouterId>) -> bool {
+    SELECTED_SOURCE_ID.with_borrow(|v| v.is_some() && v.as_ref() == source_id)
+}
+
+pub fn set_failed_nodes(uuids: HashSet<Uuid>) {
+    FAILED_NODES.with(|v| *v.borrow_mut() = uuids);
+}
+
+pub fn is_failed_node(uuid: Uuid) -> bool {
+    FAILED_NODES.with_borrow(|v| v.contains(&uuid))
+}
+
+pub fn set_blast_radius(uuids: HashSet<Uuid>) {
+    BLAST_RADIUS.with(|v| *v.borrow_mut() = uuids);
+}
+
+fn in_blast_radius(uuid: Uuid) -> bool {
+    BLAST_RADIUS.with_borrow(|v| v.contains(&uuid))
+}
+
+pub fn set_hovered_edge_endpoints(endpoints: Option<(Uuid, Uuid)>) {
+    HOVERED_EDGE_ENDPOINTS.with(|v| *v.borrow_mut() = endpoints);
+}
+
+fn is_hovered_edge_endpoint(uuid: Uuid) -> bool {
+    HOVERED_EDGE_ENDPOINTS.with_borrow(|v| v.is_some_and(|(a, b)| a == uuid || b == uuid))
+}
+
 /// Clear the hovered-area state at the start of a frame.
 pub fn clear_area_highlight() {
     HOVERED_SOURCE_ID.with(|v| *v.borrow_mut() = None);
@@ -164,6 +299,31 @@ pub fn take_label_overlays() -> Vec<LabelOverlay> {
     LABEL_OVERLAY.with(|v| v.borrow_mut().drain(..).collect())
 }
 
+pub const NODE_RADIUS: f32 = 10.0;
+pub const STUB_NETWORK_RADIUS: f32 = 7.0;
+pub const COLLAPSED_AREA_RADIUS: f32 = 18.0;
+
+fn base_radius(info: &NodeInfo) -> f32 {
+    match info {
+        NodeInfo::Network(net) if net.is_synthetic => STUB_NETWORK_RADIUS,
+        NodeInfo::Area(_) => COLLAPSED_AREA_RADIUS,
+        _ => NODE_RADIUS,
+    }
+}
+
+/// Approximate a circle outline with a dashed polyline.
+pub fn dashed_circle(center: Pos2, radius: f32, stroke: Stroke) -> Vec<Shape> {
+    const SEGMENTS: usize = 32;
+    let points: Vec<Pos2> = (0..=SEGMENTS)
+        .map(|i| {
+            let angle = i as f32 / SEGMENTS as f32 * std::f32::consts::TAU;
+            center + Vec2::angled(angle) * radius
+        })
+        .collect();
+    let circumference = std::f32::consts::TAU * radius;
+    Shape::dashed_line(&points, stroke, circumference / 16.0, circumference / 32.0)
+}
+
 impl From<NodeProps<Node>> for NetworkGraphNodeShape {
     fn from(node_props: NodeProps<Node>) -> Self {
         let payload = &node_props.payload;
@@ -172,6 +332,9 @@ impl From<NodeProps<Node>> for NetworkGraphNodeShape {
         } else {
             None
         };
+        let synthetic = matches!(&payload.info, NodeInfo::Network(net) if net.is_synthetic);
+        let external = matches!(&payload.info, NodeInfo::Network(net) if net.is_external());
+        let unresolved = matches!(&payload.info, NodeInfo::Network(net) if net.is_unresolved());
         Self {
             pos: node_props.location(),
             color: node_props.color(),
@@ -180,13 +343,17 @@ impl From<NodeProps<Node>> for NetworkGraphNodeShape {
             dragged: node_props.dragged,
             hovered: node_props.hovered,
             highlighted: false,
-            radius: 10f32,
-            external: false,
+            radius: base_radius(&payload.info) * node_scale(payload.id),
+            external,
+            unresolved,
             source_id: payload.source_id.clone(),
             node_uuid: payload.id,
             node_router_id: router_id,
             node_type: NodeType::from(&payload.info),
             theme: app::get_theme(),
+            synthetic,
+            areas: AreaKey::of(payload),
+            overloaded: payload.is_isis_overloaded(),
         }
     }
 }
@@ -204,6 +371,7 @@ impl<E: Clone, Ty: EdgeType, Ix: IndexType> DisplayNode<Node, E, Ty, Ix> for Net
         let mut res = Vec::with_capacity(4);
         let circle_center = ctx.meta.canvas_to_screen_pos(self.pos);
         let circle_radius = ctx.meta.canvas_to_screen_size(self.radius);
+        NODE_SCREEN_POSITIONS.with(|v| v.borrow_mut().insert(self.node_uuid, circle_center));
 
         // Partition highlight recompute
         let highlight_on = partition_highlight_enabled();
@@ -226,7 +394,10 @@ impl<E: Clone, Ty: EdgeType, Ix: IndexType> DisplayNode<Node, E, Ty, Ix> for Net
         // Smooth fade ring ONLY for origin
         let fade_highlighted = ctx.ctx.animate_bool(
             egui::Id::new(("partition_highlight", self.node_uuid)),
-            self.highlighted || self.hovered || self.selected,
+            self.highlighted
+                || self.hovered
+                || self.selected
+                || is_hovered_edge_endpoint(self.node_uuid),
         );
         // Neutral stroke using theme (hovered fg for emphasis)
         let hovered_fg = ctx.ctx.style().visuals.widgets.hovered.fg_stroke.color;
@@ -240,11 +411,31 @@ impl<E: Clone, Ty: EdgeType, Ix: IndexType> DisplayNode<Node, E, Ty, Ix> for Net
         let rect = egui::Rect::from_center_size(circle_center, Vec2::new(half * 2.0, half * 2.0));
         let tex_id: TextureId = match self.node_type {
             NodeType::Router => router_texture_id(ctx.ctx),
-            NodeType::Network => network_texture_id(ctx.ctx),
+            NodeType::Network | NodeType::Area => network_texture_id(ctx.ctx),
         };
         let uv = egui::Rect::from_min_max(Pos2::new(0.0, 0.0), Pos2::new(1.0, 1.0));
         res.push(Shape::image(tex_id, rect, uv, self.effective_color(ctx)));
 
+        if area_hulls::area_backgrounds_enabled() {
+            area_hulls::record_area_member(&self.areas, circle_center, circle_radius);
+        }
+
+        if matches!(self.node_type, NodeType::Area) {
+            // Solid outer ring, so an area doesn't read as a big network
+            let ring_stroke = Stroke::new(2.0, self.effective_color(ctx));
+            res.push(Shape::circle_stroke(circle_center, circle_radius * 1.15, ring_stroke));
+        }
+        if self.synthetic {
+            let dash_stroke = Stroke::new(1.0, self.effective_color(ctx));
+            res.extend(dashed_circle(circle_center, circle_radius * 1.15, dash_stroke));
+        }
+        if palette::uses_shapes() {
+            res.extend(role_marker(self.role(), circle_center, circle_radius, self.effective_color(ctx)));
+        }
+        if self.overloaded {
+            res.extend(self.overload_badge(ctx, circle_center, circle_radius));
+        }
+
         // Base circle stroke (for highlight fade ring)
         res.push(
             CircleShape {
@@ -296,6 +487,31 @@ impl<E: Clone, Ty: EdgeType, Ix: IndexType> DisplayNode<Node, E, Ty, Ix> for Net
             });
         }
 
+        if draw_target_state(self.node_uuid) == Some(true) {
+            res.push(
+                CircleShape {
+                    center: circle_center,
+                    radius: circle_radius + 2.5,
+                    fill: Color32::TRANSPARENT,
+                    stroke: Stroke {
+                        width: 2.0,
+                        color: self.theme.teal,
+                    },
+                }
+                .into(),
+            );
+        }
+
+        let fade_source = ctx.ctx.animate_bool(
+            egui::Id::new(("source_highlight", self.node_uuid)),
+            is_from_selected_source(self.source_id.as_ref()),
+        );
+        if fade_source > 0.01 {
+            // Dashed and wider than the path ring so both read on the same node
+            let stroke = Stroke::new(1.5, self.theme.yellow.linear_multiply(fade_source));
+            res.extend(dashed_circle(circle_center, circle_radius * 1.6, stroke));
+        }
+
         let path_highlighted: bool = PATH_HIGHLIGHT.with_borrow(|v| v.contains(&self.node_uuid));
 
         let fade_path = ctx.ctx.animate_bool(
@@ -322,6 +538,11 @@ impl<E: Clone, Ty: EdgeType, Ix: IndexType> DisplayNode<Node, E, Ty, Ix> for Net
             );
         }
 
+        if in_blast_radius(self.node_uuid) {
+            let stroke = Stroke::new(2.0, self.theme.red);
+            res.extend(dashed_circle(circle_center, circle_radius + 4.0, stroke));
+        }
+
         res
     }
 
@@ -333,6 +554,8 @@ impl<E: Clone, Ty: EdgeType, Ix: IndexType> DisplayNode<Node, E, Ty, Ix> for Net
         self.label = state.label.to_string();
         self.color = state.color();
         self.source_id = state.payload.source_id.clone();
+        self.overloaded = state.payload.is_isis_overloaded();
+        self.radius = base_radius(&state.payload.info) * node_scale(self.node_uuid);
         self.theme = app::get_theme();
 
         // If highlighting is enabled and this node is hovered, publish its partition (SourceId) for frame-wide highlight
@@ -347,13 +570,36 @@ impl NetworkGraphNodeShape {
         self.selected
     }
 
+    /// Small "OL" tag at the top right of the node.
+    fn overload_badge(&self, ctx: &egui_graphs::DrawContext, center: Pos2, radius: f32) -> Vec<Shape> {
+        let font = egui::FontId::monospace((radius * 0.6).clamp(7.0, 12.0));
+        let galley = ctx
+            .ctx
+            .fonts_mut(|fonts| fonts.layout_no_wrap("OL".to_string(), font, self.theme.base));
+        let anchor = center + Vec2::new(radius * 0.7, -radius * 0.7);
+        let rect = egui::Rect::from_center_size(anchor, galley.size() + Vec2::new(4.0, 2.0));
+        vec![
+            Shape::rect_filled(rect, 3.0, self.theme.red),
+            Shape::galley(rect.min + Vec2::new(2.0, 1.0), galley, self.theme.base),
+        ]
+    }
+
+    fn role(&self) -> NodeRole {
+        match self.node_type {
+            NodeType::Router => NodeRole::Router,
+            NodeType::Network if self.unresolved => NodeRole::Unresolved,
+            NodeType::Network if self.external => NodeRole::External,
+            NodeType::Network | NodeType::Area => NodeRole::Network,
+        }
+    }
+
     fn effective_color(&self, ctx: &DrawContext) -> Color32 {
-        let mut base = match self.node_type {
-            NodeType::Router => self.theme.blue,
-            NodeType::Network => self.theme.green,
-        };
+        if is_failed_node(self.node_uuid) || draw_target_state(self.node_uuid) == Some(false) {
+            return self.theme.overlay0;
+        }
+        let mut base = palette::node_color(&self.theme, self.role());
 
-        if self.hovered || self.selected {
+        if self.hovered || self.selected || is_hovered_edge_endpoint(self.node_uuid) {
             base = Color32::from_rgb(
                 base.r().saturating_add(40).min(255),
                 base.g().saturating_add(100).min(255),
@@ -385,6 +631,23 @@ impl NetworkGraphNodeShape {
     }
 }
 
+/// Outline telling network roles apart when the palette doesn't use color for it: a square frame
+/// around external routes and a double ring around unresolved pseudonodes.
+pub fn role_marker(role: NodeRole, center: Pos2, radius: f32, color: Color32) -> Vec<Shape> {
+    let stroke = Stroke::new(1.0, color);
+    match role {
+        NodeRole::External => {
+            let rect = egui::Rect::from_center_size(center, Vec2::splat(radius * 2.6));
+            vec![Shape::rect_stroke(rect, 0.0, stroke, egui::StrokeKind::Middle)]
+        }
+        NodeRole::Unresolved => vec![
+            Shape::circle_stroke(center, radius * 1.2, stroke),
+            Shape::circle_stroke(center, radius * 1.4, stroke),
+        ],
+        NodeRole::Router | NodeRole::Network => Vec::new(),
+    }
+}
+
 fn closest_point_on_circle(center: Pos2, radius: f32, dir: Vec2) -> Pos2 {
     center + dir.normalized() * (radius + 1.0)
 }
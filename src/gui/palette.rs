@@ -0,0 +1,129 @@
+/*!
+Color palettes for nodes and edges, selectable for colorblind users.
+
+`Default` keeps the catppuccin theme colors. `Deuteranopia` swaps the red/green pairs for the
+Okabe-Ito colors, which stay distinct under red-green color blindness. `Monochrome` draws
+everything in the theme's text color and tells roles apart by shape instead: rings around
+external and unresolved networks, dashed IS-IS edges and thicker asymmetric edges.
+*/
+
+use std::cell::RefCell;
+
+use catppuccin_egui::Theme;
+use egui::Color32;
+use serde::{Deserialize, Serialize};
+
+use crate::network::edge::EdgeKind;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Palette {
+    #[default]
+    Default,
+    Deuteranopia,
+    Monochrome,
+}
+
+impl Palette {
+    pub const ALL: [Palette; 3] = [Palette::Default, Palette::Deuteranopia, Palette::Monochrome];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Palette::Default => "Default",
+            Palette::Deuteranopia => "Deuteranopia-safe",
+            Palette::Monochrome => "Monochrome with shapes",
+        }
+    }
+}
+
+/// What a node stands for, as far as its color is concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeRole {
+    Router,
+    Network,
+    /// OSPF external route (Type-5/Type-7)
+    External,
+    /// IS-IS pseudonode whose prefix couldn't be resolved
+    Unresolved,
+}
+
+// Okabe-Ito colors
+const OI_ORANGE: Color32 = Color32::from_rgb(0xE6, 0x9F, 0x00);
+const OI_SKY_BLUE: Color32 = Color32::from_rgb(0x56, 0xB4, 0xE9);
+const OI_BLUISH_GREEN: Color32 = Color32::from_rgb(0x00, 0x9E, 0x73);
+const OI_YELLOW: Color32 = Color32::from_rgb(0xF0, 0xE4, 0x42);
+const OI_BLUE: Color32 = Color32::from_rgb(0x00, 0x72, 0xB2);
+const OI_VERMILLION: Color32 = Color32::from_rgb(0xD5, 0x5E, 0x00);
+const OI_REDDISH_PURPLE: Color32 = Color32::from_rgb(0xCC, 0x79, 0xA7);
+
+thread_local! {
+    static PALETTE: RefCell<Palette> = const { RefCell::new(Palette::Default) };
+}
+
+pub fn set_palette(palette: Palette) {
+    PALETTE.with(|v| *v.borrow_mut() = palette);
+}
+
+pub fn palette() -> Palette {
+    PALETTE.with(|v| *v.borrow())
+}
+
+/// True when roles are told apart by shape rather than color.
+pub fn uses_shapes() -> bool {
+    palette() == Palette::Monochrome
+}
+
+pub fn node_color(theme: &Theme, role: NodeRole) -> Color32 {
+    match (palette(), role) {
+        (Palette::Default, NodeRole::Router) => theme.blue,
+        (Palette::Default, NodeRole::Network) => theme.green,
+        (Palette::Default, NodeRole::External) => theme.flamingo,
+        (Palette::Default, NodeRole::Unresolved) => theme.maroon,
+        (Palette::Deuteranopia, NodeRole::Router) => OI_BLUE,
+        (Palette::Deuteranopia, NodeRole::Network) => OI_ORANGE,
+        (Palette::Deuteranopia, NodeRole::External) => OI_REDDISH_PURPLE,
+        (Palette::Deuteranopia, NodeRole::Unresolved) => OI_VERMILLION,
+        (Palette::Monochrome, _) => theme.text,
+    }
+}
+
+/// Color of edges carrying the given protocol tag.
+pub fn protocol_color(theme: &Theme, protocol_tag: &str) -> Option<Color32> {
+    let color = match (palette(), protocol_tag) {
+        (Palette::Default, "OSPF") => theme.sapphire,
+        (Palette::Default, "ISIS") => theme.peach,
+        (Palette::Default, "MANUAL") => theme.yellow,
+        (Palette::Deuteranopia, "OSPF") => OI_SKY_BLUE,
+        (Palette::Deuteranopia, "ISIS") => OI_VERMILLION,
+        (Palette::Deuteranopia, "MANUAL") => OI_YELLOW,
+        (Palette::Monochrome, "OSPF" | "ISIS" | "MANUAL") => theme.subtext0,
+        _ => return None,
+    };
+    Some(color)
+}
+
+/// Color telling edge kinds apart inside a bundle. `None` keeps the protocol color.
+pub fn edge_kind_color(theme: &Theme, kind: EdgeKind) -> Option<Color32> {
+    match (palette(), kind) {
+        (_, EdgeKind::Membership | EdgeKind::PointToPoint) => None,
+        // Kinds already differ by dash pattern
+        (Palette::Monochrome, _) => None,
+        (Palette::Default, EdgeKind::LogicalReachability) => Some(theme.mauve),
+        (Palette::Default, EdgeKind::External) => Some(theme.green),
+        (Palette::Default, EdgeKind::VirtualAdjacency) => Some(theme.pink),
+        (Palette::Default, EdgeKind::VirtualLink) => Some(theme.teal),
+        (Palette::Deuteranopia, EdgeKind::LogicalReachability) => Some(OI_BLUISH_GREEN),
+        (Palette::Deuteranopia, EdgeKind::External) => Some(OI_REDDISH_PURPLE),
+        (Palette::Deuteranopia, EdgeKind::VirtualAdjacency) => Some(OI_ORANGE),
+        (Palette::Deuteranopia, EdgeKind::VirtualLink) => Some(OI_BLUE),
+    }
+}
+
+/// Color of links whose two directions have different metrics.
+pub fn asymmetric_color(theme: &Theme) -> Color32 {
+    match palette() {
+        Palette::Default => theme.peach,
+        Palette::Deuteranopia => OI_VERMILLION,
+        Palette::Monochrome => theme.text,
+    }
+}
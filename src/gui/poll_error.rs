@@ -0,0 +1,92 @@
+/*!
+Why a background fetch of a source failed, so the GUI can tell a source that is down apart from
+a task that was stopped or ran out of time.
+*/
+
+use std::time::Duration;
+
+use thiserror::Error;
+
+use crate::{data_aquisition::ssh::HostKeyRejection, gui::autopoll::BuildError, parsers::isis_parser::topology::IsIsConnectError};
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum PollError {
+    /// The source couldn't be built or connected the first time
+    #[error("init failed: {0}")]
+    Init(String),
+    /// Connected, but the snapshot fetch failed
+    #[error("fetch failed: {0}")]
+    Fetch(String),
+    /// Rebuilding the source after an earlier failure failed again
+    #[error("reinit failed: {0}")]
+    Reinit(String),
+    /// The task was aborted (or panicked) before it reported anything
+    #[error("cancelled")]
+    Cancelled,
+    #[error("timed out after {0:?}")]
+    Timeout(Duration),
+    /// The SSH server's host key isn't trusted, the user has to confirm it
+    #[error("{0}")]
+    HostKey(HostKeyRejection),
+}
+
+impl From<IsIsConnectError> for PollError {
+    fn from(e: IsIsConnectError) -> Self {
+        match e {
+            IsIsConnectError::HostKey(rejection) => PollError::HostKey(rejection),
+            e => PollError::Init(e.to_string()),
+        }
+    }
+}
+
+impl PollError {
+    /// A failed `build_topology`, as `PollError::HostKey` when the host key was rejected so the
+    /// user is asked to trust it, otherwise wrapped by `wrap` (`Init` or `Reinit`).
+    pub fn from_build(e: BuildError, wrap: fn(String) -> PollError) -> Self {
+        match e {
+            BuildError::Ssh(IsIsConnectError::HostKey(rejection))
+            | BuildError::Netconf(IsIsConnectError::HostKey(rejection)) => PollError::HostKey(rejection),
+            e => wrap(e.to_string()),
+        }
+    }
+
+    /// Whether the failure says the source is unreachable. A cancelled task says nothing about
+    /// the source, so it shouldn't be marked Lost for it.
+    pub fn marks_lost(&self) -> bool {
+        !matches!(self, PollError::Cancelled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_poll_error_marks_lost() {
+        assert!(PollError::Init("refused".to_string()).marks_lost());
+        assert!(PollError::Timeout(Duration::from_secs(30)).marks_lost());
+        assert!(!PollError::Cancelled.marks_lost());
+        assert_eq!(
+            PollError::Fetch("no such OID".to_string()).to_string(),
+            "fetch failed: no such OID"
+        );
+    }
+
+    #[test]
+    fn test_poll_error_from_build() {
+        let rejection = HostKeyRejection {
+            host: "r1.lab".to_string(),
+            port: 22,
+            fingerprint: "SHA256:abc".to_string(),
+            changed: false,
+        };
+        assert_eq!(
+            PollError::from_build(BuildError::Netconf(IsIsConnectError::HostKey(rejection.clone())), PollError::Reinit),
+            PollError::HostKey(rejection)
+        );
+        assert_eq!(
+            PollError::from_build(BuildError::Ssh(IsIsConnectError::Connect("refused".to_string())), PollError::Init),
+            PollError::Init("Failed to build ISIS topology: Couldn't connect to the router, check host, port and reachability (refused)".to_string())
+        );
+    }
+}
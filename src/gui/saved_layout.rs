@@ -0,0 +1,86 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use egui::Pos2;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub const LAYOUT_FILE_ENV: &str = "OSPFVIZ_LAYOUT_FILE";
+pub const DEFAULT_LAYOUT_FILE: &str = "layout.json";
+
+/// Node positions written by the "Save layout" button, keyed by node UUID.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SavedLayout {
+    #[serde(default)]
+    pub positions: HashMap<String, [f32; 2]>,
+}
+
+impl SavedLayout {
+    pub fn from_positions(positions: &HashMap<Uuid, Pos2>) -> Self {
+        Self {
+            positions: positions
+                .iter()
+                .map(|(id, pos)| (id.to_string(), [pos.x, pos.y]))
+                .collect(),
+        }
+    }
+
+    /// Positions by UUID. Entries with an unparsable UUID are skipped.
+    pub fn to_positions(&self) -> HashMap<Uuid, Pos2> {
+        self.positions
+            .iter()
+            .filter_map(|(id, [x, y])| Some((Uuid::parse_str(id).ok()?, Pos2::new(*x, *y))))
+            .collect()
+    }
+
+    pub fn path() -> PathBuf {
+        std::env::var(LAYOUT_FILE_ENV)
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from(DEFAULT_LAYOUT_FILE))
+    }
+
+    /// Load the saved layout, `None` if there is none or it can't be read.
+    pub fn load() -> Option<Self> {
+        let path = Self::path();
+        if !path.exists() {
+            return None;
+        }
+        match Self::read(&path) {
+            Ok(layout) => Some(layout),
+            Err(e) => {
+                eprintln!("[layout] Ignoring {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    fn read(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&contents).map_err(|e| e.to_string())
+    }
+
+    pub fn save(&self) -> Result<PathBuf, String> {
+        let path = Self::path();
+        let contents = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(&path, contents).map_err(|e| e.to_string())?;
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_saved_layout_round_trip() {
+        let id = Uuid::new_v5(&Uuid::NAMESPACE_OID, b"10.0.0.1");
+        let positions = HashMap::from([(id, Pos2::new(1.5, -2.0))]);
+        let text = serde_json::to_string(&SavedLayout::from_positions(&positions)).unwrap();
+
+        let mut loaded: SavedLayout = serde_json::from_str(&text).unwrap();
+        loaded.positions.insert("not-a-uuid".to_string(), [0.0, 0.0]);
+        assert_eq!(loaded.to_positions(), positions);
+    }
+}
@@ -0,0 +1,172 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::network::{
+    edge::{EdgeMetric, UndirectedEdgeKey},
+    network_graph::NetworkGraph,
+};
+
+pub const OVERLAY_FILE_ENV: &str = "OSPFVIZ_OVERLAY_FILE";
+pub const DEFAULT_OVERLAY_FILE: &str = "manual_edges.json";
+
+/// Manual edge changes written by the "Save manual edges" button, kept apart from the discovered
+/// topology so they can be reapplied on top of a fresh one.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SavedOverlay {
+    #[serde(default)]
+    pub edges: Vec<SavedManualEdge>,
+    /// Base edges hidden with the Snip tool
+    #[serde(default)]
+    pub suppressed: Vec<UndirectedEdgeKey>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedManualEdge {
+    pub key: UndirectedEdgeKey,
+    pub metric: u32,
+}
+
+impl SavedOverlay {
+    pub fn from_graph(graph: &NetworkGraph) -> Self {
+        Self {
+            edges: graph
+                .manual_edge_specs()
+                .filter_map(|spec| match spec.metric {
+                    EdgeMetric::Manual(metric) => Some(SavedManualEdge { key: spec.key, metric }),
+                    _ => None,
+                })
+                .collect(),
+            suppressed: graph.suppressed_edges().copied().collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edges.is_empty() && self.suppressed.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.edges.len() + self.suppressed.len()
+    }
+
+    /// Reapply the entries whose endpoints are both in `known`, the merged topology before any
+    /// view filter, and remove them from the overlay. The rest stay for a later call, e.g. once
+    /// their sources have connected. Returns how many entries were applied.
+    pub fn apply(&mut self, graph: &mut NetworkGraph, known: &HashSet<Uuid>) -> usize {
+        let is_known = |key: &UndirectedEdgeKey| {
+            let (a, b) = key.endpoints();
+            known.contains(&a) && known.contains(&b)
+        };
+        let before = self.len();
+        self.edges.retain(|edge| {
+            if !is_known(&edge.key) {
+                return true;
+            }
+            graph.restore_manual_edge(edge.key, edge.metric);
+            false
+        });
+        self.suppressed.retain(|key| {
+            if !is_known(key) {
+                return true;
+            }
+            let (a, b) = key.endpoints();
+            graph.supress_base_edge(a, b, key.kind);
+            false
+        });
+        before - self.len()
+    }
+
+    pub fn path() -> PathBuf {
+        std::env::var(OVERLAY_FILE_ENV)
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from(DEFAULT_OVERLAY_FILE))
+    }
+
+    /// Load the saved overlay, `None` if there is none or it can't be read.
+    pub fn load() -> Option<Self> {
+        let path = Self::path();
+        if !path.exists() {
+            return None;
+        }
+        match Self::read(&path) {
+            Ok(overlay) => Some(overlay),
+            Err(e) => {
+                eprintln!("[overlay] Ignoring {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    fn read(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&contents).map_err(|e| e.to_string())
+    }
+
+    pub fn save(&self) -> Result<PathBuf, String> {
+        let path = Self::path();
+        let contents = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(&path, contents).map_err(|e| e.to_string())?;
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{
+        network::{edge::{EdgeKind, ReverseMetricPolicy}, node::Node},
+        topology::store::{MergeConfig, TopologyStore},
+    };
+
+    #[test]
+    fn test_saved_overlay_round_trip() {
+        let a = Uuid::new_v5(&Uuid::NAMESPACE_OID, b"10.0.0.1");
+        let b = Uuid::new_v5(&Uuid::NAMESPACE_OID, b"10.0.0.0/24");
+        let key = UndirectedEdgeKey::new(a, b, EdgeKind::Membership);
+        let overlay = SavedOverlay {
+            edges: vec![SavedManualEdge { key, metric: 10 }],
+            suppressed: vec![UndirectedEdgeKey::new(b, a, EdgeKind::PointToPoint)],
+        };
+        let text = serde_json::to_string(&overlay).unwrap();
+        let mut loaded: SavedOverlay = serde_json::from_str(&text).unwrap();
+        assert_eq!(loaded.edges, overlay.edges);
+        assert_eq!(loaded.suppressed, overlay.suppressed);
+
+        // Neither endpoint is known yet, so nothing is applied and both entries wait
+        let mut graph = NetworkGraph::default();
+        assert_eq!(loaded.apply(&mut graph, &HashSet::new()), 0);
+        assert_eq!(loaded.len(), 2);
+        assert!(!graph.any_manual_changes());
+    }
+
+    #[test]
+    fn test_saved_overlay_reapply() {
+        let json = include_str!("../../test_data/test_store.json");
+        let store: TopologyStore = serde_json::from_str(json).unwrap();
+        let nodes = store.build_merged_view_with(&MergeConfig::default()).unwrap();
+        let known: HashSet<Uuid> = nodes.iter().map(|n| n.id).collect();
+        let (a, b) = (nodes[0].id, nodes[1].id);
+
+        let mut graph = NetworkGraph::build_new(nodes.clone(), ReverseMetricPolicy::default());
+        graph.add_manual_edge(a, b, EdgeKind::VirtualAdjacency, 7).unwrap();
+        graph.supress_base_edge(a, b, EdgeKind::Membership);
+        let mut overlay = SavedOverlay::from_graph(&graph);
+        assert_eq!(overlay.len(), 2);
+
+        // `b` is hidden by a view filter but still in the topology, so both entries apply
+        let visible: Vec<Node> = nodes.into_iter().filter(|n| n.id != b).collect();
+        let mut fresh = NetworkGraph::build_new(visible, ReverseMetricPolicy::default());
+        assert_eq!(overlay.apply(&mut fresh, &known), 2);
+        assert!(overlay.is_empty());
+        assert!(fresh.is_manual_edge(a, b, EdgeKind::VirtualAdjacency));
+        assert_eq!(fresh.suppressed_edges().count(), 1);
+        let key = UndirectedEdgeKey::new(a, b, EdgeKind::VirtualAdjacency);
+        assert_eq!(SavedOverlay::from_graph(&fresh).edges, vec![SavedManualEdge { key, metric: 7 }]);
+    }
+}
@@ -0,0 +1,306 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    gui::{
+        autopoll::{AcquisitionConfig, ProtocolKind, SourceSpec},
+        credentials::{Credentials, SecretHandle},
+        palette::Palette,
+    },
+    topology::{ospf_protocol::ConsolidationPolicy, store::SourceId},
+};
+
+pub const SESSION_FILE_ENV: &str = "OSPFVIZ_SESSION_FILE";
+pub const DEFAULT_SESSION_FILE: &str = "session.toml";
+
+/// Sources known at the end of the last run and the chosen palette, written whenever either changes.
+///
+/// Secrets are never written, only their keyring handles. A fresh process has nothing pinned, so
+/// SSH sources are reconnected with the password from `Credentials` when host, port and username
+/// match, NETCONF sources with the SSH password when host and username match, SNMP sources with its
+/// community when the address matches, and come back as Lost otherwise.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Session {
+    /// Reconnect every saved source when the app starts.
+    #[serde(default)]
+    pub reconnect_on_launch: bool,
+    #[serde(default)]
+    pub palette: Palette,
+    #[serde(default)]
+    pub sources: Vec<SavedSource>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedSource {
+    pub source_id: SourceId,
+    pub acquisition: SavedAcquisition,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SavedAcquisition {
+    OspfSnmp {
+        address: SocketAddr,
+        community: SecretHandle,
+        max_repetitions: u32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        oid_file: Option<PathBuf>,
+        #[serde(default)]
+        summary_policy: ConsolidationPolicy,
+    },
+    IsisSsh {
+        host: String,
+        port: u16,
+        username: String,
+        connect_timeout_secs: u64,
+    },
+    IsisNetconf {
+        host: String,
+        port: u16,
+        username: String,
+        password: SecretHandle,
+        connect_timeout_secs: u64,
+    },
+}
+
+impl SavedSource {
+    /// `None` for specs that can't be saved without secrets (SNMPv3) or aren't supported.
+    pub fn from_spec(source_id: &SourceId, spec: &SourceSpec) -> Option<Self> {
+        let acquisition = match (&spec.protocol, &spec.acquisition) {
+            (ProtocolKind::Ospf, AcquisitionConfig::Snmp(config)) => {
+                if config.security().is_some() {
+                    return None;
+                }
+                SavedAcquisition::OspfSnmp {
+                    address: config.address(),
+                    community: config.community().clone(),
+                    max_repetitions: config.max_repetitions(),
+                    oid_file: config.oid_file().map(Path::to_path_buf),
+                    summary_policy: config.summary_policy(),
+                }
+            }
+            (ProtocolKind::Isis, AcquisitionConfig::Ssh(config)) => SavedAcquisition::IsisSsh {
+                host: config.host.clone(),
+                port: config.port,
+                username: config.username.clone(),
+                connect_timeout_secs: config.connect_timeout.as_secs(),
+            },
+            (ProtocolKind::Isis, AcquisitionConfig::Netconf(config)) => SavedAcquisition::IsisNetconf {
+                host: config.host.clone(),
+                port: config.port,
+                username: config.username.clone(),
+                password: config.password.clone(),
+                connect_timeout_secs: config.connect_timeout.as_secs(),
+            },
+            _ => return None,
+        };
+        Some(Self {
+            source_id: source_id.clone(),
+            acquisition,
+        })
+    }
+
+    /// Rebuild the spec, `Err` if a source's secret is neither pinned nor in `creds`.
+    pub fn to_spec(&self, creds: &Credentials) -> Result<SourceSpec, String> {
+        match &self.acquisition {
+            SavedAcquisition::OspfSnmp {
+                address,
+                community,
+                max_repetitions,
+                oid_file,
+                summary_policy,
+            } => {
+                let matches_creds = creds.snmp_port == address.port()
+                    && creds.snmp_host.parse::<IpAddr>() == Ok(address.ip());
+                let community = community
+                    .resolve()
+                    .or_else(|| matches_creds.then(|| creds.snmp_community.clone()))
+                    .ok_or_else(|| format!("no saved community for {}", address))?;
+                Ok(SourceSpec::new_snmp(
+                    *address,
+                    community,
+                    snmp2::Version::V2C,
+                    None,
+                    *max_repetitions,
+                    ProtocolKind::Ospf,
+                )
+                .with_oid_file(oid_file.clone())
+                .with_summary_policy(*summary_policy))
+            }
+            SavedAcquisition::IsisSsh {
+                host,
+                port,
+                username,
+                connect_timeout_secs,
+            } => {
+                if creds.ssh_host != *host || creds.ssh_port != *port || creds.ssh_username != *username
+                {
+                    return Err(format!("no saved password for {}@{}:{}", username, host, port));
+                }
+                Ok(SourceSpec::new_ssh(
+                    host.clone(),
+                    *port,
+                    username.clone(),
+                    creds.ssh_password.clone(),
+                    Duration::from_secs(*connect_timeout_secs),
+                    ProtocolKind::Isis,
+                ))
+            }
+            SavedAcquisition::IsisNetconf {
+                host,
+                port,
+                username,
+                password,
+                connect_timeout_secs,
+            } => {
+                // NETCONF logs in over SSH, usually with the same account on another port
+                let matches_creds = creds.ssh_host == *host && creds.ssh_username == *username;
+                let password = password
+                    .resolve()
+                    .or_else(|| matches_creds.then(|| creds.ssh_password.clone()))
+                    .ok_or_else(|| format!("no saved password for {}@{}:{}", username, host, port))?;
+                Ok(SourceSpec::new_netconf(
+                    host.clone(),
+                    *port,
+                    username.clone(),
+                    password,
+                    Duration::from_secs(*connect_timeout_secs),
+                    ProtocolKind::Isis,
+                ))
+            }
+        }
+    }
+}
+
+impl Session {
+    pub fn path() -> PathBuf {
+        std::env::var(SESSION_FILE_ENV)
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from(DEFAULT_SESSION_FILE))
+    }
+
+    /// Load the last session, or an empty one if there is none or it can't be read.
+    pub fn load() -> Self {
+        let path = Self::path();
+        if !path.exists() {
+            return Self::default();
+        }
+        match Self::read(&path) {
+            Ok(session) => session,
+            Err(e) => {
+                eprintln!("[session] Ignoring {}: {}", path.display(), e);
+                Self::default()
+            }
+        }
+    }
+
+    fn read(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        toml::from_str(&contents).map_err(|e| e.to_string())
+    }
+
+    pub fn save(&self) {
+        let path = Self::path();
+        let result = toml::to_string(self)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| std::fs::write(&path, contents).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            eprintln!("[session] Failed to save {}: {}", path.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+    use crate::network::router::RouterId;
+
+    #[test]
+    fn test_session_round_trip() {
+        let creds = Credentials::default();
+        let snmp = SourceSpec::new_snmp(
+            "10.0.0.1:161".parse().unwrap(),
+            "public".to_string(),
+            snmp2::Version::V2C,
+            None,
+            10,
+            ProtocolKind::Ospf,
+        )
+        .with_oid_file(Some(PathBuf::from("vendor_oids.toml")))
+        .with_summary_policy(ConsolidationPolicy::KeepSummaries);
+        let ssh = SourceSpec::new_ssh(
+            creds.ssh_host.clone(),
+            creds.ssh_port,
+            creds.ssh_username.clone(),
+            creds.ssh_password.clone(),
+            Duration::from_secs(5),
+            ProtocolKind::Isis,
+        );
+        let netconf = SourceSpec::new_netconf(
+            "10.0.0.3".to_string(),
+            830,
+            "netconf".to_string(),
+            "netconf-secret".to_string(),
+            Duration::from_secs(5),
+            ProtocolKind::Isis,
+        );
+        let session = Session {
+            reconnect_on_launch: true,
+            palette: Palette::Monochrome,
+            sources: vec![
+                SavedSource::from_spec(&RouterId::Ipv4(Ipv4Addr::new(10, 0, 0, 1)), &snmp).unwrap(),
+                SavedSource::from_spec(&RouterId::Ipv4(Ipv4Addr::new(10, 0, 0, 2)), &ssh).unwrap(),
+                SavedSource::from_spec(&RouterId::Ipv4(Ipv4Addr::new(10, 0, 0, 3)), &netconf).unwrap(),
+            ],
+        };
+
+        let text = toml::to_string(&session).unwrap();
+        assert!(!text.contains(&format!("\"{}\"", creds.ssh_password)));
+        assert!(!text.contains("public"));
+        assert!(!text.contains("netconf-secret"));
+        let loaded: Session = toml::from_str(&text).unwrap();
+        assert!(loaded.reconnect_on_launch);
+        assert_eq!(loaded.palette, Palette::Monochrome);
+        assert_eq!(loaded.sources.len(), 3);
+        assert_eq!(loaded.sources[1].source_id, RouterId::Ipv4(Ipv4Addr::new(10, 0, 0, 2)));
+        for source in &loaded.sources {
+            assert!(source.to_spec(&creds).is_ok());
+        }
+        assert!(matches!(
+            &loaded.sources[0].acquisition,
+            SavedAcquisition::OspfSnmp {
+                oid_file: Some(path),
+                summary_policy: ConsolidationPolicy::KeepSummaries,
+                ..
+            } if path == Path::new("vendor_oids.toml")
+        ));
+
+        let other_creds = Credentials {
+            ssh_host: "192.0.2.1".to_string(),
+            ..Credentials::default()
+        };
+        assert!(loaded.sources[1].to_spec(&other_creds).is_err());
+        let spec = loaded.sources[2].to_spec(&other_creds).unwrap();
+        assert!(matches!(spec.acquisition, AcquisitionConfig::Netconf(ref c) if c.port == 830));
+        assert_eq!(spec.secret().resolve().as_deref(), Some("netconf-secret"));
+        netconf.secret().forget();
+        assert!(loaded.sources[2].to_spec(&other_creds).is_err());
+
+        // Once forgotten, the community only comes back from matching credentials
+        snmp.secret().forget();
+        assert!(loaded.sources[0].to_spec(&other_creds).is_err());
+        let snmp_creds = Credentials {
+            snmp_host: "10.0.0.1".to_string(),
+            snmp_port: 161,
+            ..Credentials::default()
+        };
+        assert!(loaded.sources[0].to_spec(&snmp_creds).is_ok());
+    }
+}
@@ -0,0 +1,105 @@
+/*!
+Process-wide verbosity for the diagnostic prints scattered through parsing and graph building.
+
+Warnings and errors still go straight to `eprintln!`; the chatty progress output goes through
+`info_log!` / `debug_log!` and is dropped unless the level allows it. The level starts from the
+`OSPFVIZ_LOG` environment variable (`quiet`, `info` or `debug`) and can be changed at runtime.
+*/
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+pub const LOG_LEVEL_ENV: &str = "OSPFVIZ_LOG";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Quiet = 0,
+    Info = 1,
+    Debug = 2,
+}
+
+impl LogLevel {
+    pub const ALL: [LogLevel; 3] = [LogLevel::Quiet, LogLevel::Info, LogLevel::Debug];
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => LogLevel::Quiet,
+            1 => LogLevel::Info,
+            _ => LogLevel::Debug,
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "quiet" | "off" | "0" => Some(LogLevel::Quiet),
+            "info" | "1" => Some(LogLevel::Info),
+            "debug" | "verbose" | "2" => Some(LogLevel::Debug),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LogLevel::Quiet => "Quiet",
+            LogLevel::Info => "Info",
+            LogLevel::Debug => "Debug",
+        }
+    }
+}
+
+static LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Quiet as u8);
+
+pub fn set_level(level: LogLevel) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+pub fn level() -> LogLevel {
+    LogLevel::from_u8(LEVEL.load(Ordering::Relaxed))
+}
+
+pub fn enabled(level: LogLevel) -> bool {
+    level <= self::level()
+}
+
+/// Set the level from `OSPFVIZ_LOG`, keeping the quiet default if it is unset or invalid.
+pub fn init_from_env() {
+    let Ok(value) = std::env::var(LOG_LEVEL_ENV) else {
+        return;
+    };
+    match LogLevel::parse(&value) {
+        Some(level) => set_level(level),
+        None => eprintln!("[logging] Ignoring {}={}: expected quiet, info or debug", LOG_LEVEL_ENV, value),
+    }
+}
+
+/// `println!` shown at `Info` and above.
+macro_rules! info_log {
+    ($($arg:tt)*) => {
+        if $crate::logging::enabled($crate::logging::LogLevel::Info) {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// `println!` shown only at `Debug`.
+macro_rules! debug_log {
+    ($($arg:tt)*) => {
+        if $crate::logging::enabled($crate::logging::LogLevel::Debug) {
+            println!($($arg)*);
+        }
+    };
+}
+
+pub(crate) use {debug_log, info_log};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_level_parse_and_order() {
+        assert_eq!(LogLevel::parse(" Debug "), Some(LogLevel::Debug));
+        assert_eq!(LogLevel::parse("off"), Some(LogLevel::Quiet));
+        assert_eq!(LogLevel::parse("loud"), None);
+        assert!(LogLevel::Info < LogLevel::Debug);
+    }
+}
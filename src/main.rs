@@ -1,13 +1,62 @@
+mod cli;
 mod data_aquisition;
 mod gui;
+mod logging;
 mod network;
 mod parsers;
+#[cfg(feature = "serve")]
+mod serve;
 mod topology;
 
-use std::sync::Arc;
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+use clap::Parser;
+use cli::{Cli, Startup};
 use gui::app;
+use tokio::runtime::Runtime;
 
 fn main() {
+    logging::init_from_env();
+    let cli = Cli::parse();
+    let startup = match cli.startup() {
+        Ok(startup) => startup,
+        Err(e) => {
+            eprintln!("[cli] {}", e);
+            std::process::exit(2);
+        }
+    };
     let rt = Arc::new(tokio::runtime::Runtime::new().unwrap());
-    app::main(rt);
+
+    if let Some(addr) = cli.serve_addr() {
+        serve_headless(&rt, addr, startup);
+        return;
+    }
+
+    app::main(rt.clone(), startup);
+    shutdown_runtime(rt);
+}
+
+/// How long to wait for blocking work (SSH calls) still running after the window closes.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Shut the runtime down without waiting indefinitely on blocking SSH calls, which can't be
+/// aborted. Async tasks were already aborted when the app was dropped.
+fn shutdown_runtime(rt: Arc<Runtime>) {
+    match Arc::try_unwrap(rt) {
+        Ok(rt) => rt.shutdown_timeout(SHUTDOWN_TIMEOUT),
+        Err(_) => eprintln!("[app] Runtime still in use at exit, leaving it running"),
+    }
+}
+
+#[cfg(feature = "serve")]
+fn serve_headless(rt: &Runtime, addr: SocketAddr, startup: Startup) {
+    if let Err(e) = rt.block_on(serve::run(addr, startup)) {
+        eprintln!("[serve] Server failed: {}", e);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(not(feature = "serve"))]
+fn serve_headless(_rt: &Runtime, _addr: SocketAddr, _startup: Startup) {
+    eprintln!("--headless and --serve require building with `--features serve`");
+    std::process::exit(2);
 }
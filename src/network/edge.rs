@@ -1,3 +1,4 @@
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 #[derive(Debug, Clone)]
@@ -21,6 +22,8 @@ pub enum EdgeMetric {
     None
 }
 
+/// Raw metric value. `Other` and `None` carry no metric and convert to 0, so pathfinding should
+/// use `EdgeMetric::cost` instead to avoid treating them as free.
 impl Into<u32> for &EdgeMetric {
     fn into(self) -> u32 {
         match self {
@@ -33,7 +36,59 @@ impl Into<u32> for &EdgeMetric {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Cost substituted for unmetered edges in pathfinding unless the user picks another one.
+pub const DEFAULT_UNMETERED_COST: u32 = 1;
+
+impl EdgeMetric {
+    /// Metric value, `None` for edges without one.
+    pub fn value(&self) -> Option<u32> {
+        match self {
+            EdgeMetric::Ospf(v) | EdgeMetric::IsIs(v) | EdgeMetric::Manual(v) => Some(*v),
+            EdgeMetric::Other | EdgeMetric::None => None,
+        }
+    }
+
+    /// Pathfinding cost: the metric value, or `unmetered` for `Other`/`None` edges.
+    pub fn cost(&self, unmetered: u32) -> u32 {
+        self.value().unwrap_or(unmetered)
+    }
+
+    /// Text shown on the edge when metric labels are enabled, `None` for edges without a metric.
+    pub fn label(&self) -> Option<String> {
+        match self {
+            EdgeMetric::Ospf(m) => Some(format!("OSPF: {}", m)),
+            EdgeMetric::IsIs(m) => Some(format!("IS-IS: {}", m)),
+            EdgeMetric::Manual(m) => Some(format!("Manual: {}", m)),
+            EdgeMetric::Other | EdgeMetric::None => None,
+        }
+    }
+}
+
+/// Metric given to the reverse half of an edge when the protocol data has none for that direction
+/// (e.g. logical reachability, or a point-to-point link only one side reports).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReverseMetricPolicy {
+    /// Reuse the forward metric, treating the link as symmetric so paths across it in either
+    /// direction are costed
+    #[default]
+    Mirror,
+    /// Leave the reverse half without a metric, only advertised costs are shown
+    Strict,
+}
+
+impl ReverseMetricPolicy {
+    /// Metric of one half of an edge, given what the protocol data provided for it and the
+    /// metric of the opposite half. Applied to both halves, so a link only the far end reports a
+    /// cost for is mirrored too.
+    pub fn apply(self, opposite: &EdgeMetric, metric: EdgeMetric) -> EdgeMetric {
+        match (self, metric) {
+            (ReverseMetricPolicy::Mirror, EdgeMetric::None) => opposite.clone(),
+            (_, metric) => metric,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[allow(dead_code)]
 pub enum EdgeKind {
     /// Physical or authoritative presence on network
@@ -42,11 +97,15 @@ pub enum EdgeKind {
     LogicalReachability,
     /// External route injection
     External,
-    /// Virtual link / overlay adjacency
+    /// Overlay adjacency not backed by a protocol link
     VirtualAdjacency,
+    /// Direct router-to-router adjacency with no transit network node (e.g. IS-IS P2P link)
+    PointToPoint,
+    /// OSPF virtual link between two ABRs, tunnelled through a non-backbone transit area
+    VirtualLink,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct UndirectedEdgeKey {
     pub a: Uuid,
     pub b: Uuid,
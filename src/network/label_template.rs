@@ -0,0 +1,162 @@
+use crate::network::node::{Node, NodeInfo, OspfPayload, ProtocolData};
+use crate::parsers::isis_parser::core_lsp::Tlv;
+
+/// Placeholders understood by `LabelTemplate`, shown as a hint in the GUI.
+pub const LABEL_PLACEHOLDERS: [&str; 4] = ["{router_id}", "{prefix}", "{hostname}", "{area}"];
+
+/// Default node label per node kind, used when a node has no explicit label.
+///
+/// Placeholders are substituted from the node's `NodeInfo`:
+/// - `{router_id}`: router id (routers), designated/advertising router (networks)
+/// - `{prefix}`: network prefix (networks only)
+/// - `{hostname}`: acquired hostname (e.g. SNMP sysName) or IS-IS hostname TLV, falling back to the router id
+/// - `{area}`: OSPF area id or first IS-IS area address
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LabelTemplate {
+    pub router: String,
+    pub network: String,
+}
+
+impl Default for LabelTemplate {
+    fn default() -> Self {
+        Self {
+            router: "Router".to_string(),
+            network: "Network".to_string(),
+        }
+    }
+}
+
+impl LabelTemplate {
+    /// Label for `node`: its explicit label if set, otherwise the template for its kind.
+    pub fn label_for(&self, node: &Node) -> String {
+        if let Some(label) = &node.label {
+            return label.clone();
+        }
+        let template = match &node.info {
+            NodeInfo::Router(_) => &self.router,
+            NodeInfo::Network(_) => &self.network,
+            NodeInfo::Area(area) => return area.name.clone(),
+        };
+        Self::render(template, node)
+    }
+
+    fn render(template: &str, node: &Node) -> String {
+        if !template.contains('{') {
+            return template.to_string();
+        }
+
+        let (router_id, prefix, protocol_data) = match &node.info {
+            NodeInfo::Router(router) => (
+                router.id.to_string(),
+                String::new(),
+                router.protocol_data.as_ref(),
+            ),
+            NodeInfo::Network(network) => {
+                let router_id = match &network.protocol_data {
+                    Some(ProtocolData::Ospf(data)) => data.advertising_router.to_string(),
+                    _ => String::new(),
+                };
+                (
+                    router_id,
+                    network.ip_address.to_string(),
+                    network.protocol_data.as_ref(),
+                )
+            }
+            NodeInfo::Area(_) => (String::new(), String::new(), None),
+        };
+
+        let acquired_hostname = match &node.info {
+            NodeInfo::Router(router) => router.hostname.clone(),
+            NodeInfo::Network(_) | NodeInfo::Area(_) => None,
+        };
+        let hostname = acquired_hostname
+            .or_else(|| match protocol_data {
+                Some(ProtocolData::IsIs(data)) => data.tlvs.iter().find_map(|tlv| match tlv {
+                    Tlv::Hostname(name) => Some(name.clone()),
+                    _ => None,
+                }),
+                _ => None,
+            })
+            .unwrap_or_else(|| router_id.clone());
+
+        let area = match protocol_data {
+            Some(ProtocolData::Ospf(data)) => match &data.payload {
+                OspfPayload::Router(payload) if payload.per_area_facets.len() > 1 => payload
+                    .per_area_facets
+                    .iter()
+                    .map(|f| f.area_id.to_string())
+                    .collect::<Vec<_>>()
+                    .join(","),
+                _ => data.area_id.to_string(),
+            },
+            Some(ProtocolData::IsIs(data)) => data
+                .tlvs
+                .iter()
+                .find_map(|tlv| match tlv {
+                    Tlv::AreaAddresses(areas) => areas.addresses.first().map(|a| a.to_string()),
+                    _ => None,
+                })
+                .unwrap_or_default(),
+            _ => String::new(),
+        };
+
+        template
+            .replace("{router_id}", &router_id)
+            .replace("{prefix}", &prefix)
+            .replace("{hostname}", &hostname)
+            .replace("{area}", &area)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+    use crate::network::{
+        node::Network,
+        router::{Router, RouterId},
+    };
+
+    #[test]
+    fn test_label_template_render() {
+        let template = LabelTemplate {
+            router: "R {hostname} ({router_id})".to_string(),
+            network: "{prefix}".to_string(),
+        };
+
+        let router = Node::new(
+            NodeInfo::Router(Router {
+                id: RouterId::Ipv4(Ipv4Addr::new(10, 0, 0, 1)),
+                interfaces: vec![],
+                protocol_data: None,
+                hostname: None,
+                description: None,
+            }),
+            None,
+        );
+        // No hostname TLV, falls back to the router id
+        assert_eq!(template.label_for(&router), "R 10.0.0.1 (10.0.0.1)");
+
+        let mut named = router.clone();
+        if let NodeInfo::Router(r) = &mut named.info {
+            r.hostname = Some("core-1".to_string());
+        }
+        assert_eq!(template.label_for(&named), "R core-1 (10.0.0.1)");
+
+        let network = Node::new(
+            NodeInfo::Network(Network {
+                ip_address: "172.21.14.0/24".parse().unwrap(),
+                protocol_data: None,
+                attached_routers: vec![],
+                is_synthetic: false,
+            }),
+            None,
+        );
+        assert_eq!(template.label_for(&network), "172.21.14.0/24");
+
+        let overridden = Node::new(network.info.clone(), Some("core LAN".to_string()));
+        assert_eq!(template.label_for(&overridden), "core LAN");
+        assert_eq!(LabelTemplate::default().label_for(&network), "Network");
+    }
+}
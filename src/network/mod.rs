@@ -6,4 +6,6 @@
 pub mod router;
 pub mod node;
 pub mod edge;
-pub mod network_graph;
\ No newline at end of file
+pub mod network_graph;
+pub mod label_template;
+pub mod pseudonyms;
\ No newline at end of file
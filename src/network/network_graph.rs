@@ -1,25 +1,516 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::net::Ipv4Addr;
 
 use eframe::egui::Color32;
-use egui::Pos2;
-use egui_graphs::Graph;
-use petgraph::{Directed, csr::DefaultIx, graph::NodeIndex, prelude::StableGraph, visit::EdgeRef};
+use egui::{Pos2, Rect};
+use egui_graphs::{
+    ForceAlgorithm, FruchtermanReingoldWithCenterGravity, FruchtermanReingoldWithCenterGravityState,
+    Graph,
+};
+use petgraph::{
+    Directed,
+    algo::astar,
+    csr::DefaultIx,
+    graph::NodeIndex,
+    prelude::StableGraph,
+    visit::{EdgeFiltered, EdgeRef},
+};
 use rand::Rng;
+use thiserror::Error;
 use uuid::Uuid;
 
 use crate::{
-    gui::{app, edge_shape::NetworkGraphEdgeShape, node_shape::NetworkGraphNodeShape},
+    gui::{
+        app, area_hulls,
+        edge_shape::{self, NetworkGraphEdgeShape},
+        node_shape::NetworkGraphNodeShape,
+    },
     network::{
-        edge::{Edge, EdgeKind, EdgeMetric, ManualEdgeSpec, UndirectedEdgeKey},
-        node::{IsIsData, Node, NodeInfo, OspfData, OspfPayload, ProtocolData},
-        router::{Router, RouterId},
+        edge::{
+            DEFAULT_UNMETERED_COST, Edge, EdgeKind, EdgeMetric, ManualEdgeSpec, ReverseMetricPolicy,
+            UndirectedEdgeKey,
+        },
+        label_template::LabelTemplate,
+        pseudonyms::Pseudonyms,
+        node::{
+            CollapsedArea, IsIsData, Network, Node, NodeInfo, OspfData, OspfPayload, OspfVirtualLink, ProtocolData,
+        },
+        router::{InterfaceStats, Router, RouterId},
         // removed unused RouterId import
     },
-    parsers::isis_parser::core_lsp::Tlv,
+    logging::debug_log,
+    parsers::isis_parser::{
+        core_lsp::Tlv,
+        protocol::isis_node_areas,
+    },
 };
 
 const IF_SKIP_FUNCTIONALLY_P2P_NETWORKS: bool = false;
 
+/// Node pairs joined by more than one distinct edge, with their `(kind, protocol_tag)` variants.
+pub type ParallelEdgeGroups = HashMap<(Uuid, Uuid), Vec<(EdgeKind, Option<String>)>>;
+
+/// Point-to-point link with a different metric per direction: both ends and the metric out of each.
+type AsymmetricLink = (Uuid, Uuid, u32, u32);
+
+/// Direct IS-IS neighbors of a router (non-pseudonode Extended IS Reachability entries) and their metrics.
+/// These are point-to-point adjacencies that no pseudonode network represents.
+fn isis_p2p_neighbors(router: &Router) -> Vec<(RouterId, u32)> {
+    let Some(ProtocolData::IsIs(isis_data)) = &router.protocol_data else {
+        return Vec::new();
+    };
+    isis_data
+        .tlvs
+        .iter()
+        .filter_map(|tlv| match tlv {
+            Tlv::ExtendedReachability(tlv) => Some(tlv),
+            _ => None,
+        })
+        .flat_map(|tlv| tlv.neighbors.iter())
+        .filter(|n| n.pseudonode_id == 0)
+        .map(|n| (RouterId::IsIs(n.neighbor_id.clone()), n.metric))
+        .collect()
+}
+
+/// Mermaid labels are quoted, so only quotes need replacing.
+fn mermaid_escape(label: &str) -> String {
+    label.replace('"', "#quot;")
+}
+
+/// Escape text for GraphML element content and attribute values.
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Lowercase name of an edge kind, used as an attribute value in exports.
+fn edge_kind_name(kind: EdgeKind) -> &'static str {
+    match kind {
+        EdgeKind::Membership => "membership",
+        EdgeKind::LogicalReachability => "logical_reachability",
+        EdgeKind::External => "external",
+        EdgeKind::VirtualAdjacency => "virtual_adjacency",
+        EdgeKind::PointToPoint => "point_to_point",
+        EdgeKind::VirtualLink => "virtual_link",
+    }
+}
+
+/// Virtual links of an OSPF router that sets the V bit; empty for anything else.
+fn ospf_virtual_links(router: &Router) -> &[OspfVirtualLink] {
+    match &router.protocol_data {
+        Some(ProtocolData::Ospf(OspfData {
+            payload: OspfPayload::Router(payload),
+            ..
+        })) if payload.is_virtual_link_endpoint => &payload.virtual_links,
+        _ => &[],
+    }
+}
+
+/// Cost of `router`'s own OSPF interface into `network`, from the Router-LSA link whose
+/// link data (the interface address) falls inside the network prefix.
+fn ospf_interface_metric(router: &Router, network: &Network) -> Option<u32> {
+    let Some(ProtocolData::Ospf(data)) = &router.protocol_data else {
+        return None;
+    };
+    let OspfPayload::Router(payload) = &data.payload else {
+        return None;
+    };
+    payload
+        .link_metrics
+        .iter()
+        .find(|(ip, _)| network.ip_address.contains(std::net::IpAddr::V4(**ip)))
+        .map(|(_, metric)| *metric as u32)
+}
+
+/// Metric of the network -> router half of a membership edge. Leaving a transit network costs
+/// nothing in OSPF or IS-IS SPF, so it is 0 in the protocol of the router -> network half.
+fn membership_reverse_metric(forward: &EdgeMetric) -> EdgeMetric {
+    match forward {
+        EdgeMetric::Ospf(_) => EdgeMetric::Ospf(0),
+        EdgeMetric::IsIs(_) => EdgeMetric::IsIs(0),
+        _ => EdgeMetric::None,
+    }
+}
+
+/// IS-IS metric of `network`'s prefix in the router's Extended IP Reachability TLVs. A router can
+/// split its prefixes over several TLVs, so all of them are searched.
+fn isis_prefix_metric(isis_data: &IsIsData, network: &Network) -> Option<u32> {
+    isis_data
+        .tlvs
+        .iter()
+        .filter_map(|t| match t {
+            Tlv::ExtendedIpReachability(e) => Some(e),
+            _ => None,
+        })
+        .flat_map(|e| e.neighbors.iter())
+        .find(|n| n.prefix == network.ip_address)
+        .map(|n| n.metric)
+}
+
+/// Metric of the directed half-edge `src -> dst` while building from a `StableGraph`,
+/// taken from `src`'s own advertisements.
+/// Cost `src_node` advertises for its virtual link to `dst_uuid`.
+fn virtual_link_metric(src_node: &Node, dst_uuid: Uuid) -> EdgeMetric {
+    let NodeInfo::Router(router) = &src_node.info else {
+        return EdgeMetric::None;
+    };
+    ospf_virtual_links(router)
+        .iter()
+        .find(|vl| src_node.peer_uuid(vl.peer_router_id.to_uuidv5()) == dst_uuid)
+        .map_or(EdgeMetric::None, |vl| EdgeMetric::Ospf(vl.metric as u32))
+}
+
+fn stable_directed_metric(
+    graph: &StableGraph<Node, Edge, Directed, DefaultIx>,
+    src_idx: NodeIndex,
+    dst_idx: NodeIndex,
+) -> EdgeMetric {
+    let (Some(src_node), Some(dst_node)) = (graph.node_weight(src_idx), graph.node_weight(dst_idx))
+    else {
+        return EdgeMetric::None;
+    };
+    let NodeInfo::Router(router) = &src_node.info else {
+        return EdgeMetric::None;
+    };
+    let dst_uuid = dst_node.id;
+    match &router.protocol_data {
+        Some(ProtocolData::Ospf(ospf_data)) => {
+            if let NodeInfo::Network(network) = &dst_node.info {
+                return ospf_interface_metric(router, network)
+                    .map_or(EdgeMetric::None, EdgeMetric::Ospf);
+            }
+            let OspfPayload::Router(payload) = &ospf_data.payload else {
+                return EdgeMetric::None;
+            };
+            payload
+                .link_metrics
+                .iter()
+                .find(|(ip, _)| src_node.peer_uuid(RouterId::Ipv4(**ip).to_uuidv5()) == dst_uuid)
+                .map_or(EdgeMetric::None, |(_, metric)| EdgeMetric::Ospf(*metric as u32))
+        }
+        Some(ProtocolData::IsIs(isis_data)) => {
+            if let NodeInfo::Network(network) = &dst_node.info {
+                return isis_prefix_metric(isis_data, network)
+                    .map_or(EdgeMetric::None, EdgeMetric::IsIs);
+            }
+            isis_data
+                .tlvs
+                .iter()
+                .find_map(|tlv| match tlv {
+                    Tlv::ExtendedReachability(tlv) => Some(tlv),
+                    _ => None,
+                })
+                .and_then(|tlv| {
+                    tlv.neighbors
+                        .iter()
+                        .find(|n| {
+                            src_node.peer_uuid(RouterId::IsIs(n.neighbor_id.clone()).to_uuidv5())
+                                == dst_uuid
+                        })
+                })
+                .map_or(EdgeMetric::None, |n| EdgeMetric::IsIs(n.metric))
+        }
+        _ => EdgeMetric::None,
+    }
+}
+
+/// Layout anchor per area: the backbone at `center`, every other area evenly spaced on a circle
+/// of `radius` around it, in `areas` order.
+fn area_anchors(areas: &[Ipv4Addr], center: Pos2, radius: f32) -> HashMap<Ipv4Addr, Pos2> {
+    let outer: Vec<Ipv4Addr> = areas.iter().copied().filter(|a| !a.is_unspecified()).collect();
+    let mut anchors: HashMap<Ipv4Addr, Pos2> = outer
+        .iter()
+        .enumerate()
+        .map(|(i, area)| {
+            let angle = std::f32::consts::TAU * i as f32 / outer.len() as f32;
+            (*area, center + radius * egui::vec2(angle.cos(), angle.sin()))
+        })
+        .collect();
+    anchors.insert(Ipv4Addr::UNSPECIFIED, center);
+    anchors
+}
+
+/// An OSPF or IS-IS area, as collapsed into a single node by `NetworkGraph::collapse_areas`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum AreaKey {
+    Ospf(Ipv4Addr),
+    IsIs(String),
+}
+
+impl std::fmt::Display for AreaKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AreaKey::Ospf(area_id) => write!(f, "Area {}", area_id),
+            AreaKey::IsIs(area) => write!(f, "IS-IS area {}", area),
+        }
+    }
+}
+
+impl AreaKey {
+    /// Areas `node` belongs to, sorted so the OSPF backbone comes first. Empty for external
+    /// networks and nodes without area information.
+    pub fn of(node: &Node) -> Vec<AreaKey> {
+        let mut areas: Vec<AreaKey> =
+            area_hulls::node_areas(node).into_iter().map(AreaKey::Ospf).collect();
+        if let Some(isis_areas) = isis_node_areas(node) {
+            areas.extend(isis_areas.iter().cloned().map(AreaKey::IsIs));
+        }
+        areas.sort();
+        areas.dedup();
+        areas
+    }
+
+    /// Id of the node standing in for this area while it is collapsed.
+    fn node_id(&self) -> Uuid {
+        Uuid::new_v5(&Uuid::NAMESPACE_OID, format!("collapsed {}", self).as_bytes())
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ManualEdgeError {
+    #[error("Unknown node {0}")]
+    UnknownNode(Uuid),
+}
+
+/// At-a-glance counts for the currently displayed graph.
+#[derive(Debug, Clone, Default)]
+pub struct GraphStats {
+    pub routers: usize,
+    pub networks: usize,
+    /// Undirected edge count per kind (a derived edge and its reverse count once)
+    pub edges_by_kind: HashMap<EdgeKind, usize>,
+    pub ospf_areas: usize,
+    /// Areas shown as a single node by the collapsed view
+    pub collapsed_areas: usize,
+    pub connected_components: usize,
+    pub metric_histogram: MetricHistogram,
+}
+
+/// Distribution of edge metrics in power-of-two buckets, so a single huge metric shows up as a
+/// lone bar far from the rest instead of squashing every other link into the first bucket.
+#[derive(Debug, Clone, Default)]
+pub struct MetricHistogram {
+    /// Buckets from the smallest to the largest metric seen, including the empty ones in between
+    pub buckets: Vec<MetricBucket>,
+    pub min: Option<u32>,
+    pub max: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MetricBucket {
+    /// Inclusive bounds
+    pub low: u32,
+    pub high: u32,
+    pub count: usize,
+}
+
+impl MetricHistogram {
+    pub fn from_values(values: impl IntoIterator<Item = u32>) -> Self {
+        // Bucket 0 holds metric 0, bucket k holds [2^(k-1), 2^k - 1]
+        let bucket_of = |value: u32| (u32::BITS - value.leading_zeros()) as usize;
+        let bounds = |bucket: usize| match bucket {
+            0 => (0, 0),
+            k => (1u32 << (k - 1), ((1u64 << k) - 1) as u32),
+        };
+
+        let mut counts = [0usize; u32::BITS as usize + 1];
+        let (mut min, mut max): (Option<u32>, Option<u32>) = (None, None);
+        for value in values {
+            counts[bucket_of(value)] += 1;
+            min = Some(min.map_or(value, |m| m.min(value)));
+            max = Some(max.map_or(value, |m| m.max(value)));
+        }
+        let (Some(lo), Some(hi)) = (min, max) else {
+            return Self::default();
+        };
+        let buckets = (bucket_of(lo)..=bucket_of(hi))
+            .map(|bucket| {
+                let (low, high) = bounds(bucket);
+                MetricBucket {
+                    low,
+                    high,
+                    count: counts[bucket],
+                }
+            })
+            .collect();
+        Self { buckets, min, max }
+    }
+
+    pub fn total(&self) -> usize {
+        self.buckets.iter().map(|b| b.count).sum()
+    }
+}
+
+/// Protocol tag stored on edges derived from a node ("OSPF", "ISIS"), if the node carries protocol data.
+fn node_protocol_tag(node: &Node) -> Option<String> {
+    let protocol_data = match &node.info {
+        NodeInfo::Router(router) => router.protocol_data.as_ref(),
+        NodeInfo::Network(network) => network.protocol_data.as_ref(),
+        NodeInfo::Area(_) => None,
+    }?;
+    match protocol_data {
+        ProtocolData::Ospf(_) => Some("OSPF".to_string()),
+        ProtocolData::IsIs(_) => Some("ISIS".to_string()),
+        ProtocolData::Other(_) => None,
+    }
+}
+
+/// One node on a computed path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathHop {
+    pub node_id: Uuid,
+    /// Router id or network prefix
+    pub ident: String,
+    /// Cost of the edge from the previous hop, 0 for the first hop
+    pub cost: u32,
+    /// Running cost up to and including this hop
+    pub total: u32,
+}
+
+/// Where one polled interface of a source router landed among the router's edges.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InterfaceMapping {
+    pub ip_address: std::net::IpAddr,
+    /// Prefix and Uuid of the network the interface's edge leads to, `None` if no edge matched.
+    pub target: Option<(ipnetwork::IpNetwork, Uuid)>,
+}
+
+/// Share of a router's polled traffic carried by each mapped interface, as `(network Uuid, weight)`,
+/// plus the number of interfaces skipped because no edge matched them. An unmatched interface
+/// only drops its own weight; the others are still weighted against the router's total traffic.
+pub fn interface_traffic_weights(
+    interfaces: &[InterfaceStats],
+    mappings: &[InterfaceMapping],
+) -> (Vec<(Uuid, f32)>, usize) {
+    let total: f32 = interfaces.iter().map(|stats| stats.get_weight() as f32).sum();
+    let mut weights = Vec::new();
+    let mut skipped = 0;
+    for mapping in mappings {
+        let Some((_, dst_uuid)) = mapping.target else {
+            skipped += 1;
+            continue;
+        };
+        if total <= 0.0 {
+            continue;
+        }
+        let traffic: u64 = interfaces
+            .iter()
+            .filter(|stats| stats.ip_address == mapping.ip_address)
+            .map(|stats| stats.get_weight())
+            .sum();
+        weights.push((dst_uuid, traffic as f32 / total));
+    }
+    (weights, skipped)
+}
+
+/// Read-only view of a `NetworkGraph` with some nodes and edges treated as failed, for
+/// "what-if" pathfinding. The underlying graph is left untouched.
+pub struct FailureView<'a> {
+    graph: &'a NetworkGraph,
+    failed_nodes: &'a HashSet<Uuid>,
+    failed_edges: &'a HashSet<UndirectedEdgeKey>,
+    unmetered_cost: u32,
+}
+
+impl FailureView<'_> {
+    /// Cost used for edges without a metric (`EdgeMetric::Other`/`None`).
+    pub fn with_unmetered_cost(mut self, cost: u32) -> Self {
+        self.unmetered_cost = cost;
+        self
+    }
+
+    fn is_edge_up(&self, edge: &Edge) -> bool {
+        !self.failed_nodes.contains(&edge.source_id)
+            && !self.failed_nodes.contains(&edge.destination_id)
+            && !self.failed_edges.contains(&UndirectedEdgeKey::new(
+                edge.source_id,
+                edge.destination_id,
+                edge.kind,
+            ))
+    }
+
+    /// Ordered hops of `path` with per-hop costs. Between parallel edges the cheapest live one is used,
+    /// matching what `shortest_path` picked.
+    pub fn hops(&self, path: &[NodeIndex]) -> Vec<PathHop> {
+        let graph = self.graph.graph.g();
+        let mut previous: Option<NodeIndex> = None;
+        let mut total = 0u32;
+        path.iter()
+            .filter_map(|idx| {
+                let node = self.graph.graph.node(*idx)?.payload();
+                let cost = previous
+                    .and_then(|prev| {
+                        graph
+                            .edges_connecting(prev, *idx)
+                            .map(|e| e.weight().payload())
+                            .filter(|e| self.is_edge_up(e))
+                            .map(|e| e.metric.cost(self.unmetered_cost))
+                            .min()
+                    })
+                    .unwrap_or(0);
+                previous = Some(*idx);
+                total = total.saturating_add(cost);
+                let ident = match &node.info {
+                    NodeInfo::Router(r) => r.id.as_string(),
+                    NodeInfo::Network(n) => n.ip_address.to_string(),
+                    NodeInfo::Area(area) => area.name.clone(),
+                };
+                Some(PathHop {
+                    node_id: node.id,
+                    ident,
+                    cost,
+                    total,
+                })
+            })
+            .collect()
+    }
+
+    /// Lowest-metric path from `start` to `end` avoiding failed elements, as (cost, node indices).
+    ///
+    /// Costs are summed as u64 so long paths over wide IS-IS metrics (up to 2^24 - 1 per hop)
+    /// can't overflow; the total saturates at `u32::MAX`.
+    pub fn shortest_path(&self, start: NodeIndex, end: NodeIndex) -> Option<(u32, Vec<NodeIndex>)> {
+        let graph = self.graph.graph.g();
+        let filtered = EdgeFiltered::from_fn(graph, |e| self.is_edge_up(e.weight().payload()));
+        astar(
+            &filtered,
+            start,
+            |idx| idx == end,
+            |e| u64::from(e.weight().payload().metric.cost(self.unmetered_cost)),
+            |_| 0,
+        )
+        .map(|(cost, path)| (u32::try_from(cost).unwrap_or(u32::MAX), path))
+    }
+
+    /// Lowest-metric path visiting `stops` in order: the shortest paths between consecutive stops,
+    /// stitched together. `None` if fewer than two stops are given or any leg has no path.
+    pub fn shortest_path_via(&self, stops: &[NodeIndex]) -> Option<(u32, Vec<NodeIndex>)> {
+        let (first, _) = stops.split_first()?;
+        if stops.len() < 2 {
+            return None;
+        }
+        let mut cost = 0u32;
+        let mut path = vec![*first];
+        for leg in stops.windows(2) {
+            let (leg_cost, leg_path) = self.shortest_path(leg[0], leg[1])?;
+            cost = cost.saturating_add(leg_cost);
+            // Each leg starts where the previous one ended
+            path.extend(leg_path.into_iter().skip(1));
+        }
+        Some((cost, path))
+    }
+}
+
 /// A protocol-agnostic graph wrapper used by the GUI.
 ///
 /// Builds a graph from `Node`s and wires edges based on attached_routers.
@@ -38,6 +529,18 @@ pub struct NetworkGraph {
     pub node_id_to_index_map: HashMap<Uuid, NodeIndex>,
     manual_edges: HashMap<UndirectedEdgeKey, ManualEdgeSpec>,
     manual_removed_edges: HashSet<UndirectedEdgeKey>,
+    /// Nodes held in place while the layout keeps running, keyed by Uuid so pins survive reconcile
+    pinned_positions: HashMap<Uuid, Pos2>,
+    /// Positions from a restored layout, used instead of random seeding when these nodes appear
+    saved_positions: HashMap<Uuid, Pos2>,
+    /// Default labels for nodes without an explicit label
+    label_template: LabelTemplate,
+    /// Show pseudonyms instead of the real labels, see `set_anonymize()`
+    anonymize: bool,
+    pseudonyms: Pseudonyms,
+    reverse_metric_policy: ReverseMetricPolicy,
+    /// Nodes standing in for a collapsed area, see `collapse_areas()`
+    collapsed_areas: HashMap<Uuid, AreaKey>,
 }
 
 impl Default for NetworkGraph {
@@ -47,14 +550,29 @@ impl Default for NetworkGraph {
             node_id_to_index_map: HashMap::new(),
             manual_edges: HashMap::new(),
             manual_removed_edges: HashSet::new(),
+            pinned_positions: HashMap::new(),
+            saved_positions: HashMap::new(),
+            label_template: LabelTemplate::default(),
+            anonymize: false,
+            pseudonyms: Pseudonyms::default(),
+            reverse_metric_policy: ReverseMetricPolicy::default(),
+            collapsed_areas: HashMap::new(),
         }
     }
 }
 
+/// Label shown for `node`: its pseudonym while anonymizing, otherwise the template label.
+fn display_label(template: &LabelTemplate, pseudonyms: Option<&mut Pseudonyms>, node: &Node) -> String {
+    match pseudonyms {
+        Some(pseudonyms) => pseudonyms.label_for(node),
+        None => template.label_for(node),
+    }
+}
+
 impl NetworkGraph {
     /// Build a new NetworkGraph from a list of protocol-agnostic nodes.
     /// This method avoids panics by validating lookups and ignores incomplete references.
-    pub fn build_new(nodes: Vec<Node>) -> Self {
+    pub fn build_new(nodes: Vec<Node>, reverse_metric_policy: ReverseMetricPolicy) -> Self {
         let (mut graph, node_id_to_index_map) = {
             let mut graph = StableGraph::new();
             let mut node_id_to_index_map = HashMap::new();
@@ -76,87 +594,46 @@ impl NetworkGraph {
 
         // Materialize edges
         for (src_idx, src_uuid, dst_uuid, kind) in edge_specs {
-            let metric = {
-                if let Some(src_node) = graph.node_weight(src_idx) {
-                    if let NodeInfo::Router(router) = &src_node.info {
-                        match &router.protocol_data {
-                            Some(ProtocolData::Ospf(ospf_data)) => {
-                                if let OspfPayload::Router(payload) = &ospf_data.payload {
-                                    let metric_by_uuid: HashMap<Uuid, u16> = payload
-                                        .link_metrics
-                                        .iter()
-                                        .map(|(ip, metric)| {
-                                            (RouterId::Ipv4(*ip).to_uuidv5(), *metric)
-                                        })
-                                        .collect();
-                                    if let Some(metric) = metric_by_uuid.get(&dst_uuid) {
-                                        EdgeMetric::Ospf(*metric as u32)
-                                    } else {
-                                        EdgeMetric::None
-                                    }
-                                } else {
-                                    EdgeMetric::None
-                                }
-                            }
-                            Some(ProtocolData::IsIs(isis_data)) => {
-                                if let Some(Tlv::ExtendedReachability(tlv)) = isis_data
-                                    .tlvs
-                                    .iter()
-                                    .find(|tlv| matches!(tlv, Tlv::ExtendedReachability(_)))
-                                {
-                                    let metrics_by_uuid: HashMap<Uuid, u32> = tlv
-                                        .neighbors
-                                        .iter()
-                                        .map(|n| {
-                                            (
-                                                RouterId::IsIs(n.neighbor_id.clone()).to_uuidv5(),
-                                                n.metric,
-                                            )
-                                        })
-                                        .collect();
-                                    if let Some(metric) = metrics_by_uuid.get(&dst_uuid) {
-                                        EdgeMetric::IsIs(*metric as u32)
-                                    } else {
-                                        EdgeMetric::None
-                                    }
-                                } else {
-                                    EdgeMetric::None
-                                }
-                            }
-                            None => EdgeMetric::None,
-                            _ => panic!("Unexpected protocol data"),
-                        }
-                    } else {
-                        EdgeMetric::None
-                    }
-                } else {
-                    EdgeMetric::None
-                }
-            };
-
-            if let EdgeMetric::None = metric {
-                println!("Metric is None");
-            }
             if let Some(&dst_idx) = node_id_to_index_map.get(&dst_uuid) {
+                let metric = match kind {
+                    EdgeKind::VirtualLink => virtual_link_metric(&graph[src_idx], dst_uuid),
+                    _ => stable_directed_metric(&graph, src_idx, dst_idx),
+                };
+                if let EdgeMetric::None = metric {
+                    debug_log!("Metric is None");
+                }
+                let reverse_metric = match kind {
+                    EdgeKind::Membership => membership_reverse_metric(&metric),
+                    EdgeKind::VirtualLink => virtual_link_metric(&graph[dst_idx], src_uuid),
+                    _ => stable_directed_metric(&graph, dst_idx, src_idx),
+                };
+                let (metric, reverse_metric) = (
+                    reverse_metric_policy.apply(&reverse_metric, metric.clone()),
+                    reverse_metric_policy.apply(&metric, reverse_metric),
+                );
+                let protocol_tag = [src_idx, dst_idx]
+                    .into_iter()
+                    .filter_map(|idx| graph.node_weight(idx))
+                    .find_map(node_protocol_tag);
                 let edge_src_to_dst = Edge {
                     source_id: src_uuid,
                     destination_id: dst_uuid,
                     kind: kind.clone(),
                     metric: metric,
-                    protocol_tag: Some("OSPF".to_string()),
+                    protocol_tag: protocol_tag.clone(),
                 };
                 graph.add_edge(src_idx, dst_idx, edge_src_to_dst);
                 let edge_dst_to_src = Edge {
                     source_id: dst_uuid,
                     destination_id: src_uuid,
                     kind,
-                    metric: EdgeMetric::None,
-                    protocol_tag: Some("OSPF".to_string()),
+                    metric: reverse_metric,
+                    protocol_tag,
                 };
                 graph.add_edge(dst_idx, src_idx, edge_dst_to_src);
             }
         }
-        eprintln!(
+        debug_log!(
             "[network_graph::build_new] materialized {} edges ({} nodes)",
             graph.edge_count(),
             graph.node_count()
@@ -191,15 +668,7 @@ impl NetworkGraph {
             let position = Pos2::new(rng.random_range(0.0..40.0), rng.random_range(0.0..40.0));
             node.set_location(position);
             let payload = node.payload();
-            let label = if let Some(label) = &payload.label {
-                label.clone()
-            } else {
-                // Default label - network IP for Network and Router ID for router
-                match &payload.info {
-                    NodeInfo::Network(_) => "Network".to_string(),
-                    NodeInfo::Router(_) => "Router".to_string(),
-                }
-            };
+            let label = LabelTemplate::default().label_for(payload);
             let theme = app::get_theme();
 
             // Set label; color will be derived by NetworkGraphNodeShape via theme visuals
@@ -209,13 +678,14 @@ impl NetworkGraph {
         Self {
             graph,
             node_id_to_index_map,
+            reverse_metric_policy,
             ..Default::default()
         }
     }
 
     /// Reconcile the existing graph in place to match the provided nodes (by UUID).
     /// - Updates/keeps positions for existing nodes
-    /// - Adds new nodes with a seeded position
+    /// - Adds new nodes at their saved layout position, or a random seeded one
     /// - Removes vanished nodes
     /// - Rebuilds edges from current nodes (router -> network)
     pub fn reconcile(&mut self, desired_nodes: Vec<Node>) {
@@ -237,12 +707,24 @@ impl NetworkGraph {
         }
         for id in to_remove {
             if let Some(idx) = self.node_id_to_index_map.remove(&id) {
+                // Collapsed areas are rebuilt after every reconcile, keep them where they were
+                if self.collapsed_areas.contains_key(&id)
+                    && let Some(node) = self.graph.node(idx)
+                {
+                    self.saved_positions.insert(id, node.location());
+                }
                 // Removing a node should drop its incident edges automatically.
                 // Adjust this if your egui_graphs version uses a different removal API.
                 let _ = self.graph.remove_node(idx);
             }
         }
 
+        self.collapsed_areas.clear();
+
+        if self.anonymize {
+            self.pseudonyms.register_all(desired_map.values());
+        }
+
         // 3) Add or update remaining nodes
         for (id, desired) in desired_map.iter() {
             if let Some(&idx) = self.node_id_to_index_map.get(id) {
@@ -254,13 +736,11 @@ impl NetworkGraph {
                     *node.payload_mut() = desired.clone(); // requires a payload_mut() API; if not available, re-add node.
 
                     // Reapply label/color logic based on the new payload.
-                    let label = desired
-                        .label
-                        .clone()
-                        .unwrap_or_else(|| match &desired.info {
-                            NodeInfo::Network(_) => "Network".to_string(),
-                            NodeInfo::Router(_) => "Router".to_string(),
-                        });
+                    let label = display_label(
+                        &self.label_template,
+                        self.anonymize.then_some(&mut self.pseudonyms),
+                        desired,
+                    );
                     let router_color = Color32::BLUE;
                     let network_color = Color32::GREEN;
                     let inter_area_color = Color32::LIGHT_GREEN;
@@ -268,7 +748,7 @@ impl NetworkGraph {
                         inter_area_color
                     } else {
                         match &desired.info {
-                            NodeInfo::Network(_) => network_color,
+                            NodeInfo::Network(_) | NodeInfo::Area(_) => network_color,
                             NodeInfo::Router(_) => router_color,
                         }
                     };
@@ -279,9 +759,11 @@ impl NetworkGraph {
                 // New node: add to graph and id map
                 let idx = self.graph.add_node(desired.clone());
 
-                // Seed a position near origin or random small radius.
+                // Seed at the saved layout position, else near origin in a random small radius.
                 // You could improve this by seeding near attached routers/networks when available.
-                let pos = Pos2::new(rng.random_range(0.0..40.0), rng.random_range(0.0..40.0));
+                let pos = self.saved_positions.get(id).copied().unwrap_or_else(|| {
+                    Pos2::new(rng.random_range(0.0..40.0), rng.random_range(0.0..40.0))
+                });
                 if let Some(n) = self.graph.node_mut(idx) {
                     n.set_location(pos);
                     
@@ -294,17 +776,15 @@ impl NetworkGraph {
                         inter_area_color
                     } else {
                         match &payload.info {
-                            NodeInfo::Network(_) => network_color,
+                            NodeInfo::Network(_) | NodeInfo::Area(_) => network_color,
                             NodeInfo::Router(_) => router_color,
                         }
                     };
-                    let label = desired
-                        .label
-                        .clone()
-                        .unwrap_or_else(|| match &desired.info {
-                            NodeInfo::Network(_) => "Network".to_string(),
-                            NodeInfo::Router(_) => "Router".to_string(),
-                        });
+                    let label = display_label(
+                        &self.label_template,
+                        self.anonymize.then_some(&mut self.pseudonyms),
+                        desired,
+                    );
                     n.set_label(label);
                 }
 
@@ -316,6 +796,8 @@ impl NetworkGraph {
         self.clear_all_edges();
         let edge_specs = self.collect_edge_specs_live();
         self.materialize_edges(edge_specs, "[network_graph::reconcile]");
+        // Overlay entries with an endpoint outside `desired` are kept: view filters hide nodes
+        // without removing them, and the edges come back with their endpoints
         self.apply_overlay_after_reconcile();
     }
 
@@ -349,7 +831,7 @@ impl NetworkGraph {
 
                 // Membership
                 for rid in &network.attached_routers {
-                    let r_uuid = rid.to_uuidv5();
+                    let r_uuid = graph[net_index].peer_uuid(rid.to_uuidv5());
                     if let Some(&r_idx) = id_map.get(&r_uuid) {
                         let kind = EdgeKind::Membership;
                         if seen.insert((r_uuid, net_uuid, kind.clone())) {
@@ -370,7 +852,7 @@ impl NetworkGraph {
                             {
                                 continue;
                             }
-                            let abr_uuid = s.origin_abr.to_uuidv5();
+                            let abr_uuid = graph[net_index].peer_uuid(s.origin_abr.to_uuidv5());
                             if let Some(&abr_idx) = id_map.get(&abr_uuid) {
                                 let kind = EdgeKind::LogicalReachability;
                                 if seen.insert((abr_uuid, net_uuid, kind.clone())) {
@@ -381,6 +863,45 @@ impl NetworkGraph {
                     }
                 }
             }
+
+            // OSPF virtual links, between endpoints that both set the V bit
+            if let NodeInfo::Router(router) = &graph[net_index].info {
+                let r_uuid = graph[net_index].id;
+                for vl in ospf_virtual_links(router) {
+                    let peer_uuid = graph[net_index].peer_uuid(vl.peer_router_id.to_uuidv5());
+                    let peer_is_endpoint = id_map.get(&peer_uuid).is_some_and(|idx| {
+                        match &graph[*idx].info {
+                            NodeInfo::Router(peer) => !ospf_virtual_links(peer).is_empty(),
+                            _ => false,
+                        }
+                    });
+                    if peer_uuid == r_uuid || !peer_is_endpoint {
+                        continue;
+                    }
+                    let kind = EdgeKind::VirtualLink;
+                    let key = UndirectedEdgeKey::new(r_uuid, peer_uuid, kind);
+                    if seen.insert((key.a, key.b, kind)) {
+                        specs.push((net_index, r_uuid, peer_uuid, kind));
+                    }
+                }
+            }
+
+            // IS-IS point-to-point adjacencies (no pseudonode)
+            if let NodeInfo::Router(router) = &graph[net_index].info {
+                let r_uuid = graph[net_index].id;
+                for (neighbor, _) in isis_p2p_neighbors(router) {
+                    let n_uuid = graph[net_index].peer_uuid(neighbor.to_uuidv5());
+                    if n_uuid == r_uuid || !id_map.contains_key(&n_uuid) {
+                        continue;
+                    }
+                    // Both routers report the adjacency; keep one spec per pair
+                    let kind = EdgeKind::PointToPoint;
+                    let key = UndirectedEdgeKey::new(r_uuid, n_uuid, kind);
+                    if seen.insert((key.a, key.b, kind)) {
+                        specs.push((net_index, r_uuid, n_uuid, kind));
+                    }
+                }
+            }
         }
 
         (specs, node_indices_to_remove)
@@ -402,7 +923,7 @@ impl NetworkGraph {
 
                     // Membership
                     for rid in &network.attached_routers {
-                        let r_uuid = rid.to_uuidv5();
+                        let r_uuid = payload.peer_uuid(rid.to_uuidv5());
                         if let Some(&r_idx) = self.node_id_to_index_map.get(&r_uuid) {
                             let kind = EdgeKind::Membership;
                             if seen.insert((r_uuid, *net_uuid, kind.clone())) {
@@ -421,7 +942,7 @@ impl NetworkGraph {
                                 {
                                     continue;
                                 }
-                                let abr_uuid = s.origin_abr.to_uuidv5();
+                                let abr_uuid = payload.peer_uuid(s.origin_abr.to_uuidv5());
                                 if let Some(&abr_idx) = self.node_id_to_index_map.get(&abr_uuid) {
                                     let kind = EdgeKind::LogicalReachability;
                                     if seen.insert((abr_uuid, *net_uuid, kind.clone())) {
@@ -432,6 +953,44 @@ impl NetworkGraph {
                         }
                     }
                 }
+
+                // OSPF virtual links, between endpoints that both set the V bit
+                if let NodeInfo::Router(router) = &payload.info {
+                    for vl in ospf_virtual_links(router) {
+                        let peer_uuid = payload.peer_uuid(vl.peer_router_id.to_uuidv5());
+                        let peer_is_endpoint = self
+                            .node_id_to_index_map
+                            .get(&peer_uuid)
+                            .and_then(|idx| self.graph.node(*idx))
+                            .is_some_and(|n| match &n.payload().info {
+                                NodeInfo::Router(peer) => !ospf_virtual_links(peer).is_empty(),
+                                _ => false,
+                            });
+                        if peer_uuid == *net_uuid || !peer_is_endpoint {
+                            continue;
+                        }
+                        let kind = EdgeKind::VirtualLink;
+                        let key = UndirectedEdgeKey::new(*net_uuid, peer_uuid, kind);
+                        if seen.insert((key.a, key.b, kind)) {
+                            specs.push((net_idx, *net_uuid, peer_uuid, kind));
+                        }
+                    }
+                }
+
+                // IS-IS point-to-point adjacencies (no pseudonode)
+                if let NodeInfo::Router(router) = &payload.info {
+                    for (neighbor, _) in isis_p2p_neighbors(router) {
+                        let n_uuid = payload.peer_uuid(neighbor.to_uuidv5());
+                        if n_uuid == *net_uuid || !self.node_id_to_index_map.contains_key(&n_uuid) {
+                            continue;
+                        }
+                        let kind = EdgeKind::PointToPoint;
+                        let key = UndirectedEdgeKey::new(*net_uuid, n_uuid, kind);
+                        if seen.insert((key.a, key.b, kind)) {
+                            specs.push((net_idx, *net_uuid, n_uuid, kind));
+                        }
+                    }
+                }
             }
         }
 
@@ -441,7 +1000,11 @@ impl NetworkGraph {
     /// Helper: materialize edge specs into the live graph
     fn materialize_edges(&mut self, specs: Vec<(NodeIndex, Uuid, Uuid, EdgeKind)>, log_tag: &str) {
         let mut added = 0usize;
+        let show_logical_reachability = edge_shape::logical_reachability_enabled();
         for (src_idx, src_uuid, dst_uuid, kind) in specs {
+            if kind == EdgeKind::LogicalReachability && !show_logical_reachability {
+                continue;
+            }
             let metric = match kind {
                 // Membership edges don't carry a metric
                 EdgeKind::Membership => self.membership_metric(src_idx, src_uuid, dst_uuid),
@@ -449,30 +1012,50 @@ impl NetworkGraph {
                 EdgeKind::LogicalReachability => {
                     self.logical_reachability_metric(src_idx, src_uuid, dst_uuid)
                 }
+                EdgeKind::PointToPoint => self.point_to_point_metric(src_idx, dst_uuid),
+                EdgeKind::VirtualLink => self.virtual_link_metric(src_idx, dst_uuid),
                 // Default: no metric
                 _ => EdgeMetric::None,
             };
             if let Some(&dst_idx) = self.node_id_to_index_map.get(&dst_uuid) {
+                // Each direction carries the cost advertised by its own source
+                let reverse_metric = match kind {
+                    EdgeKind::Membership => membership_reverse_metric(&metric),
+                    EdgeKind::PointToPoint => self.point_to_point_metric(dst_idx, src_uuid),
+                    EdgeKind::VirtualLink => self.virtual_link_metric(dst_idx, src_uuid),
+                    _ => EdgeMetric::None,
+                };
+                let policy = self.reverse_metric_policy;
+                let (metric, reverse_metric) = (
+                    policy.apply(&reverse_metric, metric.clone()),
+                    policy.apply(&metric, reverse_metric),
+                );
+                // Tag by the protocol that produced the endpoints; synthetic stubs carry no
+                // protocol data, so fall back to the other endpoint.
+                let protocol_tag = [src_idx, dst_idx]
+                    .into_iter()
+                    .filter_map(|idx| self.graph.node(idx))
+                    .find_map(|n| node_protocol_tag(n.payload()));
                 let edge_src_to_dst = Edge {
                     source_id: src_uuid,
                     destination_id: dst_uuid,
                     kind: kind.clone(),
                     metric: metric,
-                    protocol_tag: Some("OSPF".to_string()),
+                    protocol_tag: protocol_tag.clone(),
                 };
                 self.graph.add_edge(src_idx, dst_idx, edge_src_to_dst);
                 let edge_dst_to_src = Edge {
                     source_id: dst_uuid,
                     destination_id: src_uuid,
                     kind,
-                    metric: EdgeMetric::None,
-                    protocol_tag: Some("OSPF".to_string()),
+                    metric: reverse_metric,
+                    protocol_tag,
                 };
                 self.graph.add_edge(dst_idx, src_idx, edge_dst_to_src);
                 added += 2;
             }
         }
-        eprintln!("{log_tag} materialized {added} edges");
+        debug_log!("{log_tag} materialized {added} edges");
     }
 
     fn membership_metric(&self, src_idx: NodeIndex, src_uuid: Uuid, dst_uuid: Uuid) -> EdgeMetric {
@@ -488,10 +1071,42 @@ impl NetworkGraph {
 
         match &router.protocol_data {
             Some(ProtocolData::IsIs(isis_data)) => self.isis_membership_metric(dst_uuid, isis_data),
+            Some(ProtocolData::Ospf(_)) => {
+                let network = self
+                    .node_id_to_index_map
+                    .get(&dst_uuid)
+                    .and_then(|idx| self.graph.node(*idx))
+                    .and_then(|n| match &n.payload().info {
+                        NodeInfo::Network(network) => Some(network),
+                        _ => None,
+                    });
+                network
+                    .and_then(|network| ospf_interface_metric(router, network))
+                    .map_or(EdgeMetric::None, EdgeMetric::Ospf)
+            }
             _ => EdgeMetric::None,
         }
     }
 
+    fn virtual_link_metric(&self, src_idx: NodeIndex, dst_uuid: Uuid) -> EdgeMetric {
+        self.graph
+            .node(src_idx)
+            .map_or(EdgeMetric::None, |n| virtual_link_metric(n.payload(), dst_uuid))
+    }
+
+    fn point_to_point_metric(&self, src_idx: NodeIndex, dst_uuid: Uuid) -> EdgeMetric {
+        let Some(src_node) = self.graph.node(src_idx).map(|n| n.payload()) else {
+            return EdgeMetric::None;
+        };
+        let NodeInfo::Router(router) = &src_node.info else {
+            return EdgeMetric::None;
+        };
+        isis_p2p_neighbors(router)
+            .into_iter()
+            .find(|(neighbor, _)| src_node.peer_uuid(neighbor.to_uuidv5()) == dst_uuid)
+            .map_or(EdgeMetric::None, |(_, metric)| EdgeMetric::IsIs(metric))
+    }
+
     fn isis_membership_metric(&self, dst_uuid: Uuid, isis_data: &IsIsData) -> EdgeMetric {
         let dst_idx = match self.node_id_to_index_map.get(&dst_uuid) {
             Some(i) => i,
@@ -508,25 +1123,7 @@ impl NetworkGraph {
             _ => return EdgeMetric::None,
         };
 
-        let ext_ip_reach = match isis_data.tlvs.iter().find_map(|t| {
-            if let Tlv::ExtendedIpReachability(e) = t {
-                Some(e)
-            } else {
-                None
-            }
-        }) {
-            Some(e) => e,
-            None => return EdgeMetric::None,
-        };
-
-        match ext_ip_reach
-            .neighbors
-            .iter()
-            .find(|n| n.prefix == network.ip_address)
-        {
-            Some(nbr) => EdgeMetric::IsIs(nbr.metric),
-            None => EdgeMetric::None,
-        }
+        isis_prefix_metric(isis_data, network).map_or(EdgeMetric::None, EdgeMetric::IsIs)
     }
 
     fn logical_reachability_metric(
@@ -601,8 +1198,27 @@ impl NetworkGraph {
         }
     }
 
-    pub fn add_manual_edge(&mut self, a: Uuid, b: Uuid, kind: EdgeKind, metric: u32) {
-        let key = UndirectedEdgeKey::new(a, b, kind.clone());
+    /// Add a manual edge between two nodes of the live graph. Nothing is stored if either
+    /// endpoint is unknown.
+    pub fn add_manual_edge(
+        &mut self,
+        a: Uuid,
+        b: Uuid,
+        kind: EdgeKind,
+        metric: u32,
+    ) -> Result<(), ManualEdgeError> {
+        for id in [a, b] {
+            if !self.node_id_to_index_map.contains_key(&id) {
+                return Err(ManualEdgeError::UnknownNode(id));
+            }
+        }
+        self.restore_manual_edge(UndirectedEdgeKey::new(a, b, kind), metric);
+        Ok(())
+    }
+
+    /// Add a manual edge without checking its endpoints against the live graph, for saved
+    /// overlays the caller checked against the whole topology. It shows once both endpoints do.
+    pub fn restore_manual_edge(&mut self, key: UndirectedEdgeKey, metric: u32) {
         let spec = ManualEdgeSpec::new(key, metric);
 
         self.manual_edges.insert(key, spec);
@@ -618,7 +1234,7 @@ impl NetworkGraph {
             spec.set_metric(metric);
             self.apply_manual_edge_live(key);
         } else {
-            println!("Edge not found")
+            debug_log!("[network_graph] update_manual_edge: no manual edge {:?}", key);
         }
     }
 
@@ -638,6 +1254,16 @@ impl NetworkGraph {
         self.remove_edge_pair_live(a, b, kind);
     }
 
+    /// Manually added edges, for saving the overlay.
+    pub fn manual_edge_specs(&self) -> impl Iterator<Item = &ManualEdgeSpec> {
+        self.manual_edges.values()
+    }
+
+    /// Base edges hidden with `supress_base_edge`, for saving the overlay.
+    pub fn suppressed_edges(&self) -> impl Iterator<Item = &UndirectedEdgeKey> {
+        self.manual_removed_edges.iter()
+    }
+
     pub fn any_manual_changes(&self) -> bool {
         !self.manual_edges.is_empty() || !self.manual_removed_edges.is_empty()
     }
@@ -699,83 +1325,1028 @@ impl NetworkGraph {
         }
     }
 
-    pub fn is_manual_edge(&self, a: Uuid, b: Uuid, kind: EdgeKind) -> bool {
-        self.manual_edges
-            .contains_key(&UndirectedEdgeKey::new(a, b, kind))
+    /// Metrics of both directed halves of a link: `(a -> b, b -> a)`. `None` if that half is missing.
+    pub fn edge_pair_metrics(
+        &self,
+        a: Uuid,
+        b: Uuid,
+        kind: EdgeKind,
+    ) -> (Option<EdgeMetric>, Option<EdgeMetric>) {
+        let mut forward = None;
+        let mut reverse = None;
+        for (_, e) in self.graph.edges_iter() {
+            let edge = e.payload();
+            if edge.kind != kind {
+                continue;
+            }
+            if edge.source_id == a && edge.destination_id == b {
+                forward = Some(edge.metric.clone());
+            } else if edge.source_id == b && edge.destination_id == a {
+                reverse = Some(edge.metric.clone());
+            }
+        }
+        (forward, reverse)
     }
 
-    fn remove_edge_pair_live(&mut self, a: Uuid, b: Uuid, kind: EdgeKind) {
-        // Find and remove both directions matching (a->b) and (b->a) with given kind
-        let mut to_remove = Vec::new();
-        for (ei, e) in self.graph.edges_iter() {
-            if e.payload().source_id == a
-                && e.payload().destination_id == b
-                && e.payload().kind == kind
-            {
-                to_remove.push(ei);
-            } else if e.payload().source_id == b
-                && e.payload().destination_id == a
-                && e.payload().kind == kind
+    /// Router pairs whose link cost differs by direction, as `(a, b, a -> b, b -> a)`.
+    /// Covers point-to-point adjacencies and transit networks with exactly two attached routers
+    /// (where each router's cost into the network is its cost towards the other). Links with an
+    /// unknown metric in either direction are skipped.
+    pub fn asymmetric_links(&self) -> Vec<AsymmetricLink> {
+        self.find_asymmetric_links()
+            .into_iter()
+            .map(|(link, _)| link)
+            .collect()
+    }
+
+    /// Edges making up the links reported by `asymmetric_links`, for highlighting.
+    pub fn asymmetric_edge_keys(&self) -> HashSet<UndirectedEdgeKey> {
+        self.find_asymmetric_links()
+            .into_iter()
+            .flat_map(|(_, keys)| keys)
+            .collect()
+    }
+
+    /// Membership edges between OSPF transit networks and their designated router, for highlighting.
+    pub fn designated_router_edge_keys(&self) -> HashSet<UndirectedEdgeKey> {
+        self.graph
+            .nodes_iter()
+            .filter_map(|(_, node)| {
+                let node = node.payload();
+                let NodeInfo::Network(net) = &node.info else {
+                    return None;
+                };
+                let (dr, _) = net.ospf_designated_router()?;
+                let dr_uuid = node.peer_uuid(dr.to_uuidv5());
+                self.node_id_to_index_map
+                    .contains_key(&dr_uuid)
+                    .then(|| UndirectedEdgeKey::new(dr_uuid, node.id, EdgeKind::Membership))
+            })
+            .collect()
+    }
+
+    fn find_asymmetric_links(&self) -> Vec<(AsymmetricLink, Vec<UndirectedEdgeKey>)> {
+        let mut p2p: HashMap<(Uuid, Uuid), u32> = HashMap::new();
+        let mut transit: HashMap<Uuid, Vec<(Uuid, u32)>> = HashMap::new();
+        for (_, e) in self.graph.edges_iter() {
+            let edge = e.payload();
+            let Some(metric) = edge.metric.value() else {
+                continue;
+            };
+            match edge.kind {
+                EdgeKind::PointToPoint => {
+                    p2p.insert((edge.source_id, edge.destination_id), metric);
+                }
+                EdgeKind::Membership => {
+                    let from_router = self
+                        .node_id_to_index_map
+                        .get(&edge.source_id)
+                        .and_then(|idx| self.graph.node(*idx))
+                        .is_some_and(|n| matches!(n.payload().info, NodeInfo::Router(_)));
+                    if from_router {
+                        transit
+                            .entry(edge.destination_id)
+                            .or_default()
+                            .push((edge.source_id, metric));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut links = Vec::new();
+        for (&(a, b), &forward) in &p2p {
+            if a < b
+                && let Some(&reverse) = p2p.get(&(b, a))
+                && forward != reverse
             {
-                to_remove.push(ei);
+                links.push((
+                    (a, b, forward, reverse),
+                    vec![UndirectedEdgeKey::new(a, b, EdgeKind::PointToPoint)],
+                ));
             }
         }
-        for ei in to_remove {
-            let _ = self.graph.remove_edge(ei);
+        for (net, routers) in transit {
+            let [(a, forward), (b, reverse)] = routers[..] else {
+                continue;
+            };
+            if a == b || forward == reverse {
+                continue;
+            }
+            let (a, b, forward, reverse) = if a < b {
+                (a, b, forward, reverse)
+            } else {
+                (b, a, reverse, forward)
+            };
+            links.push((
+                (a, b, forward, reverse),
+                vec![
+                    UndirectedEdgeKey::new(a, net, EdgeKind::Membership),
+                    UndirectedEdgeKey::new(b, net, EdgeKind::Membership),
+                ],
+            ));
         }
+        links.sort_by_key(|(link, _)| *link);
+        links
     }
 
-    pub fn apply_overlay_after_reconcile(&mut self) {
-        // 1) Remove overridden base edges
-        for key in self.manual_removed_edges.clone() {
-            let (a, b) = key.endpoints();
-            self.remove_edge_pair_live(a, b, key.kind.clone());
+    /// Node pairs joined by more than one distinct edge, keyed by the ordered pair, with their
+    /// `(kind, protocol_tag)` variants in a stable order. Both directed halves count once.
+    pub fn parallel_edge_groups(&self) -> ParallelEdgeGroups {
+        let mut groups: ParallelEdgeGroups = HashMap::new();
+        for (_, e) in self.graph.edges_iter() {
+            let edge = e.payload();
+            let (a, b) = UndirectedEdgeKey::new(edge.source_id, edge.destination_id, edge.kind)
+                .endpoints();
+            let variant = (edge.kind, edge.protocol_tag.clone());
+            let group = groups.entry((a, b)).or_default();
+            if !group.contains(&variant) {
+                group.push(variant);
+            }
         }
-        // 2) Add manual edges with stored metrics
-        for key in self.manual_edges.keys().cloned().collect::<Vec<_>>() {
-            self.apply_manual_edge_live(key);
+        groups.retain(|_, group| group.len() > 1);
+        for group in groups.values_mut() {
+            group.sort_by(|(ka, ta), (kb, tb)| (*ka as u8, ta).cmp(&(*kb as u8, tb)));
         }
+        groups
     }
-}
 
-impl ToString for NetworkGraph {
-    fn to_string(&self) -> String {
-        use petgraph::Direction;
-        let mut output = String::from("Network Graph {\n");
+    /// Mermaid `graph LR` source for the displayed graph. Routers are boxes, networks are rounded;
+    /// both directed halves of a link become one line labelled with their metrics, dashed for
+    /// anything other than membership and point-to-point links.
+    pub fn to_mermaid(&self) -> String {
+        let order = self.ordered_node_indices();
+        let ids: HashMap<Uuid, usize> = order
+            .iter()
+            .enumerate()
+            .filter_map(|(i, idx)| self.graph.node(*idx).map(|n| (n.payload().id, i)))
+            .collect();
 
-        // Collect nodes with indices for deterministic ordering (Routers first, then Networks, then by identifier)
-        let mut nodes: Vec<(
-            NodeIndex,
-            &egui_graphs::Node<Node, Edge, Directed, DefaultIx, NetworkGraphNodeShape>,
-        )> = self.graph.nodes_iter().collect();
-        nodes.sort_by(|(_, a), (_, b)| {
-            let ta = match &a.payload().info {
-                NodeInfo::Router(_) => 0,
-                NodeInfo::Network(_) => 1,
-            };
-            let tb = match &b.payload().info {
-                NodeInfo::Router(_) => 0,
-                NodeInfo::Network(_) => 1,
+        let mut output = String::from("graph LR\n");
+        for (i, idx) in order.iter().enumerate() {
+            let Some(node) = self.graph.node(*idx) else {
+                continue;
             };
-            if ta != tb {
-                return ta.cmp(&tb);
+            let label = mermaid_escape(&node.label());
+            match node.payload().info {
+                NodeInfo::Router(_) => output += &format!("    n{}[\"{}\"]\n", i, label),
+                NodeInfo::Network(_) => output += &format!("    n{}(\"{}\")\n", i, label),
+                NodeInfo::Area(_) => output += &format!("    n{}[[\"{}\"]]\n", i, label),
+            }
+        }
+
+        // Collapse both directed halves into one link, keeping each direction's metric
+        let mut links: HashMap<UndirectedEdgeKey, (Option<u32>, Option<u32>)> = HashMap::new();
+        for (_, e) in self.graph.edges_iter() {
+            let edge = e.payload();
+            let key = UndirectedEdgeKey::new(edge.source_id, edge.destination_id, edge.kind);
+            let entry = links.entry(key).or_default();
+            if edge.source_id == key.a {
+                entry.0 = edge.metric.value();
+            } else {
+                entry.1 = edge.metric.value();
             }
-            let sa = match &a.payload().info {
-                NodeInfo::Router(r) => r.id.as_string(),
-                NodeInfo::Network(n) => n.ip_address.to_string(),
+        }
+        // Node numbers, kind and the metric in each direction
+        type Link = (usize, usize, EdgeKind, Option<u32>, Option<u32>);
+        let mut links: Vec<Link> = links
+            .into_iter()
+            .filter_map(|(key, (forward, reverse))| {
+                Some((*ids.get(&key.a)?, *ids.get(&key.b)?, key.kind, forward, reverse))
+            })
+            .map(|(a, b, kind, forward, reverse)| {
+                // Number links left to right in the node order
+                if a <= b { (a, b, kind, forward, reverse) } else { (b, a, kind, reverse, forward) }
+            })
+            .collect();
+        links.sort_by_key(|(a, b, kind, _, _)| (*a, *b, *kind as u8));
+
+        for (a, b, kind, forward, reverse) in links {
+            let arrow = match kind {
+                EdgeKind::Membership | EdgeKind::PointToPoint => "---",
+                _ => "-.-",
             };
-            let sb = match &b.payload().info {
-                NodeInfo::Router(r) => r.id.as_string(),
-                NodeInfo::Network(n) => n.ip_address.to_string(),
+            let label = match (forward, reverse) {
+                (Some(f), Some(r)) if f != r => format!("{} / {}", f, r),
+                (Some(m), _) | (None, Some(m)) => m.to_string(),
+                (None, None) => String::new(),
             };
-            sa.cmp(&sb)
-        });
+            if label.is_empty() {
+                output += &format!("    n{} {} n{}\n", a, arrow, b);
+            } else {
+                output += &format!("    n{} {}|\"{}\"| n{}\n", a, arrow, label, b);
+            }
+        }
+        output
+    }
 
-        for (idx, (node_index, node)) in nodes.iter().enumerate() {
-            let payload = node.payload();
-            let (kind, ident) = match &payload.info {
-                NodeInfo::Router(r) => ("Router", r.id.as_string()),
-                NodeInfo::Network(n) => ("Network", n.ip_address.to_string()),
+    /// GraphML document for the displayed graph, for yEd, Gephi and similar tools. Like the Mermaid
+    /// export, both directed halves of a link become one undirected edge; `metric` is the cost from
+    /// `source` to `target` and `reverse_metric` is only set when the other direction differs.
+    pub fn to_graphml(&self) -> String {
+        let order = self.ordered_node_indices();
+        let ids: HashMap<Uuid, usize> = order
+            .iter()
+            .enumerate()
+            .filter_map(|(i, idx)| self.graph.node(*idx).map(|n| (n.payload().id, i)))
+            .collect();
+
+        let mut output = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+            "  <key id=\"kind\" for=\"node\" attr.name=\"kind\" attr.type=\"string\"/>\n",
+            "  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n",
+            "  <key id=\"area\" for=\"node\" attr.name=\"area\" attr.type=\"string\"/>\n",
+            "  <key id=\"router_id\" for=\"node\" attr.name=\"router_id\" attr.type=\"string\"/>\n",
+            "  <key id=\"prefix\" for=\"node\" attr.name=\"prefix\" attr.type=\"string\"/>\n",
+            "  <key id=\"edge_kind\" for=\"edge\" attr.name=\"kind\" attr.type=\"string\"/>\n",
+            "  <key id=\"metric\" for=\"edge\" attr.name=\"metric\" attr.type=\"long\"/>\n",
+            "  <key id=\"reverse_metric\" for=\"edge\" attr.name=\"reverse_metric\" attr.type=\"long\"/>\n",
+            "  <key id=\"protocol_tag\" for=\"edge\" attr.name=\"protocol_tag\" attr.type=\"string\"/>\n",
+            "  <graph id=\"topology\" edgedefault=\"undirected\">\n",
+        ));
+
+        for (i, idx) in order.iter().enumerate() {
+            let Some(node) = self.graph.node(*idx) else {
+                continue;
+            };
+            output += &format!("    <node id=\"n{}\">\n", i);
+            let (kind, id_key, id_value) = match &node.payload().info {
+                NodeInfo::Router(router) => ("router", "router_id", router.id.to_string()),
+                NodeInfo::Network(network) => ("network", "prefix", network.ip_address.to_string()),
+                NodeInfo::Area(area) => ("area", "area", area.name.clone()),
+            };
+            output += &format!("      <data key=\"kind\">{}</data>\n", kind);
+            output += &format!("      <data key=\"label\">{}</data>\n", xml_escape(&node.label()));
+            let areas: Vec<String> = area_hulls::node_areas(node.payload())
+                .iter()
+                .map(|area| area.to_string())
+                .collect();
+            if !areas.is_empty() {
+                output += &format!("      <data key=\"area\">{}</data>\n", areas.join(","));
+            }
+            output += &format!("      <data key=\"{}\">{}</data>\n", id_key, xml_escape(&id_value));
+            output += "    </node>\n";
+        }
+
+        // Metric in each direction and the protocol tag
+        type Metrics = (Option<u32>, Option<u32>, Option<String>);
+        type Link = (usize, usize, EdgeKind, Option<u32>, Option<u32>, Option<String>);
+
+        // Collapse both directed halves into one edge, keeping each direction's metric
+        let mut links: HashMap<UndirectedEdgeKey, Metrics> = HashMap::new();
+        for (_, e) in self.graph.edges_iter() {
+            let edge = e.payload();
+            let key = UndirectedEdgeKey::new(edge.source_id, edge.destination_id, edge.kind);
+            let entry = links.entry(key).or_default();
+            if edge.source_id == key.a {
+                entry.0 = edge.metric.value();
+            } else {
+                entry.1 = edge.metric.value();
+            }
+            if entry.2.is_none() {
+                entry.2 = edge.protocol_tag.clone();
+            }
+        }
+        let mut links: Vec<Link> = links
+            .into_iter()
+            .filter_map(|(key, (forward, reverse, tag))| {
+                Some((*ids.get(&key.a)?, *ids.get(&key.b)?, key.kind, forward, reverse, tag))
+            })
+            .map(|(a, b, kind, forward, reverse, tag)| {
+                if a <= b {
+                    (a, b, kind, forward, reverse, tag)
+                } else {
+                    (b, a, kind, reverse, forward, tag)
+                }
+            })
+            .collect();
+        links.sort_by_key(|(a, b, kind, _, _, _)| (*a, *b, *kind as u8));
+
+        for (i, (a, b, kind, forward, reverse, tag)) in links.into_iter().enumerate() {
+            output += &format!("    <edge id=\"e{}\" source=\"n{}\" target=\"n{}\">\n", i, a, b);
+            output += &format!("      <data key=\"edge_kind\">{}</data>\n", edge_kind_name(kind));
+            let (metric, reverse_metric) = match (forward, reverse) {
+                (Some(f), Some(r)) if f != r => (Some(f), Some(r)),
+                (Some(m), _) | (None, Some(m)) => (Some(m), None),
+                (None, None) => (None, None),
+            };
+            if let Some(metric) = metric {
+                output += &format!("      <data key=\"metric\">{}</data>\n", metric);
+            }
+            if let Some(reverse_metric) = reverse_metric {
+                output += &format!("      <data key=\"reverse_metric\">{}</data>\n", reverse_metric);
+            }
+            if let Some(tag) = tag {
+                output += &format!("      <data key=\"protocol_tag\">{}</data>\n", xml_escape(&tag));
+            }
+            output += "    </edge>\n";
+        }
+
+        output += "  </graph>\n</graphml>\n";
+        output
+    }
+
+    /// Node indices in a stable order: routers first, then networks, each sorted by identifier.
+    pub fn ordered_node_indices(&self) -> Vec<NodeIndex> {
+        let mut nodes: Vec<(u8, String, NodeIndex)> = self
+            .graph
+            .nodes_iter()
+            .map(|(idx, node)| match &node.payload().info {
+                NodeInfo::Router(r) => (0, r.id.as_string(), idx),
+                NodeInfo::Network(n) => (1, n.ip_address.to_string(), idx),
+                NodeInfo::Area(area) => (2, area.name.clone(), idx),
+            })
+            .collect();
+        nodes.sort();
+        nodes.into_iter().map(|(_, _, idx)| idx).collect()
+    }
+
+    /// Current label of the node with the given Uuid, if it is in the graph.
+    pub fn node_label(&self, id: Uuid) -> Option<String> {
+        let idx = self.node_id_to_index_map.get(&id)?;
+        self.graph.node(*idx).map(|n| n.label().to_string())
+    }
+
+    pub fn is_manual_edge(&self, a: Uuid, b: Uuid, kind: EdgeKind) -> bool {
+        self.manual_edges
+            .contains_key(&UndirectedEdgeKey::new(a, b, kind))
+    }
+
+    fn remove_edge_pair_live(&mut self, a: Uuid, b: Uuid, kind: EdgeKind) {
+        // Find and remove both directions matching (a->b) and (b->a) with given kind
+        let mut to_remove = Vec::new();
+        for (ei, e) in self.graph.edges_iter() {
+            if e.payload().source_id == a
+                && e.payload().destination_id == b
+                && e.payload().kind == kind
+            {
+                to_remove.push(ei);
+            } else if e.payload().source_id == b
+                && e.payload().destination_id == a
+                && e.payload().kind == kind
+            {
+                to_remove.push(ei);
+            }
+        }
+        for ei in to_remove {
+            let _ = self.graph.remove_edge(ei);
+        }
+    }
+
+    pub fn apply_overlay_after_reconcile(&mut self) {
+        // 1) Remove overridden base edges
+        for key in self.manual_removed_edges.clone() {
+            let (a, b) = key.endpoints();
+            self.remove_edge_pair_live(a, b, key.kind.clone());
+        }
+        // 2) Add manual edges with stored metrics
+        for key in self.manual_edges.keys().cloned().collect::<Vec<_>>() {
+            self.apply_manual_edge_live(key);
+        }
+    }
+
+    /// Replace the default label template and relabel every node accordingly.
+    pub fn set_label_template(&mut self, template: LabelTemplate) {
+        self.label_template = template;
+        self.relabel_all();
+    }
+
+    pub fn anonymize(&self) -> bool {
+        self.anonymize
+    }
+
+    pub fn reverse_metric_policy(&self) -> ReverseMetricPolicy {
+        self.reverse_metric_policy
+    }
+
+    /// Takes effect when edges are next rebuilt by `reconcile`.
+    pub fn set_reverse_metric_policy(&mut self, policy: ReverseMetricPolicy) {
+        self.reverse_metric_policy = policy;
+    }
+
+    /// Label nodes with stable pseudonyms (R1, N1, ...) instead of their real labels. Only the
+    /// labels change; pseudonyms are kept when this is turned off, so they come back the same.
+    pub fn set_anonymize(&mut self, anonymize: bool) {
+        self.anonymize = anonymize;
+        if anonymize {
+            self.pseudonyms
+                .register_all(self.graph.nodes_iter().map(|(_, node)| node.payload()));
+        }
+        self.relabel_all();
+    }
+
+    pub fn pseudonyms(&self) -> &Pseudonyms {
+        &self.pseudonyms
+    }
+
+    fn relabel_all(&mut self) {
+        let indices: Vec<NodeIndex> = self.node_id_to_index_map.values().copied().collect();
+        for idx in indices {
+            if let Some(node) = self.graph.node_mut(idx) {
+                let label = display_label(
+                    &self.label_template,
+                    self.anonymize.then_some(&mut self.pseudonyms),
+                    node.payload(),
+                );
+                node.set_label(label);
+            }
+        }
+    }
+
+    /// Pin a node at its current location, or unpin it if already pinned. Returns the new pinned state.
+    pub fn toggle_pin(&mut self, uuid: Uuid) -> bool {
+        if self.pinned_positions.remove(&uuid).is_some() {
+            return false;
+        }
+        let Some(pos) = self
+            .node_id_to_index_map
+            .get(&uuid)
+            .and_then(|idx| self.graph.node(*idx))
+            .map(|node| node.location())
+        else {
+            return false;
+        };
+        self.pinned_positions.insert(uuid, pos);
+        true
+    }
+
+    pub fn is_pinned(&self, uuid: Uuid) -> bool {
+        self.pinned_positions.contains_key(&uuid)
+    }
+
+    pub fn pinned_count(&self) -> usize {
+        self.pinned_positions.len()
+    }
+
+    pub fn clear_pins(&mut self) {
+        self.pinned_positions.clear();
+    }
+
+    /// Current position of every node, for saving the layout.
+    pub fn node_positions(&self) -> HashMap<Uuid, Pos2> {
+        self.graph
+            .nodes_iter()
+            .map(|(_, node)| (node.payload().id, node.location()))
+            .collect()
+    }
+
+    /// Largest distance a node moved since `previous` was taken with `node_positions`. `None`
+    /// when nodes were added or removed in between.
+    pub fn max_displacement(&self, previous: &HashMap<Uuid, Pos2>) -> Option<f32> {
+        if previous.len() != self.graph.node_count() {
+            return None;
+        }
+        self.graph.nodes_iter().try_fold(0.0f32, |max, (_, node)| {
+            let before = previous.get(&node.payload().id)?;
+            Some(max.max(before.distance(node.location())))
+        })
+    }
+
+    /// Smallest rectangle containing every node center, `None` for an empty graph.
+    pub fn bounding_box(&self) -> Option<Rect> {
+        let mut nodes = self.graph.nodes_iter().map(|(_, node)| node.location());
+        let first = nodes.next()?;
+        Some(nodes.fold(Rect::from_min_max(first, first), |bounds, pos| bounds.union(Rect::from_min_max(pos, pos))))
+    }
+
+    /// Move the nodes present in `positions` there, and keep the map so nodes added later by
+    /// `reconcile` start at their saved position too. Returns how many nodes were moved.
+    pub fn restore_layout(&mut self, positions: HashMap<Uuid, Pos2>) -> usize {
+        let mut moved = 0;
+        for (uuid, pos) in &positions {
+            let Some(node) = self
+                .node_id_to_index_map
+                .get(uuid)
+                .and_then(|idx| self.graph.node_mut(*idx))
+            else {
+                continue;
+            };
+            node.set_location(*pos);
+            if let Some(pinned_pos) = self.pinned_positions.get_mut(uuid) {
+                *pinned_pos = *pos;
+            }
+            moved += 1;
+        }
+        self.saved_positions = positions;
+        moved
+    }
+
+    /// Move pinned nodes back to their pinned location after a layout step.
+    /// Dragging a pinned node moves the pin with it.
+    pub fn apply_pins(&mut self) {
+        for (uuid, pinned_pos) in self.pinned_positions.iter_mut() {
+            let Some(idx) = self.node_id_to_index_map.get(uuid) else {
+                continue;
+            };
+            let Some(node) = self.graph.node_mut(*idx) else {
+                continue;
+            };
+            if node.dragged() {
+                *pinned_pos = node.location();
+            } else {
+                node.set_location(*pinned_pos);
+            }
+        }
+    }
+
+    /// Run `steps` force layout steps with the center gravity retargeted at `focus`. The gravity
+    /// pulls towards the middle of the view it's given, so the `view_size` view is centered on the
+    /// focus node; its size only sets the spring length, as on screen. Returns the stepped state.
+    pub fn step_layout_with_focus(
+        &mut self,
+        state: FruchtermanReingoldWithCenterGravityState,
+        focus: NodeIndex,
+        view_size: egui::Vec2,
+        steps: usize,
+    ) -> FruchtermanReingoldWithCenterGravityState {
+        let mut layout = FruchtermanReingoldWithCenterGravity::from_state(state);
+        for _ in 0..steps {
+            let Some(center) = self.graph.node(focus).map(|node| node.location()) else {
+                break;
+            };
+            layout.step(&mut self.graph, Rect::from_center_size(center, view_size));
+        }
+        layout.state()
+    }
+
+    /// One step of area clustering on top of the force layout: every OSPF node is pulled towards
+    /// its area's anchor by `strength` (0..1) of the remaining distance, and intra-area edges get a
+    /// little extra attraction. The backbone is anchored at the graph's centroid and the other
+    /// areas are spread evenly on a circle around it; ABRs settle between their areas' anchors.
+    /// Does nothing with fewer than two areas.
+    pub fn apply_area_gravity(&mut self, strength: f32) {
+        let members: Vec<(NodeIndex, Pos2, Vec<Ipv4Addr>)> = self
+            .graph
+            .nodes_iter()
+            .map(|(idx, node)| (idx, node.location(), area_hulls::node_areas(node.payload())))
+            .filter(|(_, _, areas)| !areas.is_empty())
+            .collect();
+        let mut areas: Vec<Ipv4Addr> = members.iter().flat_map(|(_, _, a)| a.iter().copied()).collect();
+        areas.sort();
+        areas.dedup();
+        if areas.len() < 2 {
+            return;
+        }
+
+        let count = members.len() as f32;
+        let center = members
+            .iter()
+            .fold(egui::Vec2::ZERO, |sum, (_, pos, _)| sum + pos.to_vec2())
+            / count;
+        let center = center.to_pos2();
+        let radius = members
+            .iter()
+            .map(|(_, pos, _)| pos.distance(center))
+            .fold(1.0f32, f32::max)
+            * 0.6;
+
+        let anchors = area_anchors(&areas, center, radius);
+
+        let mut targets: HashMap<NodeIndex, egui::Vec2> = HashMap::new();
+        let mut node_areas: HashMap<NodeIndex, Vec<Ipv4Addr>> = HashMap::new();
+        for (idx, pos, areas) in members {
+            let anchor = areas
+                .iter()
+                .fold(egui::Vec2::ZERO, |sum, area| sum + anchors[area].to_vec2())
+                / areas.len() as f32;
+            targets.insert(idx, (anchor - pos.to_vec2()) * strength);
+            node_areas.insert(idx, areas);
+        }
+
+        // Extra pull along edges that stay within one area
+        for (_, edge) in self.graph.edges_iter() {
+            let edge = edge.payload();
+            let (Some(&a), Some(&b)) = (
+                self.node_id_to_index_map.get(&edge.source_id),
+                self.node_id_to_index_map.get(&edge.destination_id),
+            ) else {
+                continue;
+            };
+            let (Some(areas_a), Some(areas_b)) = (node_areas.get(&a), node_areas.get(&b)) else {
+                continue;
+            };
+            if !areas_a.iter().any(|area| areas_b.contains(area)) {
+                continue;
+            }
+            let (Some(pos_a), Some(pos_b)) = (self.graph.node(a), self.graph.node(b)) else {
+                continue;
+            };
+            let pull = (pos_b.location() - pos_a.location()) * (0.1 * strength);
+            *targets.get_mut(&a).unwrap() += pull;
+            *targets.get_mut(&b).unwrap() -= pull;
+        }
+
+        for (idx, shift) in targets {
+            if let Some(node) = self.graph.node_mut(idx)
+                && !node.dragged()
+            {
+                let pos = node.location();
+                node.set_location(pos + shift);
+            }
+        }
+    }
+
+    /// Pathfinding view that excludes `failed_nodes`, `failed_edges` and every edge touching a failed node.
+    pub fn with_failed<'a>(
+        &'a self,
+        failed_nodes: &'a HashSet<Uuid>,
+        failed_edges: &'a HashSet<UndirectedEdgeKey>,
+    ) -> FailureView<'a> {
+        FailureView {
+            graph: self,
+            failed_nodes,
+            failed_edges,
+            unmetered_cost: DEFAULT_UNMETERED_COST,
+        }
+    }
+
+    /// Nodes reachable from `root` that can no longer be reached once `failed` is down, following
+    /// edge direction. `failed` itself isn't included; if it is the root, everything it reached is
+    /// lost. Empty if either node isn't in the graph.
+    pub fn unreachable_if_removed(&self, root: Uuid, failed: Uuid) -> HashSet<Uuid> {
+        let (Some(&root_idx), Some(&failed_idx)) = (
+            self.node_id_to_index_map.get(&root),
+            self.node_id_to_index_map.get(&failed),
+        ) else {
+            return HashSet::new();
+        };
+        let before = self.reachable_from(root_idx, None);
+        let after = self.reachable_from(root_idx, Some(failed_idx));
+        before
+            .difference(&after)
+            .filter(|idx| **idx != failed_idx)
+            .filter_map(|idx| self.graph.node(*idx).map(|n| n.payload().id))
+            .collect()
+    }
+
+    /// Nodes reachable from `root` along edge direction, never entering `skip`.
+    fn reachable_from(&self, root: NodeIndex, skip: Option<NodeIndex>) -> HashSet<NodeIndex> {
+        let mut seen: HashSet<NodeIndex> = HashSet::new();
+        if Some(root) == skip {
+            return seen;
+        }
+        let mut queue: VecDeque<NodeIndex> = VecDeque::from([root]);
+        seen.insert(root);
+        while let Some(idx) = queue.pop_front() {
+            for next in self.graph.g().neighbors(idx) {
+                if Some(next) != skip && seen.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+        seen
+    }
+
+    /// Drop every edge that doesn't join two consecutive nodes of `path`, in either direction.
+    pub fn retain_path_edges(&mut self, path: &[Uuid]) {
+        let hops: HashSet<(Uuid, Uuid)> = path
+            .windows(2)
+            .flat_map(|w| [(w[0], w[1]), (w[1], w[0])])
+            .collect();
+        let off_path: Vec<_> = self
+            .graph
+            .edges_iter()
+            .filter(|(_, e)| {
+                let e = e.payload();
+                !hops.contains(&(e.source_id, e.destination_id))
+            })
+            .map(|(ei, _)| ei)
+            .collect();
+        for ei in off_path {
+            let _ = self.graph.remove_edge(ei);
+        }
+    }
+
+    /// Replace every area not in `expanded` by a single node, for topologies too big to read
+    /// router by router. A node goes into its first area (the backbone, for ABRs) once all of its
+    /// areas are collapsed, so ABRs of an expanded area stay visible. Edges leaving a collapsed
+    /// area are merged into one per direction and neighbor, with the cheapest metric among the
+    /// crossing links. Undone by the next `reconcile`.
+    pub fn collapse_areas(&mut self, expanded: &HashSet<AreaKey>) {
+        let mut home: HashMap<Uuid, AreaKey> = HashMap::new();
+        let mut members: BTreeMap<AreaKey, Vec<(Uuid, Pos2, Option<RouterId>)>> = BTreeMap::new();
+        for (_, node) in self.graph.nodes_iter() {
+            let payload = node.payload();
+            let areas = AreaKey::of(payload);
+            if areas.is_empty() || areas.iter().any(|area| expanded.contains(area)) {
+                continue;
+            }
+            let router_id = match &payload.info {
+                NodeInfo::Router(router) => Some(router.id.clone()),
+                NodeInfo::Network(_) | NodeInfo::Area(_) => None,
+            };
+            members
+                .entry(areas[0].clone())
+                .or_default()
+                .push((payload.id, node.location(), router_id));
+            home.insert(payload.id, areas[0].clone());
+        }
+        if home.is_empty() {
+            return;
+        }
+
+        let endpoint = |id: Uuid| home.get(&id).map_or(id, AreaKey::node_id);
+        let cost = |edge: &Edge| edge.metric.value().unwrap_or(u32::MAX);
+        let mut merged: HashMap<(Uuid, Uuid), Edge> = HashMap::new();
+        for (_, edge) in self.graph.edges_iter() {
+            let edge = edge.payload();
+            let (src, dst) = (endpoint(edge.source_id), endpoint(edge.destination_id));
+            if src == dst || (src == edge.source_id && dst == edge.destination_id) {
+                continue;
+            }
+            let candidate = Edge {
+                source_id: src,
+                destination_id: dst,
+                ..edge.clone()
+            };
+            let kept = merged.entry((src, dst)).or_insert_with(|| candidate.clone());
+            if cost(&candidate) < cost(kept) {
+                *kept = candidate;
+            }
+        }
+
+        // Remember where the members were, so expanding the area puts them back there
+        for id in home.keys() {
+            if let Some(idx) = self.node_id_to_index_map.remove(id) {
+                if let Some(node) = self.graph.node(idx) {
+                    self.saved_positions.insert(*id, node.location());
+                }
+                let _ = self.graph.remove_node(idx);
+            }
+        }
+
+        for (area, members) in members {
+            let id = area.node_id();
+            let center = members
+                .iter()
+                .fold(egui::Vec2::ZERO, |sum, (_, pos, _)| sum + pos.to_vec2())
+                / members.len() as f32;
+            let center = center.to_pos2();
+            let node = Node {
+                info: NodeInfo::Area(CollapsedArea {
+                    name: area.to_string(),
+                    routers: members.iter().filter_map(|(_, _, rid)| rid.clone()).collect(),
+                    members: members.len(),
+                }),
+                label: Some(format!("{} ({} nodes)", area, members.len())),
+                source_id: None,
+                id,
+                source_scoped: false,
+            };
+            let label = display_label(
+                &self.label_template,
+                self.anonymize.then_some(&mut self.pseudonyms),
+                &node,
+            );
+            let pos = self.saved_positions.get(&id).copied().unwrap_or(center);
+            let idx = self.graph.add_node(node);
+            if let Some(n) = self.graph.node_mut(idx) {
+                n.set_location(pos);
+                n.set_label(label);
+            }
+            self.node_id_to_index_map.insert(id, idx);
+            self.collapsed_areas.insert(id, area);
+        }
+
+        for ((src, dst), edge) in merged {
+            if let (Some(&src_idx), Some(&dst_idx)) =
+                (self.node_id_to_index_map.get(&src), self.node_id_to_index_map.get(&dst))
+            {
+                self.graph.add_edge(src_idx, dst_idx, edge);
+            }
+        }
+    }
+
+    /// Area the node `id` stands in for, if it is a collapsed area.
+    pub fn collapsed_area(&self, id: &Uuid) -> Option<&AreaKey> {
+        self.collapsed_areas.get(id)
+    }
+
+    /// Uuids of all nodes within `hops` edges of `center` (BFS, edge direction ignored).
+    /// `hops == 0` yields only the center itself.
+    pub fn neighborhood(&self, center: NodeIndex, hops: usize) -> HashSet<Uuid> {
+        let mut seen: HashSet<NodeIndex> = HashSet::new();
+        let mut queue: VecDeque<(NodeIndex, usize)> = VecDeque::new();
+        if self.graph.node(center).is_none() {
+            return HashSet::new();
+        }
+        seen.insert(center);
+        queue.push_back((center, 0));
+
+        while let Some((idx, depth)) = queue.pop_front() {
+            if depth >= hops {
+                continue;
+            }
+            for next in self.graph.g().neighbors_undirected(idx) {
+                if seen.insert(next) {
+                    queue.push_back((next, depth + 1));
+                }
+            }
+        }
+
+        seen.into_iter()
+            .filter_map(|idx| self.graph.node(idx).map(|n| n.payload().id))
+            .collect()
+    }
+
+    /// Match the polled interfaces of the router `src_uuid` to the networks its outgoing edges lead
+    /// to, by the longest prefix containing the interface address. Each network takes at most one
+    /// interface. Loopbacks are left out, they never have an edge. `None` if the router isn't shown.
+    pub fn map_interfaces(
+        &self,
+        src_uuid: Uuid,
+        interfaces: &[InterfaceStats],
+    ) -> Option<Vec<InterfaceMapping>> {
+        let src_idx = *self.node_id_to_index_map.get(&src_uuid)?;
+        let mut networks: Vec<(ipnetwork::IpNetwork, Uuid)> = self
+            .graph
+            .edges_directed(src_idx, petgraph::Direction::Outgoing)
+            .filter_map(|edge| {
+                let dst_uuid = edge.weight().payload().destination_id;
+                let dst_idx = self.node_id_to_index_map.get(&dst_uuid)?;
+                match &self.graph.node(*dst_idx)?.payload().info {
+                    NodeInfo::Network(net) => Some((net.ip_address, dst_uuid)),
+                    NodeInfo::Router(_) | NodeInfo::Area(_) => None,
+                }
+            })
+            .collect();
+
+        let mappings = interfaces
+            .iter()
+            .filter(|stats| !stats.ip_address.is_loopback())
+            .map(|stats| {
+                let best = networks
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, (prefix, _))| prefix.contains(stats.ip_address))
+                    .max_by_key(|(_, (prefix, _))| prefix.prefix())
+                    .map(|(i, _)| i);
+                InterfaceMapping {
+                    ip_address: stats.ip_address,
+                    target: best.map(|i| networks.swap_remove(i)),
+                }
+            })
+            .collect();
+        Some(mappings)
+    }
+
+    /// Network node whose prefix contains `ip`. Overlapping prefixes resolve to the longest match.
+    pub fn find_network_containing(&self, ip: std::net::IpAddr) -> Option<NodeIndex> {
+        self.graph
+            .nodes_iter()
+            .filter_map(|(idx, node)| match &node.payload().info {
+                NodeInfo::Network(net) if net.ip_address.contains(ip) => {
+                    Some((idx, net.ip_address.prefix()))
+                }
+                _ => None,
+            })
+            .max_by_key(|(_, prefix_len)| *prefix_len)
+            .map(|(idx, _)| idx)
+    }
+
+    /// Find a node from a search query:
+    /// - an IP address selects the network containing it (longest prefix match)
+    /// - a prefix (`a.b.c.d/len`) selects that exact network
+    /// - anything else is matched case-insensitively against labels and router ids
+    pub fn search(&self, query: &str) -> Option<NodeIndex> {
+        let query = query.trim();
+        if query.is_empty() {
+            return None;
+        }
+        if let Ok(ip) = query.parse::<std::net::IpAddr>() {
+            return self.find_network_containing(ip);
+        }
+        if let Ok(prefix) = query.parse::<ipnetwork::IpNetwork>() {
+            return self.graph.nodes_iter().find_map(|(idx, node)| match &node.payload().info {
+                NodeInfo::Network(net) if net.ip_address == prefix => Some(idx),
+                _ => None,
+            });
+        }
+
+        let needle = query.to_lowercase();
+        self.graph.nodes_iter().find_map(|(idx, node)| {
+            let router_id = match &node.payload().info {
+                NodeInfo::Router(router) => router.id.to_string(),
+                NodeInfo::Network(_) | NodeInfo::Area(_) => String::new(),
+            };
+            (node.label().to_lowercase().contains(&needle)
+                || router_id.to_lowercase().contains(&needle))
+            .then_some(idx)
+        })
+    }
+
+    /// Compute node/edge/area/component counts. Walks the whole graph, so callers should cache it.
+    pub fn stats(&self) -> GraphStats {
+        let mut stats = GraphStats::default();
+        let mut areas: HashSet<std::net::Ipv4Addr> = HashSet::new();
+
+        for (_, node) in self.graph.nodes_iter() {
+            match &node.payload().info {
+                NodeInfo::Router(router) => {
+                    stats.routers += 1;
+                    if let Some(ProtocolData::Ospf(data)) = &router.protocol_data {
+                        areas.insert(data.area_id);
+                        if let OspfPayload::Router(payload) = &data.payload {
+                            areas.extend(payload.per_area_facets.iter().map(|f| f.area_id));
+                        }
+                    }
+                }
+                NodeInfo::Network(network) => {
+                    stats.networks += 1;
+                    // External LSAs are AS-scoped, their area id carries no meaning
+                    if let Some(ProtocolData::Ospf(data)) = &network.protocol_data
+                        && !network.is_external()
+                    {
+                        areas.insert(data.area_id);
+                    }
+                }
+                NodeInfo::Area(_) => stats.collapsed_areas += 1,
+            }
+        }
+        stats.ospf_areas = areas.len();
+
+        let edge_keys: HashSet<UndirectedEdgeKey> = self
+            .graph
+            .edges_iter()
+            .map(|(_, edge)| {
+                let payload = edge.payload();
+                UndirectedEdgeKey::new(payload.source_id, payload.destination_id, payload.kind)
+            })
+            .collect();
+        for key in edge_keys {
+            *stats.edges_by_kind.entry(key.kind).or_default() += 1;
+        }
+
+        let mut visited: HashSet<NodeIndex> = HashSet::new();
+        for start in self.graph.g().node_indices() {
+            if !visited.insert(start) {
+                continue;
+            }
+            stats.connected_components += 1;
+            let mut queue = VecDeque::from([start]);
+            while let Some(idx) = queue.pop_front() {
+                for next in self.graph.g().neighbors_undirected(idx) {
+                    if visited.insert(next) {
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+
+        stats.metric_histogram = MetricHistogram::from_values(self.metric_values());
+        stats
+    }
+
+    /// Number of distinct neighbors of every node. Derived edges exist in both directions and
+    /// parallel edges of different kinds are common, so edges aren't counted directly.
+    pub fn node_degrees(&self) -> HashMap<Uuid, usize> {
+        self.graph
+            .nodes_iter()
+            .map(|(idx, node)| {
+                let neighbors: HashSet<NodeIndex> =
+                    self.graph.g().neighbors_undirected(idx).collect();
+                (node.payload().id, neighbors.len())
+            })
+            .collect()
+    }
+
+    /// Metric of every directed edge that has one. The network -> router halves of membership
+    /// edges are skipped, their 0 is implied by the protocol rather than configured.
+    pub fn metric_values(&self) -> Vec<u32> {
+        self.graph
+            .edges_iter()
+            .map(|(_, edge)| edge.payload())
+            .filter(|edge| {
+                edge.kind != EdgeKind::Membership
+                    || !self
+                        .node_id_to_index_map
+                        .get(&edge.source_id)
+                        .and_then(|idx| self.graph.node(*idx))
+                        .is_some_and(|node| matches!(node.payload().info, NodeInfo::Network(_)))
+            })
+            .filter_map(|edge| edge.metric.value())
+            .collect()
+    }
+}
+
+impl ToString for NetworkGraph {
+    fn to_string(&self) -> String {
+        use petgraph::Direction;
+        let mut output = String::from("Network Graph {\n");
+
+        let nodes: Vec<_> = self
+            .ordered_node_indices()
+            .into_iter()
+            .filter_map(|idx| self.graph.node(idx).map(|node| (idx, node)))
+            .collect();
+
+        for (idx, (node_index, node)) in nodes.iter().enumerate() {
+            let payload = node.payload();
+            let (kind, ident) = match &payload.info {
+                NodeInfo::Router(r) => ("Router", r.id.as_string()),
+                NodeInfo::Network(n) => ("Network", n.ip_address.to_string()),
+                NodeInfo::Area(area) => ("Collapsed", area.name.clone()),
             };
 
             output += &format!("    {} {} {{\n", kind, ident);
@@ -789,6 +2360,7 @@ impl ToString for NetworkGraph {
                     self.graph.node(target).map(|n| match &n.payload().info {
                         NodeInfo::Router(r) => format!("Router {}", r.id.as_string()),
                         NodeInfo::Network(net) => format!("Network {}", net.ip_address),
+                        NodeInfo::Area(area) => format!("Collapsed {}", area.name),
                     })
                 })
                 .collect();
@@ -803,6 +2375,7 @@ impl ToString for NetworkGraph {
                     self.graph.node(source).map(|n| match &n.payload().info {
                         NodeInfo::Router(r) => format!("Router {}", r.id.as_string()),
                         NodeInfo::Network(net) => format!("Network {}", net.ip_address),
+                        NodeInfo::Area(area) => format!("Collapsed {}", area.name),
                     })
                 })
                 .collect();
@@ -823,3 +2396,848 @@ impl ToString for NetworkGraph {
         output
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use petgraph::visit::IntoEdgeReferences;
+
+    use super::*;
+    use crate::topology::store::{MergeConfig, TopologyStore};
+
+    fn graph_from_test_store() -> NetworkGraph {
+        let json = include_str!("../../test_data/test_store.json");
+        let store: TopologyStore = serde_json::from_str(json).unwrap();
+        let nodes = store
+            .build_merged_view_with(&MergeConfig::default())
+            .unwrap();
+        NetworkGraph::build_new(nodes, ReverseMetricPolicy::default())
+    }
+
+    /// Every shortest path with at least one intermediate hop, with nothing failed, as (cost, nodes).
+    fn multi_hop_paths(graph: &NetworkGraph) -> Vec<(u32, Vec<NodeIndex>)> {
+        let no_nodes = HashSet::new();
+        let no_edges = HashSet::new();
+        let view = graph.with_failed(&no_nodes, &no_edges);
+        let indices: Vec<NodeIndex> = graph.node_id_to_index_map.values().copied().collect();
+        indices
+            .iter()
+            .flat_map(|a| indices.iter().map(move |b| (*a, *b)))
+            .filter_map(|(a, b)| view.shortest_path(a, b).filter(|(_, path)| path.len() >= 3))
+            .collect()
+    }
+
+    fn multi_hop_path(graph: &NetworkGraph) -> (u32, Vec<NodeIndex>) {
+        multi_hop_paths(graph)
+            .into_iter()
+            .next()
+            .expect("test store should contain a multi-hop path")
+    }
+
+    #[test]
+    fn test_map_interfaces() {
+        let graph = graph_from_test_store();
+        // A router with an outgoing edge into a network, and that network's prefix
+        let (src_uuid, prefix, net_uuid) = graph
+            .graph
+            .edges_iter()
+            .find_map(|(_, e)| {
+                let edge = e.payload();
+                let src = graph.graph.node(graph.node_id_to_index_map[&edge.source_id])?;
+                let dst = graph.graph.node(graph.node_id_to_index_map[&edge.destination_id])?;
+                match (&src.payload().info, &dst.payload().info) {
+                    (NodeInfo::Router(_), NodeInfo::Network(net)) if net.ip_address.prefix() < 32 => {
+                        Some((edge.source_id, net.ip_address, edge.destination_id))
+                    }
+                    _ => None,
+                }
+            })
+            .unwrap();
+
+        let stats = |ip: std::net::IpAddr| InterfaceStats {
+            ip_address: ip,
+            tx_bytes: None,
+            tx_packets: None,
+            rx_bytes: None,
+            rx_packets: None,
+        };
+        let inside = prefix.iter().nth(1).unwrap();
+        let interfaces = [
+            stats(inside),
+            stats("127.0.0.1".parse().unwrap()),
+            stats("203.0.113.1".parse().unwrap()),
+        ];
+
+        let mappings = graph.map_interfaces(src_uuid, &interfaces).unwrap();
+        assert_eq!(mappings.len(), 2, "loopbacks are skipped");
+        assert_eq!(mappings[0].ip_address, inside);
+        assert_eq!(mappings[0].target, Some((prefix, net_uuid)));
+        assert_eq!(mappings[1].target, None);
+
+        assert!(graph.map_interfaces(Uuid::nil(), &interfaces).is_none());
+    }
+
+    #[test]
+    fn test_interface_traffic_weights_skip_unmapped() {
+        let stats = |ip: &str, bytes: u64| InterfaceStats {
+            ip_address: ip.parse().unwrap(),
+            tx_bytes: Some(bytes),
+            tx_packets: None,
+            rx_bytes: Some(bytes),
+            rx_packets: None,
+        };
+        let interfaces = [stats("10.0.0.1", 100), stats("10.0.1.1", 100), stats("10.0.2.1", 200)];
+        let (net_b, net_c) = (Uuid::from_u128(2), Uuid::from_u128(3));
+        let mapping = |ip: &str, target: Option<(&str, Uuid)>| InterfaceMapping {
+            ip_address: ip.parse().unwrap(),
+            target: target.map(|(prefix, uuid)| (prefix.parse().unwrap(), uuid)),
+        };
+        // The first interface has no edge; it must not stop the others from being weighted
+        let mappings = [
+            mapping("10.0.0.1", None),
+            mapping("10.0.1.1", Some(("10.0.1.0/24", net_b))),
+            mapping("10.0.2.1", Some(("10.0.2.0/24", net_c))),
+        ];
+
+        let (weights, skipped) = interface_traffic_weights(&interfaces, &mappings);
+        assert_eq!(skipped, 1);
+        assert_eq!(weights, vec![(net_b, 0.25), (net_c, 0.5)]);
+    }
+
+    #[test]
+    fn test_to_graphml() {
+        let mut graph = graph_from_test_store();
+        let (idx, _) = graph.graph.nodes_iter().next().unwrap();
+        graph.graph.node_mut(idx).unwrap().set_label("R1 <core> & \"edge\"".to_string());
+
+        let graphml = graph.to_graphml();
+        assert!(graphml.starts_with("<?xml"));
+        assert!(graphml.contains("R1 &lt;core&gt; &amp; &quot;edge&quot;"));
+        assert!(!graphml.contains("<core>"));
+        assert_eq!(graphml.matches("<node ").count(), graph.graph.node_count());
+
+        // Each undirected link is exported once
+        let links: HashSet<UndirectedEdgeKey> = graph
+            .graph
+            .edges_iter()
+            .map(|(_, e)| {
+                let edge = e.payload();
+                UndirectedEdgeKey::new(edge.source_id, edge.destination_id, edge.kind)
+            })
+            .collect();
+        assert_eq!(graphml.matches("<edge ").count(), links.len());
+        assert!(graphml.contains("<data key=\"area\">0.0.0.0</data>"));
+    }
+
+    #[test]
+    fn test_designated_router_edge_keys() {
+        let graph = graph_from_test_store();
+        let keys = graph.designated_router_edge_keys();
+        assert!(!keys.is_empty());
+
+        let edges: HashSet<UndirectedEdgeKey> = graph
+            .graph
+            .edges_iter()
+            .map(|(_, e)| {
+                let edge = e.payload();
+                UndirectedEdgeKey::new(edge.source_id, edge.destination_id, edge.kind)
+            })
+            .collect();
+        for key in &keys {
+            assert!(edges.contains(key), "DR edge {:?} is not in the graph", key);
+        }
+    }
+
+    #[test]
+    fn test_neighborhood_hops() {
+        let graph = graph_from_test_store();
+
+        let (&center_uuid, &center_idx) = graph
+            .node_id_to_index_map
+            .iter()
+            .find(|(_, idx)| {
+                matches!(graph.graph.node(**idx).unwrap().payload().info, NodeInfo::Network(_))
+                    && graph.graph.g().neighbors_undirected(**idx).next().is_some()
+            })
+            .expect("test store should contain a connected network");
+
+        let zero = graph.neighborhood(center_idx, 0);
+        assert_eq!(zero, HashSet::from([center_uuid]));
+
+        let one = graph.neighborhood(center_idx, 1);
+        let expected_one: HashSet<Uuid> = graph
+            .graph
+            .g()
+            .neighbors_undirected(center_idx)
+            .map(|idx| graph.graph.node(idx).unwrap().payload().id)
+            .chain(std::iter::once(center_uuid))
+            .collect();
+        assert_eq!(one, expected_one);
+
+        let two = graph.neighborhood(center_idx, 2);
+        assert!(two.is_superset(&one));
+    }
+    #[test]
+    fn test_find_network_containing() {
+        let graph = graph_from_test_store();
+
+        let idx = graph
+            .find_network_containing("172.21.14.5".parse().unwrap())
+            .expect("172.21.14.0/24 should contain the address");
+        let NodeInfo::Network(net) = &graph.graph.node(idx).unwrap().payload().info else {
+            panic!("expected a network node");
+        };
+        assert_eq!(net.ip_address, "172.21.14.0/24".parse::<ipnetwork::IpNetwork>().unwrap());
+
+        assert!(graph.find_network_containing("192.0.2.1".parse().unwrap()).is_none());
+        assert_eq!(graph.search(" 172.21.14.5 "), Some(idx));
+        assert_eq!(graph.search("172.21.14.0/24"), Some(idx));
+    }
+
+    #[test]
+    fn test_with_failed_reroutes() {
+        let graph = graph_from_test_store();
+        let uuid = |idx: NodeIndex| graph.graph.node(idx).unwrap().payload().id;
+        let no_nodes = HashSet::new();
+        let no_edges = HashSet::new();
+        let healthy = graph.with_failed(&no_nodes, &no_edges);
+
+        let (cost, path) = multi_hop_path(&graph);
+        let hops = healthy.hops(&path);
+        assert_eq!(hops.len(), path.len());
+        assert_eq!(hops[0].cost, 0);
+        assert_eq!(hops.last().unwrap().total, cost);
+
+        // A path with a way around its first intermediate hop takes it once that hop is down
+        let (path, rerouted) = multi_hop_paths(&graph)
+            .into_iter()
+            .find_map(|(_, path)| {
+                let failed_nodes = HashSet::from([uuid(path[1])]);
+                let view = graph.with_failed(&failed_nodes, &no_edges);
+                let (_, rerouted) = view.shortest_path(path[0], *path.last().unwrap())?;
+                Some((path, rerouted))
+            })
+            .expect("test store should contain a path around a failed node");
+        assert!(!rerouted.contains(&path[1]));
+        assert_eq!((rerouted.first(), rerouted.last()), (path.first(), path.last()));
+
+        // Failing every edge between the first two hops, whatever its kind, avoids that link
+        let link_edges = |path: &[NodeIndex]| -> HashSet<UndirectedEdgeKey> {
+            graph
+                .graph
+                .g()
+                .edges_connecting(path[0], path[1])
+                .map(|e| e.weight().payload())
+                .map(|e| UndirectedEdgeKey::new(e.source_id, e.destination_id, e.kind))
+                .collect()
+        };
+        let (path, rerouted) = multi_hop_paths(&graph)
+            .into_iter()
+            .find_map(|(_, path)| {
+                let failed_edges = link_edges(&path);
+                let view = graph.with_failed(&no_nodes, &failed_edges);
+                let (_, rerouted) = view.shortest_path(path[0], *path.last().unwrap())?;
+                Some((path, rerouted))
+            })
+            .expect("test store should contain a path around a failed link");
+        assert!(!rerouted.windows(2).any(|w| w == [path[0], path[1]]));
+    }
+
+    #[test]
+    fn test_unreachable_if_removed() {
+        let graph = graph_from_test_store();
+        let uuid = |idx: NodeIndex| graph.graph.node(idx).unwrap().payload().id;
+        let (_, path) = multi_hop_path(&graph);
+        let (root, failed) = (path[0], path[1]);
+
+        // Lost are exactly the nodes no path reaches once the failed node is down
+        let unreachable = graph.unreachable_if_removed(uuid(root), uuid(failed));
+        let failed_nodes = HashSet::from([uuid(failed)]);
+        let no_edges = HashSet::new();
+        let view = graph.with_failed(&failed_nodes, &no_edges);
+        for node in graph.reachable_from(root, None) {
+            if node != failed {
+                let lost = view.shortest_path(root, node).is_none();
+                assert_eq!(unreachable.contains(&uuid(node)), lost);
+            }
+        }
+        assert!(!unreachable.contains(&uuid(root)));
+        assert!(!unreachable.contains(&uuid(failed)));
+
+        // Losing the root loses everything it reached
+        let everything = graph.reachable_from(root, None).len() - 1;
+        assert_eq!(graph.unreachable_if_removed(uuid(root), uuid(root)).len(), everything);
+        assert!(graph.unreachable_if_removed(Uuid::nil(), uuid(failed)).is_empty());
+    }
+
+    #[test]
+    fn test_asymmetric_links() {
+        let mut graph = graph_from_test_store();
+
+        // Take any forward/reverse edge pair and turn it into a point-to-point link
+        let (forward, reverse) = graph
+            .graph
+            .g()
+            .edge_references()
+            .find_map(|e| {
+                let reverse = graph.graph.g().find_edge(e.target(), e.source())?;
+                Some((e.id(), reverse))
+            })
+            .expect("test store should contain a bidirectional edge");
+        for (ei, metric) in [(forward, EdgeMetric::Ospf(10)), (reverse, EdgeMetric::Ospf(30))] {
+            let edge = graph.graph.edge_mut(ei).unwrap().payload_mut();
+            edge.kind = EdgeKind::PointToPoint;
+            edge.metric = metric;
+        }
+        let (a, b) = {
+            let edge = graph.graph.edge(forward).unwrap().payload();
+            (edge.source_id, edge.destination_id)
+        };
+        let expected = if a < b { (a, b, 10, 30) } else { (b, a, 30, 10) };
+
+        assert!(graph.asymmetric_links().contains(&expected));
+        assert!(graph
+            .asymmetric_edge_keys()
+            .contains(&UndirectedEdgeKey::new(a, b, EdgeKind::PointToPoint)));
+
+        graph.graph.edge_mut(reverse).unwrap().payload_mut().metric = EdgeMetric::Ospf(10);
+        assert!(!graph.asymmetric_links().iter().any(|l| (l.0, l.1) == (expected.0, expected.1)));
+    }
+
+    #[test]
+    fn test_metrics_from_store() {
+        let json = include_str!("../../test_data/test_store.json");
+        let store: TopologyStore = serde_json::from_str(json).unwrap();
+        let nodes = store.build_merged_view_with(&MergeConfig::default()).unwrap();
+        // Strict, so nothing is mirrored over a half the protocol data didn't fill
+        let graph = NetworkGraph::build_new(nodes, ReverseMetricPolicy::Strict);
+        let g = graph.graph.g();
+
+        let mut memberships = 0;
+        for edge in g.edge_references() {
+            let (src, dst) = (edge.source(), edge.target());
+            let src_node = graph.graph.node(src).unwrap().payload();
+            let dst_node = graph.graph.node(dst).unwrap().payload();
+            let (NodeInfo::Router(router), NodeInfo::Network(network)) = (&src_node.info, &dst_node.info)
+            else {
+                continue;
+            };
+            // Stub and summary networks have no interface of the router in them
+            let Some(cost) = ospf_interface_metric(router, network) else {
+                continue;
+            };
+            // Router -> network carries the interface cost, network -> router costs nothing
+            assert!(matches!(edge.weight().payload().metric, EdgeMetric::Ospf(m) if m == cost));
+            let back = g
+                .edges_connecting(dst, src)
+                .map(|e| e.weight().payload())
+                .find(|e| e.kind == edge.weight().payload().kind)
+                .expect("membership edges come in pairs");
+            assert!(matches!(back.metric, EdgeMetric::Ospf(0)));
+            memberships += 1;
+        }
+        assert!(memberships > 0, "test store should contain OSPF memberships with interface costs");
+    }
+
+    #[test]
+    fn test_virtual_link_edges() {
+        let json = include_str!("../../test_data/test_store.json");
+        let store: TopologyStore = serde_json::from_str(json).unwrap();
+        let mut nodes = store
+            .build_merged_view_with(&MergeConfig::default())
+            .unwrap();
+        let ospf_router_ids: Vec<RouterId> = nodes
+            .iter()
+            .filter_map(|n| match &n.info {
+                NodeInfo::Router(r) if matches!(r.protocol_data, Some(ProtocolData::Ospf(_))) => {
+                    Some(r.id.clone())
+                }
+                _ => None,
+            })
+            .take(2)
+            .collect();
+        let [a, b] = [ospf_router_ids[0].clone(), ospf_router_ids[1].clone()];
+
+        // Give both routers a virtual link to the other, with a different cost each way
+        for node in &mut nodes {
+            let NodeInfo::Router(router) = &mut node.info else {
+                continue;
+            };
+            let (peer, metric) = match &router.id {
+                id if *id == a => (b.clone(), 25),
+                id if *id == b => (a.clone(), 30),
+                _ => continue,
+            };
+            let Some(ProtocolData::Ospf(OspfData {
+                payload: OspfPayload::Router(payload),
+                ..
+            })) = &mut router.protocol_data
+            else {
+                continue;
+            };
+            payload.is_virtual_link_endpoint = true;
+            payload.virtual_links = vec![OspfVirtualLink {
+                peer_router_id: peer,
+                transit_area_id: Ipv4Addr::new(0, 0, 0, 1),
+                metric,
+            }];
+        }
+
+        let graph = NetworkGraph::build_new(nodes, ReverseMetricPolicy::default());
+        let (a_uuid, b_uuid) = (a.to_uuidv5(), b.to_uuidv5());
+        let virtual_links: Vec<&Edge> = graph
+            .graph
+            .edges_iter()
+            .map(|(_, e)| e.payload())
+            .filter(|e| e.kind == EdgeKind::VirtualLink)
+            .collect();
+        assert_eq!(virtual_links.len(), 2);
+        for edge in virtual_links {
+            let expected = match (edge.source_id, edge.destination_id) {
+                (src, dst) if src == a_uuid && dst == b_uuid => 25,
+                (src, dst) if src == b_uuid && dst == a_uuid => 30,
+                other => panic!("virtual link between unexpected routers {:?}", other),
+            };
+            assert!(matches!(edge.metric, EdgeMetric::Ospf(m) if m == expected));
+        }
+    }
+
+    #[test]
+    fn test_parallel_edge_groups() {
+        let mut graph = graph_from_test_store();
+        let (a, b) = graph
+            .graph
+            .edges_iter()
+            .map(|(_, e)| e.payload())
+            .find(|e| e.kind == EdgeKind::Membership)
+            .map(|e| (e.source_id, e.destination_id))
+            .expect("test store should contain a membership edge");
+        let key = if a < b { (a, b) } else { (b, a) };
+        assert!(!graph.parallel_edge_groups().contains_key(&key));
+
+        // A manual edge of another kind between the same pair forms a bundle
+        graph.add_manual_edge(a, b, EdgeKind::VirtualAdjacency, 5).unwrap();
+        let groups = graph.parallel_edge_groups();
+        let group = &groups[&key];
+        assert_eq!(group.len(), 2);
+        assert_eq!(group[0].0, EdgeKind::Membership);
+        assert_eq!(group[1], (EdgeKind::VirtualAdjacency, Some("MANUAL".to_string())));
+    }
+
+    #[test]
+    fn test_to_mermaid() {
+        let graph = graph_from_test_store();
+        let mermaid = graph.to_mermaid();
+        let mut lines = mermaid.lines();
+        assert_eq!(lines.next(), Some("graph LR"));
+
+        let node_lines = lines.clone().filter(|l| l.ends_with("\"]") || l.ends_with("\")")).count();
+        assert_eq!(node_lines, graph.graph.node_count());
+
+        // Every directed pair is collapsed into a single link line
+        let link_lines = lines.count() - node_lines;
+        let undirected: HashSet<UndirectedEdgeKey> = graph
+            .graph
+            .edges_iter()
+            .map(|(_, e)| e.payload())
+            .map(|e| UndirectedEdgeKey::new(e.source_id, e.destination_id, e.kind))
+            .collect();
+        assert_eq!(link_lines, undirected.len());
+        assert_eq!(mermaid_escape("a \"b\""), "a #quot;b#quot;");
+    }
+
+    #[test]
+    fn test_stats_counts() {
+        let graph = graph_from_test_store();
+        let stats = graph.stats();
+
+        assert_eq!(stats.routers + stats.networks, graph.graph.node_count());
+        assert!(stats.connected_components >= 1);
+        assert!(stats.connected_components <= graph.graph.node_count());
+        // Derived edges are inserted in both directions but counted once
+        let undirected: usize = stats.edges_by_kind.values().sum();
+        assert!(undirected <= graph.graph.edge_count());
+        assert!(undirected > 0);
+        assert_eq!(stats.metric_histogram.total(), graph.metric_values().len());
+    }
+
+    #[test]
+    fn test_manual_edges_need_live_endpoints() {
+        let mut graph = graph_from_test_store();
+        let mut ids = graph.node_positions().into_keys();
+        let (a, b) = (ids.next().unwrap(), ids.next().unwrap());
+        let unknown = Uuid::new_v5(&Uuid::NAMESPACE_OID, b"not in the graph");
+
+        assert!(graph.add_manual_edge(a, unknown, EdgeKind::VirtualAdjacency, 5).is_err());
+        assert!(!graph.any_manual_changes());
+
+        graph.add_manual_edge(a, b, EdgeKind::VirtualAdjacency, 5).unwrap();
+        graph.supress_base_edge(a, b, EdgeKind::Membership);
+        assert!(graph.is_manual_edge(a, b, EdgeKind::VirtualAdjacency));
+
+        let manual_edges = |graph: &NetworkGraph| {
+            graph
+                .graph
+                .edges_iter()
+                .filter(|(_, e)| e.payload().kind == EdgeKind::VirtualAdjacency)
+                .count()
+        };
+        assert_eq!(manual_edges(&graph), 2);
+
+        // Reconcile without `b`, as a view filter would: the overlay outlives the hidden node
+        let nodes: Vec<Node> = graph.graph.nodes_iter().map(|(_, n)| n.payload().clone()).collect();
+        let filtered: Vec<Node> = nodes.iter().filter(|n| n.id != b).cloned().collect();
+        graph.reconcile(filtered);
+        assert!(graph.is_manual_edge(a, b, EdgeKind::VirtualAdjacency));
+        assert_eq!(graph.suppressed_edges().count(), 1);
+        assert_eq!(manual_edges(&graph), 0);
+
+        graph.reconcile(nodes);
+        assert_eq!(manual_edges(&graph), 2);
+    }
+
+    #[test]
+    fn test_step_layout_with_focus() {
+        // Same grid on both graphs instead of the random seeding, in node id order
+        let seed_grid = |graph: &mut NetworkGraph| -> NodeIndex {
+            let mut ids: Vec<Uuid> = graph.node_id_to_index_map.keys().copied().collect();
+            ids.sort();
+            for (i, id) in ids.iter().enumerate() {
+                let idx = graph.node_id_to_index_map[id];
+                let pos = Pos2::new((i % 5) as f32 * 40.0, (i / 5) as f32 * 40.0);
+                graph.graph.node_mut(idx).unwrap().set_location(pos);
+            }
+            graph.node_id_to_index_map[&ids[0]]
+        };
+        let mut graph = graph_from_test_store();
+        let mut baseline = graph_from_test_store();
+        let focus = seed_grid(&mut graph);
+        seed_grid(&mut baseline);
+        let centroid = |graph: &NetworkGraph| {
+            let sum = graph
+                .graph
+                .nodes_iter()
+                .fold(egui::Vec2::ZERO, |sum, (_, node)| sum + node.location().to_vec2());
+            (sum / graph.graph.node_count() as f32).to_pos2()
+        };
+        let start = centroid(&graph);
+
+        let mut state = FruchtermanReingoldWithCenterGravityState::default();
+        state.base.is_running = true;
+        let view_size = egui::vec2(800.0, 600.0);
+        // The same steps with the gravity left at the middle of the graph
+        let mut layout = FruchtermanReingoldWithCenterGravity::from_state(state.clone());
+        for _ in 0..100 {
+            layout.step(&mut baseline.graph, Rect::from_center_size(start, view_size));
+        }
+        let state = graph.step_layout_with_focus(state, focus, view_size, 100);
+        assert_eq!(state.base.step_count, 100);
+
+        // The graph drifts towards the focus node, the baseline stays put
+        let drift = centroid(&graph) - start;
+        let towards_focus = graph.graph.node(focus).unwrap().location() - start;
+        let baseline_drift = centroid(&baseline) - start;
+        assert!(drift.dot(towards_focus) > 0.0);
+        assert!(drift.length() > baseline_drift.length() * 2.0);
+    }
+
+    #[test]
+    fn test_node_degrees() {
+        let graph = graph_from_test_store();
+        let degrees = graph.node_degrees();
+        assert_eq!(degrees.len(), graph.graph.node_count());
+        for (_, edge) in graph.graph.edges_iter() {
+            let edge = edge.payload();
+            assert!(degrees[&edge.source_id] >= 1);
+            assert!(degrees[&edge.destination_id] >= 1);
+        }
+        // Derived edges come in pairs, the reverse half doesn't add a neighbor
+        let total: usize = degrees.values().sum();
+        assert!(total <= graph.graph.edge_count() * 2);
+    }
+
+    #[test]
+    fn test_metric_histogram_buckets() {
+        let histogram = MetricHistogram::from_values([10, 10, 12, 1, 65_535]);
+        assert_eq!((histogram.min, histogram.max), (Some(1), Some(65_535)));
+        assert_eq!(histogram.total(), 5);
+        assert_eq!(histogram.buckets.first().map(|b| (b.low, b.high)), Some((1, 1)));
+        assert_eq!(
+            histogram.buckets.last().copied(),
+            Some(MetricBucket { low: 32_768, high: 65_535, count: 1 })
+        );
+        let tens = histogram.buckets.iter().find(|b| b.low <= 10 && 10 <= b.high).unwrap();
+        assert_eq!(tens.count, 3);
+
+        assert!(MetricHistogram::from_values(std::iter::empty()).buckets.is_empty());
+        let top = MetricHistogram::from_values([u32::MAX]);
+        assert_eq!(top.buckets[0].high, u32::MAX);
+    }
+
+    #[test]
+    fn test_isis_membership_metric_label_and_cost() {
+        use crate::parsers::isis_parser::core_lsp::{
+            ExtendedIpReachabilityNeighbor, ExtendedIpReachabilityTlv, IsLevel, LspId, SystemId,
+        };
+
+        // Widest metric an Extended IP Reachability TLV can carry
+        const WIDE: u32 = 0xFF_FFFF;
+        let prefix = |p: &str, metric| ExtendedIpReachabilityNeighbor {
+            metric,
+            up_down: true,
+            prefix: p.parse().unwrap(),
+            mt_id: Default::default(),
+        };
+        let system_id = SystemId::from_string("0000.0000.0001").unwrap();
+        let router_id = RouterId::IsIs(system_id.clone());
+        let router = Node::new(
+            NodeInfo::Router(Router {
+                id: router_id.clone(),
+                interfaces: vec![],
+                protocol_data: Some(ProtocolData::IsIs(IsIsData {
+                    is_level: IsLevel::Level2,
+                    lsp_id: LspId::new_from(&system_id, 0, 0),
+                    net_address: None,
+                    flags: Default::default(),
+                    areas: Vec::new(),
+                    sequence_number: None,
+                    holdtime: None,
+                    // The prefix sits in the second TLV
+                    tlvs: vec![
+                        Tlv::ExtendedIpReachability(ExtendedIpReachabilityTlv {
+                            neighbors: vec![prefix("192.0.2.0/24", 10)],
+                        }),
+                        Tlv::ExtendedIpReachability(ExtendedIpReachabilityTlv {
+                            neighbors: vec![prefix("10.0.0.0/24", WIDE)],
+                        }),
+                    ],
+                })),
+                hostname: None,
+                description: None,
+            }),
+            None,
+        );
+        let network = Node::new(
+            NodeInfo::Network(Network {
+                ip_address: "10.0.0.0/24".parse().unwrap(),
+                protocol_data: None,
+                attached_routers: vec![router_id],
+                is_synthetic: false,
+            }),
+            None,
+        );
+        let (router_uuid, network_uuid) = (router.id, network.id);
+
+        let mut graph = NetworkGraph::build_new(
+            vec![router.clone(), network.clone()],
+            ReverseMetricPolicy::default(),
+        );
+        let r = graph.node_id_to_index_map[&router_uuid];
+        let n = graph.node_id_to_index_map[&network_uuid];
+        let edge = graph.graph.g().edges_connecting(r, n).next().unwrap();
+        let metric = &edge.weight().payload().metric;
+        assert!(matches!(metric, EdgeMetric::IsIs(WIDE)));
+        assert_eq!(metric.label().as_deref(), Some("IS-IS: 16777215"));
+
+        let no_nodes = HashSet::new();
+        let no_edges = HashSet::new();
+        let (cost, path) = graph.with_failed(&no_nodes, &no_edges).shortest_path(r, n).unwrap();
+        assert_eq!(cost, WIDE);
+        assert_eq!(path, vec![r, n]);
+
+        // The live rebuild path computes the same metric
+        graph.reconcile(vec![router, network]);
+        let r = graph.node_id_to_index_map[&router_uuid];
+        let n = graph.node_id_to_index_map[&network_uuid];
+        let edge = graph.graph.g().edges_connecting(r, n).next().unwrap();
+        assert!(matches!(edge.weight().payload().metric, EdgeMetric::IsIs(WIDE)));
+    }
+
+    #[test]
+    fn test_shortest_path_via_waypoints() {
+        let graph = graph_from_test_store();
+        let no_nodes = HashSet::new();
+        let no_edges = HashSet::new();
+        let view = graph.with_failed(&no_nodes, &no_edges);
+        let (direct_cost, direct) = multi_hop_path(&graph);
+        let (start, middle, end) = (direct[0], direct[1], *direct.last().unwrap());
+
+        // A waypoint already on the shortest path doesn't make it longer
+        let (cost, path) = view.shortest_path_via(&[start, middle, end]).unwrap();
+        assert_eq!(cost, direct_cost);
+        assert_eq!((path.first(), path.last()), (Some(&start), Some(&end)));
+        assert!(path.contains(&middle));
+
+        // Going there and back stitches both legs without repeating the turning point
+        let (back_cost, back) = view.shortest_path(end, start).unwrap();
+        let (cost, path) = view.shortest_path_via(&[start, end, start]).unwrap();
+        assert_eq!(cost, direct_cost + back_cost);
+        assert_eq!(path.len(), direct.len() + back.len() - 1);
+        assert_eq!(path.first(), Some(&start));
+        assert_eq!(path[direct.len() - 1], end);
+
+        assert!(view.shortest_path_via(&[start]).is_none());
+    }
+
+    #[test]
+    fn test_retain_path_edges() {
+        let mut graph = graph_from_test_store();
+        let (_, path) = multi_hop_path(&graph);
+        let uuids: Vec<Uuid> = path
+            .iter()
+            .map(|idx| graph.graph.node(*idx).unwrap().payload().id)
+            .collect();
+
+        graph.retain_path_edges(&uuids);
+        assert!(graph.graph.edge_count() >= path.len() - 1);
+        for (_, edge) in graph.graph.edges_iter() {
+            let edge = edge.payload();
+            let src = uuids.iter().position(|id| *id == edge.source_id).unwrap();
+            let dst = uuids.iter().position(|id| *id == edge.destination_id).unwrap();
+            assert_eq!(src.abs_diff(dst), 1);
+        }
+    }
+
+    #[test]
+    fn test_area_anchors() {
+        let center = Pos2::new(10.0, 10.0);
+        let areas: Vec<Ipv4Addr> = ["0.0.0.0", "0.0.0.1", "0.0.0.2"]
+            .iter()
+            .map(|a| a.parse().unwrap())
+            .collect();
+        let anchors = area_anchors(&areas, center, 100.0);
+
+        assert_eq!(anchors.len(), 3);
+        assert_eq!(anchors[&Ipv4Addr::UNSPECIFIED], center);
+        let (a, b) = (anchors[&areas[1]], anchors[&areas[2]]);
+        assert!((a.distance(center) - 100.0).abs() < 1e-3);
+        assert!((b.distance(center) - 100.0).abs() < 1e-3);
+        // Two outer areas sit on opposite sides of the backbone
+        assert!((a.distance(b) - 200.0).abs() < 1e-3);
+
+        // The test store spans areas 0 and 1, so gravity has something to pull apart
+        let mut graph = graph_from_test_store();
+        let before: Vec<Pos2> = graph.graph.nodes_iter().map(|(_, n)| n.location()).collect();
+        graph.apply_area_gravity(0.5);
+        let after: Vec<Pos2> = graph.graph.nodes_iter().map(|(_, n)| n.location()).collect();
+        assert_ne!(before, after);
+    }
+
+    #[test]
+    fn test_restore_layout() {
+        let mut graph = graph_from_test_store();
+        let mut positions = graph.node_positions();
+        let uuid = *positions.keys().next().unwrap();
+        positions.insert(uuid, Pos2::new(500.0, -500.0));
+        assert_eq!(graph.restore_layout(positions.clone()), positions.len());
+        assert_eq!(graph.node_positions(), positions);
+
+        // A fresh graph seeds nodes added by reconcile at their saved positions
+        let json = include_str!("../../test_data/test_store.json");
+        let store: TopologyStore = serde_json::from_str(json).unwrap();
+        let nodes = store.build_merged_view_with(&MergeConfig::default()).unwrap();
+        let mut fresh = NetworkGraph::default();
+        assert_eq!(fresh.restore_layout(positions.clone()), 0);
+        fresh.reconcile(nodes);
+        let fresh_positions = fresh.node_positions();
+        assert_eq!(fresh_positions[&uuid], Pos2::new(500.0, -500.0));
+        for (id, pos) in &fresh_positions {
+            if let Some(saved) = positions.get(id) {
+                assert_eq!(pos, saved);
+            }
+        }
+    }
+
+    #[test]
+    fn test_reverse_metric_policy() {
+        let json = include_str!("../../test_data/test_store.json");
+        let store: TopologyStore = serde_json::from_str(json).unwrap();
+        let nodes = store.build_merged_view_with(&MergeConfig::default()).unwrap();
+        let metrics = |policy: ReverseMetricPolicy| {
+            let mut graph = NetworkGraph::default();
+            graph.set_reverse_metric_policy(policy);
+            graph.reconcile(nodes.clone());
+            graph
+                .graph
+                .edges_iter()
+                .map(|(_, e)| {
+                    let edge = e.payload();
+                    ((edge.source_id, edge.destination_id, edge.kind), edge.metric.label())
+                })
+                .collect::<HashMap<_, _>>()
+        };
+        let strict = metrics(ReverseMetricPolicy::Strict);
+        let mirror = metrics(ReverseMetricPolicy::Mirror);
+        assert_eq!(strict.len(), mirror.len());
+
+        for (&(src, dst, kind), metric) in &mirror {
+            // Mirroring only fills in missing reverse metrics, advertised ones are kept
+            if strict[&(src, dst, kind)].is_some() {
+                assert_eq!(metric, &strict[&(src, dst, kind)]);
+            }
+            let Some(reverse) = mirror.get(&(dst, src, kind)) else {
+                continue;
+            };
+            if metric.is_some() {
+                assert!(reverse.is_some(), "{:?} {} -> {} has no metric", kind, dst, src);
+            }
+        }
+    }
+
+    #[test]
+    fn test_collapse_areas() {
+        let mut graph = graph_from_test_store();
+        let backbone = AreaKey::Ospf(Ipv4Addr::UNSPECIFIED);
+        let area_1 = AreaKey::Ospf(Ipv4Addr::new(0, 0, 0, 1));
+        let area_1_only: Vec<Uuid> = graph
+            .graph
+            .nodes_iter()
+            .map(|(_, n)| n.payload())
+            .filter(|n| AreaKey::of(n) == vec![area_1.clone()])
+            .map(|n| n.id)
+            .collect();
+        assert!(!area_1_only.is_empty());
+
+        // Pseudonyms are for routers and prefixes, an area keeps its own name
+        graph.set_anonymize(true);
+        graph.collapse_areas(&HashSet::new());
+        let collapsed: Vec<&AreaKey> = graph
+            .graph
+            .nodes_iter()
+            .filter_map(|(_, n)| graph.collapsed_area(&n.payload().id))
+            .collect();
+        assert_eq!(collapsed.len(), 2);
+        assert_eq!(graph.stats().collapsed_areas, 2);
+        let area_1_node = graph.graph.node(graph.node_id_to_index_map[&area_1.node_id()]).unwrap();
+        assert!(matches!(&area_1_node.payload().info, NodeInfo::Area(area) if area.name == "Area 0.0.0.1"));
+        assert!(area_1_node.label().starts_with("Area 0.0.0.1 ("), "{}", area_1_node.label());
+        for (_, node) in graph.graph.nodes_iter() {
+            let id = node.payload().id;
+            assert!(graph.collapsed_area(&id).is_some() || AreaKey::of(node.payload()).is_empty());
+        }
+        for (_, edge) in graph.graph.edges_iter() {
+            let edge = edge.payload();
+            assert_ne!(edge.source_id, edge.destination_id);
+            assert!(graph.node_id_to_index_map.contains_key(&edge.source_id));
+            assert!(graph.node_id_to_index_map.contains_key(&edge.destination_id));
+        }
+        // The two areas are joined through the ABRs, which went into the backbone
+        assert!(graph.graph.edges_iter().any(|(_, e)| {
+            let e = e.payload();
+            graph.collapsed_area(&e.source_id) == Some(&backbone)
+                && graph.collapsed_area(&e.destination_id) == Some(&area_1)
+        }));
+
+        // Reconciling expands everything again, expanding area 1 keeps its nodes
+        let nodes: Vec<Node> = graph_from_test_store()
+            .graph
+            .nodes_iter()
+            .map(|(_, n)| n.payload().clone())
+            .collect();
+        graph.reconcile(nodes);
+        graph.collapse_areas(&HashSet::from([area_1]));
+        for id in &area_1_only {
+            assert!(graph.node_id_to_index_map.contains_key(id));
+        }
+        assert!(graph.node_id_to_index_map.contains_key(&backbone.node_id()));
+    }
+}
@@ -1,9 +1,9 @@
 use std::{collections::HashMap, net::Ipv4Addr};
 
-use crate::{network::router::{Router, RouterId}, parsers::isis_parser::core_lsp::{IsLevel, LspId, NetAddress, Tlv}};
+use crate::{network::router::{Router, RouterId}, parsers::isis_parser::core_lsp::{IsLevel, LspFlags, LspId, NetAddress, Tlv}};
 use ipnetwork::IpNetwork;
 use nom_derive::Parse;
-use ospf_parser::OspfLinkStateAdvertisement;
+use ospf_parser::{OspfLinkStateAdvertisement, OspfLinkStateAdvertisementHeader};
 use serde::{Deserialize, Serialize, ser::SerializeStruct};
 use uuid::Uuid;
 
@@ -14,6 +14,15 @@ pub struct Node {
     pub label: Option<String>,
     pub source_id: Option<RouterId>,
     pub id: Uuid,
+    /// Set in the un-fused view, where every source gets its own copy of shared routers and
+    /// networks. The node then refers to other nodes by their copy from the same source.
+    #[serde(default)]
+    pub source_scoped: bool,
+}
+
+/// UUID of the copy of node `id` reported by `source`, used by the un-fused view.
+pub fn source_scoped_uuid(id: Uuid, source: &RouterId) -> Uuid {
+    Uuid::new_v5(&id, source.to_string().as_bytes())
 }
 
 impl Node {
@@ -24,12 +33,38 @@ impl Node {
                 &Uuid::NAMESPACE_OID,
                 network.ip_address.to_string().as_bytes(),
             ),
+            NodeInfo::Area(area) => Uuid::new_v5(
+                &Uuid::NAMESPACE_OID,
+                format!("collapsed {}", area.name).as_bytes(),
+            ),
         };
         Self {
             info,
             label,
             source_id: None,
             id: uuid,
+            source_scoped: false,
+        }
+    }
+
+    /// This node as its source alone reported it, under its own UUID and with `label`.
+    /// Nodes without a source are returned unchanged.
+    pub fn into_source_scoped(mut self, label: String) -> Self {
+        let Some(source) = &self.source_id else {
+            return self;
+        };
+        self.id = source_scoped_uuid(self.id, source);
+        self.label = Some(label);
+        self.source_scoped = true;
+        self
+    }
+
+    /// UUID under which this node finds the node `id` it refers to: the copy from its own
+    /// source when it is source-scoped, `id` itself otherwise.
+    pub fn peer_uuid(&self, id: Uuid) -> Uuid {
+        match &self.source_id {
+            Some(source) if self.source_scoped => source_scoped_uuid(id, source),
+            _ => id,
         }
     }
 
@@ -50,6 +85,18 @@ impl Node {
                 // Optional future logic: if router is ABR (multiple areas or has summary LSAs)
                 false
             }
+            NodeInfo::Area(_) => false,
+        }
+    }
+
+    /// IS-IS router that set the Overload bit in its LSP.
+    pub fn is_isis_overloaded(&self) -> bool {
+        match &self.info {
+            NodeInfo::Router(router) => matches!(
+                &router.protocol_data,
+                Some(ProtocolData::IsIs(data)) if data.flags.overload
+            ),
+            NodeInfo::Network(_) | NodeInfo::Area(_) => false,
         }
     }
 }
@@ -58,6 +105,19 @@ impl Node {
 pub enum NodeInfo {
     Router(Router),
     Network(Network),
+    /// Stand-in for a whole area in the collapsed view, never reported by a source
+    Area(CollapsedArea),
+}
+
+/// The routers and networks of one area, collapsed into a single node by
+/// `NetworkGraph::collapse_areas`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollapsedArea {
+    /// Display name, "Area 0.0.0.1" or "IS-IS area 49.0001"
+    pub name: String,
+    pub routers: Vec<RouterId>,
+    /// Routers and networks that went into this node
+    pub members: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -66,6 +126,45 @@ pub struct Network {
     pub ip_address: IpNetwork,
     pub protocol_data: Option<ProtocolData>,
     pub attached_routers: Vec<RouterId>,
+    /// True for networks inferred from Router-LSA stub links rather than advertised by the protocol.
+    #[serde(default)]
+    pub is_synthetic: bool,
+}
+
+impl Network {
+    /// True if this network is an OSPF external route (AS-External Type-5 or NSSA Type-7).
+    pub fn is_external(&self) -> bool {
+        match &self.protocol_data {
+            Some(ProtocolData::Ospf(data)) => matches!(
+                *data.advertisement,
+                OspfLinkStateAdvertisement::ASExternalLink(_)
+                    | OspfLinkStateAdvertisement::NSSAASExternal(_)
+            ),
+            _ => false,
+        }
+    }
+
+    /// True for an IS-IS pseudonode network whose prefix couldn't be resolved (it keeps the
+    /// 0.0.0.0/32 placeholder).
+    pub fn is_unresolved(&self) -> bool {
+        matches!(self.protocol_data, Some(ProtocolData::IsIs(_)))
+            && self.ip_address.prefix() == 32
+            && self.ip_address.ip().is_unspecified()
+    }
+
+    /// Designated router of an OSPF transit network as `(router ID, interface address)`.
+    /// The DR originates the Network-LSA, whose link state ID is the DR's interface address.
+    /// The LSDB doesn't record the BDR, so there's no counterpart for it.
+    pub fn ospf_designated_router(&self) -> Option<(RouterId, RouterId)> {
+        let Some(ProtocolData::Ospf(data)) = &self.protocol_data else {
+            return None;
+        };
+        let OspfPayload::Network(payload) = &data.payload else {
+            return None;
+        };
+        let interface = payload.designated_router_id.clone()?;
+        Some((RouterId::Ipv4(data.advertising_router), interface))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -92,7 +191,12 @@ pub struct OspfRouterPayload {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OspfVirtualLink {
     pub peer_router_id: crate::network::router::RouterId,
+    /// Area the link is tunnelled through. The backbone Router-LSA carrying the link doesn't name
+    /// it, so it stays unspecified until fusion finds the area whose Router-LSA has the V bit set.
     pub transit_area_id: std::net::Ipv4Addr,
+    /// Cost of the intra-area path to the peer through the transit area.
+    #[serde(default)]
+    pub metric: u16,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -100,7 +204,10 @@ pub struct PerAreaRouterFacet {
     pub area_id: Ipv4Addr,
     pub p2p_link_count: usize,
     pub transit_link_count: usize,
-    pub stub_link_count: usize
+    pub stub_link_count: usize,
+    /// V bit of this area's Router-LSA: the area is the transit area of a virtual link.
+    #[serde(default)]
+    pub is_virtual_link_transit: bool,
 }
 
 impl OspfRouterPayload {
@@ -196,6 +303,35 @@ pub struct OspfDataWire {
     pub lsa_hex: String
 }
 
+impl OspfData {
+    pub fn header(&self) -> &OspfLinkStateAdvertisementHeader {
+        match self.advertisement.as_ref() {
+            OspfLinkStateAdvertisement::RouterLinks(ad) => &ad.header,
+            OspfLinkStateAdvertisement::NetworkLinks(ad) => &ad.header,
+            OspfLinkStateAdvertisement::SummaryLinkIpNetwork(ad) => &ad.header,
+            OspfLinkStateAdvertisement::SummaryLinkAsbr(ad) => &ad.header,
+            OspfLinkStateAdvertisement::ASExternalLink(ad) => &ad.header,
+            OspfLinkStateAdvertisement::NSSAASExternal(ad) => &ad.header,
+            OspfLinkStateAdvertisement::OpaqueLinkLocalScope(ad) => &ad.header,
+            OspfLinkStateAdvertisement::OpaqueAreaLocalScope(ad) => &ad.header,
+            OspfLinkStateAdvertisement::OpaqueASWideScope(ad) => &ad.header,
+        }
+    }
+
+    /// LS age in seconds when the LSA was fetched, without the DoNotAge bit (RFC 1793).
+    pub fn age(&self) -> u16 {
+        self.header().ls_age & 0x7fff
+    }
+
+    pub fn sequence_number(&self) -> u32 {
+        self.header().ls_seq_number
+    }
+
+    pub fn near_expiry(&self) -> bool {
+        OSPF_MAX_AGE.saturating_sub(self.age()) < EXPIRY_WARNING_SECS
+    }
+}
+
 impl Serialize for OspfData {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -243,6 +379,30 @@ pub struct IsIsData {
     pub lsp_id: LspId,
     pub net_address: Option<NetAddress>,
     pub tlvs: Vec<Tlv>,
+    #[serde(default)]
+    pub flags: LspFlags,
+    /// IS-IS areas the node was seen in, see `Lsp::areas`
+    #[serde(default)]
+    pub areas: Vec<String>,
+    #[serde(default)]
+    pub sequence_number: Option<String>,
+    /// Remaining lifetime of the LSP in seconds when it was fetched
+    #[serde(default)]
+    pub holdtime: Option<u16>,
+}
+
+/// Remaining lifetime below which an LSP or LSA is shown as about to be purged. Routers refresh
+/// their own advertisements long before this, so one this close to expiry has stopped being
+/// refreshed.
+pub const EXPIRY_WARNING_SECS: u16 = 60;
+
+/// OSPF MaxAge (RFC 2328), an LSA of this age is flushed from the LSDB.
+pub const OSPF_MAX_AGE: u16 = 3600;
+
+impl IsIsData {
+    pub fn near_expiry(&self) -> bool {
+        self.holdtime.is_some_and(|secs| secs < EXPIRY_WARNING_SECS)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,182 @@
+use std::collections::{BTreeSet, HashMap};
+
+use uuid::Uuid;
+
+use crate::network::node::{Node, NodeInfo, ProtocolData};
+use crate::parsers::isis_parser::core_lsp::Tlv;
+
+/// Stand-in names (R1, R2, N1, ...) for sharing screenshots without real addresses or hostnames.
+///
+/// A node gets its pseudonym the first time it is seen and keeps it for as long as this value
+/// lives, so toggling anonymization or reloading the graph doesn't reshuffle them. Only display
+/// text goes through here, the nodes themselves are never changed.
+#[derive(Debug, Clone, Default)]
+pub struct Pseudonyms {
+    names: HashMap<Uuid, String>,
+    routers: usize,
+    networks: usize,
+    /// Real identifier text (router id, hostname, prefix, interface address) -> pseudonym
+    replacements: HashMap<String, String>,
+    /// Distinct lengths of the `replacements` keys, so `scrub` can look candidates up directly
+    lengths: BTreeSet<usize>,
+}
+
+impl Pseudonyms {
+    /// Give every node in `nodes` without a pseudonym one. New ones are numbered in router id /
+    /// prefix order, so a fresh session numbers the same topology the same way.
+    pub fn register_all<'a>(&mut self, nodes: impl IntoIterator<Item = &'a Node>) {
+        let mut new: Vec<(bool, String, &Node)> = nodes
+            .into_iter()
+            .filter(|node| !self.names.contains_key(&node.id))
+            .filter_map(|node| match &node.info {
+                NodeInfo::Router(router) => Some((false, router.id.to_string(), node)),
+                NodeInfo::Network(network) => Some((true, network.ip_address.to_string(), node)),
+                NodeInfo::Area(_) => None,
+            })
+            .collect();
+        new.sort_by(|a, b| (a.0, &a.1).cmp(&(b.0, &b.1)));
+        for (_, _, node) in new {
+            self.register(node);
+        }
+    }
+
+    /// Pseudonym of `node`, assigning one if it has none yet. Collapsed areas keep their label.
+    pub fn label_for(&mut self, node: &Node) -> String {
+        if let NodeInfo::Area(area) = &node.info {
+            return node.label.clone().unwrap_or_else(|| area.name.clone());
+        }
+        self.register(node);
+        self.names[&node.id].clone()
+    }
+
+    fn register(&mut self, node: &Node) {
+        if self.names.contains_key(&node.id) {
+            return;
+        }
+        let name = match &node.info {
+            NodeInfo::Router(router) => {
+                self.routers += 1;
+                let name = format!("R{}", self.routers);
+                self.replace(router.id.to_string(), &name);
+                if let Some(hostname) = &router.hostname {
+                    self.replace(hostname.clone(), &name);
+                }
+                if let Some(ProtocolData::IsIs(data)) = &router.protocol_data {
+                    for tlv in &data.tlvs {
+                        if let Tlv::Hostname(hostname) = tlv {
+                            self.replace(hostname.clone(), &name);
+                        }
+                    }
+                }
+                for (i, interface) in router.interfaces.iter().enumerate() {
+                    self.replace(interface.to_string(), &format!("{}-if{}", name, i + 1));
+                }
+                name
+            }
+            NodeInfo::Network(network) => {
+                self.networks += 1;
+                let name = format!("N{}", self.networks);
+                self.replace(network.ip_address.to_string(), &name);
+                name
+            }
+            NodeInfo::Area(_) => return,
+        };
+        if let Some(label) = &node.label {
+            self.replace(label.clone(), &name);
+        }
+        self.names.insert(node.id, name);
+    }
+
+    /// First pseudonym registered for a piece of text wins; IS-IS pseudonodes share a
+    /// placeholder prefix, for one.
+    fn replace(&mut self, real: String, pseudonym: &str) {
+        if !real.is_empty() {
+            self.lengths.insert(real.len());
+            self.replacements.entry(real).or_insert_with(|| pseudonym.to_string());
+        }
+    }
+
+    /// `text` with every known identifier swapped for its pseudonym. Identifiers only match as
+    /// whole words, so 10.0.0.1 is left alone inside 10.0.0.12.
+    pub fn scrub(&self, text: &str) -> String {
+        let is_word = |c: char| c.is_alphanumeric() || c == '-' || c == '_';
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text;
+        'outer: while let Some(c) = rest.chars().next() {
+            let preceded_by_word = out.chars().next_back().is_some_and(is_word);
+            if !preceded_by_word {
+                // Longest first, a prefix like 10.0.0.0/24 must win over the address 10.0.0.0
+                for &len in self.lengths.iter().rev() {
+                    let Some(pseudonym) = rest.get(..len).and_then(|real| self.replacements.get(real))
+                    else {
+                        continue;
+                    };
+                    if !rest[len..].chars().next().is_some_and(is_word) {
+                        out.push_str(pseudonym);
+                        rest = &rest[len..];
+                        continue 'outer;
+                    }
+                }
+            }
+            out.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr};
+
+    use super::*;
+    use crate::network::{
+        node::Network,
+        router::{Router, RouterId},
+    };
+
+    fn router(last_octet: u8, hostname: Option<&str>) -> Node {
+        Node::new(
+            NodeInfo::Router(Router {
+                id: RouterId::Ipv4(Ipv4Addr::new(10, 0, 0, last_octet)),
+                interfaces: vec![IpAddr::V4(Ipv4Addr::new(172, 16, 0, last_octet))],
+                protocol_data: None,
+                hostname: hostname.map(str::to_string),
+                description: None,
+            }),
+            None,
+        )
+    }
+
+    #[test]
+    fn test_pseudonyms_are_stable() {
+        let r12 = router(12, None);
+        let r1 = router(1, Some("core-1"));
+        let network = Node::new(
+            NodeInfo::Network(Network {
+                ip_address: "10.0.0.0/24".parse().unwrap(),
+                protocol_data: None,
+                attached_routers: vec![],
+                is_synthetic: false,
+            }),
+            None,
+        );
+
+        let mut pseudonyms = Pseudonyms::default();
+        pseudonyms.register_all([&r12, &network, &r1]);
+        assert_eq!(pseudonyms.label_for(&r1), "R1");
+        assert_eq!(pseudonyms.label_for(&r12), "R2");
+        assert_eq!(pseudonyms.label_for(&network), "N1");
+
+        // Known nodes keep their pseudonym, new ones are numbered after them
+        let r3 = router(3, None);
+        pseudonyms.register_all([&r3, &r1]);
+        assert_eq!(pseudonyms.label_for(&r3), "R3");
+        assert_eq!(pseudonyms.label_for(&r1), "R1");
+
+        assert_eq!(
+            pseudonyms.scrub("core-1 (10.0.0.1) via 172.16.0.12 in 10.0.0.0/24, not 10.0.0.100"),
+            "R1 (R1) via R2-if1 in N1, not 10.0.0.100"
+        );
+    }
+}
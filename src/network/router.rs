@@ -1,12 +1,13 @@
 use std::{
     fmt::Display,
     net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    time::Duration,
 };
 
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::{network::node::ProtocolData, parsers::isis_parser::core_lsp::{SystemId}};
+use crate::{network::node::{Node, NodeInfo, ProtocolData}, parsers::isis_parser::core_lsp::{SystemId}};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct InterfaceStats {
@@ -17,7 +18,34 @@ pub struct InterfaceStats {
     pub rx_packets: Option<u64>,
 }
 
+/// Per-second throughput derived from two consecutive `InterfaceStats` samples.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct InterfaceRates {
+    pub rx_bps: Option<f64>,
+    pub tx_bps: Option<f64>,
+    pub rx_pps: Option<f64>,
+    pub tx_pps: Option<f64>,
+}
+
 impl InterfaceStats {
+    /// Throughput since `previous` was sampled `elapsed` ago. A counter that went backwards
+    /// (wrap or device reset) or is missing in either sample yields `None` for that rate.
+    pub fn rates_since(&self, previous: &InterfaceStats, elapsed: Duration) -> InterfaceRates {
+        let secs = elapsed.as_secs_f64();
+        if secs <= 0.0 {
+            return InterfaceRates::default();
+        }
+        let per_second = |now: Option<u64>, before: Option<u64>| {
+            now?.checked_sub(before?).map(|delta| delta as f64 / secs)
+        };
+        InterfaceRates {
+            rx_bps: per_second(self.rx_bytes, previous.rx_bytes).map(|v| v * 8.0),
+            tx_bps: per_second(self.tx_bytes, previous.tx_bytes).map(|v| v * 8.0),
+            rx_pps: per_second(self.rx_packets, previous.rx_packets),
+            tx_pps: per_second(self.tx_packets, previous.tx_packets),
+        }
+    }
+
     pub fn get_weight(&self) -> u64 {
         let tx_bytes = self.tx_bytes.unwrap_or(0);
         let rx_bytes = self.rx_bytes.unwrap_or(0);
@@ -133,6 +161,34 @@ pub struct Router {
     pub id: RouterId,
     pub interfaces: Vec<IpAddr>,
     pub protocol_data: Option<ProtocolData>,
+    /// Device hostname reported by the acquisition source (e.g. SNMP sysName), if any.
+    #[serde(default)]
+    pub hostname: Option<String>,
+    /// Free-form device description reported by the acquisition source (e.g. SNMP sysDescr).
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Device identity reported by an acquisition source for the router it is polling.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SystemInfo {
+    pub router_id: RouterId,
+    pub hostname: Option<String>,
+    pub description: Option<String>,
+}
+
+impl SystemInfo {
+    /// Attach hostname/description to the router node matching `router_id`, if present.
+    pub fn apply(&self, nodes: &mut [Node]) {
+        for node in nodes {
+            if let NodeInfo::Router(router) = &mut node.info
+                && router.id == self.router_id
+            {
+                router.hostname = self.hostname.clone();
+                router.description = self.description.clone();
+            }
+        }
+    }
 }
 
 impl Display for Router {
@@ -147,6 +203,9 @@ impl Display for Router {
                 .collect::<Vec<_>>()
                 .join(", ")
         )?;
+        if let Some(hostname) = &self.hostname {
+            write!(f, "\nHostname: {}", hostname)?;
+        }
         if let Some(data) = &self.protocol_data {
             write!(f, "\nProtocol Data: {}", data)?;
         }
@@ -8,6 +8,8 @@ use ipnetwork::IpNetwork;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::logging::debug_log;
+
 #[derive(Error, Debug)]
 pub enum LspError {
     #[error("Invalid system ID: {0}")]
@@ -39,12 +41,20 @@ pub struct Lsp {
     pub is_level: IsLevel,
     /// The sequence number of the LSP (used for versioning).
     pub sequence_number: Option<String>,
-    /// Remaining lifetime of the LSP in seconds.
+    /// Remaining lifetime of the LSP in seconds, as the hex of its native-endian bytes.
+    /// Use `holdtime_secs()` to read it.
     pub holdtime: Option<String>,
     /// Whatever that means...
     pub area_addr: Option<AreaAddress>,
     /// The list of TLVs (Type-Length-Value) contained in this LSP.
     pub tlvs: Vec<Tlv>,
+    /// Attached, Partition repair and Overload bits from the LSP header.
+    #[serde(default)]
+    pub flags: LspFlags,
+    /// Names of the IS-IS areas whose LSPDB this LSP was found in. Routers taking part in
+    /// several areas (L2 backbone routers) carry all of them.
+    #[serde(default)]
+    pub areas: Vec<String>,
 }
 
 impl Lsp {
@@ -64,30 +74,43 @@ impl Lsp {
             sequence_number,
             holdtime,
             area_addr,
-            tlvs
+            tlvs,
+            flags: LspFlags::default(),
+            areas: Vec::new(),
         }
     }
+
+    pub fn with_flags(mut self, flags: LspFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Remaining lifetime in seconds, `None` if the source didn't report one.
+    pub fn holdtime_secs(&self) -> Option<u16> {
+        let bytes = hex::decode(self.holdtime.as_ref()?).ok()?;
+        Some(u16::from_ne_bytes(bytes.try_into().ok()?))
+    }
     
     pub fn get_net_address(&self) -> Option<NetAddress> {
-        println!("get_net_address called");
+        debug_log!("get_net_address called");
         if let Some(Tlv::AreaAddresses(t)) = self.get_tlvs_by(|t| matches!(t, Tlv::AreaAddresses(_))).first() {
-            println!("AreaAddresses TLV found");
+            debug_log!("AreaAddresses TLV found");
             t.addresses.first().map(|area| {
                 NetAddress { area_address: area.clone(), system_id: self.system_id.clone() }
             })
         } else if let Some(area_addr) = &self.area_addr {
-            println!("AreaName found");
+            debug_log!("AreaName found");
             let net_addr_string = format!("{}.{}", area_addr, self.system_id);
             let net = NetAddress::from_str(&net_addr_string).ok();
             
             if net.is_none() {
-                println!("Invalid NetAddress: {}", &net_addr_string);
+                eprintln!("[core_lsp] Invalid NetAddress: {}", &net_addr_string);
             }
             
             net
             
         } else {
-            println!("No area address found");
+            debug_log!("No area address found");
             None
         }
     }
@@ -221,7 +244,10 @@ pub struct ExtendedIpReachabilityNeighbor {
     pub metric: u32,
     /// Up is true, down is false
     pub up_down: bool,
-    pub prefix: IpNetwork
+    pub prefix: IpNetwork,
+    /// Topology this prefix is advertised in.
+    #[serde(default)]
+    pub mt_id: MtId,
 }
 
 impl ExtendedIpReachabilityNeighbor {
@@ -229,7 +255,57 @@ impl ExtendedIpReachabilityNeighbor {
         Self {
             prefix,
             metric,
-            up_down: is_up
+            up_down: is_up,
+            mt_id: MtId::Standard,
+        }
+    }
+
+    pub fn with_mt_id(mut self, mt_id: MtId) -> Self {
+        self.mt_id = mt_id;
+        self
+    }
+}
+
+/// Multi-topology ID (RFC 5120) of a reachability entry.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum MtId {
+    /// MT 0, the standard topology. FRR reports plain TLV 22/135 entries as "Extended".
+    #[default]
+    Standard,
+    Ipv4Management,
+    Ipv6Unicast,
+    Ipv4Multicast,
+    Ipv6Multicast,
+    Ipv6Management,
+    Other(String),
+}
+
+impl MtId {
+    /// Parse the `mtId` string from FRR JSON output ("Extended", "IPv6 unicast", "ipv6-unicast", ...).
+    pub fn from_frr(mt_id: &str) -> Self {
+        let normalized = mt_id.trim().to_lowercase().replace(['-', '_'], " ");
+        match normalized.as_str() {
+            "" | "extended" | "standard" | "ipv4 unicast" => MtId::Standard,
+            "ipv4 mgmt" | "ipv4 management" => MtId::Ipv4Management,
+            "ipv6 unicast" => MtId::Ipv6Unicast,
+            "ipv4 multicast" => MtId::Ipv4Multicast,
+            "ipv6 multicast" => MtId::Ipv6Multicast,
+            "ipv6 mgmt" | "ipv6 management" => MtId::Ipv6Management,
+            _ => MtId::Other(mt_id.to_string()),
+        }
+    }
+}
+
+impl Display for MtId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MtId::Standard => write!(f, "Standard (MT 0)"),
+            MtId::Ipv4Management => write!(f, "IPv4 management"),
+            MtId::Ipv6Unicast => write!(f, "IPv6 unicast"),
+            MtId::Ipv4Multicast => write!(f, "IPv4 multicast"),
+            MtId::Ipv6Multicast => write!(f, "IPv6 multicast"),
+            MtId::Ipv6Management => write!(f, "IPv6 management"),
+            MtId::Other(name) => write!(f, "{}", name),
         }
     }
 }
@@ -293,6 +369,9 @@ pub struct ExtendedIsNeighbor {
     pub metric: u32,
     /// Pseudonode ID, if this is equal to 0 it's not a pseudonode.
     pub pseudonode_id: u8,
+    /// Topology this adjacency belongs to.
+    #[serde(default)]
+    pub mt_id: MtId,
 }
 
 /// Represents a neighbor relationship (adjacency) between routers.
@@ -352,7 +431,7 @@ impl LspId {
 
     /// Extracts the System ID (first 6 bytes) from the LSP ID.
     pub fn get_system_id(&self) -> Result<SystemId, LspError> {
-        println!("LspId::get_system_id returns {:?}", &self.raw_id[0..6]);
+        debug_log!("LspId::get_system_id returns {:?}", &self.raw_id[0..6]);
         SystemId::new(
             &self.raw_id[0..6]
         )
@@ -461,7 +540,7 @@ impl Display for SystemId {
 
 impl SystemId {
     pub fn new(id_bytes: &[u8]) -> Result<Self, LspError> {
-        println!("SystemId::new received {:?}", id_bytes);
+        debug_log!("SystemId::new received {:?}", id_bytes);
         if id_bytes.len() != 6 {
             Err(LspError::InvalidSystemId(format!("{:?}", id_bytes)))
         } else {
@@ -536,6 +615,56 @@ impl Display for AreaAddress {
     }
 }
 
+impl AreaAddress {
+    /// Parse the dotted hex notation, e.g. "49.0001". Odd-length groups get a leading zero.
+    pub fn from_dotted(value: &str) -> Option<Self> {
+        let mut raw_address = Vec::new();
+        for part in value.split('.') {
+            let part = if part.len() % 2 != 0 {
+                format!("0{}", part)
+            } else {
+                part.to_string()
+            };
+            raw_address.append(&mut hex::decode(part).ok()?);
+        }
+        Some(Self { raw_address })
+    }
+}
+
+/// ATT/P/OL bits of an LSP header.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LspFlags {
+    /// Attached to another area, L1 routers use it as their default exit
+    pub attached: bool,
+    /// Supports partition repair
+    pub partition_repair: bool,
+    /// Overloaded, other routers don't route transit traffic through it
+    pub overload: bool,
+}
+
+impl LspFlags {
+    /// Parse FRR's `attPOl` field, e.g. "0/0/1".
+    pub fn from_att_p_ol(value: &str) -> Result<Self, LspError> {
+        let bad_format = || LspError::BadDataFormat("attPOl".to_string(), value.to_string());
+        let bits: Vec<bool> = value
+            .split('/')
+            .map(|bit| match bit.trim() {
+                "0" => Ok(false),
+                "1" => Ok(true),
+                _ => Err(bad_format()),
+            })
+            .collect::<Result<_, _>>()?;
+        match bits[..] {
+            [attached, partition_repair, overload] => Ok(Self {
+                attached,
+                partition_repair,
+                overload,
+            }),
+            _ => Err(bad_format()),
+        }
+    }
+}
+
 /// Indicates the IS-IS level at which an LSP or adjacency operates.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum IsLevel {
@@ -557,4 +686,14 @@ mod tests {
         assert!(id.is_ok());
         _ = dbg!(id);
     }
+
+    #[test]
+    fn test_lsp_flags_from_att_p_ol() {
+        let flags = LspFlags::from_att_p_ol("0/0/1").unwrap();
+        assert!(flags.overload);
+        assert!(!flags.attached && !flags.partition_repair);
+        assert!(LspFlags::from_att_p_ol("1/0/0").unwrap().attached);
+        assert!(LspFlags::from_att_p_ol("0/0").is_err());
+        assert!(LspFlags::from_att_p_ol("0/2/0").is_err());
+    }
 }
@@ -49,11 +49,14 @@ JSON structure:
 */
 
 use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read};
 
+use flate2::read::GzDecoder;
 use ipnetwork::IpNetwork;
 use serde::Deserialize;
 
-use crate::parsers::isis_parser::{core_lsp::{AreaAddress, AreaAddressesTlv, ExtendedIpReachabilityNeighbor, ExtendedIpReachabilityTlv, ExtendedIsNeighbor, IsExtendedReachabilityTlv, IsLevel, Lsp, LspError, LspId, RouterCapabilityTlv, SystemId, Tlv}, hostname::HostnameMap};
+use crate::logging::debug_log;
+use crate::parsers::isis_parser::{core_lsp::{AreaAddress, AreaAddressesTlv, ExtendedIpReachabilityNeighbor, ExtendedIpReachabilityTlv, ExtendedIsNeighbor, IsExtendedReachabilityTlv, IsLevel, Lsp, LspError, LspFlags, LspId, MtId, RouterCapabilityTlv, SystemId, Tlv}, hostname::HostnameMap};
 
 #[derive(Debug, Deserialize)]
 pub struct JsonLspdb {
@@ -64,19 +67,35 @@ impl JsonLspdb {
     pub fn from_string(json: &str) -> Result<Self, serde_json::Error> {
         serde_json::from_str(json)
     }
+
+    /// Read an LSPDB dump from plain JSON or a gzip-compressed capture (`.json.gz`),
+    /// detected from the gzip magic bytes.
+    #[allow(dead_code)]
+    pub fn from_reader(reader: impl Read) -> Result<Self, serde_json::Error> {
+        const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+        let mut reader = BufReader::new(reader);
+        let is_gzip = reader
+            .fill_buf()
+            .map_err(serde_json::Error::io)?
+            .starts_with(&GZIP_MAGIC);
+        if is_gzip {
+            serde_json::from_reader(GzDecoder::new(reader))
+        } else {
+            serde_json::from_reader(reader)
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
 pub struct JsonArea {
     #[serde(rename = "area")]
-    #[allow(dead_code)]
     pub area_props: JsonAreaProps,
     pub levels: Vec<JsonLevel>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct JsonAreaProps {
-    #[allow(dead_code)]
     pub name: Option<String>,
 }
 
@@ -99,7 +118,6 @@ pub struct JsonLsp {
     chksum: String,
     holdtime: u16,
     #[serde(rename = "attPOl")]
-    #[allow(dead_code)]
     att_p_ol_flags: String,
     // TLVs below
     #[serde(rename = "supportedProtocols")]
@@ -124,26 +142,7 @@ pub struct JsonLsp {
 
 impl JsonLsp {
     pub fn get_area_address(&self) -> Option<AreaAddress> {
-        if let Some(area_address) = &self.area_addr {
-            let string_parts: Vec<String> = area_address
-                .split(".")
-                .map(|part| {
-                    if part.len() % 2 != 0 {
-                        format!("0{}", part)
-                    } else {
-                        part.to_string()
-                    }          
-                })
-                .collect();
-            let mut raw: Vec<u8> = Vec::new();
-            for part in string_parts {
-                let mut bytes = hex::decode(part).ok()?;
-                raw.append(&mut bytes);
-            }
-            Some(AreaAddress { raw_address: raw })
-        } else {
-            None
-        }
+        AreaAddress::from_dotted(self.area_addr.as_ref()?)
     }
     pub fn try_into_lsp(self, is_level: u32, hostname_map: &HostnameMap) -> Result<Lsp, LspError> {
         let hostname = if let Some(hostname) = self.hostname.as_ref() {
@@ -153,7 +152,9 @@ impl JsonLsp {
             let parts: Vec<&str> = self.id_section.id.split(".").collect();
             parts.get(0).ok_or(LspError::MissingData("Missing hostname".to_string()))?.to_string()
         };
-        let system_id = hostname_map.get_system_id_by_hostname(&hostname).ok_or(LspError::MissingData("Missing system ID".to_string()))?;
+        let system_id = hostname_map.get_system_id_by_hostname(&hostname).ok_or_else(|| {
+            LspError::MissingData(format!("Missing system ID for hostname '{}', add it to the hostname map", hostname))
+        })?;
         let lsp_id = self.id_section.get_lsp_id(&system_id.to_string());
         let lsp_id = lsp_id?;
         let is_level: IsLevel = match is_level {
@@ -161,23 +162,26 @@ impl JsonLsp {
             2 => IsLevel::Level2,
             _ => return Err(LspError::InvalidIsLevel(is_level)),
         };
+        let flags = LspFlags::from_att_p_ol(&self.att_p_ol_flags).unwrap_or_else(|e| {
+            eprintln!("[frr_json_lsp] {}: {}, assuming no ATT/P/OL bits", self.id_section.id, e);
+            LspFlags::default()
+        });
         let mut tlvs: Vec<Tlv> = Vec::new();
         
-        println!("Area address");
+        debug_log!("Area address");
         if let Some(area_address) = &self.get_area_address() {
             let tlv = AreaAddressesTlv::new(vec![area_address.clone()]);
             tlvs.push(Tlv::AreaAddresses(tlv));
         } else {
-            println!("No area address found");
-            dbg!(&self);
+            debug_log!("No area address found in {:#?}", self);
         }
         
-        println!("Router capability");
+        debug_log!("Router capability");
         if let Some(router_cap) = &self.router_capability {
             tlvs.push(Tlv::RouterCapability(router_cap.try_into()?));
         }
         
-        println!("Extended reachabilities");
+        debug_log!("Extended reachabilities");
         if let Some(ext_reaches) = &self.extended_reachabilities {
             let mut neighbors: Vec<ExtendedIsNeighbor> = Vec::with_capacity(ext_reaches.len());
             for reach in ext_reaches {
@@ -186,7 +190,7 @@ impl JsonLsp {
             tlvs.push(Tlv::ExtendedReachability(IsExtendedReachabilityTlv { neighbors }));
         }
         
-        println!("Extended IP reachabilities");
+        debug_log!("Extended IP reachabilities");
         if let Some(ext_ip_reaches) = &self.extended_ip_reachabilities {
             let mut neighbors: Vec<ExtendedIpReachabilityNeighbor> = Vec::with_capacity(ext_ip_reaches.len());
             for reach in ext_ip_reaches {
@@ -203,10 +207,27 @@ impl JsonLsp {
             Some(hex::encode(self.holdtime.to_ne_bytes())),
             self.get_area_address(),
             tlvs,
-        ))
+        )
+        .with_flags(flags))
     }
 }
 
+/// Entry for LSP `id` as FRR prints it, with placeholder header fields and `fields` set on top.
+#[cfg(test)]
+pub fn lsp_fixture(id: &str, fields: serde_json::Value) -> serde_json::Value {
+    let mut lsp = serde_json::json!({
+        "lsp": { "id": id, "own": "*", "ownLSP": true },
+        "pduLen": 101,
+        "seqNumber": "0x00000001",
+        "chksum": "0x462b",
+        "holdtime": 1115,
+        "attPOl": "0/0/0"
+    });
+    if let (Some(lsp), serde_json::Value::Object(fields)) = (lsp.as_object_mut(), fields) {
+        lsp.extend(fields);
+    }
+    lsp
+}
 
 
 #[derive(Debug, Deserialize)]
@@ -280,7 +301,6 @@ impl TryInto<RouterCapabilityTlv> for &JsonRouterCapability {
 #[derive(Debug, Deserialize)]
 pub struct JsonExtendedReachabilityNeighbor {
     #[serde(rename = "mtId")]
-    #[allow(dead_code)]
     mt_id: String,
     id: String,
     metric: u32
@@ -315,7 +335,8 @@ impl TryInto<ExtendedIsNeighbor> for &JsonExtendedReachabilityNeighbor {
         Ok(ExtendedIsNeighbor {
             neighbor_id: system_id,
             metric: self.metric,
-            pseudonode_id: pseudonode_id
+            pseudonode_id: pseudonode_id,
+            mt_id: MtId::from_frr(&self.mt_id),
         })
     }
 }
@@ -323,7 +344,6 @@ impl TryInto<ExtendedIsNeighbor> for &JsonExtendedReachabilityNeighbor {
 #[derive(Debug, Deserialize)]
 pub struct JsonExtendedIpReachability {
     #[serde(rename = "mtId")]
-    #[allow(dead_code)]
     mt_id: String,
     #[serde(rename = "ipReach")]
     prefix: String,
@@ -341,16 +361,41 @@ impl TryInto<ExtendedIpReachabilityNeighbor> for &JsonExtendedIpReachability {
             prefix,
             self.metric,
             !self.down
-        ))
+        )
+        .with_mt_id(MtId::from_frr(&self.mt_id)))
     }
 }
 
+#[cfg(test)]
 mod tests {
     #[allow(unused_imports)]
     use serde_json::json;
     #[allow(unused_imports)]
     use super::*;
     
+    #[test]
+    fn test_from_reader_gzip() {
+        let plain = JsonLspdb::from_string(include_str!("../../../test_data/lspdb_dump.json")).unwrap();
+        let gz_bytes: &[u8] = include_bytes!("../../../test_data/lspdb_dump.json.gz");
+
+        let from_gz = JsonLspdb::from_reader(gz_bytes).unwrap();
+        let from_plain_reader =
+            JsonLspdb::from_reader(include_bytes!("../../../test_data/lspdb_dump.json").as_slice())
+                .unwrap();
+
+        for lspdb in [&from_gz, &from_plain_reader] {
+            assert_eq!(lspdb.areas.len(), plain.areas.len());
+            let lsp_count = |db: &JsonLspdb| {
+                db.areas
+                    .iter()
+                    .flat_map(|a| a.levels.iter())
+                    .map(|l| l.lsps.len())
+                    .sum::<usize>()
+            };
+            assert_eq!(lsp_count(lspdb), lsp_count(&plain));
+        }
+    }
+
     #[test]
     fn test_areas_deserialization() {
         let json = include_str!("../../../test_data/lspdb_dump.json");
@@ -638,4 +683,33 @@ mod tests {
         println!("{:#?}", &result);
         println!("{}", result.system_id)
     }
+
+    #[test]
+    fn test_missing_system_id_names_hostname() {
+        let json = lsp_fixture("r9.00-00", json!({ "hostname": "r9" }));
+        let json_lsp: JsonLsp = serde_json::from_value(json).unwrap();
+        let hostname_input = include_str!("../../../test_data/isis_hostname_map_input.txt");
+        let hostname_map = HostnameMap::build_map_from_lines(hostname_input.lines());
+
+        let err = json_lsp.try_into_lsp(1, &hostname_map).unwrap_err();
+        assert!(err.to_string().contains("'r9'"), "{}", err);
+    }
+
+    #[test]
+    fn test_overload_bit_parsed() {
+        let hostname_input = include_str!("../../../test_data/isis_hostname_map_input.txt");
+        let hostname_map = HostnameMap::build_map_from_lines(hostname_input.lines());
+        let parse = |att_p_ol: &str| {
+            let json = lsp_fixture("r1.00-00", json!({ "attPOl": att_p_ol, "hostname": "r1" }));
+            let json_lsp: JsonLsp = serde_json::from_value(json).unwrap();
+            json_lsp.try_into_lsp(2, &hostname_map).unwrap()
+        };
+
+        let lsp = parse("0/0/1");
+        assert!(lsp.flags.overload);
+        assert!(!lsp.flags.attached);
+
+        // A malformed field keeps the LSP, without flags
+        assert_eq!(parse("0/0").flags, LspFlags::default());
+    }
 }
@@ -1,4 +1,5 @@
 pub mod ssh_source;
+pub mod netconf_source;
 pub mod byte_lsp;
 pub mod core_lsp;
 pub mod frr_json_lsp;
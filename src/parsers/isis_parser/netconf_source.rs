@@ -0,0 +1,427 @@
+/*!
+IS-IS source reading the LSPDB over NETCONF, for routers that expose the `ietf-isis` YANG model
+(RFC 9130) instead of a shell with `vtysh`.
+
+The model already carries system IDs, so LSPs are decoded straight into `Lsp`s without a
+hostname map. Interface counters come from `ietf-interfaces` (RFC 8343).
+*/
+
+use std::net::IpAddr;
+
+use async_trait::async_trait;
+use ipnetwork::IpNetwork;
+
+use crate::{
+    data_aquisition::netconf::{NetconfClient, XmlElement},
+    logging::debug_log,
+    network::router::InterfaceStats,
+    parsers::isis_parser::{
+        core_lsp::{
+            AreaAddress, AreaAddressesTlv, ExtendedIpReachabilityNeighbor,
+            ExtendedIpReachabilityTlv, ExtendedIsNeighbor, IsExtendedReachabilityTlv, IsLevel,
+            Lsp, LspFlags, LspId, MtId, SystemId, Tlv,
+        },
+        protocol::{IsisLspdb, JsonIsisProtocol},
+    },
+    topology::{
+        protocol::{AcquisitionError, AcquisitionSource},
+        store::SourceId,
+    },
+};
+
+const ISIS_FILTER: &str = r#"<routing xmlns="urn:ietf:params:xml:ns:yang:ietf-routing">
+  <control-plane-protocols>
+    <control-plane-protocol>
+      <isis xmlns="urn:ietf:params:xml:ns:yang:ietf-isis"/>
+    </control-plane-protocol>
+  </control-plane-protocols>
+</routing>"#;
+
+const INTERFACES_FILTER: &str =
+    r#"<interfaces xmlns="urn:ietf:params:xml:ns:yang:ietf-interfaces"/>"#;
+
+pub struct IsisNetconfSource {
+    client: NetconfClient,
+    /// `<isis>` read by `fetch_source_id`, decoded by the `fetch_raw` that follows it in a poll
+    isis: Option<XmlElement>,
+}
+
+impl IsisNetconfSource {
+    pub fn new(client: NetconfClient) -> Self {
+        Self { client, isis: None }
+    }
+
+    /// The `<isis>` container of the first IS-IS instance.
+    async fn fetch_isis(&self) -> Result<XmlElement, AcquisitionError> {
+        if !self.client.ssh().is_connected() {
+            return Err(AcquisitionError::Transport(
+                "SSH client is not connected".to_string(),
+            ));
+        }
+        let data = self
+            .client
+            .get(ISIS_FILTER)
+            .await
+            .map_err(|e| AcquisitionError::Transport(e.to_string()))?;
+        data.find_descendant("isis")
+            .cloned()
+            .ok_or_else(|| AcquisitionError::Invalid("No ietf-isis instance in reply".to_string()))
+    }
+}
+
+/// Decode every LSP under `isis/database/levels`.
+pub fn decode_lspdb(isis: &XmlElement) -> Result<Vec<Lsp>, AcquisitionError> {
+    let Some(database) = isis.child("database") else {
+        return Ok(Vec::new());
+    };
+    let mut lsps = Vec::new();
+    for level in database.children_named("levels") {
+        let is_level = match level.child_text("level") {
+            Some("1") => IsLevel::Level1,
+            Some("2") => IsLevel::Level2,
+            other => {
+                return Err(AcquisitionError::Invalid(format!(
+                    "Invalid IS-IS level {:?}",
+                    other
+                )));
+            }
+        };
+        for lsp in level.children_named("lsp") {
+            lsps.push(decode_lsp(lsp, is_level.clone())?);
+        }
+    }
+    debug_log!("[IsisNetconfSource] decoded {} lsps", lsps.len());
+    Ok(lsps)
+}
+
+fn decode_lsp(lsp: &XmlElement, is_level: IsLevel) -> Result<Lsp, AcquisitionError> {
+    let invalid = |e: &dyn std::fmt::Display| AcquisitionError::Invalid(e.to_string());
+    let lsp_id = LspId::from_string(required_text(lsp, "lsp-id")?).map_err(|e| invalid(&e))?;
+    let system_id = lsp_id.get_system_id().map_err(|e| invalid(&e))?;
+
+    // Same encodings as the FRR path, so both sources compare equal downstream
+    let sequence_number = lsp
+        .child_text("sequence")
+        .and_then(|seq| seq.parse::<u32>().ok())
+        .map(|seq| format!("{:#010x}", seq));
+    let holdtime = lsp
+        .child_text("remaining-lifetime")
+        .and_then(|lifetime| lifetime.parse::<u16>().ok())
+        .map(|lifetime| hex::encode(lifetime.to_ne_bytes()));
+
+    let mut flags = LspFlags::default();
+    if let Some(bits) = lsp.find(&["attributes", "lsp-flags"]) {
+        for bit in bits.text.split_whitespace() {
+            match bit {
+                "overload" => flags.overload = true,
+                "partitioned" => flags.partition_repair = true,
+                bit if bit.starts_with("attached-") => flags.attached = true,
+                _ => {}
+            }
+        }
+    }
+
+    let mut tlvs = Vec::new();
+    let area_addresses: Vec<AreaAddress> = lsp
+        .children_named("area-addresses")
+        .filter_map(|area| AreaAddress::from_dotted(area.text.trim()))
+        .collect();
+    if !area_addresses.is_empty() {
+        tlvs.push(Tlv::AreaAddresses(AreaAddressesTlv::new(area_addresses.clone())));
+    }
+    if let Some(hostname) = lsp.child_text("dynamic-hostname") {
+        tlvs.push(Tlv::Hostname(hostname.to_string()));
+    }
+
+    let mut neighbors = Vec::new();
+    if let Some(reach) = lsp.child("extended-is-neighbor") {
+        for neighbor in reach.children_named("neighbor") {
+            neighbors.extend(decode_is_neighbor(neighbor, MtId::Standard)?);
+        }
+    }
+    if let Some(reach) = lsp.child("mt-is-neighbor") {
+        for neighbor in reach.children_named("neighbor") {
+            neighbors.extend(decode_is_neighbor(neighbor, mt_id(neighbor))?);
+        }
+    }
+    if !neighbors.is_empty() {
+        tlvs.push(Tlv::ExtendedReachability(IsExtendedReachabilityTlv { neighbors }));
+    }
+
+    let mut prefixes = Vec::new();
+    if let Some(reach) = lsp.child("extended-ipv4-reachability") {
+        for prefix in reach.children_named("prefixes") {
+            prefixes.push(decode_ipv4_prefix(prefix, MtId::Standard)?);
+        }
+    }
+    if let Some(reach) = lsp.child("mt-extended-ipv4-reachability") {
+        for prefix in reach.children_named("prefixes") {
+            prefixes.push(decode_ipv4_prefix(prefix, mt_id(prefix))?);
+        }
+    }
+    if !prefixes.is_empty() {
+        tlvs.push(Tlv::ExtendedIpReachability(ExtendedIpReachabilityTlv {
+            neighbors: prefixes,
+        }));
+    }
+
+    Ok(Lsp::new(
+        lsp_id,
+        system_id,
+        is_level,
+        sequence_number,
+        holdtime,
+        area_addresses.into_iter().next(),
+        tlvs,
+    )
+    .with_flags(flags))
+}
+
+/// One entry per instance of a parallel adjacency, like FRR lists them.
+fn decode_is_neighbor(
+    neighbor: &XmlElement,
+    mt_id: MtId,
+) -> Result<Vec<ExtendedIsNeighbor>, AcquisitionError> {
+    // extended-system-id, "xxxx.xxxx.xxxx.pn"
+    let neighbor_id_str = required_text(neighbor, "neighbor-id")?;
+    let bad_id = || AcquisitionError::Invalid(format!("Invalid neighbor-id {}", neighbor_id_str));
+    let (system_id, pseudonode_id) = neighbor_id_str.rsplit_once('.').ok_or_else(bad_id)?;
+    let neighbor_id = SystemId::from_string(system_id).map_err(|_| bad_id())?;
+    let pseudonode_id = u8::from_str_radix(pseudonode_id, 16).map_err(|_| bad_id())?;
+
+    let Some(instances) = neighbor.child("instances") else {
+        return Ok(Vec::new());
+    };
+    instances
+        .children_named("instance")
+        .map(|instance| {
+            Ok(ExtendedIsNeighbor {
+                neighbor_id: neighbor_id.clone(),
+                metric: parse_required(instance, "metric")?,
+                pseudonode_id,
+                mt_id: mt_id.clone(),
+            })
+        })
+        .collect()
+}
+
+fn decode_ipv4_prefix(
+    prefix: &XmlElement,
+    mt_id: MtId,
+) -> Result<ExtendedIpReachabilityNeighbor, AcquisitionError> {
+    let address: IpAddr = parse_required(prefix, "ip-prefix")?;
+    let prefix_len: u8 = parse_required(prefix, "prefix-len")?;
+    let network = IpNetwork::new(address, prefix_len)
+        .map_err(|e| AcquisitionError::Invalid(e.to_string()))?;
+    // YANG's up-down is the "leaked down the hierarchy" bit, ours is "up"
+    let leaked_down = prefix.child_text("up-down") == Some("true");
+    Ok(
+        ExtendedIpReachabilityNeighbor::new(network, parse_required(prefix, "metric")?, !leaked_down)
+            .with_mt_id(mt_id),
+    )
+}
+
+/// Numeric `mt-id` leaf, with the RFC 5120 assignments.
+fn mt_id(element: &XmlElement) -> MtId {
+    match element.child_text("mt-id").unwrap_or("0") {
+        "0" => MtId::Standard,
+        "1" => MtId::Ipv4Management,
+        "2" => MtId::Ipv6Unicast,
+        "3" => MtId::Ipv4Multicast,
+        "4" => MtId::Ipv6Multicast,
+        "5" => MtId::Ipv6Management,
+        other => MtId::Other(other.to_string()),
+    }
+}
+
+fn required_text<'a>(element: &'a XmlElement, name: &str) -> Result<&'a str, AcquisitionError> {
+    element.child_text(name).ok_or_else(|| {
+        AcquisitionError::Invalid(format!("Missing <{}> in <{}>", name, element.name))
+    })
+}
+
+fn parse_required<T: std::str::FromStr>(
+    element: &XmlElement,
+    name: &str,
+) -> Result<T, AcquisitionError> {
+    let text = required_text(element, name)?;
+    text.parse()
+        .map_err(|_| AcquisitionError::Invalid(format!("Invalid <{}> value '{}'", name, text)))
+}
+
+/// Counters per interface address from `ietf-interfaces` operational state.
+pub fn decode_interface_stats(data: &XmlElement) -> Vec<InterfaceStats> {
+    let counter = |interface: &XmlElement, name: &str| {
+        interface
+            .find(&["statistics", name])
+            .and_then(|value| value.text.trim().parse().ok())
+    };
+    let mut stats = Vec::new();
+    let interfaces = ["interfaces", "interfaces-state"]
+        .into_iter()
+        .filter_map(|name| data.child(name))
+        .flat_map(|interfaces| interfaces.children_named("interface"));
+    for interface in interfaces {
+        let Some(ipv4) = interface.child("ipv4") else {
+            continue;
+        };
+        for address in ipv4.children_named("address") {
+            let Some(ip_address) = address.child_text("ip").and_then(|ip| ip.parse().ok()) else {
+                continue;
+            };
+            stats.push(InterfaceStats {
+                ip_address,
+                rx_bytes: counter(interface, "in-octets"),
+                tx_bytes: counter(interface, "out-octets"),
+                rx_packets: counter(interface, "in-unicast-pkts"),
+                tx_packets: counter(interface, "out-unicast-pkts"),
+            });
+        }
+    }
+    stats
+}
+
+#[async_trait]
+impl AcquisitionSource<JsonIsisProtocol> for IsisNetconfSource {
+    async fn fetch_raw(&mut self) -> Result<Vec<IsisLspdb>, AcquisitionError> {
+        let isis = match self.isis.take() {
+            Some(isis) => isis,
+            None => self.fetch_isis().await?,
+        };
+        Ok(vec![IsisLspdb::Decoded(decode_lspdb(&isis)?)])
+    }
+
+    async fn fetch_source_id(&mut self) -> Result<SourceId, AcquisitionError> {
+        let isis = self.fetch_isis().await?;
+        let system_id = required_text(&isis, "system-id")?;
+        let system_id = SystemId::from_string(system_id)
+            .map_err(|e| AcquisitionError::Invalid(e.to_string()))?;
+        self.isis = Some(isis);
+        Ok(SourceId::IsIs(system_id))
+    }
+
+    async fn fetch_stats(&mut self) -> Result<Vec<InterfaceStats>, AcquisitionError> {
+        let data = self
+            .client
+            .get(INTERFACES_FILTER)
+            .await
+            .map_err(|e| AcquisitionError::Transport(e.to_string()))?;
+        Ok(decode_interface_stats(&data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ISIS_REPLY: &str = r#"<data xmlns="urn:ietf:params:xml:ns:netconf:base:1.0">
+<routing xmlns="urn:ietf:params:xml:ns:yang:ietf-routing">
+ <control-plane-protocols><control-plane-protocol>
+  <type>isis</type><name>core</name>
+  <isis xmlns="urn:ietf:params:xml:ns:yang:ietf-isis">
+   <system-id>0000.0000.0001</system-id>
+   <database>
+    <levels>
+     <level>2</level>
+     <lsp>
+      <lsp-id>0000.0000.0001.00-00</lsp-id>
+      <remaining-lifetime>1115</remaining-lifetime>
+      <sequence>3</sequence>
+      <attributes><lsp-flags>attached-default overload</lsp-flags></attributes>
+      <area-addresses>49.0001</area-addresses>
+      <dynamic-hostname>r1</dynamic-hostname>
+      <extended-is-neighbor>
+       <neighbor>
+        <neighbor-id>0000.0000.0002.00</neighbor-id>
+        <instances><instance><id>0</id><metric>10</metric></instance></instances>
+       </neighbor>
+       <neighbor>
+        <neighbor-id>0000.0000.0003.01</neighbor-id>
+        <instances><instance><id>0</id><metric>20</metric></instance></instances>
+       </neighbor>
+      </extended-is-neighbor>
+      <extended-ipv4-reachability>
+       <prefixes>
+        <up-down>false</up-down><ip-prefix>10.0.12.0</ip-prefix>
+        <prefix-len>24</prefix-len><metric>10</metric>
+       </prefixes>
+      </extended-ipv4-reachability>
+      <mt-is-neighbor>
+       <neighbor>
+        <mt-id>2</mt-id><neighbor-id>0000.0000.0002.00</neighbor-id>
+        <instances><instance><id>0</id><metric>15</metric></instance></instances>
+       </neighbor>
+      </mt-is-neighbor>
+     </lsp>
+    </levels>
+   </database>
+  </isis>
+ </control-plane-protocol></control-plane-protocols>
+</routing>
+</data>"#;
+
+    #[test]
+    fn test_decode_lspdb() {
+        let data = XmlElement::parse(ISIS_REPLY).unwrap();
+        let isis = data.find_descendant("isis").unwrap();
+        assert_eq!(isis.child_text("system-id"), Some("0000.0000.0001"));
+
+        let lsps = decode_lspdb(isis).unwrap();
+        assert_eq!(lsps.len(), 1);
+        let lsp = &lsps[0];
+        assert_eq!(lsp.system_id, SystemId::from_string("0000.0000.0001").unwrap());
+        assert_eq!(lsp.is_level, IsLevel::Level2);
+        assert_eq!(lsp.sequence_number.as_deref(), Some("0x00000003"));
+        assert_eq!(lsp.holdtime_secs(), Some(1115));
+        assert!(lsp.flags.overload && lsp.flags.attached && !lsp.flags.partition_repair);
+        assert_eq!(lsp.area_addr.as_ref().unwrap().raw_address, [0x49, 0x00, 0x01]);
+
+        let neighbors: Vec<_> = lsp
+            .tlvs
+            .iter()
+            .filter_map(|tlv| match tlv {
+                Tlv::ExtendedReachability(reach) => Some(&reach.neighbors),
+                _ => None,
+            })
+            .flatten()
+            .map(|n| (n.neighbor_id.to_string(), n.pseudonode_id, n.metric, n.mt_id.clone()))
+            .collect();
+        assert_eq!(
+            neighbors,
+            [
+                ("0000.0000.0002".to_string(), 0, 10, MtId::Standard),
+                ("0000.0000.0003".to_string(), 1, 20, MtId::Standard),
+                ("0000.0000.0002".to_string(), 0, 15, MtId::Ipv6Unicast),
+            ]
+        );
+
+        let Some(Tlv::ExtendedIpReachability(reach)) = lsp
+            .tlvs
+            .iter()
+            .find(|tlv| matches!(tlv, Tlv::ExtendedIpReachability(_)))
+        else {
+            panic!("no IP reachability TLV");
+        };
+        assert_eq!(reach.neighbors[0].prefix, "10.0.12.0/24".parse().unwrap());
+        assert!(reach.neighbors[0].up_down);
+    }
+
+    #[test]
+    fn test_decode_interface_stats() {
+        let data = XmlElement::parse(
+            r#"<data><interfaces xmlns="urn:ietf:params:xml:ns:yang:ietf-interfaces">
+<interface><name>eth1</name>
+ <ipv4 xmlns="urn:ietf:params:xml:ns:yang:ietf-ip"><address><ip>10.0.12.1</ip></address></ipv4>
+ <statistics><in-octets>100</in-octets><out-octets>200</out-octets></statistics>
+</interface>
+<interface><name>lo</name></interface>
+</interfaces></data>"#,
+        )
+        .unwrap();
+        let stats = decode_interface_stats(&data);
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].ip_address, "10.0.12.1".parse::<IpAddr>().unwrap());
+        assert_eq!((stats[0].rx_bytes, stats[0].tx_bytes), (Some(100), Some(200)));
+        assert_eq!(stats[0].rx_packets, None);
+    }
+}
@@ -1,15 +1,21 @@
-use std::{collections::HashSet, net::Ipv4Addr};
+use std::{
+    collections::{HashMap, HashSet},
+    net::Ipv4Addr,
+};
 
 use ipnetwork::IpNetwork;
 use uuid::Uuid;
 
 use crate::{
+    logging::{debug_log, info_log},
     network::{
         node::{IsIsData, Network, Node, NodeInfo, ProtocolData},
         router::{Router, RouterId},
     },
     parsers::isis_parser::{
-        core_lsp::{ExtendedIpReachabilityTlv, Lsp, LspError, LspId, NetAddress, SystemId, Tlv},
+        core_lsp::{
+            ExtendedIpReachabilityTlv, Lsp, LspError, LspId, MtId, NetAddress, SystemId, Tlv,
+        },
         frr_json_lsp::JsonLspdb,
         hostname::HostnameMap,
     },
@@ -21,6 +27,18 @@ Due to how bad FRR's LSPDB JSON output is, we need to get the VRF data to get th
 System ID for all LSPs instead of hostnames.
 
 */
+/// What the merged view does with a pseudonode network whose prefix can't be resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnresolvedPrefixPolicy {
+    /// Keep the network on the placeholder prefix, with an id derived from its LSP id so
+    /// unresolved networks don't collide. Rendered distinctly in the GUI.
+    #[default]
+    Keep,
+    /// Drop the network and connect its attached routers to each other directly, as
+    /// point-to-point adjacencies with each router's metric towards the pseudonode.
+    Collapse,
+}
+
 pub struct JsonIsisProtocol {
     hostname_map: HostnameMap,
 }
@@ -30,48 +48,76 @@ impl JsonIsisProtocol {
         Self { hostname_map }
     }
 
+    /// Replace the hostname <-> system ID mapping used to resolve LSP IDs on the next parse.
+    pub fn set_hostname_map(&mut self, hostname_map: HostnameMap) {
+        self.hostname_map = hostname_map;
+    }
+
     fn lsp_to_router(&self, lsp: Lsp) -> Result<Router, ProtocolTopologyError> {
         let id = RouterId::IsIs(lsp.system_id.clone());
         let net_address = lsp.get_net_address();
+        let holdtime = lsp.holdtime_secs();
         let protocol_data = ProtocolData::IsIs(IsIsData {
+            sequence_number: lsp.sequence_number,
+            holdtime,
+            flags: lsp.flags,
             is_level: lsp.is_level,
             lsp_id: lsp.lsp_id,
             tlvs: lsp.tlvs,
             net_address: net_address,
+            areas: lsp.areas,
         });
 
         Ok(Router {
             id,
             interfaces: Vec::new(), // We leave this empty since IS-IS works at the link layer
             protocol_data: Some(protocol_data),
+            hostname: None,
+            description: None,
         })
     }
 
     fn lsp_to_network(&self, lsp: Lsp) -> Result<Network, ProtocolTopologyError> {
         let protocol_data = ProtocolData::IsIs(IsIsData {
             net_address: lsp.get_net_address(),
+            holdtime: lsp.holdtime_secs(),
+            sequence_number: lsp.sequence_number,
+            flags: lsp.flags,
             is_level: lsp.is_level,
             lsp_id: lsp.lsp_id,
             tlvs: lsp.tlvs,
+            areas: lsp.areas,
         });
 
         // Moved to post_processing - pseudonode LSP doesn't hold the IP prefix
-        let ip_prefix: IpNetwork = IpNetwork::new(
-            std::net::IpAddr::V4(Ipv4Addr::from_octets([0, 0, 0, 0])),
-            32,
-        )
-        .unwrap();
+        let ip_prefix: IpNetwork = placeholder_network_prefix();
 
         Ok(Network {
             ip_address: ip_prefix,
             protocol_data: Some(protocol_data),
             attached_routers: vec![],
+            is_synthetic: false,
         })
     }
 }
 
+/// One LSPDB as delivered by an IS-IS acquisition source.
+pub enum IsisLspdb {
+    /// FRR's `show isis database detail json`, which names LSPs by hostname; the hostname map
+    /// turns them back into system IDs.
+    Frr(JsonLspdb),
+    /// LSPs the source already decoded itself, e.g. from the ietf-isis YANG model.
+    Decoded(Vec<Lsp>),
+}
+
+impl From<JsonLspdb> for IsisLspdb {
+    fn from(lspdb: JsonLspdb) -> Self {
+        IsisLspdb::Frr(lspdb)
+    }
+}
+
 impl RoutingProtocol for JsonIsisProtocol {
-    type RawRecord = JsonLspdb;
+    type RawRecord = IsisLspdb;
 
     type ParsedItem = Lsp;
 
@@ -79,18 +125,24 @@ impl RoutingProtocol for JsonIsisProtocol {
         &self,
         raw: Self::RawRecord,
     ) -> Result<Vec<Self::ParsedItem>, crate::topology::protocol::ProtocolParseError> {
+        let raw = match raw {
+            IsisLspdb::Frr(lspdb) => lspdb,
+            IsisLspdb::Decoded(lsps) => return Ok(lsps),
+        };
         let mut lsps = Vec::new();
         for area in raw.areas {
             for level in area.levels {
                 let level_no = level.id;
                 for lsp in level.lsps {
-                    let parsed = lsp
+                    let mut parsed = lsp
                         .try_into_lsp(level_no, &self.hostname_map)
                         .map_err(|e| e.into())?;
+                    parsed.areas.extend(area.area_props.name.clone());
                     lsps.push(parsed);
                 }
             }
         }
+        tag_lsp_areas(&mut lsps);
         Ok(lsps)
     }
 
@@ -108,7 +160,7 @@ impl RoutingProtocol for JsonIsisProtocol {
             None
         };
 
-        println!("Processing LSP of ID: {}", &item.lsp_id);
+        debug_log!("Processing LSP of ID: {}", &item.lsp_id);
 
         let node_info = if item.lsp_id.is_pseudonode() {
             NodeInfo::Network(Self::lsp_to_network(&self, item)?)
@@ -116,7 +168,7 @@ impl RoutingProtocol for JsonIsisProtocol {
             NodeInfo::Router(Self::lsp_to_router(&self, item)?)
         };
 
-        println!("Processed successfully");
+        debug_log!("Processed successfully");
 
         Ok(Some(Node::new(node_info, label)))
     }
@@ -132,7 +184,7 @@ impl RoutingProtocol for JsonIsisProtocol {
         //   ISIS_POST_MAX_ROUTERS  - if set to a positive integer, only consider up to that many router nodes when resolving prefixes.
         use std::env;
 
-        println!(
+        debug_log!(
             "[JsonIsisProtocol::post_process] start: total nodes={}",
             nodes.len()
         );
@@ -145,10 +197,11 @@ impl RoutingProtocol for JsonIsisProtocol {
             match &node.info {
                 NodeInfo::Network(_) => network_idxs.push(idx),
                 NodeInfo::Router(_) => router_idxs.push(idx),
+                NodeInfo::Area(_) => {}
             }
         }
 
-        println!(
+        debug_log!(
             "[JsonIsisProtocol::post_process] found {} networks and {} routers",
             network_idxs.len(),
             router_idxs.len()
@@ -163,13 +216,13 @@ impl RoutingProtocol for JsonIsisProtocol {
             .and_then(|s| s.parse::<usize>().ok());
 
         if let Some(m) = max_networks {
-            println!(
+            debug_log!(
                 "[JsonIsisProtocol::post_process] limiting network processing to {}",
                 m
             );
         }
         if let Some(m) = max_routers {
-            println!(
+            debug_log!(
                 "[JsonIsisProtocol::post_process] limiting router consideration to {}",
                 m
             );
@@ -191,7 +244,7 @@ impl RoutingProtocol for JsonIsisProtocol {
                 router_iter_idxs_full.iter().cloned().collect()
             };
 
-            println!(
+            debug_log!(
                 "[JsonIsisProtocol::post_process] processing network idx={} with {} router candidates",
                 net_idx,
                 router_subset.len()
@@ -218,7 +271,7 @@ impl RoutingProtocol for JsonIsisProtocol {
                     };
 
                     if let Some(dis_router) = dis_router {
-                        println!("Found DIS router: {:?}", dis_router);
+                        debug_log!("Found DIS router: {:?}", dis_router);
                         if let RouterId::IsIs(net_address) = dis_router.id {
                             let area_address = net_address.area_address.clone();
                             let system_id = data.lsp_id.get_system_id().map_err(|e| {
@@ -231,7 +284,7 @@ impl RoutingProtocol for JsonIsisProtocol {
                                 area_address,
                                 system_id,
                             };
-                            println!("Found NET address: {}", &net_addr);
+                            debug_log!("Found NET address: {}", &net_addr);
                             Ok(net_addr)
                         } else {
                             Err(ProtocolTopologyError::Semantic(
@@ -279,7 +332,7 @@ impl RoutingProtocol for JsonIsisProtocol {
                             net.ip_address.to_string().as_bytes(),
                         );
 
-                        println!(
+                        debug_log!(
                             "[JsonIsisProtocol::post_process] set prefix for network idx={} to {} (recomputed id={})",
                             net_idx, net.ip_address, node.id
                         );
@@ -287,9 +340,16 @@ impl RoutingProtocol for JsonIsisProtocol {
                 }
                 Err(e) => {
                     eprintln!(
-                        "[JsonIsisProtocol::post_process] failed to resolve prefix for network idx={} due to {:?}",
+                        "[JsonIsisProtocol::post_process] failed to resolve prefix for network idx={} due to {:?}, keeping it unresolved",
                         net_idx, e
                     );
+                    // The merged view may still collapse it, see `UnresolvedPrefixPolicy`
+                    let node = &mut nodes[net_idx];
+                    if let NodeInfo::Network(net) = &node.info
+                        && let Some(ProtocolData::IsIs(data)) = &net.protocol_data
+                    {
+                        node.id = unresolved_network_id(&data.lsp_id);
+                    }
                 }
             }
 
@@ -308,19 +368,19 @@ impl RoutingProtocol for JsonIsisProtocol {
                                 .map(|neighbor| neighbor.neighbor_id.clone())
                                 .collect()
                         } else {
-                            println!("Missing extended reachability TLV");
+                            debug_log!("Missing extended reachability TLV");
                             return Err(ProtocolTopologyError::Semantic(
                                 "Missing extended reachability TLV".to_string(),
                             ));
                         }
                     } else {
-                        println!("Missing ISIS protocol data");
+                        debug_log!("Missing ISIS protocol data");
                         return Err(ProtocolTopologyError::Semantic(
                             "Missing ISIS protocol data".to_string(),
                         ));
                     }
                 } else {
-                    println!("Missing ISIS protocol data");
+                    debug_log!("Missing ISIS protocol data");
                     return Err(ProtocolTopologyError::Semantic(
                         "Missing ISIS protocol data".to_string(),
                     ));
@@ -331,15 +391,318 @@ impl RoutingProtocol for JsonIsisProtocol {
                     .into_iter()
                     .map(|net| RouterId::IsIs(net))
                     .collect();
-                println!("Attached routers set: {:?}", net.attached_routers);
+                debug_log!("Attached routers set: {:?}", net.attached_routers);
             }
         }
 
-        println!("[JsonIsisProtocol::post_process] complete");
+        info_log!("[JsonIsisProtocol::post_process] complete");
         Ok(())
     }
 }
 
+/// Give every LSP of a system (or pseudonode) the areas of all its LSPs, so a router that shows
+/// up in several areas, like an L2 backbone router, is tagged with each of them whichever of its
+/// LSPs ends up in the graph.
+fn tag_lsp_areas(lsps: &mut [Lsp]) {
+    let mut areas: HashMap<(SystemId, u8), Vec<String>> = HashMap::new();
+    for lsp in lsps.iter() {
+        let entry = areas
+            .entry((lsp.system_id.clone(), lsp.lsp_id.get_pseudonode_id()))
+            .or_default();
+        entry.extend(lsp.areas.iter().cloned());
+    }
+    for entry in areas.values_mut() {
+        entry.sort();
+        entry.dedup();
+    }
+    for lsp in lsps.iter_mut() {
+        if let Some(all) = areas.get(&(lsp.system_id.clone(), lsp.lsp_id.get_pseudonode_id())) {
+            lsp.areas = all.clone();
+        }
+    }
+}
+
+/// Names of the IS-IS areas a node was seen in (see `Lsp::areas`), `None` for non-IS-IS nodes.
+pub fn isis_node_areas(node: &Node) -> Option<&[String]> {
+    let protocol_data = match &node.info {
+        NodeInfo::Router(r) => &r.protocol_data,
+        NodeInfo::Network(n) => &n.protocol_data,
+        NodeInfo::Area(_) => return None,
+    };
+    match protocol_data {
+        Some(ProtocolData::IsIs(data)) => Some(&data.areas),
+        _ => None,
+    }
+}
+
+/// Names of the IS-IS areas present in `nodes`, sorted.
+pub fn isis_areas<'a>(nodes: impl IntoIterator<Item = &'a Node>) -> Vec<String> {
+    let mut areas: Vec<String> = nodes
+        .into_iter()
+        .filter_map(isis_node_areas)
+        .flatten()
+        .cloned()
+        .collect();
+    areas.sort();
+    areas.dedup();
+    areas
+}
+
+/// Keep only the IS-IS nodes of `area`. Routers in several areas stay as long as `area` is one
+/// of them. IS-IS nodes without area tags (sources that don't report the area) and non-IS-IS
+/// nodes are kept.
+pub fn retain_isis_area(nodes: &mut Vec<Node>, area: &str) {
+    nodes.retain(|node| {
+        isis_node_areas(node).is_none_or(|areas| areas.is_empty() || areas.iter().any(|a| a == area))
+    });
+}
+
+/// Topologies (MT IDs) present in the reachability TLVs of the given IS-IS nodes, sorted.
+pub fn isis_topologies<'a>(nodes: impl IntoIterator<Item = &'a Node>) -> Vec<MtId> {
+    let mut topologies: Vec<MtId> = nodes
+        .into_iter()
+        .filter_map(|node| match &node.info {
+            NodeInfo::Router(r) => match &r.protocol_data {
+                Some(ProtocolData::IsIs(data)) => Some(data),
+                _ => None,
+            },
+            NodeInfo::Network(_) | NodeInfo::Area(_) => None,
+        })
+        .flat_map(|data| data.tlvs.iter())
+        .flat_map(|tlv| match tlv {
+            Tlv::ExtendedReachability(t) => t.neighbors.iter().map(|n| n.mt_id.clone()).collect(),
+            Tlv::ExtendedIpReachability(t) => t.neighbors.iter().map(|n| n.mt_id.clone()).collect(),
+            _ => Vec::new(),
+        })
+        .collect();
+    topologies.sort();
+    topologies.dedup();
+    topologies
+}
+
+/// Restrict IS-IS nodes to a single topology: router adjacencies and prefixes from other MT IDs
+/// are dropped, and pseudonode networks lose attached routers that have no adjacency to them in `mt_id`.
+/// Pseudonode LSPs themselves are MT-neutral (RFC 5120), so their neighbor lists are kept.
+/// Non-IS-IS nodes are untouched.
+pub fn retain_isis_topology(nodes: &mut [Node], mt_id: &MtId) {
+    for node in nodes.iter_mut() {
+        let NodeInfo::Router(router) = &mut node.info else {
+            continue;
+        };
+        let Some(ProtocolData::IsIs(data)) = &mut router.protocol_data else {
+            continue;
+        };
+        for tlv in data.tlvs.iter_mut() {
+            match tlv {
+                Tlv::ExtendedReachability(t) => t.neighbors.retain(|n| &n.mt_id == mt_id),
+                Tlv::ExtendedIpReachability(t) => t.neighbors.retain(|n| &n.mt_id == mt_id),
+                _ => {}
+            }
+        }
+    }
+
+    // Which pseudonodes each router still reaches in this topology
+    let mut reached: HashMap<RouterId, HashSet<(SystemId, u8)>> = HashMap::new();
+    for node in nodes.iter() {
+        if let NodeInfo::Router(router) = &node.info
+            && let Some(ProtocolData::IsIs(data)) = &router.protocol_data
+        {
+            let entry = reached.entry(router.id.clone()).or_default();
+            for tlv in &data.tlvs {
+                if let Tlv::ExtendedReachability(t) = tlv {
+                    entry.extend(
+                        t.neighbors
+                            .iter()
+                            .filter(|n| n.pseudonode_id != 0)
+                            .map(|n| (n.neighbor_id.clone(), n.pseudonode_id)),
+                    );
+                }
+            }
+        }
+    }
+
+    for node in nodes.iter_mut() {
+        let NodeInfo::Network(net) = &mut node.info else {
+            continue;
+        };
+        let Some(ProtocolData::IsIs(data)) = &net.protocol_data else {
+            continue;
+        };
+        let Ok(dis_id) = data.lsp_id.get_system_id() else {
+            continue;
+        };
+        let pseudonode = (dis_id, data.lsp_id.get_pseudonode_id());
+        // Routers missing from this node set are kept, we can't tell their topologies
+        net.attached_routers
+            .retain(|rid| reached.get(rid).is_none_or(|set| set.contains(&pseudonode)));
+    }
+}
+
+/// Prefix assigned to pseudonode networks until `post_process` resolves the real one.
+pub fn placeholder_network_prefix() -> IpNetwork {
+    IpNetwork::new(
+        std::net::IpAddr::V4(Ipv4Addr::from_octets([0, 0, 0, 0])),
+        32,
+    )
+    .unwrap()
+}
+
+/// Id of a pseudonode network kept on the placeholder prefix, unique per pseudonode LSP.
+pub fn unresolved_network_id(lsp_id: &LspId) -> Uuid {
+    Uuid::new_v5(
+        &Uuid::NAMESPACE_OID,
+        format!("isis-unresolved:{}", lsp_id).as_bytes(),
+    )
+}
+
+/// Replace every unresolved pseudonode network in `nodes` with direct adjacencies between its
+/// attached routers and drop it. Returns the number of networks collapsed.
+pub fn collapse_unresolved_networks(nodes: &mut Vec<Node>) -> usize {
+    let unresolved: Vec<usize> = nodes
+        .iter()
+        .enumerate()
+        .filter(|(_, node)| matches!(&node.info, NodeInfo::Network(net) if net.is_unresolved()))
+        .map(|(idx, _)| idx)
+        .collect();
+    for &idx in &unresolved {
+        collapse_pseudonode(nodes, idx);
+    }
+    let mut idx = 0;
+    nodes.retain(|_| {
+        let keep = !unresolved.contains(&idx);
+        idx += 1;
+        keep
+    });
+    unresolved.len()
+}
+
+/// Replace the pseudonode at `net_idx` with direct adjacencies: each attached router's
+/// reachability entry for the pseudonode becomes one entry per other attached router, with the
+/// same metric. The network node itself is left for the caller to remove.
+fn collapse_pseudonode(nodes: &mut [Node], net_idx: usize) {
+    let NodeInfo::Network(net) = &nodes[net_idx].info else {
+        return;
+    };
+    let Some(ProtocolData::IsIs(data)) = &net.protocol_data else {
+        return;
+    };
+    let Ok(dis_system_id) = data.lsp_id.get_system_id() else {
+        return;
+    };
+    let pseudonode_id = data.lsp_id.get_pseudonode_id();
+    let attached: Vec<SystemId> = data
+        .tlvs
+        .iter()
+        .filter_map(|tlv| match tlv {
+            Tlv::ExtendedReachability(tlv) => Some(tlv),
+            _ => None,
+        })
+        .flat_map(|tlv| tlv.neighbors.iter())
+        .filter(|n| n.pseudonode_id == 0)
+        .map(|n| n.neighbor_id.clone())
+        .collect();
+
+    for node in nodes.iter_mut() {
+        let NodeInfo::Router(router) = &mut node.info else {
+            continue;
+        };
+        let RouterId::IsIs(own_id) = router.id.clone() else {
+            continue;
+        };
+        if !attached.contains(&own_id) {
+            continue;
+        }
+        let Some(ProtocolData::IsIs(router_data)) = &mut router.protocol_data else {
+            continue;
+        };
+        for tlv in router_data.tlvs.iter_mut() {
+            let Tlv::ExtendedReachability(tlv) = tlv else {
+                continue;
+            };
+            let Some(pos) = tlv.neighbors.iter().position(|n| {
+                n.neighbor_id == dis_system_id && n.pseudonode_id == pseudonode_id
+            }) else {
+                continue;
+            };
+            let via_pseudonode = tlv.neighbors.remove(pos);
+            for peer in attached.iter().filter(|peer| **peer != own_id) {
+                if !tlv.neighbors.iter().any(|n| n.neighbor_id == *peer && n.pseudonode_id == 0) {
+                    let mut direct = via_pseudonode.clone();
+                    direct.neighbor_id = peer.clone();
+                    direct.pseudonode_id = 0;
+                    tlv.neighbors.push(direct);
+                }
+            }
+        }
+    }
+}
+
+/// Outcome of pseudonode prefix resolution over a set of post-processed IS-IS nodes.
+#[derive(Debug, Clone, Default)]
+pub struct PrefixResolutionReport {
+    /// Networks whose prefix was resolved from the DIS / attached routers.
+    pub resolved: usize,
+    /// LSP ids of networks that kept the placeholder prefix.
+    pub unresolved: Vec<String>,
+    /// Networks whose id doesn't match the id recomputed from their current prefix.
+    pub stale_ids: usize,
+}
+
+impl PrefixResolutionReport {
+    pub fn from_nodes<'a>(nodes: impl IntoIterator<Item = &'a Node>) -> Self {
+        let placeholder = placeholder_network_prefix();
+        let mut report = Self::default();
+        for node in nodes {
+            let NodeInfo::Network(net) = &node.info else {
+                continue;
+            };
+            let Some(ProtocolData::IsIs(data)) = &net.protocol_data else {
+                continue;
+            };
+
+            if net.ip_address == placeholder {
+                report.unresolved.push(data.lsp_id.to_string());
+            } else {
+                report.resolved += 1;
+            }
+
+            let expected_id = if net.ip_address == placeholder {
+                unresolved_network_id(&data.lsp_id)
+            } else {
+                Uuid::new_v5(&Uuid::NAMESPACE_OID, net.ip_address.to_string().as_bytes())
+            };
+            if node.id != expected_id {
+                report.stale_ids += 1;
+            }
+        }
+        report
+    }
+
+    pub fn total(&self) -> usize {
+        self.resolved + self.unresolved.len()
+    }
+
+    /// Unresolved networks get per-LSP ids, so they only collide when the same pseudonode LSP
+    /// shows up more than once.
+    pub fn has_collisions(&self) -> bool {
+        let unique: HashSet<&String> = self.unresolved.iter().collect();
+        unique.len() < self.unresolved.len()
+    }
+}
+
+impl std::fmt::Display for PrefixResolutionReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}/{} networks resolved, {} on placeholder prefix, {} stale ids",
+            self.resolved,
+            self.total(),
+            self.unresolved.len(),
+            self.stale_ids
+        )
+    }
+}
+
 impl Into<ProtocolParseError> for LspError {
     fn into(self) -> ProtocolParseError {
         match self {
@@ -367,7 +730,7 @@ fn resolve_network_prefix(
 ) -> Result<IpNetwork, ProtocolTopologyError> {
     // Debug-enabled resolver: emits progress logs and provides short-circuiting for diagnostics.
     use std::env;
-    println!("[resolve_network_prefix] start");
+    debug_log!("[resolve_network_prefix] start");
 
     // Step 0: Extract data and check if network is a pseudonode
     let network = if let NodeInfo::Network(net) = &network_node.info {
@@ -400,7 +763,7 @@ fn resolve_network_prefix(
         .ok()
         .and_then(|s| s.parse::<usize>().ok());
     if let Some(m) = max_router_consider {
-        println!("[resolve_network_prefix] limiting router scan to {}", m);
+        debug_log!("[resolve_network_prefix] limiting router scan to {}", m);
     }
 
     // Step 1: try to find and check DIS (diagnostic only; no single-prefix shortcut)
@@ -414,24 +777,24 @@ fn resolve_network_prefix(
         });
 
     if let Some(dis_data) = &dis_data {
-        println!("[resolve_network_prefix] found candidate DIS data");
+        debug_log!("[resolve_network_prefix] found candidate DIS data");
         if let Some(Tlv::ExtendedIpReachability(ext_ip_reach)) = dis_data
             .tlvs
             .iter()
             .find(|t| matches!(t, Tlv::ExtendedIpReachability(_)))
         {
-            println!(
+            debug_log!(
                 "[resolve_network_prefix] DIS has ExtendedIpReachability with {} neighbors",
                 ext_ip_reach.neighbors.len()
             );
             if ext_ip_reach.neighbors.len() == 1 {
-                println!(
+                debug_log!(
                     "[resolve_network_prefix] DIS ext-ip-reach has 1 entry; not using DIS-only shortcut"
                 );
             }
         }
     } else {
-        println!("[resolve_network_prefix] no DIS candidate found");
+        debug_log!("[resolve_network_prefix] no DIS candidate found");
     }
 
     // Step 2: find all routers connected to our network, if they all advertise the same prefix
@@ -441,7 +804,7 @@ fn resolve_network_prefix(
         .iter()
         .find(|t| matches!(t, Tlv::ExtendedReachability(_)))
     {
-        println!(
+        debug_log!(
             "[resolve_network_prefix] ExtendedReach found with {} neighbors",
             ext_reach.neighbors.len()
         );
@@ -469,7 +832,7 @@ fn resolve_network_prefix(
             .filter(|data| neighbor_lsp_ids.contains(&data.lsp_id))
             .collect();
 
-        println!(
+        debug_log!(
             "[resolve_network_prefix] collected {} neighbor IS-IS data entries",
             neighbor_isis_data.len()
         );
@@ -477,7 +840,7 @@ fn resolve_network_prefix(
         // If same-level neighbors are insufficient, try a cross-level fallback:
         // gather neighbor IS-IS data ignoring level, and attempt intersection there.
         if neighbor_isis_data.len() < 2 {
-            println!(
+            debug_log!(
                 "[resolve_network_prefix] insufficient same-level neighbors ({}); trying cross-level fallback",
                 neighbor_isis_data.len()
             );
@@ -512,7 +875,7 @@ fn resolve_network_prefix(
                 }
             }
 
-            println!(
+            debug_log!(
                 "[resolve_network_prefix] cross-level candidates: {}",
                 neighbor_isis_data_fallback.len()
             );
@@ -533,7 +896,7 @@ fn resolve_network_prefix(
                     })
                     .collect();
 
-                println!(
+                debug_log!(
                     "[resolve_network_prefix] cross-level ext-ip-reach TLVs: {}",
                     neighbor_ext_ip_reaches.len()
                 );
@@ -570,7 +933,7 @@ fn resolve_network_prefix(
                                 }
                             }
                             if let Some(prefix) = best {
-                                println!(
+                                debug_log!(
                                     "[resolve_network_prefix] cross-level common prefix: {}",
                                     prefix
                                 );
@@ -581,7 +944,7 @@ fn resolve_network_prefix(
                 }
             }
 
-            println!(
+            debug_log!(
                 "[resolve_network_prefix] cross-level fallback failed; leaving prefix unresolved"
             );
             return Err(ProtocolTopologyError::Semantic(
@@ -604,20 +967,20 @@ fn resolve_network_prefix(
             })
             .collect();
 
-        println!(
+        debug_log!(
             "[resolve_network_prefix] collected {} neighbor ExtendedIpReach TLVs",
             neighbor_ext_ip_reaches.len()
         );
 
         let common_prefix = find_common_prefix(&neighbor_ext_ip_reaches);
         if let Some(prefix) = common_prefix {
-            println!(
+            debug_log!(
                 "[resolve_network_prefix] found common prefix among neighbors: {}",
                 prefix
             );
             return Ok(prefix);
         } else {
-            println!("[resolve_network_prefix] no common prefix among neighbors");
+            debug_log!("[resolve_network_prefix] no common prefix among neighbors");
         }
 
         fn find_common_prefix(reaches: &[&ExtendedIpReachabilityTlv]) -> Option<IpNetwork> {
@@ -663,13 +1026,21 @@ fn resolve_network_prefix(
     ))
 }
 
+#[cfg(test)]
 mod tests {
     #[allow(unused)]
     use super::*;
     #[allow(unused)]
-    use crate::parsers::isis_parser::frr_json_lsp::JsonLsp;
+    use crate::parsers::isis_parser::frr_json_lsp::{JsonLsp, lsp_fixture};
     #[allow(unused)]
     use serde_json::json;
+    use crate::{
+        network::router::InterfaceStats,
+        topology::{
+            protocol::{AcquisitionError, AcquisitionSource, Topology},
+            source::TopologySource,
+        },
+    };
 
     #[test]
     fn test_lsp_to_router() {
@@ -734,7 +1105,7 @@ mod tests {
 
         let hostname_map = HostnameMap::build_map_from_lines(map_input.lines());
 
-        let proto = JsonIsisProtocol { hostname_map };
+        let proto = JsonIsisProtocol::new(hostname_map);
         let lsp = json_lsp.try_into_lsp(1, &proto.hostname_map).unwrap();
         let parsed = proto.item_to_node(lsp).unwrap();
 
@@ -779,7 +1150,7 @@ mod tests {
 
         let hostname_map = HostnameMap::build_map_from_lines(map_input.lines());
 
-        let proto = JsonIsisProtocol { hostname_map };
+        let proto = JsonIsisProtocol::new(hostname_map);
         let lsp = json_lsp.try_into_lsp(1, &proto.hostname_map).unwrap();
         let parsed = proto.item_to_node(lsp).unwrap();
 
@@ -790,4 +1161,291 @@ mod tests {
 
         println!("Parsed ISIS network: {:#?}", parsed);
     }
+
+    #[test]
+    fn test_retain_isis_topology() {
+        assert_eq!(MtId::from_frr("Extended"), MtId::Standard);
+        assert_eq!(MtId::from_frr("IPv6 unicast"), MtId::Ipv6Unicast);
+        assert_eq!(MtId::from_frr("ipv6-unicast"), MtId::Ipv6Unicast);
+
+        let router = lsp_fixture("r1.00-00", json!({
+            "hostname": "r1",
+            "extReach": [
+                { "mtId": "Extended", "id": "0000.0000.0001.5a", "metric": 10 },
+                { "mtId": "IPv6 unicast", "id": "0000.0000.0004.00", "metric": 20 }
+            ],
+            "extIpReach": [
+                { "mtId": "Extended", "ipReach": "172.21.14.0/24", "ipReachMetric": 10, "down": false },
+                { "mtId": "IPv6 unicast", "ipReach": "2001:db8::/64", "ipReachMetric": 10, "down": false }
+            ]
+        }));
+        let pseudonode = lsp_fixture("r1.5a-00", json!({
+            "extReach": [
+                { "mtId": "Extended", "id": "0000.0000.0001.00", "metric": 0 },
+                { "mtId": "Extended", "id": "0000.0000.0004.00", "metric": 0 }
+            ]
+        }));
+
+        let map_input = include_str!("../../../test_data/isis_hostname_map_input.txt");
+        let proto = JsonIsisProtocol::new(HostnameMap::build_map_from_lines(map_input.lines()));
+        let mut nodes: Vec<Node> = [router, pseudonode]
+            .into_iter()
+            .map(|v| {
+                let lsp = serde_json::from_value::<JsonLsp>(v)
+                    .unwrap()
+                    .try_into_lsp(1, &proto.hostname_map)
+                    .unwrap();
+                proto.item_to_node(lsp).unwrap().unwrap()
+            })
+            .collect();
+        proto.post_process(&mut nodes).unwrap();
+        assert_eq!(isis_topologies(&nodes), vec![MtId::Standard, MtId::Ipv6Unicast]);
+
+        let counts = |nodes: &[Node]| {
+            let mut neighbors = 0;
+            let mut prefixes = 0;
+            let mut attached = 0;
+            for node in nodes {
+                match &node.info {
+                    NodeInfo::Router(r) => {
+                        let Some(ProtocolData::IsIs(data)) = &r.protocol_data else { continue };
+                        for tlv in &data.tlvs {
+                            match tlv {
+                                Tlv::ExtendedReachability(t) => neighbors += t.neighbors.len(),
+                                Tlv::ExtendedIpReachability(t) => prefixes += t.neighbors.len(),
+                                _ => {}
+                            }
+                        }
+                    }
+                    NodeInfo::Network(n) => attached += n.attached_routers.len(),
+                    NodeInfo::Area(_) => {}
+                }
+            }
+            (neighbors, prefixes, attached)
+        };
+
+        let mut standard = nodes.clone();
+        retain_isis_topology(&mut standard, &MtId::Standard);
+        // r4 isn't in the node set, so its attachment is kept
+        assert_eq!(counts(&standard), (1, 1, 2));
+
+        let mut ipv6 = nodes.clone();
+        retain_isis_topology(&mut ipv6, &MtId::Ipv6Unicast);
+        // r1 has no IPv6 adjacency to the pseudonode
+        assert_eq!(counts(&ipv6), (1, 1, 1));
+    }
+
+    #[test]
+    fn test_prefix_resolution_report() {
+        let json = lsp_fixture("r1.5a-00", json!({
+            "extReach": [{ "mtId": "Extended", "id": "0000.0000.0001.00", "metric": 0 }]
+        }));
+
+        let json_lsp: JsonLsp = serde_json::from_value(json).unwrap();
+        let map_input = include_str!("../../../test_data/isis_hostname_map_input.txt");
+        let hostname_map = HostnameMap::build_map_from_lines(map_input.lines());
+        let proto = JsonIsisProtocol::new(hostname_map);
+        let lsp = json_lsp.try_into_lsp(1, &proto.hostname_map).unwrap();
+        let mut unresolved = proto.item_to_node(lsp).unwrap().unwrap();
+        if let NodeInfo::Network(net) = &unresolved.info
+            && let Some(ProtocolData::IsIs(data)) = &net.protocol_data
+        {
+            unresolved.id = unresolved_network_id(&data.lsp_id);
+        }
+
+        let mut resolved = unresolved.clone();
+        if let NodeInfo::Network(net) = &mut resolved.info {
+            net.ip_address = "172.21.14.0/24".parse().unwrap();
+        }
+        resolved.id = Uuid::new_v5(&Uuid::NAMESPACE_OID, "172.21.14.0/24".as_bytes());
+
+        let mut stale = resolved.clone();
+        if let NodeInfo::Network(net) = &mut stale.info {
+            net.ip_address = "172.21.15.0/24".parse().unwrap();
+        }
+
+        let report =
+            PrefixResolutionReport::from_nodes(&[unresolved.clone(), unresolved, resolved, stale]);
+        assert_eq!(report.resolved, 2);
+        assert_eq!(report.unresolved.len(), 2);
+        assert_eq!(report.stale_ids, 1);
+        assert!(report.has_collisions());
+    }
+
+    #[test]
+    fn test_unresolved_prefix_policy() {
+        // Neither router advertises a prefix, so the pseudonode can't be resolved
+        let router = |id: &str, hostname: &str| {
+            lsp_fixture(id, json!({
+                "hostname": hostname,
+                "extReach": [
+                    { "mtId": "Extended", "id": "0000.0000.0001.5a", "metric": 10 }
+                ]
+            }))
+        };
+        let pseudonode = lsp_fixture("r1.5a-00", json!({
+            "extReach": [
+                { "mtId": "Extended", "id": "0000.0000.0001.00", "metric": 0 },
+                { "mtId": "Extended", "id": "0000.0000.0004.00", "metric": 0 }
+            ]
+        }));
+
+        let map_input = include_str!("../../../test_data/isis_hostname_map_input.txt");
+        let build = || {
+            let proto = JsonIsisProtocol::new(HostnameMap::build_map_from_lines(map_input.lines()));
+            let lsps = [router("r1.00-00", "r1"), router("r4.00-00", "r4"), pseudonode.clone()];
+            let mut nodes: Vec<Node> = lsps
+                .into_iter()
+                .map(|v| {
+                    let lsp = serde_json::from_value::<JsonLsp>(v)
+                        .unwrap()
+                        .try_into_lsp(1, &proto.hostname_map)
+                        .unwrap();
+                    proto.item_to_node(lsp).unwrap().unwrap()
+                })
+                .collect();
+            proto.post_process(&mut nodes).unwrap();
+            nodes
+        };
+
+        let kept = build();
+        assert_eq!(kept.len(), 3);
+        let network = kept.iter().find(|n| matches!(n.info, NodeInfo::Network(_))).unwrap();
+        let NodeInfo::Network(net) = &network.info else { unreachable!() };
+        assert!(net.is_unresolved());
+        let lsp_id = LspId::from_string("0000.0000.0001.5a-00").unwrap();
+        assert_eq!(network.id, unresolved_network_id(&lsp_id));
+        assert!(!PrefixResolutionReport::from_nodes(&kept).has_collisions());
+
+        let mut collapsed = build();
+        assert_eq!(collapse_unresolved_networks(&mut collapsed), 1);
+        assert_eq!(collapsed.len(), 2);
+        for node in &collapsed {
+            let NodeInfo::Router(r) = &node.info else {
+                panic!("network should have been collapsed");
+            };
+            let Some(ProtocolData::IsIs(data)) = &r.protocol_data else { unreachable!() };
+            let neighbors: Vec<&crate::parsers::isis_parser::core_lsp::ExtendedIsNeighbor> = data
+                .tlvs
+                .iter()
+                .filter_map(|tlv| match tlv {
+                    Tlv::ExtendedReachability(t) => Some(t),
+                    _ => None,
+                })
+                .flat_map(|t| t.neighbors.iter())
+                .collect();
+            assert_eq!(neighbors.len(), 1);
+            assert_eq!(neighbors[0].pseudonode_id, 0);
+            assert_eq!(neighbors[0].metric, 10);
+            assert_ne!(RouterId::IsIs(neighbors[0].neighbor_id.clone()), r.id);
+        }
+    }
+
+    #[test]
+    fn test_isis_area_filter() {
+        let lsp = |id: &str| lsp_fixture(id, json!({}));
+        // r1 is the L2 router between the two areas
+        let lspdb = json!({
+            "areas": [
+                {
+                    "area": { "name": "east" },
+                    "levels": [
+                        { "id": 1, "lsps": [lsp("r1.00-00"), lsp("r2.00-00")] },
+                        { "id": 2, "lsps": [lsp("r1.00-00")] }
+                    ]
+                },
+                {
+                    "area": { "name": "west" },
+                    "levels": [{ "id": 1, "lsps": [lsp("r1.00-00"), lsp("r3.00-00")] }]
+                }
+            ]
+        });
+        let lspdb: JsonLspdb = serde_json::from_value(lspdb).unwrap();
+
+        let map_input = include_str!("../../../test_data/isis_hostname_map_input.txt");
+        let proto = JsonIsisProtocol::new(HostnameMap::build_map_from_lines(map_input.lines()));
+        let lsps = proto.parse(lspdb.into()).unwrap();
+        for lsp in &lsps {
+            let expected = match lsp.system_id.to_string().as_str() {
+                "0000.0000.0001" => vec!["east", "west"],
+                "0000.0000.0002" => vec!["east"],
+                _ => vec!["west"],
+            };
+            assert_eq!(lsp.areas, expected);
+        }
+
+        let nodes: Vec<Node> = lsps
+            .into_iter()
+            .filter_map(|lsp| proto.item_to_node(lsp).unwrap())
+            .collect();
+        assert_eq!(isis_areas(&nodes), vec!["east", "west"]);
+
+        let ids = |nodes: &[Node]| {
+            let mut ids: Vec<Uuid> = nodes.iter().map(|n| n.id).collect();
+            ids.sort();
+            ids.dedup();
+            ids
+        };
+        let router = |system_id: &str| {
+            RouterId::IsIs(SystemId::from_string(system_id).unwrap()).to_uuidv5()
+        };
+        let mut west = nodes.clone();
+        retain_isis_area(&mut west, "west");
+        let mut expected = vec![router("0000.0000.0001"), router("0000.0000.0003")];
+        expected.sort();
+        assert_eq!(ids(&west), expected);
+    }
+
+    /// Hands out a fixed LSPDB and, when `hostnames` is set, refreshes the hostname map from it.
+    struct FixedLspdb {
+        lspdb: serde_json::Value,
+        hostnames: Option<&'static str>,
+    }
+
+    #[async_trait::async_trait]
+    impl AcquisitionSource<JsonIsisProtocol> for FixedLspdb {
+        async fn refresh_protocol(&mut self, protocol: &mut JsonIsisProtocol) -> Result<(), AcquisitionError> {
+            if let Some(hostnames) = self.hostnames {
+                protocol.set_hostname_map(HostnameMap::build_map_from_lines(hostnames.lines()));
+            }
+            Ok(())
+        }
+
+        async fn fetch_raw(&mut self) -> Result<Vec<IsisLspdb>, AcquisitionError> {
+            let lspdb: JsonLspdb = serde_json::from_value(self.lspdb.clone()).unwrap();
+            Ok(vec![lspdb.into()])
+        }
+
+        async fn fetch_source_id(&mut self) -> Result<RouterId, AcquisitionError> {
+            Ok(RouterId::IsIs(SystemId::from_string("0000.0000.0001").unwrap()))
+        }
+
+        async fn fetch_stats(&mut self) -> Result<Vec<InterfaceStats>, AcquisitionError> {
+            Ok(Vec::new())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hostname_map_refreshed_before_fetch() {
+        let lsp = |id: &str| lsp_fixture(id, json!({}));
+        let lspdb = json!({
+            "areas": [{
+                "area": { "name": "east" },
+                "levels": [{ "id": 1, "lsps": [lsp("r1.00-00"), lsp("r2.00-00")] }]
+            }]
+        });
+        let empty = || JsonIsisProtocol::new(HostnameMap::build_map_from_lines(std::iter::empty::<&str>()));
+
+        // Nothing refreshes the empty map, so no LSP resolves
+        let mut stale = Topology::new(empty(), FixedLspdb { lspdb: lspdb.clone(), hostnames: None });
+        assert!(stale.fetch_nodes().await.unwrap().is_empty());
+        assert!(stale.take_parse_warnings()[0].message.contains("'r1'"));
+
+        let map_input = include_str!("../../../test_data/isis_hostname_map_input.txt");
+        let mut refreshed = Topology::new(empty(), FixedLspdb { lspdb, hostnames: Some(map_input) });
+        let nodes = refreshed.fetch_nodes().await.unwrap();
+        let r2 = RouterId::IsIs(SystemId::from_string("0000.0000.0002").unwrap()).to_uuidv5();
+        assert!(nodes.iter().any(|node| node.id == r2));
+        assert!(refreshed.take_parse_warnings().is_empty());
+    }
 }
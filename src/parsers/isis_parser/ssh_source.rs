@@ -1,9 +1,9 @@
 use async_trait::async_trait;
 
 use crate::{
-    data_aquisition::ssh::SshClient, network::router::InterfaceStats, parsers::isis_parser::{
+    data_aquisition::ssh::SshClient, logging::debug_log, network::router::InterfaceStats, parsers::isis_parser::{
         core_lsp::NetAddress, frr_json_lsp::JsonLspdb, hostname::HostnameMap,
-        protocol::JsonIsisProtocol,
+        protocol::{IsisLspdb, JsonIsisProtocol},
     }, topology::{
         protocol::{AcquisitionError, AcquisitionSource},
         store::SourceId,
@@ -21,9 +21,9 @@ impl IsisSshSource {
     }
 
     pub async fn fetch_hostname_map(&self) -> Result<HostnameMap, AcquisitionError> {
-        println!("[IsisSshSource] fetch_hostname_map: start");
+        debug_log!("[IsisSshSource] fetch_hostname_map: start");
         if !self.client.is_connected() {
-            println!("[IsisSshSource] fetch_hostname_map: client not connected");
+            debug_log!("[IsisSshSource] fetch_hostname_map: client not connected");
             return Err(AcquisitionError::Transport(
                 "SSH client is not connected".to_string(),
             ));
@@ -37,12 +37,12 @@ impl IsisSshSource {
                 AcquisitionError::Transport(format!("Failed to execute command: {}", e))
             })?;
 
-        println!(
+        debug_log!(
             "[IsisSshSource] fetch_hostname_map: got output length {}",
             output.len()
         );
         let map = HostnameMap::build_map_from_lines(output.lines());
-        println!(
+        debug_log!(
             "[IsisSshSource] fetch_hostname_map: built hostname map ({} entries)",
             map.len()
         );
@@ -50,9 +50,9 @@ impl IsisSshSource {
     }
 
     async fn fetch_json_lspdb(&self) -> Result<JsonLspdb, AcquisitionError> {
-        println!("[IsisSshSource] fetch_json_lspdb: start");
+        debug_log!("[IsisSshSource] fetch_json_lspdb: start");
         if !self.client.is_connected() {
-            println!("[IsisSshSource] fetch_json_lspdb: client not connected");
+            debug_log!("[IsisSshSource] fetch_json_lspdb: client not connected");
             return Err(AcquisitionError::Transport(
                 "SSH client is not connected".to_string(),
             ));
@@ -63,7 +63,7 @@ impl IsisSshSource {
             .execute_command("vtysh -c 'show isis database detail json'")
             .await
             .map_err(|e| AcquisitionError::Transport(format!("Failed to retrieve LSPDB: {}", e)))?;
-        println!(
+        debug_log!(
             "[IsisSshSource] fetch_json_lspdb: received output size {}",
             output.len()
         );
@@ -77,7 +77,7 @@ impl IsisSshSource {
             .flat_map(|a| a.levels.iter())
             .map(|l| l.lsps.len())
             .sum();
-        println!(
+        debug_log!(
             "[IsisSshSource] fetch_json_lspdb: parsed JsonLspdb with {} areas and {} total lsps",
             lspdb.areas.len(),
             total_lsps
@@ -87,7 +87,7 @@ impl IsisSshSource {
         if let Ok(max_str) = env::var("ISIS_MAX_LSPS") {
             if let Ok(max) = max_str.parse::<usize>() {
                 if max > 0 {
-                    println!(
+                    debug_log!(
                         "[IsisSshSource] fetch_json_lspdb: ISIS_MAX_LSPS={} set, truncating to max",
                         max
                     );
@@ -104,13 +104,13 @@ impl IsisSshSource {
                             }
                         }
                     }
-                    println!(
+                    debug_log!(
                         "[IsisSshSource] fetch_json_lspdb: truncated, removed {} lsps",
                         removed
                     );
                 }
             } else {
-                println!(
+                eprintln!(
                     "[IsisSshSource] fetch_json_lspdb: invalid ISIS_MAX_LSPS value '{}'",
                     max_str
                 );
@@ -121,9 +121,9 @@ impl IsisSshSource {
     }
 
     async fn fetch_source_id(&self) -> Result<SourceId, AcquisitionError> {
-        println!("[IsisSshSource] fetch_source_id: start");
+        debug_log!("[IsisSshSource] fetch_source_id: start");
         if !self.client.is_connected() {
-            println!("[IsisSshSource] fetch_source_id: client not connected");
+            debug_log!("[IsisSshSource] fetch_source_id: client not connected");
             return Err(AcquisitionError::Transport(
                 "SSH client is not connected".to_string(),
             ));
@@ -137,7 +137,7 @@ impl IsisSshSource {
                 AcquisitionError::Transport(format!("Failed to retrieve source ID: {}", e))
             })?;
 
-        println!(
+        debug_log!(
             "[IsisSshSource] fetch_source_id: got output size {}",
             output.len()
         );
@@ -156,7 +156,7 @@ impl IsisSshSource {
         let cmd_output = self.client
             .execute_command("ip -j -s link show").await
             .map_err(|err| AcquisitionError::Transport(err.to_string()))?;
-        println!("Stats cmd output: {}", cmd_output);
+        debug_log!("[IsisSshSource] fetch_if_id_to_stats: output {}", cmd_output);
         let json: serde_json::Value = serde_json::from_str(&cmd_output)
             .map_err(|err| AcquisitionError::Invalid(err.to_string()))?;
         
@@ -216,7 +216,7 @@ impl IsisSshSource {
             .await
             .map_err(|e| AcquisitionError::Invalid(format!("Failed to execute command: {}", e)))?;
         
-        println!("IP command output: {}", cmd_output);
+        debug_log!("[IsisSshSource] fetch_if_id_to_ip: output {}", cmd_output);
         
         let json: Value = serde_json::from_str(&cmd_output)
             .map_err(|e| AcquisitionError::Invalid(format!("Failed to parse JSON: {}", e)))?;
@@ -227,7 +227,7 @@ impl IsisSshSource {
             for if_obj in interfaces.values() {
                 if let Value::Object(if_details) = if_obj {
                     let id = if_details.get("index").and_then(|v| v.as_u64());
-                    dbg!(&id);
+                    debug_log!("[IsisSshSource] fetch_if_id_to_ip: interface index {:?}", id);
                     let mut ip = if let Some(Value::Array(ips)) = if_details.get("ipAddresses") {
                         let primary_ip_objs: Vec<_> = ips.iter().filter_map(|ip| {
                             if let Value::Object(ip_obj) = ip {
@@ -247,7 +247,7 @@ impl IsisSshSource {
                         
                         if let Some(primary_ip_obj) = primary_ip_objs.first() {
                             if let Some(Value::String(ip)) = primary_ip_obj.get("address") {
-                                dbg!(ip);
+                                debug_log!("[IsisSshSource] fetch_if_id_to_ip: primary address {}", ip);
                                 let ip = ip.split('/').next().unwrap().to_string();
                                 Some(ip.parse::<IpAddr>().map_err(|_| AcquisitionError::Invalid(format!("Invalid IP address: {}", ip)))?)
                             } else {
@@ -292,25 +292,33 @@ struct Stats {
 
 #[async_trait]
 impl AcquisitionSource<JsonIsisProtocol> for IsisSshSource {
-    async fn fetch_raw(&mut self) -> Result<Vec<JsonLspdb>, AcquisitionError> {
-        println!("[IsisSshSource] fetch_raw: start");
+    async fn fetch_raw(&mut self) -> Result<Vec<IsisLspdb>, AcquisitionError> {
+        debug_log!("[IsisSshSource] fetch_raw: start");
         let lspdb = self.fetch_json_lspdb().await?;
-        println!("[IsisSshSource] fetch_raw: returning 1 JsonLspdb");
-        Ok(vec![lspdb])
+        debug_log!("[IsisSshSource] fetch_raw: returning 1 JsonLspdb");
+        Ok(vec![lspdb.into()])
+    }
+
+    /// Re-read `show isis hostname` before every poll, so routers that joined since the topology
+    /// was built resolve to a system ID.
+    async fn refresh_protocol(&mut self, protocol: &mut JsonIsisProtocol) -> Result<(), AcquisitionError> {
+        let hostname_map = self.fetch_hostname_map().await?;
+        protocol.set_hostname_map(hostname_map);
+        Ok(())
     }
 
     async fn fetch_source_id(&mut self) -> Result<SourceId, AcquisitionError> {
         // IMPORTANT: call the inherent method explicitly to avoid accidental recursion.
         // We have an inherent async method `fetch_source_id(&self)` above; call it with an explicit receiver.
-        println!("[IsisSshSource] trait fetch_source_id: delegating to inherent method");
+        debug_log!("[IsisSshSource] trait fetch_source_id: delegating to inherent method");
         IsisSshSource::fetch_source_id(&*self).await
     }
     
     async fn fetch_stats(&mut self) -> Result<Vec<InterfaceStats>, AcquisitionError> {
         let if_id_to_stats = self.fetch_if_id_to_stats().await?;
-        dbg!(&if_id_to_stats);
+        debug_log!("[IsisSshSource] fetch_stats: {:?}", if_id_to_stats);
         let if_id_to_ip = self.fetch_if_id_to_ip().await?;
-        dbg!(&if_id_to_ip);
+        debug_log!("[IsisSshSource] fetch_stats: {:?}", if_id_to_ip);
         let mut stats = Vec::new();
         
         for (if_id, ip_address) in if_id_to_ip {
@@ -330,7 +338,7 @@ impl AcquisitionSource<JsonIsisProtocol> for IsisSshSource {
 }
 
 mod tests {
-    use crate::data_aquisition::ssh::SshError;
+    use crate::data_aquisition::ssh::{HostKeyPolicy, SshError};
 
     use super::*;
     
@@ -341,7 +349,9 @@ mod tests {
             "localhost".to_string(),
             "password".to_string(),
             2221,
-        );
+        )
+        // The lab containers get a new host key every time they are rebuilt
+        .with_host_key_policy(HostKeyPolicy::Off);
 
         client.connect().await?;
 
@@ -1,12 +1,58 @@
-use crate::{data_aquisition::ssh::SshClient, parsers::isis_parser::{protocol::JsonIsisProtocol, ssh_source::IsisSshSource}, topology::protocol::{AcquisitionError, Topology}};
+use std::time::Duration;
+
+use thiserror::Error;
+
+use crate::{data_aquisition::{netconf::NetconfClient, ssh::{HostKeyRejection, SshClient, SshError}}, parsers::isis_parser::{hostname::HostnameMap, netconf_source::IsisNetconfSource, protocol::JsonIsisProtocol, ssh_source::IsisSshSource}, topology::protocol::{AcquisitionError, Topology}};
 
 
 pub type IsIsTopology = Topology<JsonIsisProtocol, IsisSshSource>;
+pub type IsIsNetconfTopology = Topology<JsonIsisProtocol, IsisNetconfSource>;
+
+const CONNECT_ATTEMPTS: u32 = 3;
+const CONNECT_INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Why an IS-IS topology over SSH couldn't be set up, split so the GUI can tell the user what to fix.
+#[derive(Debug, Error)]
+pub enum IsIsConnectError {
+    #[error("Authentication failed, check username and password ({0})")]
+    AuthFailed(String),
+    #[error("Couldn't connect to the router, check host, port and reachability ({0})")]
+    Connect(String),
+    #[error("Connected, but running vtysh failed ({0})")]
+    CommandFailed(String),
+    #[error("Connected, but the router output couldn't be parsed ({0})")]
+    ParseFailed(String),
+    /// Kept apart from `Connect` so the GUI can ask whether to trust the key
+    #[error("{0}")]
+    HostKey(HostKeyRejection),
+}
+
+impl From<SshError> for IsIsConnectError {
+    fn from(e: SshError) -> Self {
+        match e {
+            SshError::SshAuthError(_) => IsIsConnectError::AuthFailed(e.to_string()),
+            SshError::CommandError(_) => IsIsConnectError::CommandFailed(e.to_string()),
+            SshError::HostKey(rejection) => IsIsConnectError::HostKey(rejection),
+            SshError::TcpError(_) | SshError::Timeout(_) | SshError::SshError(_) | SshError::AsyncError(_) => {
+                IsIsConnectError::Connect(e.to_string())
+            }
+        }
+    }
+}
+
+impl From<AcquisitionError> for IsIsConnectError {
+    fn from(e: AcquisitionError) -> Self {
+        match e {
+            AcquisitionError::Transport(msg) => IsIsConnectError::CommandFailed(msg),
+            AcquisitionError::Invalid(msg) => IsIsConnectError::ParseFailed(msg),
+        }
+    }
+}
 
 impl IsIsTopology {
-    pub async fn new_from_ssh_client(mut client: SshClient) -> Result<Self, AcquisitionError> {
+    pub async fn new_from_ssh_client(mut client: SshClient) -> Result<Self, IsIsConnectError> {
         if !client.is_connected() {
-            client.connect().await.map_err(|e| AcquisitionError::Transport(format!("Couldn't connect to SSH client: {}", e)))?;
+            client.connect_with_retry(CONNECT_ATTEMPTS, CONNECT_INITIAL_BACKOFF).await?;
         }
         
         let source = IsisSshSource::new(client);
@@ -19,4 +65,16 @@ impl IsIsTopology {
         
         Ok(topology)
     }
-}
\ No newline at end of file
+}
+
+impl IsIsNetconfTopology {
+    /// The `ietf-isis` model names LSPs by system ID, so no hostname map is needed.
+    pub async fn new_from_netconf_client(mut client: NetconfClient) -> Result<Self, IsIsConnectError> {
+        if !client.ssh().is_connected() {
+            client.ssh_mut().connect_with_retry(CONNECT_ATTEMPTS, CONNECT_INITIAL_BACKOFF).await?;
+        }
+
+        let protocol = JsonIsisProtocol::new(HostnameMap::build_map_from_lines(std::iter::empty::<&str>()));
+        Ok(Topology::new(protocol, IsisNetconfSource::new(client)))
+    }
+}
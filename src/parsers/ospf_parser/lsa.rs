@@ -1,7 +1,9 @@
 use crate::{
     network::node::{
-        Node, NodeInfo, OspfData, OspfRouterPayload, PerAreaRouterFacet, ProtocolData,
+        Node, NodeInfo, OspfData, OspfRouterPayload, OspfVirtualLink, PerAreaRouterFacet,
+        ProtocolData,
     },
+    logging::debug_log,
     parsers::ospf_parser::source::OspfRawRow, topology::protocol::ProtocolTopologyError,
 };
 use ipnetwork::IpNetwork;
@@ -91,8 +93,12 @@ impl TryInto<Node> for OspfLsdbEntry {
             OspfLinkStateAdvertisement::SummaryLinkIpNetwork(_) => {
                 NodeInfo::Network(parse_lsa_type_3(&self)?)
             }
+            OspfLinkStateAdvertisement::ASExternalLink(_)
+            | OspfLinkStateAdvertisement::NSSAASExternal(_) => {
+                NodeInfo::Network(parse_lsa_type_5_7(&self)?)
+            }
             _ => {
-                println!("Unsupported advertisement type");
+                debug_log!("Unsupported advertisement type");
                 return Err(LsaError::InvalidLsaType);
             }
         };
@@ -110,9 +116,11 @@ pub fn parse_lsa_type_1_to_router(lsa: &OspfLsdbEntry) -> Result<Router, LsaErro
         };
 
     let router_id = RouterId::Ipv4(lsa.router_id);
+    // Stub links carry the network mask as link data, not an interface address
     let interfaces: Vec<IpAddr> = advertisement
         .links
         .iter()
+        .filter(|link| link.link_type != ospf_parser::OspfRouterLinkType::Stub)
         .map(|link| IpAddr::V4(link.link_data()))
         .collect();
     // Compute link counts and per-link metrics from Router-LSA links
@@ -121,6 +129,7 @@ pub fn parse_lsa_type_1_to_router(lsa: &OspfLsdbEntry) -> Result<Router, LsaErro
     let mut stub_link_count = 0usize;
     let mut link_metrics: std::collections::HashMap<Ipv4Addr, u16> =
         std::collections::HashMap::new();
+    let mut virtual_links = Vec::new();
 
     for link in &advertisement.links {
         // Use link_data as a stable IPv4 key (for p2p this is the local interface IP).
@@ -130,7 +139,12 @@ pub fn parse_lsa_type_1_to_router(lsa: &OspfLsdbEntry) -> Result<Router, LsaErro
             ospf_parser::OspfRouterLinkType::PointToPoint => p2p_link_count += 1,
             ospf_parser::OspfRouterLinkType::Transit => transit_link_count += 1,
             ospf_parser::OspfRouterLinkType::Stub => stub_link_count += 1,
-            ospf_parser::OspfRouterLinkType::Virtual => {}
+            // Virtual: link id = peer router id, link data = own interface
+            ospf_parser::OspfRouterLinkType::Virtual => virtual_links.push(OspfVirtualLink {
+                peer_router_id: RouterId::Ipv4(link.link_id()),
+                transit_area_id: Ipv4Addr::UNSPECIFIED,
+                metric: link.tos_0_metric,
+            }),
             _ => {}
         }
     }
@@ -158,8 +172,9 @@ pub fn parse_lsa_type_1_to_router(lsa: &OspfLsdbEntry) -> Result<Router, LsaErro
             p2p_link_count,
             transit_link_count,
             stub_link_count,
+            is_virtual_link_transit: is_virtual_link_endpoint,
         }],
-        virtual_links: vec![],
+        virtual_links,
     };
 
     let checksum = Some(advertisement.header.ls_checksum);
@@ -178,6 +193,8 @@ pub fn parse_lsa_type_1_to_router(lsa: &OspfLsdbEntry) -> Result<Router, LsaErro
         id: router_id,
         interfaces,
         protocol_data: Some(ProtocolData::Ospf(ospf_data)),
+        hostname: None,
+        description: None,
     };
 
     Ok(router)
@@ -223,6 +240,7 @@ pub fn parse_lsa_type_2_to_network(lsa: &OspfLsdbEntry) -> Result<Network, LsaEr
         ip_address: network,
         protocol_data: Some(protocol_data),
         attached_routers: attached_routers,
+        is_synthetic: false,
     })
 }
 
@@ -264,6 +282,63 @@ pub fn parse_lsa_type_3(lsa: &OspfLsdbEntry) -> Result<Network, LsaError> {
         // Attach the originating ABR so the summary network is connected;
         // later consolidation will fold this into a detailed Type-2 if present.
         attached_routers: vec![], //vec![RouterId::Ipv4(lsa.router_id)],
+        is_synthetic: false,
+    })
+}
+
+/// AS-External (Type-5) and NSSA-External (Type-7) LSAs become a network node for the
+/// external prefix, attached to the originating ASBR.
+pub fn parse_lsa_type_5_7(lsa: &OspfLsdbEntry) -> Result<Network, LsaError> {
+    let (network_mask, metric, forwarding_address, route_tag, checksum) =
+        match &*lsa.advertisement {
+            OspfLinkStateAdvertisement::ASExternalLink(ad) => (
+                ad.network_mask(),
+                ad.metric,
+                ad.forwarding_address(),
+                ad.external_route_tag,
+                ad.header.ls_checksum,
+            ),
+            OspfLinkStateAdvertisement::NSSAASExternal(ad) => (
+                ad.network_mask(),
+                ad.metric,
+                ad.forwarding_address(),
+                ad.external_route_tag,
+                ad.header.ls_checksum,
+            ),
+            _ => return Err(LsaError::InvalidLsaType),
+        };
+
+    let net_addr = IpNetwork::with_netmask(IpAddr::V4(lsa.link_state_id), IpAddr::V4(network_mask))
+        .map_err(|_| LsaError::InvalidNetworkMask(network_mask))?;
+
+    let protocol_data = ProtocolData::Ospf(OspfData {
+        area_id: lsa.area_id,
+        advertisement: lsa.advertisement.clone(),
+        link_state_id: lsa.link_state_id,
+        advertising_router: lsa.router_id,
+        checksum: Some(checksum),
+        payload: crate::network::node::OspfPayload::Network(
+            crate::network::node::OspfNetworkPayload {
+                designated_router_id: None,
+                summaries: vec![],
+                externals: vec![crate::network::node::OspfExternalNetPayload {
+                    origin_asbr: RouterId::Ipv4(lsa.router_id),
+                    metric,
+                    // Zero means "not set" for both fields
+                    route_tag: (route_tag != 0).then_some(route_tag),
+                    forwarding_address: (!forwarding_address.is_unspecified())
+                        .then_some(forwarding_address),
+                }],
+            },
+        ),
+        raw_lsa_bytes: lsa.raw_lsa_bytes.clone(),
+    });
+
+    Ok(Network {
+        ip_address: net_addr,
+        protocol_data: Some(protocol_data),
+        attached_routers: vec![RouterId::Ipv4(lsa.router_id)],
+        is_synthetic: false,
     })
 }
 
@@ -275,6 +350,280 @@ mod tests {
     use crate::parsers::ospf_parser::source::OspfDataSource;
 
     use super::*;
+    use crate::network::{node::OspfPayload, router::InterfaceStats};
+    use crate::topology::{
+        ospf_protocol::{ConsolidationPolicy, OspfFederator, OspfProtocol, summary_network_id},
+        protocol::{AcquisitionError, AcquisitionSource, ProtocolFederator, Topology},
+        source::{SnapshotSource, TopologySource},
+        store::SourceId,
+    };
+
+    const AREA: Ipv4Addr = Ipv4Addr::new(0, 0, 0, 0);
+    const R1: Ipv4Addr = Ipv4Addr::new(10, 0, 0, 1);
+    const R2: Ipv4Addr = Ipv4Addr::new(10, 0, 0, 2);
+    const TRANSIT_AREA: Ipv4Addr = Ipv4Addr::new(0, 0, 0, 1);
+
+    /// Raw LSDB row in the backbone for an LSA of `ls_type` with the given body.
+    fn raw_row(ls_type: u8, link_state_id: Ipv4Addr, router_id: Ipv4Addr, body: &[u8]) -> OspfRawRow {
+        OspfRawRow::synthetic(AREA, ls_type, link_state_id, router_id, body)
+    }
+
+    /// Router-LSA from an ABR with one transit link (DR 172.21.14.2) and one stub link.
+    fn router_lsa_row() -> OspfRawRow {
+        let mut body = Vec::new();
+        body.extend_from_slice(&0x0100u16.to_be_bytes()); // flags: B (ABR)
+        body.extend_from_slice(&2u16.to_be_bytes()); // number of links
+        // Transit: link id = DR interface, link data = own interface
+        body.extend_from_slice(&[172, 21, 14, 2, 172, 21, 14, 1, 2, 0]);
+        body.extend_from_slice(&10u16.to_be_bytes());
+        // Stub: link id = network, link data = mask
+        body.extend_from_slice(&[192, 168, 1, 0, 255, 255, 255, 0, 3, 0]);
+        body.extend_from_slice(&1u16.to_be_bytes());
+        raw_row(1, R1, R1, &body)
+    }
+
+    /// Backbone Router-LSA from an ABR with a virtual link to R2. The V bit isn't set here, it
+    /// belongs to the transit area's Router-LSA.
+    fn virtual_link_router_lsa_row() -> OspfRawRow {
+        let mut body = Vec::new();
+        body.extend_from_slice(&0x0100u16.to_be_bytes()); // flags: B
+        body.extend_from_slice(&1u16.to_be_bytes()); // number of links
+        // Virtual: link id = peer router id, link data = own interface
+        body.extend_from_slice(&R2.octets());
+        body.extend_from_slice(&[172, 21, 14, 1, 4, 0]);
+        body.extend_from_slice(&25u16.to_be_bytes());
+        raw_row(1, R1, R1, &body)
+    }
+
+    /// The same ABR's Router-LSA in the transit area (V and B bits set), with one P2P link to R2.
+    fn transit_area_router_lsa_row() -> OspfRawRow {
+        let mut body = Vec::new();
+        body.extend_from_slice(&0x0500u16.to_be_bytes()); // flags: V, B
+        body.extend_from_slice(&1u16.to_be_bytes()); // number of links
+        // P2P: link id = neighbor router id, link data = own interface
+        body.extend_from_slice(&R2.octets());
+        body.extend_from_slice(&[172, 21, 14, 1, 1, 0]);
+        body.extend_from_slice(&25u16.to_be_bytes());
+        OspfRawRow::synthetic(TRANSIT_AREA, 1, R1, R1, &body)
+    }
+
+    /// Network-LSA for 172.21.14.0/24 originated by the DR, with both routers attached.
+    fn network_lsa_row() -> OspfRawRow {
+        let mut body = vec![255, 255, 255, 0];
+        body.extend_from_slice(&R1.octets());
+        body.extend_from_slice(&R2.octets());
+        raw_row(2, Ipv4Addr::new(172, 21, 14, 2), R2, &body)
+    }
+
+    /// Summary-LSA (Type-3) for 192.168.5.0/24 at metric 20.
+    fn summary_lsa_row() -> OspfRawRow {
+        raw_row(3, Ipv4Addr::new(192, 168, 5, 0), R1, &[255, 255, 255, 0, 0, 0, 0, 20])
+    }
+
+    /// ASBR-Summary-LSA (Type-4), which has no node representation.
+    fn asbr_summary_lsa_row() -> OspfRawRow {
+        raw_row(4, R2, R1, &[0, 0, 0, 0, 0, 0, 0, 20])
+    }
+
+    fn row_to_node(row: OspfRawRow) -> Result<Node, LsaError> {
+        OspfLsdbEntry::try_from(row)?.try_into()
+    }
+
+    #[test]
+    fn test_router_lsa_row_to_node() {
+        let node = row_to_node(router_lsa_row()).unwrap();
+        let NodeInfo::Router(router) = &node.info else {
+            panic!("expected a router, got {:?}", node.info);
+        };
+        assert_eq!(router.id, RouterId::Ipv4(R1));
+        // The stub link's mask isn't an interface
+        assert_eq!(router.interfaces, vec![IpAddr::V4(Ipv4Addr::new(172, 21, 14, 1))]);
+
+        let Some(ProtocolData::Ospf(data)) = &router.protocol_data else {
+            panic!("expected OSPF protocol data");
+        };
+        assert_eq!(data.area_id, AREA);
+        assert_eq!(data.advertising_router, R1);
+        assert_eq!(data.checksum, Some(0x1234));
+        assert_eq!(data.age(), 1);
+        assert_eq!(data.sequence_number(), 0x8000_0001);
+        assert!(!data.near_expiry());
+        let OspfPayload::Router(payload) = &data.payload else {
+            panic!("expected a router payload");
+        };
+        assert!(payload.is_abr);
+        assert!(!payload.is_asbr);
+        assert_eq!(payload.transit_link_count, 1);
+        assert_eq!(payload.stub_link_count, 1);
+        assert_eq!(payload.p2p_link_count, 0);
+        assert_eq!(payload.link_metrics.get(&Ipv4Addr::new(172, 21, 14, 1)), Some(&10));
+    }
+
+    fn router_payload(node: &Node) -> &OspfRouterPayload {
+        let NodeInfo::Router(router) = &node.info else {
+            panic!("expected a router, got {:?}", node.info);
+        };
+        let Some(ProtocolData::Ospf(data)) = &router.protocol_data else {
+            panic!("expected OSPF protocol data");
+        };
+        let OspfPayload::Router(payload) = &data.payload else {
+            panic!("expected a router payload");
+        };
+        payload
+    }
+
+    #[test]
+    fn test_virtual_link_row_to_node() {
+        let backbone = row_to_node(virtual_link_router_lsa_row()).unwrap();
+        let payload = router_payload(&backbone);
+        assert!(!payload.is_virtual_link_endpoint);
+        assert!(!payload.per_area_facets[0].is_virtual_link_transit);
+        assert_eq!(payload.virtual_links.len(), 1);
+        assert_eq!(payload.virtual_links[0].peer_router_id, RouterId::Ipv4(R2));
+        assert_eq!(payload.virtual_links[0].metric, 25);
+        assert!(payload.virtual_links[0].transit_area_id.is_unspecified());
+
+        let transit = row_to_node(transit_area_router_lsa_row()).unwrap();
+        let payload = router_payload(&transit);
+        assert!(payload.is_virtual_link_endpoint);
+        assert!(payload.per_area_facets[0].is_virtual_link_transit);
+        assert!(payload.virtual_links.is_empty());
+
+        // Fusion takes the V bit and the transit area from the transit area's LSA
+        let fused = OspfFederator::new().merge_routers(&[backbone, transit]);
+        let payload = router_payload(&fused);
+        assert!(payload.is_virtual_link_endpoint);
+        assert_eq!(payload.virtual_links.len(), 1);
+        assert_eq!(payload.virtual_links[0].transit_area_id, TRANSIT_AREA);
+    }
+
+    #[test]
+    fn test_network_lsa_row_to_node() {
+        let node = row_to_node(network_lsa_row()).unwrap();
+        let NodeInfo::Network(network) = &node.info else {
+            panic!("expected a network, got {:?}", node.info);
+        };
+        assert_eq!(network.ip_address, "172.21.14.0/24".parse::<IpNetwork>().unwrap());
+        assert_eq!(
+            network.attached_routers,
+            vec![RouterId::Ipv4(R1), RouterId::Ipv4(R2)]
+        );
+        assert!(!network.is_synthetic);
+
+        let Some(ProtocolData::Ospf(data)) = &network.protocol_data else {
+            panic!("expected OSPF protocol data");
+        };
+        let OspfPayload::Network(payload) = &data.payload else {
+            panic!("expected a network payload");
+        };
+        assert_eq!(
+            payload.designated_router_id,
+            Some(RouterId::Ipv4(Ipv4Addr::new(172, 21, 14, 2)))
+        );
+        assert!(payload.summaries.is_empty());
+    }
+
+    #[test]
+    fn test_summary_lsa_row_to_node() {
+        let node = row_to_node(summary_lsa_row()).unwrap();
+        let NodeInfo::Network(network) = &node.info else {
+            panic!("expected a network, got {:?}", node.info);
+        };
+        assert_eq!(network.ip_address, "192.168.5.0/24".parse::<IpNetwork>().unwrap());
+        assert!(network.attached_routers.is_empty());
+
+        let Some(ProtocolData::Ospf(data)) = &network.protocol_data else {
+            panic!("expected OSPF protocol data");
+        };
+        let OspfPayload::Network(payload) = &data.payload else {
+            panic!("expected a network payload");
+        };
+        assert_eq!(payload.summaries.len(), 1);
+        assert_eq!(payload.summaries[0].metric, 20);
+        assert_eq!(payload.summaries[0].origin_abr, RouterId::Ipv4(R1));
+    }
+
+    #[test]
+    fn test_unsupported_lsa_row() {
+        let entry = OspfLsdbEntry::try_from(asbr_summary_lsa_row()).unwrap();
+        let node: Result<Node, LsaError> = entry.try_into();
+        assert!(matches!(node, Err(LsaError::InvalidLsaType)));
+
+        let garbage = OspfRawRow::new(AREA, R1, R1, vec![0, 1, 2]);
+        assert!(matches!(
+            OspfLsdbEntry::try_from(garbage),
+            Err(LsaError::WrongDataType)
+        ));
+    }
+
+    /// Acquisition source that hands out fixed rows, for driving `Topology` without a router.
+    struct FixedRows(Vec<OspfRawRow>);
+
+    #[async_trait::async_trait]
+    impl AcquisitionSource<OspfProtocol> for FixedRows {
+        async fn fetch_raw(&mut self) -> Result<Vec<OspfRawRow>, AcquisitionError> {
+            Ok(self.0.clone())
+        }
+
+        async fn fetch_source_id(&mut self) -> Result<SourceId, AcquisitionError> {
+            Ok(RouterId::Ipv4(R1))
+        }
+
+        async fn fetch_stats(&mut self) -> Result<Vec<InterfaceStats>, AcquisitionError> {
+            Ok(Vec::new())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_skips_malformed_rows() {
+        let garbage = OspfRawRow::new(AREA, R2, R2, vec![0, 1, 2]);
+        let rows = vec![router_lsa_row(), garbage, network_lsa_row()];
+        let mut topology = Topology::new(OspfProtocol::new(), FixedRows(rows));
+
+        let (source_id, nodes, _, warnings) = topology.fetch_snapshot().await.unwrap();
+        assert_eq!(source_id, RouterId::Ipv4(R1));
+        assert!(nodes.iter().any(|node| node.id == RouterId::Ipv4(R1).to_uuidv5()));
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].record, 1);
+
+        // Warnings belong to the last fetch only
+        assert!(topology.take_parse_warnings().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_summary_consolidation_policy() {
+        // Summary for the same prefix as the transit network in network_lsa_row()
+        let summary = raw_row(3, Ipv4Addr::new(172, 21, 14, 0), R1, &[255, 255, 255, 0, 0, 0, 0, 20]);
+        let rows = vec![router_lsa_row(), network_lsa_row(), summary];
+        let prefix: IpNetwork = "172.21.14.0/24".parse().unwrap();
+        let networks_for = |nodes: &[Node]| -> Vec<Node> {
+            nodes
+                .iter()
+                .filter(|n| matches!(&n.info, NodeInfo::Network(net) if net.ip_address == prefix))
+                .cloned()
+                .collect()
+        };
+
+        let fold = OspfProtocol::new().with_consolidation_policy(ConsolidationPolicy::FoldSummaries);
+        let mut topology = Topology::new(fold, FixedRows(rows.clone()));
+        let folded = networks_for(&topology.fetch_nodes().await.unwrap());
+        assert_eq!(folded.len(), 1);
+
+        let keep = |rows| {
+            Topology::new(OspfProtocol::new(), FixedRows(rows))
+                .with_consolidation_policy(ConsolidationPolicy::KeepSummaries)
+        };
+        let kept = networks_for(&keep(rows.clone()).fetch_nodes().await.unwrap());
+        assert_eq!(kept.len(), 2);
+        assert_ne!(kept[0].id, kept[1].id);
+        assert!(kept.iter().any(|n| n.id == summary_network_id(prefix)));
+
+        // A source that only sees the summary gives it the same id
+        let summary_only = vec![rows[0].clone(), rows[2].clone()];
+        let kept = networks_for(&keep(summary_only).fetch_nodes().await.unwrap());
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].id, summary_network_id(prefix));
+    }
 
     #[tokio::test]
     async fn test_parse_lsas_to_lsdb_entries() {
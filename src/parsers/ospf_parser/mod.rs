@@ -26,6 +26,7 @@ User interface for visualizing the graph
 */
 pub mod lsa;
 pub mod snmp;
+pub mod snmp_oids;
 pub mod snmp_source;
 pub mod source;
 
@@ -0,0 +1,286 @@
+/*!
+OIDs walked by the SNMP OSPF source, configurable for agents that deviate from the standard
+OSPF-MIB (RFC 4750) or expose the same tables under an enterprise subtree.
+
+The defaults are the standard OIDs. An OID map file (TOML) only needs the entries that differ;
+everything left out keeps its default:
+
+```toml
+router_id = "1.3.6.1.4.1.9999.14.1.1.0"
+
+[lsdb]
+entry = "1.3.6.1.4.1.9999.14.4.1"
+advertisement = 9
+```
+
+What the LSDB reconstruction needs:
+
+- `router_id` (ospfRouterId): identifies the source, required.
+- `lsdb` (ospfLsdbEntry): the area-scoped LSAs, required. Area id, link state id, advertising
+  router and advertisement columns are read on every full walk. The sequence and checksum
+  columns are optional; without them every poll walks the whole table.
+- `ext_lsdb` (ospfExtLsdbEntry): AS-external LSAs, optional. Polls go on without it.
+- `sys_name` / `sys_descr`: node labels only, optional.
+- The IF-MIB and IP-MIB entries: interface traffic for edge weights, optional.
+*/
+
+use std::{path::Path, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use snmp2::Oid;
+
+/// Columns of a table entry, as sub-identifiers appended to `entry`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LsdbTableOids {
+    entry: String,
+    area_id: u32,
+    lsid: u32,
+    router_id: u32,
+    sequence: u32,
+    checksum: u32,
+    advertisement: u32,
+}
+
+impl Default for LsdbTableOids {
+    fn default() -> Self {
+        Self {
+            entry: "1.3.6.1.2.1.14.4.1".to_string(),
+            area_id: 1,
+            lsid: 3,
+            router_id: 4,
+            sequence: 5,
+            checksum: 7,
+            advertisement: 8,
+        }
+    }
+}
+
+/// Columns of ospfLsdbEntry read by the SNMP source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LsdbColumn {
+    AreaId,
+    Lsid,
+    RouterId,
+    Sequence,
+    Checksum,
+    Advertisement,
+}
+
+impl LsdbTableOids {
+    /// OID of the table entry, the prefix every row of the table shares.
+    pub fn entry(&self) -> Result<Oid<'static>, String> {
+        parse_oid("lsdb.entry", &self.entry)
+    }
+
+    /// OID of `column`, optionally followed by a row index.
+    pub fn column(&self, column: LsdbColumn, index: Option<&str>) -> Result<Oid<'static>, String> {
+        let sub_id = match column {
+            LsdbColumn::AreaId => self.area_id,
+            LsdbColumn::Lsid => self.lsid,
+            LsdbColumn::RouterId => self.router_id,
+            LsdbColumn::Sequence => self.sequence,
+            LsdbColumn::Checksum => self.checksum,
+            LsdbColumn::Advertisement => self.advertisement,
+        };
+        column_oid("lsdb.entry", &self.entry, sub_id, index)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExtLsdbTableOids {
+    entry: String,
+    lsid: u32,
+    router_id: u32,
+    advertisement: u32,
+}
+
+impl Default for ExtLsdbTableOids {
+    fn default() -> Self {
+        Self {
+            entry: "1.3.6.1.2.1.14.12.1".to_string(),
+            lsid: 2,
+            router_id: 3,
+            advertisement: 7,
+        }
+    }
+}
+
+/// Columns of ospfExtLsdbEntry read by the SNMP source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtLsdbColumn {
+    Lsid,
+    RouterId,
+    Advertisement,
+}
+
+impl ExtLsdbTableOids {
+    /// OID of the table entry, the prefix every row of the table shares.
+    pub fn entry(&self) -> Result<Oid<'static>, String> {
+        parse_oid("ext_lsdb.entry", &self.entry)
+    }
+
+    /// OID of `column`, without a row index.
+    pub fn column(&self, column: ExtLsdbColumn) -> Result<Oid<'static>, String> {
+        let sub_id = match column {
+            ExtLsdbColumn::Lsid => self.lsid,
+            ExtLsdbColumn::RouterId => self.router_id,
+            ExtLsdbColumn::Advertisement => self.advertisement,
+        };
+        column_oid("ext_lsdb.entry", &self.entry, sub_id, None)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SnmpOidMap {
+    router_id: String,
+    lsdb: LsdbTableOids,
+    ext_lsdb: ExtLsdbTableOids,
+    sys_name: String,
+    sys_descr: String,
+    if_in_octets: String,
+    if_out_octets: String,
+    if_in_ucast_pkts: String,
+    if_out_ucast_pkts: String,
+    ip_ad_ent_if_index: String,
+}
+
+impl Default for SnmpOidMap {
+    fn default() -> Self {
+        Self {
+            router_id: "1.3.6.1.2.1.14.1.1.0".to_string(),
+            lsdb: LsdbTableOids::default(),
+            ext_lsdb: ExtLsdbTableOids::default(),
+            sys_name: "1.3.6.1.2.1.1.5.0".to_string(),
+            sys_descr: "1.3.6.1.2.1.1.1.0".to_string(),
+            if_in_octets: "1.3.6.1.2.1.2.2.1.10".to_string(),
+            if_out_octets: "1.3.6.1.2.1.2.2.1.16".to_string(),
+            if_in_ucast_pkts: "1.3.6.1.2.1.2.2.1.11".to_string(),
+            if_out_ucast_pkts: "1.3.6.1.2.1.2.2.1.17".to_string(),
+            ip_ad_ent_if_index: "1.3.6.1.2.1.4.20.1.2".to_string(),
+        }
+    }
+}
+
+impl SnmpOidMap {
+    /// Read an OID map file. Every OID is checked here, so a bad entry fails the load instead
+    /// of the first poll that walks it.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("can't read {}: {}", path.display(), e))?;
+        let map: Self = toml::from_str(&contents)
+            .map_err(|e| format!("can't parse {}: {}", path.display(), e))?;
+        map.validate()
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        Ok(map)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        self.router_id()?;
+        self.lsdb.entry()?;
+        self.ext_lsdb.entry()?;
+        self.sys_name()?;
+        self.sys_descr()?;
+        self.if_in_octets()?;
+        self.if_out_octets()?;
+        self.if_in_ucast_pkts()?;
+        self.if_out_ucast_pkts()?;
+        self.ip_ad_ent_if_index()?;
+        Ok(())
+    }
+
+    pub fn lsdb(&self) -> &LsdbTableOids {
+        &self.lsdb
+    }
+
+    pub fn ext_lsdb(&self) -> &ExtLsdbTableOids {
+        &self.ext_lsdb
+    }
+
+    pub fn router_id(&self) -> Result<Oid<'static>, String> {
+        parse_oid("router_id", &self.router_id)
+    }
+
+    pub fn sys_name(&self) -> Result<Oid<'static>, String> {
+        parse_oid("sys_name", &self.sys_name)
+    }
+
+    pub fn sys_descr(&self) -> Result<Oid<'static>, String> {
+        parse_oid("sys_descr", &self.sys_descr)
+    }
+
+    pub fn if_in_octets(&self) -> Result<Oid<'static>, String> {
+        parse_oid("if_in_octets", &self.if_in_octets)
+    }
+
+    pub fn if_out_octets(&self) -> Result<Oid<'static>, String> {
+        parse_oid("if_out_octets", &self.if_out_octets)
+    }
+
+    pub fn if_in_ucast_pkts(&self) -> Result<Oid<'static>, String> {
+        parse_oid("if_in_ucast_pkts", &self.if_in_ucast_pkts)
+    }
+
+    pub fn if_out_ucast_pkts(&self) -> Result<Oid<'static>, String> {
+        parse_oid("if_out_ucast_pkts", &self.if_out_ucast_pkts)
+    }
+
+    pub fn ip_ad_ent_if_index(&self) -> Result<Oid<'static>, String> {
+        parse_oid("ip_ad_ent_if_index", &self.ip_ad_ent_if_index)
+    }
+}
+
+/// Parse the map entry `name`, naming it in the error.
+fn parse_oid(name: &str, value: &str) -> Result<Oid<'static>, String> {
+    Oid::from_str(value).map_err(|_| format!("{} is not a valid OID: '{}'", name, value))
+}
+
+/// OID of column `sub_id` of a table entry, optionally followed by a row index.
+fn column_oid(name: &str, entry: &str, sub_id: u32, index: Option<&str>) -> Result<Oid<'static>, String> {
+    match index {
+        Some(index) => parse_oid(name, &format!("{}.{}.{}", entry, sub_id, index)),
+        None => parse_oid(name, &format!("{}.{}", entry, sub_id)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_oid_map() {
+        let map: SnmpOidMap = toml::from_str(
+            r#"
+            router_id = "1.3.6.1.4.1.9999.14.1.1.0"
+
+            [lsdb]
+            entry = "1.3.6.1.4.1.9999.14.4.1"
+            advertisement = 9
+            "#,
+        )
+        .unwrap();
+        assert!(map.validate().is_ok());
+        assert_eq!(map.lsdb.advertisement, 9);
+        // Left out, so still the standard OSPF-MIB columns
+        assert_eq!(map.lsdb.area_id, 1);
+        assert_eq!(map.ext_lsdb, ExtLsdbTableOids::default());
+        assert_eq!(map.sys_name, SnmpOidMap::default().sys_name);
+        assert_eq!(
+            map.lsdb().column(LsdbColumn::Advertisement, Some("0.0.0.0.1")).unwrap(),
+            Oid::from_str("1.3.6.1.4.1.9999.14.4.1.9.0.0.0.0.1").unwrap()
+        );
+
+        let bad = SnmpOidMap {
+            sys_name: "not an oid".to_string(),
+            ..Default::default()
+        };
+        assert!(bad.validate().is_err());
+        // A map that skipped validation reports the bad entry instead of panicking
+        assert_eq!(
+            bad.sys_name(),
+            Err("sys_name is not a valid OID: 'not an oid'".to_string())
+        );
+    }
+}
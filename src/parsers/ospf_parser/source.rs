@@ -26,6 +26,59 @@ pub struct OspfRawRow {
     pub lsa_bytes: Vec<u8>,
 }
 
+impl OspfRawRow {
+    pub fn new(
+        area_id: Ipv4Addr,
+        link_state_id: Ipv4Addr,
+        router_id: Ipv4Addr,
+        lsa_bytes: Vec<u8>,
+    ) -> Self {
+        Self {
+            area_id,
+            link_state_id,
+            router_id,
+            lsa_bytes,
+        }
+    }
+}
+
+#[cfg(test)]
+impl OspfRawRow {
+    /// Row for a synthetic LSA of `ls_type` with the given body, as an LSDB walk returns it.
+    /// The checksum isn't verified by the parser.
+    pub fn synthetic(
+        area_id: Ipv4Addr,
+        ls_type: u8,
+        link_state_id: Ipv4Addr,
+        router_id: Ipv4Addr,
+        body: &[u8],
+    ) -> Self {
+        // options: E-bit
+        Self::synthetic_with_options(area_id, 0x02, ls_type, link_state_id, router_id, body)
+    }
+
+    pub fn synthetic_with_options(
+        area_id: Ipv4Addr,
+        options: u8,
+        ls_type: u8,
+        link_state_id: Ipv4Addr,
+        router_id: Ipv4Addr,
+        body: &[u8],
+    ) -> Self {
+        let mut bytes = Vec::with_capacity(20 + body.len());
+        bytes.extend_from_slice(&1u16.to_be_bytes()); // LS age
+        bytes.push(options);
+        bytes.push(ls_type);
+        bytes.extend_from_slice(&link_state_id.octets());
+        bytes.extend_from_slice(&router_id.octets());
+        bytes.extend_from_slice(&0x8000_0001u32.to_be_bytes()); // sequence number
+        bytes.extend_from_slice(&0x1234u16.to_be_bytes()); // checksum
+        bytes.extend_from_slice(&((20 + body.len()) as u16).to_be_bytes());
+        bytes.extend_from_slice(body);
+        Self::new(area_id, link_state_id, router_id, bytes)
+    }
+}
+
 /// Errors that can occur when fetching OSPF raw rows from a data source.
 #[derive(Debug, Clone)]
 pub enum OspfSourceError {
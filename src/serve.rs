@@ -0,0 +1,368 @@
+/*!
+Optional headless HTTP control endpoint, built with the `serve` feature and started with
+`--headless` or `--serve`. Sources and a store given on the command line are loaded first.
+
+Endpoints (JSON in, JSON out):
+- `GET /topology`: current merged topology
+- `GET /sources`: known sources and their health
+- `POST /sources`: add a source and take its first snapshot, e.g.
+  `{"kind": "ospf_snmp", "address": "10.0.0.1:161", "community": "public"}` or
+  `{"kind": "isis_ssh", "host": "10.0.0.2", "username": "admin", "password": "secret"}`
+- `POST /poll`: re-poll every source added through the endpoint
+
+OID map files are only taken from the command line (`--snmp-oids`), a request can't name a path
+for the server to read.
+
+This is a minimal HTTP/1.1 implementation: one request per connection, no keep-alive or chunking.
+
+There is no authentication. Anyone who can reach the endpoint can read the topology, hand the
+server SNMP communities or SSH passwords and make it connect to any host it can route to. The
+default address is loopback; only bind something like `--serve 0.0.0.0:9000` on a trusted network.
+*/
+
+use std::{collections::HashMap, net::SocketAddr, sync::Arc, time::SystemTime};
+
+use serde::Deserialize;
+use serde_json::json;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+    sync::Mutex,
+};
+
+use crate::{
+    cli::Startup,
+    data_aquisition::{snmp::DEFAULT_MAX_REPETITIONS, ssh},
+    gui::autopoll::{ProtocolKind, SourceSpec},
+    logging::{debug_log, info_log},
+    topology::store::{MergeConfig, SourceId, TopologyStore},
+};
+
+/// Request bodies larger than this are rejected.
+const MAX_BODY_LEN: usize = 64 * 1024;
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum AddSourceRequest {
+    OspfSnmp {
+        address: SocketAddr,
+        community: String,
+        #[serde(default = "default_max_repetitions")]
+        max_repetitions: u32,
+    },
+    IsisSsh {
+        host: String,
+        #[serde(default = "default_ssh_port")]
+        port: u16,
+        username: String,
+        password: String,
+    },
+}
+
+fn default_max_repetitions() -> u32 {
+    DEFAULT_MAX_REPETITIONS
+}
+
+fn default_ssh_port() -> u16 {
+    22
+}
+
+impl From<AddSourceRequest> for SourceSpec {
+    fn from(request: AddSourceRequest) -> Self {
+        match request {
+            AddSourceRequest::OspfSnmp {
+                address,
+                community,
+                max_repetitions,
+            } => SourceSpec::new_snmp(
+                address,
+                community,
+                snmp2::Version::V2C,
+                None,
+                max_repetitions,
+                ProtocolKind::Ospf,
+            ),
+            AddSourceRequest::IsisSsh {
+                host,
+                port,
+                username,
+                password,
+            } => SourceSpec::new_ssh(
+                host,
+                port,
+                username,
+                password,
+                ssh::DEFAULT_CONNECT_TIMEOUT,
+                ProtocolKind::Isis,
+            ),
+        }
+    }
+}
+
+/// State shared by all connections.
+#[derive(Default)]
+struct ServerState {
+    store: Arc<Mutex<TopologyStore>>,
+    specs: Mutex<HashMap<SourceId, SourceSpec>>,
+}
+
+impl ServerState {
+    /// Take a snapshot from `spec` and store it. The store lock isn't held while the source is polled.
+    async fn poll(&self, spec: &SourceSpec) -> Result<SourceId, String> {
+        let mut source = spec.build_topology().await.map_err(|e| e.to_string())?;
+        let started = std::time::Instant::now();
+        let (src_id, nodes, stats, warnings) = source
+            .fetch_snapshot()
+            .await
+            .map_err(|e| format!("fetch failed: {}", e))?;
+        let elapsed = started.elapsed();
+        let mut store = self.store.lock().await;
+        store.replace_partition_with_warnings(&src_id, nodes, stats, warnings, SystemTime::now());
+        store.record_fetch(&src_id, elapsed);
+        Ok(src_id)
+    }
+
+    /// Take the first snapshot of `spec` and keep it for `/poll`.
+    async fn add_spec(&self, spec: SourceSpec) -> Result<SourceId, String> {
+        let src_id = self.poll(&spec).await?;
+        info_log!("[serve] Added source {}", src_id);
+        self.specs.lock().await.insert(src_id.clone(), spec);
+        Ok(src_id)
+    }
+
+    async fn add_source(&self, body: &[u8]) -> Response {
+        let request: AddSourceRequest = match serde_json::from_slice(body) {
+            Ok(request) => request,
+            Err(e) => return Response::error(400, format!("invalid source: {}", e)),
+        };
+        match self.add_spec(SourceSpec::from(request)).await {
+            Ok(src_id) => {
+                let body = json!({ "source_id": src_id.to_string() });
+                Response::json(201, body.to_string())
+            }
+            Err(e) => Response::error(502, e),
+        }
+    }
+
+    async fn poll_all(&self) -> Response {
+        let specs: Vec<(SourceId, SourceSpec)> = self
+            .specs
+            .lock()
+            .await
+            .iter()
+            .map(|(id, spec)| (id.clone(), spec.clone()))
+            .collect();
+
+        let mut errors = serde_json::Map::new();
+        for (src_id, spec) in &specs {
+            if let Err(e) = self.poll(spec).await {
+                eprintln!("[serve] Poll of {} failed: {}", src_id, e);
+                self.store.lock().await.mark_lost(src_id, SystemTime::now());
+                errors.insert(src_id.to_string(), e.into());
+            }
+        }
+        let body = json!({ "polled": specs.len(), "errors": errors });
+        Response::json(200, body.to_string())
+    }
+
+    async fn sources(&self) -> Response {
+        let store = self.store.lock().await;
+        let sources: Vec<serde_json::Value> = store
+            .sources_iter()
+            .map(|(src_id, state)| {
+                json!({
+                    "source_id": src_id.to_string(),
+                    "health": state.health,
+                    "nodes": state.partition.nodes.len(),
+                    "last_snapshot": humantime::format_rfc3339_seconds(state.last_snapshot).to_string(),
+                })
+            })
+            .collect();
+        Response::json(200, serde_json::Value::from(sources).to_string())
+    }
+
+    async fn topology(&self) -> Response {
+        match self
+            .store
+            .lock()
+            .await
+            .export_topology_json(&MergeConfig::default())
+        {
+            Ok(json) => Response::json(200, json),
+            Err(e) => Response::error(500, e.to_string()),
+        }
+    }
+
+    async fn route(&self, method: &str, path: &str, body: &[u8]) -> Response {
+        match (method, path) {
+            ("GET", "/topology") => self.topology().await,
+            ("GET", "/sources") => self.sources().await,
+            ("POST", "/sources") => self.add_source(body).await,
+            ("POST", "/poll") => self.poll_all().await,
+            (_, "/topology" | "/sources" | "/poll") => Response::error(405, "method not allowed"),
+            _ => Response::error(404, "not found"),
+        }
+    }
+}
+
+struct Response {
+    status: u16,
+    body: String,
+}
+
+impl Response {
+    fn json(status: u16, body: String) -> Self {
+        Self { status, body }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Self::json(status, json!({ "error": message.into() }).to_string())
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            201 => "Created",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            413 => "Payload Too Large",
+            500 => "Internal Server Error",
+            502 => "Bad Gateway",
+            _ => "",
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.status,
+            self.reason(),
+            self.body.len(),
+            self.body
+        )
+        .into_bytes()
+    }
+}
+
+async fn handle_connection(stream: TcpStream, state: Arc<ServerState>) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    // `None` once a Content-Length header fails to parse
+    let mut content_length = Some(0usize);
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).await? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':')
+            && name.trim().eq_ignore_ascii_case("content-length")
+        {
+            content_length = value.trim().parse().ok();
+        }
+    }
+
+    let response = match content_length {
+        None => Response::error(400, "invalid Content-Length"),
+        Some(len) if len > MAX_BODY_LEN => Response::error(413, "request body too large"),
+        Some(len) => {
+            let mut body = vec![0u8; len];
+            reader.read_exact(&mut body).await?;
+            state.route(&method, &path, &body).await
+        }
+    };
+    debug_log!("[serve] {} {} -> {}", method, path, response.status);
+
+    let mut stream = reader.into_inner();
+    stream.write_all(&response.to_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// Serve the control endpoint on `addr` until the process exits, after loading the store and
+/// connecting the sources from `startup`. Sources that fail to connect are logged and skipped.
+pub async fn run(addr: SocketAddr, startup: Startup) -> std::io::Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    let state = Arc::new(ServerState::default());
+    if let Some(store) = startup.store {
+        *state.store.lock().await = store;
+    }
+    for spec in startup.sources {
+        if let Err(e) = state.add_spec(spec).await {
+            eprintln!("[serve] Startup source failed: {}", e);
+        }
+    }
+    info_log!("[serve] Listening on http://{}", listener.local_addr()?);
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let state = state.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream, state).await {
+                eprintln!("[serve] Connection from {} failed: {}", peer, e);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_source_request_parsing() {
+        let request: AddSourceRequest = serde_json::from_str(
+            r#"{"kind": "ospf_snmp", "address": "10.0.0.1:161", "community": "public"}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            request,
+            AddSourceRequest::OspfSnmp { max_repetitions: DEFAULT_MAX_REPETITIONS, .. }
+        ));
+
+        let request: AddSourceRequest = serde_json::from_str(
+            r#"{"kind": "isis_ssh", "host": "r1", "username": "admin", "password": "x"}"#,
+        )
+        .unwrap();
+        assert!(matches!(request, AddSourceRequest::IsisSsh { port: 22, .. }));
+
+        assert!(serde_json::from_str::<AddSourceRequest>(r#"{"kind": "netconf"}"#).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_routes_without_sources() {
+        let state = ServerState::default();
+        let topology = state.route("GET", "/topology", &[]).await;
+        assert_eq!(topology.status, 200);
+        assert_eq!(topology.body, "[]");
+
+        assert_eq!(state.route("POST", "/poll", &[]).await.status, 200);
+        assert_eq!(state.route("DELETE", "/sources", &[]).await.status, 405);
+        assert_eq!(state.route("GET", "/nope", &[]).await.status, 404);
+        assert_eq!(state.route("POST", "/sources", b"{}").await.status, 400);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_content_length() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            handle_connection(stream, Arc::new(ServerState::default())).await
+        });
+
+        let mut client = TcpStream::connect(addr).await.unwrap();
+        client
+            .write_all(b"POST /sources HTTP/1.1\r\nContent-Length: lots\r\n\r\n{}")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).await.unwrap();
+        server.await.unwrap().unwrap();
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request"), "{}", response);
+    }
+}
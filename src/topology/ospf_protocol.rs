@@ -1,30 +1,79 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, net::Ipv4Addr};
 
 use crate::{
     data_aquisition::snmp::SnmpClient,
+    logging::{debug_log, info_log},
     network::{
         node::{
-            Network as NetStruct, Node, NodeInfo, OspfPayload, PerAreaRouterFacet, ProtocolData,
+            Network as NetStruct, Node, NodeInfo, OspfPayload, OspfVirtualLink,
+            PerAreaRouterFacet, ProtocolData,
         },
-        router::{InterfaceStats, RouterId},
+        router::{InterfaceStats, RouterId, SystemInfo},
     },
     parsers::ospf_parser::{
         lsa::{LsaError, OspfLsdbEntry},
+        snmp_oids::SnmpOidMap,
         source::{OspfDataSource, OspfRawRow},
     },
     topology::protocol::{
-        AcquisitionError, FederationError, ProtocolFederator, ProtocolParseError, ProtocolTopologyError
+        AcquisitionError, AcquisitionSource, FederationError, ProtocolFederator, ProtocolParseError,
+        ProtocolTopologyError,
     },
 };
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 
 use egui::ahash::HashSet;
 use ipnetwork::IpNetwork;
 use uuid::Uuid;
 
-/// Stateless OSPF protocol adapter. Parsing & node mapping are record-local;
+/// What consolidation does with a Type-3 summary network whose prefix also has a detailed
+/// (Type-2) network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsolidationPolicy {
+    /// Fold the summary into the detailed network, keeping its ABR metrics in the payload.
+    #[default]
+    FoldSummaries,
+    /// Keep every summary as a separate inter-area node, with an id from `summary_network_id`
+    /// so it never collides with the detailed network, whichever source reported that.
+    KeepSummaries,
+}
+
+/// Node id of a summary network kept as its own node.
+pub fn summary_network_id(prefix: IpNetwork) -> Uuid {
+    Uuid::new_v5(
+        &Uuid::NAMESPACE_OID,
+        format!("ospf-summary:{}", prefix).as_bytes(),
+    )
+}
+
+/// OSPF protocol adapter. Parsing & node mapping are record-local;
 /// consolidation and augmentation (summary folding & stub synthesis) happen in `post_process`.
-pub struct OspfProtocol;
+pub struct OspfProtocol {
+    consolidation: ConsolidationPolicy,
+}
+
+impl OspfProtocol {
+    /// Adapter folding summaries into detailed networks.
+    pub fn new() -> Self {
+        Self {
+            consolidation: ConsolidationPolicy::default(),
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn with_consolidation_policy(mut self, policy: ConsolidationPolicy) -> Self {
+        self.consolidation = policy;
+        self
+    }
+}
+
+impl Default for OspfProtocol {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl super::protocol::RoutingProtocol for OspfProtocol {
     type RawRecord = OspfRawRow;
@@ -54,7 +103,7 @@ impl super::protocol::RoutingProtocol for OspfProtocol {
         &self,
         nodes: &mut Vec<Node>,
     ) -> Result<(), super::protocol::ProtocolTopologyError> {
-        consolidate_networks(nodes)?;
+        consolidate_networks(nodes, self.consolidation)?;
         synthesize_stub_networks(nodes)?;
         Ok(())
     }
@@ -62,7 +111,10 @@ impl super::protocol::RoutingProtocol for OspfProtocol {
 
 /* ---------------------- Consolidation (Summary ↔ Detailed) ---------------------- */
 
-fn consolidate_networks(nodes: &mut Vec<Node>) -> Result<(), ProtocolTopologyError> {
+fn consolidate_networks(
+    nodes: &mut Vec<Node>,
+    policy: ConsolidationPolicy,
+) -> Result<(), ProtocolTopologyError> {
     use std::collections::{HashMap, HashSet};
 
     // Classification of OSPF network nodes based on advertisement variant.
@@ -70,6 +122,7 @@ fn consolidate_networks(nodes: &mut Vec<Node>) -> Result<(), ProtocolTopologyErr
     enum NetKind {
         Detailed, // Type-2 Network-LSA
         Summary,  // Type-3 Summary IP Network LSA
+        External, // Type-5 AS-External / Type-7 NSSA-External LSA
         Other,
     }
 
@@ -81,6 +134,7 @@ fn consolidate_networks(nodes: &mut Vec<Node>) -> Result<(), ProtocolTopologyErr
                     match *data.advertisement {
                         NetworkLinks(_) => NetKind::Detailed,
                         SummaryLinkIpNetwork(_) => NetKind::Summary,
+                        ASExternalLink(_) | NSSAASExternal(_) => NetKind::External,
                         _ => NetKind::Other,
                     }
                 } else {
@@ -91,18 +145,22 @@ fn consolidate_networks(nodes: &mut Vec<Node>) -> Result<(), ProtocolTopologyErr
         }
     }
 
-    // Separate routers; build per-prefix map merging summary & detailed.
+    // Separate routers (and anything else that isn't a network); build per-prefix map merging summary & detailed.
+    // Kept summaries get their own slot (second key field) so they only merge with each other.
     let mut routers: Vec<Node> = Vec::new();
-    let mut by_prefix: HashMap<IpNetwork, Node> = HashMap::new();
+    let mut by_prefix: HashMap<(IpNetwork, bool), Node> = HashMap::new();
 
     // Drain original vec to avoid double borrow issues.
     let original = std::mem::take(nodes);
     for node in original.into_iter() {
         match &node.info {
-            NodeInfo::Router(_) => routers.push(node),
+            NodeInfo::Router(_) | NodeInfo::Area(_) => routers.push(node),
             NodeInfo::Network(net) => {
-                let key = net.ip_address;
                 let kind = classify(&node);
+                let key = (
+                    net.ip_address,
+                    policy == ConsolidationPolicy::KeepSummaries && kind == NetKind::Summary,
+                );
                 match by_prefix.remove(&key) {
                     None => {
                         by_prefix.insert(key, node);
@@ -117,18 +175,24 @@ fn consolidate_networks(nodes: &mut Vec<Node>) -> Result<(), ProtocolTopologyErr
                             (NetKind::Summary, NetKind::Detailed) => {
                                 (node, existing, kind, existing_kind)
                             }
+                            // Internal facets win over external ones for the same prefix
+                            (NetKind::External, NetKind::Detailed | NetKind::Summary) => {
+                                (node, existing, kind, existing_kind)
+                            }
                             // Same kind or other cases - keep first as base
                             _ => (existing, node, existing_kind, kind),
                         };
 
-                        // Merge attached routers if any side is detailed OR both summary.
+                        // Merge attached routers if any side is detailed OR both summary OR both external.
+                        // An external facet never adds its ASBR to an internal network.
                         if let (NodeInfo::Network(base_net), NodeInfo::Network(extra_net)) =
                             (&mut base.info, &extra.info)
                         {
-                            let should_union_attached = matches!(base_kind, NetKind::Detailed)
+                            let should_union_attached = (matches!(base_kind, NetKind::Detailed)
+                                && !matches!(extra_kind, NetKind::External))
                                 || matches!(extra_kind, NetKind::Detailed)
-                                || (matches!(base_kind, NetKind::Summary)
-                                    && matches!(extra_kind, NetKind::Summary));
+                                || (base_kind == extra_kind
+                                    && matches!(base_kind, NetKind::Summary | NetKind::External));
                             if should_union_attached {
                                 let mut seen: HashSet<uuid::Uuid> = base_net
                                     .attached_routers
@@ -179,6 +243,10 @@ fn consolidate_networks(nodes: &mut Vec<Node>) -> Result<(), ProtocolTopologyErr
                                                 }
                                             }
                                         }
+                                        merge_externals(
+                                            &mut base_payload.externals,
+                                            &extra_payload.externals,
+                                        );
                                     }
                                     _ => {
                                         // Non-network OSPF payload patterns ignored.
@@ -194,6 +262,14 @@ fn consolidate_networks(nodes: &mut Vec<Node>) -> Result<(), ProtocolTopologyErr
         }
     }
 
+    // Kept summaries always get their own id, even when this source has no detailed network for
+    // the prefix, so they don't fuse with another source's detailed network in the merged view
+    for ((prefix, kept), summary) in by_prefix.iter_mut() {
+        if *kept {
+            summary.id = summary_network_id(*prefix);
+        }
+    }
+
     // Rebuild consolidated node list
     let mut consolidated: Vec<Node> = routers;
     consolidated.extend(by_prefix.into_values());
@@ -201,6 +277,22 @@ fn consolidate_networks(nodes: &mut Vec<Node>) -> Result<(), ProtocolTopologyErr
     Ok(())
 }
 
+/// Union external route entries, keyed by originating ASBR and metric.
+fn merge_externals(
+    base: &mut Vec<crate::network::node::OspfExternalNetPayload>,
+    extra: &[crate::network::node::OspfExternalNetPayload],
+) {
+    let mut seen: std::collections::HashSet<(u32, uuid::Uuid)> = base
+        .iter()
+        .map(|e| (e.metric, e.origin_asbr.to_uuidv5()))
+        .collect();
+    for e in extra {
+        if seen.insert((e.metric, e.origin_asbr.to_uuidv5())) {
+            base.push(e.clone());
+        }
+    }
+}
+
 /* ---------------------- Stub Network Synthesis ---------------------- */
 
 fn synthesize_stub_networks(nodes: &mut Vec<Node>) -> Result<(), ProtocolTopologyError> {
@@ -270,6 +362,7 @@ fn synthesize_stub_networks(nodes: &mut Vec<Node>) -> Result<(), ProtocolTopolog
             ip_address: stub_prefix,
             protocol_data: None,
             attached_routers: vec![rid.clone()],
+            is_synthetic: true,
         };
         nodes.push(Node::new(NodeInfo::Network(synthetic_net), None));
         existing_prefixes.insert(stub_prefix);
@@ -316,6 +409,11 @@ impl OspfSnmpAcquisition {
             inner: crate::parsers::ospf_parser::snmp_source::OspfSnmpSource::new(client),
         }
     }
+
+    pub fn with_oids(mut self, oids: SnmpOidMap) -> Self {
+        self.inner = self.inner.with_oids(oids);
+        self
+    }
 }
 
 #[async_trait]
@@ -354,14 +452,38 @@ impl super::protocol::AcquisitionSource<OspfProtocol> for OspfSnmpAcquisition {
             }
         })
     }
+
+    async fn fetch_system_info(&mut self) -> Result<Option<SystemInfo>, AcquisitionError> {
+        self.inner.fetch_system_info().await.map(Some).map_err(|e| match e {
+            crate::parsers::ospf_parser::source::OspfSourceError::Acquisition(s) => {
+                super::protocol::AcquisitionError::Transport(s)
+            }
+            crate::parsers::ospf_parser::source::OspfSourceError::Invalid(s) => {
+                super::protocol::AcquisitionError::Invalid(s)
+            }
+        })
+    }
 }
 
 /// Convenience alias matching previous API style.
 pub type OspfSnmpTopology = super::protocol::Topology<OspfProtocol, OspfSnmpAcquisition>;
 
+impl<S: AcquisitionSource<OspfProtocol>> super::protocol::Topology<OspfProtocol, S> {
+    /// Whether summary networks are folded on the next fetch.
+    pub fn with_consolidation_policy(mut self, policy: ConsolidationPolicy) -> Self {
+        self.protocol_mut().consolidation = policy;
+        self
+    }
+}
+
 impl OspfSnmpTopology {
     pub fn from_snmp_client(client: SnmpClient) -> Self {
-        Self::new(OspfProtocol, OspfSnmpAcquisition::new(client))
+        Self::new(OspfProtocol::new(), OspfSnmpAcquisition::new(client))
+    }
+
+    /// Like `from_snmp_client`, walking the tables at `oids` instead of the standard ones.
+    pub fn from_snmp_client_with_oids(client: SnmpClient, oids: SnmpOidMap) -> Self {
+        Self::new(OspfProtocol::new(), OspfSnmpAcquisition::new(client).with_oids(oids))
     }
 }
 
@@ -374,8 +496,7 @@ impl OspfFederator {
     }
 
     fn select_base<'a>(facets: &'a [Node]) -> &'a Node {
-        // Later: precedence by source health/timestamp.
-        // For now: first facet is base (already roughly deterministic).
+        // The store hands facets over in precedence order (health, then snapshot age)
         &facets[0]
     }
 
@@ -462,40 +583,79 @@ impl ProtocolFederator for OspfFederator {
 
         // Aggregate flags & link metrics across facets
         let mut is_asbr = false;
+        // B bit as set in the Router-LSAs, before fusion decides from the visible areas
+        let mut reports_abr = false;
         let mut is_virtual = false;
         let mut is_nssa = false;
-        let mut per_area: HashMap<std::net::Ipv4Addr, (usize, usize, usize)> = HashMap::new();
+        let mut per_area: HashMap<std::net::Ipv4Addr, (usize, usize, usize, bool)> = HashMap::new();
         let mut link_metrics: HashMap<std::net::Ipv4Addr, u16> = HashMap::new();
+        let mut virtual_links: Vec<OspfVirtualLink> = Vec::new();
 
         for facet in facets {
             if let NodeInfo::Router(r) = &facet.info {
                 if let Some(ProtocolData::Ospf(pd)) = &r.protocol_data {
                     if let OspfPayload::Router(rp) = &pd.payload {
                         is_asbr |= rp.is_asbr;
+                        reports_abr |= rp.is_abr;
                         is_virtual |= rp.is_virtual_link_endpoint;
                         is_nssa |= rp.is_nssa_capable;
                         for f in &rp.per_area_facets {
-                            let entry = per_area.entry(f.area_id).or_insert((0, 0, 0));
+                            let entry = per_area.entry(f.area_id).or_insert((0, 0, 0, false));
                             entry.0 += f.p2p_link_count;
                             entry.1 += f.transit_link_count;
                             entry.2 += f.stub_link_count;
+                            entry.3 |= f.is_virtual_link_transit;
                         }
+                        for vl in &rp.virtual_links {
+                            if !virtual_links.iter().any(|v| v.peer_router_id == vl.peer_router_id) {
+                                virtual_links.push(vl.clone());
+                            }
+                        }
+                        // Facets arrive highest precedence first, so the first report of a link wins
                         for (k, v) in &rp.link_metrics {
-                            link_metrics.insert(*k, *v); // last wins; refine if needed
+                            link_metrics.entry(*k).or_insert(*v);
                         }
                     }
                 }
             }
         }
 
+        if let NodeInfo::Router(r) = &base.info {
+            let mut areas: Vec<_> = per_area.keys().collect();
+            areas.sort();
+            if reports_abr && per_area.len() <= 1 {
+                info_log!(
+                    "[federation] Router {} sets the B bit but only area {:?} is polled, so it isn't \
+                     shown as an ABR; add an area override if you know its other areas",
+                    r.id,
+                    areas
+                );
+            } else {
+                debug_log!("[federation] Router {} inferred in areas {:?}", r.id, areas);
+            }
+        }
+
         // Recompute totals
         let (mut total_p2p, mut total_transit, mut total_stub) = (0usize, 0usize, 0usize);
-        for (_, (p2p, transit, stub)) in &per_area {
+        for (_, (p2p, transit, stub, _)) in &per_area {
             total_p2p += *p2p;
             total_transit += *transit;
             total_stub += *stub;
         }
 
+        // The backbone LSA carrying the virtual links doesn't name the transit area; take it from
+        // the non-backbone area whose Router-LSA has the V bit set
+        let transit_area = per_area
+            .iter()
+            .filter(|(area_id, (_, _, _, is_transit))| *is_transit && !area_id.is_unspecified())
+            .map(|(area_id, _)| *area_id)
+            .min();
+        if let Some(transit_area) = transit_area {
+            for vl in virtual_links.iter_mut().filter(|vl| vl.transit_area_id.is_unspecified()) {
+                vl.transit_area_id = transit_area;
+            }
+        }
+
         // Mutate base payload
         if let NodeInfo::Router(r) = &mut base.info {
             if let Some(ProtocolData::Ospf(pd)) = &mut r.protocol_data {
@@ -508,17 +668,28 @@ impl ProtocolFederator for OspfFederator {
                     rp.transit_link_count = total_transit;
                     rp.stub_link_count = total_stub;
                     rp.link_metrics = link_metrics;
+                    rp.virtual_links = virtual_links;
                     rp.per_area_facets = per_area
                         .into_iter()
-                        .map(|(area_id, (p2p, transit, stub))| PerAreaRouterFacet {
+                        .map(|(area_id, (p2p, transit, stub, is_virtual_link_transit))| PerAreaRouterFacet {
                             area_id,
                             p2p_link_count: p2p,
                             transit_link_count: transit,
                             stub_link_count: stub,
+                            is_virtual_link_transit,
                         })
                         .collect();
                 }
             }
+            // Only the source polling this router knows its sysName/sysDescr
+            if r.hostname.is_none() || r.description.is_none() {
+                for facet in facets {
+                    if let NodeInfo::Router(other) = &facet.info {
+                        r.hostname = r.hostname.take().or_else(|| other.hostname.clone());
+                        r.description = r.description.take().or_else(|| other.description.clone());
+                    }
+                }
+            }
         }
         base
     }
@@ -528,6 +699,7 @@ impl ProtocolFederator for OspfFederator {
         // Partition by LSA kind (still needed if some sources only have Summary)
         let mut detailed: Vec<Node> = Vec::new();
         let mut summary: Vec<Node> = Vec::new();
+        let mut external: Vec<Node> = Vec::new();
 
         for n in facets {
             if let NodeInfo::Network(net) = &n.info {
@@ -539,17 +711,26 @@ impl ProtocolFederator for OspfFederator {
                         ospf_parser::OspfLinkStateAdvertisement::SummaryLinkIpNetwork(_) => {
                             summary.push(n.clone())
                         }
+                        ospf_parser::OspfLinkStateAdvertisement::ASExternalLink(_)
+                        | ospf_parser::OspfLinkStateAdvertisement::NSSAASExternal(_) => {
+                            external.push(n.clone())
+                        }
                         _ => {}
                     }
                 }
             }
         }
 
-        // Choose base: prefer any Detailed
+        // Choose base: prefer any Detailed, then Summary, then External
+        let base_is_external = detailed.is_empty() && summary.is_empty();
         let mut base = if !detailed.is_empty() {
             detailed.remove(0)
-        } else {
+        } else if !summary.is_empty() {
             summary.remove(0)
+        } else if !external.is_empty() {
+            external.remove(0)
+        } else {
+            facets[0].clone()
         };
 
         if let NodeInfo::Network(base_net) = &mut base.info {
@@ -559,12 +740,21 @@ impl ProtocolFederator for OspfFederator {
                 .iter()
                 .map(|r| r.to_uuidv5())
                 .collect();
-            for extra in detailed.into_iter().chain(summary.into_iter()) {
+            let externals = external.into_iter().map(|n| (true, n));
+            for (is_external, extra) in detailed
+                .into_iter()
+                .chain(summary)
+                .map(|n| (false, n))
+                .chain(externals)
+            {
                 if let NodeInfo::Network(net) = &extra.info {
-                    for rid in &net.attached_routers {
-                        let id = rid.to_uuidv5();
-                        if seen.insert(id) {
-                            base_net.attached_routers.push(rid.clone());
+                    // ASBRs only attach to purely external prefixes
+                    if !is_external || base_is_external {
+                        for rid in &net.attached_routers {
+                            let id = rid.to_uuidv5();
+                            if seen.insert(id) {
+                                base_net.attached_routers.push(rid.clone());
+                            }
                         }
                     }
                     // Merge summaries
@@ -585,6 +775,7 @@ impl ProtocolFederator for OspfFederator {
                                     base_np.summaries.push(s.clone());
                                 }
                             }
+                            merge_externals(&mut base_np.externals, &extra_np.externals);
                         }
                     }
                 }
@@ -627,3 +818,49 @@ impl ProtocolFederator for OspfFederator {
         Ok(())
     }
 }
+
+/// Replace the areas of a merged OSPF node with the ones the operator says are right, for when
+/// the polled sources don't see every area. A router gets one facet per area, keeping the link
+/// counts of areas it already had, and is an ABR when that's more than one like in
+/// `merge_routers`. A network takes the first area. Returns `false` if the node has no areas
+/// to override (not OSPF, AS-external, or `areas` is empty).
+pub fn override_node_areas(node: &mut Node, areas: &[Ipv4Addr]) -> bool {
+    let Some(&first) = areas.first() else {
+        return false;
+    };
+    match &mut node.info {
+        NodeInfo::Router(router) => {
+            let Some(ProtocolData::Ospf(data)) = &mut router.protocol_data else {
+                return false;
+            };
+            data.area_id = first;
+            if let OspfPayload::Router(rp) = &mut data.payload {
+                rp.per_area_facets.retain(|f| areas.contains(&f.area_id));
+                for area_id in areas {
+                    if !rp.per_area_facets.iter().any(|f| f.area_id == *area_id) {
+                        rp.per_area_facets.push(PerAreaRouterFacet {
+                            area_id: *area_id,
+                            p2p_link_count: 0,
+                            transit_link_count: 0,
+                            stub_link_count: 0,
+                            is_virtual_link_transit: false,
+                        });
+                    }
+                }
+                rp.is_abr = rp.per_area_facets.len() > 1;
+            }
+            true
+        }
+        NodeInfo::Network(network) => {
+            if network.is_external() {
+                return false;
+            }
+            let Some(ProtocolData::Ospf(data)) = &mut network.protocol_data else {
+                return false;
+            };
+            data.area_id = first;
+            true
+        }
+        NodeInfo::Area(_) => false,
+    }
+}
@@ -6,10 +6,14 @@ use async_trait::async_trait;
 use thiserror::Error;
 
 use crate::{
-    network::{node::Node, router::InterfaceStats},
+    logging::{debug_log, info_log},
+    network::{
+        node::Node,
+        router::{InterfaceStats, SystemInfo},
+    },
     topology::{
         TopologySource,
-        source::{SnapshotSource, TopologyError},
+        source::{ParseWarning, SnapshotSource, TopologyError},
         store::SourceId,
     },
 };
@@ -67,6 +71,17 @@ pub trait AcquisitionSource<P: RoutingProtocol>: Send + Sync {
     async fn fetch_raw(&mut self) -> AcquisitionResult<Vec<P::RawRecord>>;
     async fn fetch_source_id(&mut self) -> AcquisitionResult<SourceId>;
     async fn fetch_stats(&mut self) -> AcquisitionResult<Vec<InterfaceStats>>;
+
+    /// Update whatever `protocol` needs to parse the next `fetch_raw`, e.g. a name mapping that
+    /// changes on the device. Called before every fetch, sources without such state do nothing.
+    async fn refresh_protocol(&mut self, _protocol: &mut P) -> AcquisitionResult<()> {
+        Ok(())
+    }
+
+    /// Hostname/description of the polled device. Sources without such data return `None`.
+    async fn fetch_system_info(&mut self) -> AcquisitionResult<Option<SystemInfo>> {
+        Ok(None)
+    }
 }
 
 /// Routing protocol contract.
@@ -87,6 +102,8 @@ where
 {
     protocol: P,
     source: S,
+    // Records skipped by the last fetch_nodes, handed out by take_parse_warnings
+    warnings: Vec<ParseWarning>,
 }
 
 impl<P, S> Topology<P, S>
@@ -95,7 +112,11 @@ where
     S: AcquisitionSource<P>,
 {
     pub fn new(protocol: P, source: S) -> Self {
-        Self { protocol, source }
+        Self {
+            protocol,
+            source,
+            warnings: Vec::new(),
+        }
     }
 
     #[allow(unused)]
@@ -103,6 +124,10 @@ where
         &self.protocol
     }
 
+    pub fn protocol_mut(&mut self) -> &mut P {
+        &mut self.protocol
+    }
+
     #[allow(unused)]
     pub fn source(&self) -> &S {
         &self.source
@@ -150,12 +175,17 @@ where
     S: AcquisitionSource<P>,
 {
     async fn fetch_nodes(&mut self) -> Result<Vec<Node>, TopologyError> {
-        println!("[topology] fetch_nodes: starting");
+        debug_log!("[topology] fetch_nodes: starting");
+
+        // A failed refresh leaves the protocol as it was, the fetch can still succeed with it
+        if let Err(e) = self.source.refresh_protocol(&mut self.protocol).await {
+            eprintln!("[topology] refresh_protocol failed, keeping the previous state: {:?}", e);
+        }
 
         // Fetch raw records from the underlying acquisition source.
         let raw = match self.source.fetch_raw().await {
             Ok(r) => {
-                println!("[topology] fetch_raw: received {} raw record(s)", r.len());
+                debug_log!("[topology] fetch_raw: received {} raw record(s)", r.len());
                 r
             }
             Err(e) => {
@@ -165,13 +195,15 @@ where
         };
 
         let mut nodes: Vec<Node> = Vec::new();
+        self.warnings.clear();
 
         // Parse each raw record via the protocol implementation.
+        // A malformed record is skipped and reported as a warning instead of failing the snapshot.
         for (rec_idx, record) in raw.into_iter().enumerate() {
-            println!("[topology] parsing record #{}", rec_idx);
+            debug_log!("[topology] parsing record #{}", rec_idx);
             let parsed_items = match self.protocol.parse(record) {
                 Ok(items) => {
-                    println!(
+                    debug_log!(
                         "[topology] parsed {} item(s) from record #{}",
                         items.len(),
                         rec_idx
@@ -180,41 +212,48 @@ where
                 }
                 Err(e) => {
                     eprintln!(
-                        "[topology] protocol.parse failed for record #{}: {:?}",
+                        "[topology] protocol.parse failed for record #{}, skipping: {:?}",
                         rec_idx, e
                     );
-                    return Err(TopologyError::from(e));
+                    self.warnings.push(ParseWarning {
+                        record: rec_idx,
+                        message: TopologyError::from(e).to_string(),
+                    });
+                    continue;
                 }
             };
 
             for (item_idx, item) in parsed_items.into_iter().enumerate() {
                 match self.protocol.item_to_node(item) {
                     Ok(Some(node)) => {
-                        println!(
+                        debug_log!(
                             "[topology] item_to_node: record #{}, item #{} -> produced node",
                             rec_idx, item_idx
                         );
                         nodes.push(node);
                     }
                     Ok(None) => {
-                        println!(
+                        debug_log!(
                             "[topology] item_to_node: record #{}, item #{} -> no node produced",
                             rec_idx, item_idx
                         );
                     }
                     Err(e) => {
                         eprintln!(
-                            "[topology] item_to_node error: record #{}, item #{}: {:?}",
+                            "[topology] item_to_node error: record #{}, item #{}, skipping: {:?}",
                             rec_idx, item_idx, e
                         );
-                        return Err(TopologyError::from(e));
+                        self.warnings.push(ParseWarning {
+                            record: rec_idx,
+                            message: TopologyError::from(e).to_string(),
+                        });
                     }
                 }
             }
         }
 
         // Allow the protocol to post-process the collected nodes before returning.
-        println!(
+        debug_log!(
             "[topology] running protocol.post_process on {} node(s)",
             nodes.len()
         );
@@ -222,13 +261,25 @@ where
             eprintln!("[topology] protocol.post_process failed: {:?}", e);
             return Err(TopologyError::from(e));
         }
-        println!(
-            "[topology] post_process complete, returning {} node(s)",
-            nodes.len()
+        info_log!(
+            "[topology] post_process complete, returning {} node(s), skipped {} record(s)",
+            nodes.len(),
+            self.warnings.len()
         );
 
+        // System info only decorates the source's own router, so failures are not fatal.
+        match self.source.fetch_system_info().await {
+            Ok(Some(info)) => info.apply(&mut nodes),
+            Ok(None) => {}
+            Err(e) => eprintln!("[topology] fetch_system_info failed: {:?}", e),
+        }
+
         Ok(nodes)
     }
+
+    fn take_parse_warnings(&mut self) -> Vec<ParseWarning> {
+        std::mem::take(&mut self.warnings)
+    }
 }
 
 #[async_trait]
@@ -4,6 +4,7 @@ GUI-facing topology provider interface.
 This module defines:
 - `TopologyError`: minimal error type for topology retrieval.
 - `TopologySource`: an async trait that returns protocol-agnostic nodes for rendering.
+- `ParseWarning`: a record that was skipped while parsing, kept so a snapshot can still succeed.
 
 Adapters (e.g., OSPF-over-SNMP, OSPF-over-RESTCONF) should implement `TopologySource`
 and encapsulate how they obtain and parse data.
@@ -13,6 +14,7 @@ use std::fmt::Display;
 
 use async_trait::async_trait;
 use egui::epaint::stats;
+use serde::{Deserialize, Serialize};
 
 use crate::network::{node::Node, router::{InterfaceStats, RouterId}};
 
@@ -36,11 +38,30 @@ impl Display for TopologyError {
 
 impl std::error::Error for TopologyError {}
 
+/// A raw record (e.g. one LSDB row) that failed to parse or convert and was left out of the snapshot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParseWarning {
+    /// Index of the raw record in the fetched batch.
+    pub record: usize,
+    pub message: String,
+}
+
+impl Display for ParseWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "record #{}: {}", self.record, self.message)
+    }
+}
+
 /// A small async interface for providing topology data to the GUI.
 /// Implementations hide transport/protocol details and return protocol-agnostic nodes.
 #[async_trait]
 pub trait TopologySource: Send + Sync {
     async fn fetch_nodes(&mut self) -> TopologyResult<Vec<Node>>;
+
+    /// Records skipped by the last `fetch_nodes`. Sources that never skip records return none.
+    fn take_parse_warnings(&mut self) -> Vec<ParseWarning> {
+        Vec::new()
+    }
 }
 
 type SourceId = RouterId;
@@ -54,15 +75,20 @@ pub trait SnapshotSource: TopologySource {
     async fn fetch_source_id(&mut self) -> TopologyResult<SourceId>;
     
     /// Fetches nodes and source id and returns a tuple.
-    async fn fetch_snapshot(&mut self) -> TopologyResult<(SourceId, Vec<Node>, Vec<InterfaceStats>)> {
+    /// Malformed records don't fail the snapshot; they are left out and returned as warnings.
+    async fn fetch_snapshot(&mut self) -> TopologyResult<Snapshot> {
         let source_id = self.fetch_source_id().await?;
         let nodes = self.fetch_nodes().await?;
+        let warnings = self.take_parse_warnings();
         let stats = self.fetch_stats().await?;
-        Ok((source_id, nodes, stats))
+        Ok((source_id, nodes, stats, warnings))
     }
     
     async fn fetch_stats(&mut self) -> TopologyResult<Vec<InterfaceStats>>;
 }
 
+/// One source's nodes and interface stats, plus the records skipped while parsing them.
+pub type Snapshot = (SourceId, Vec<Node>, Vec<InterfaceStats>, Vec<ParseWarning>);
+
 /// Convenience result alias for topology operations.
 pub type TopologyResult<T> = Result<T, TopologyError>;
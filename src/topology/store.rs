@@ -5,22 +5,30 @@ This module defines:
 - `Partition`: Collection of nodes originating from one source (e.g. a router from which the topology data is collected)
 - `SourceHealth`: Represents a source's status
 - `SourceState`: Holds information about a source, as well as the partition it manages.
+- `TopologyStore`: All known sources. Serialized stores carry a schema `version`; load them through
+  `TopologyStore::from_json` so older payloads are migrated first.
 */
 
 use crate::{
+    logging::debug_log,
     network::{
-        node::{Node, NodeInfo},
-        router::{InterfaceStats, RouterId},
+        node::{Node, NodeInfo, OspfPayload, ProtocolData},
+        router::{InterfaceRates, InterfaceStats, RouterId},
+    },
+    parsers::isis_parser::protocol::{
+        UnresolvedPrefixPolicy, collapse_unresolved_networks, placeholder_network_prefix,
     },
     topology::{
-        ospf_protocol::OspfFederator,
+        ospf_protocol::{OspfFederator, override_node_areas},
         protocol::{FederationError, ProtocolFederator},
+        source::ParseWarning,
     },
 };
 use ipnetwork::IpNetwork;
+use ospf_parser::OspfLinkStateAdvertisement;
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::{HashMap, HashSet}, net::IpAddr, time::SystemTime
+    collections::{HashMap, HashSet, VecDeque}, net::{IpAddr, Ipv4Addr}, time::{Duration, SystemTime}
 };
 use thiserror::Error;
 use uuid::Uuid;
@@ -79,7 +87,18 @@ pub struct SourceState {
     pub last_snapshot: SystemTime, // when we last replaced the snapshot successfully
     pub last_connected: SystemTime, // when acquisition last succeeded
     pub last_status_change: SystemTime, // when health last changed
+    #[serde(default)]
     pub interface_stats: Vec<InterfaceStats>,
+    /// Interface stats from the snapshot before `last_snapshot`, used to derive rates.
+    #[serde(default)]
+    pub previous_interface_stats: Vec<InterfaceStats>,
+    #[serde(default)]
+    pub previous_snapshot: Option<SystemTime>,
+    /// Records skipped while parsing the last snapshot.
+    #[serde(default)]
+    pub parse_warnings: Vec<ParseWarning>,
+    #[serde(default)]
+    pub acquisition: AcquisitionMetrics,
 }
 impl SourceState {
     /// Creates a new `SourceState` from a `Partition` and the `Instant` of the last data update.
@@ -91,8 +110,27 @@ impl SourceState {
             last_connected: ts,
             last_status_change: ts,
             interface_stats,
+            previous_interface_stats: Vec::new(),
+            previous_snapshot: None,
+            parse_warnings: Vec::new(),
+            acquisition: AcquisitionMetrics::default(),
         }
     }
+
+    /// Throughput of the interface with the given IP address between the last two snapshots.
+    /// Returns `None` while only one sample exists.
+    pub fn interface_rates(&self, ip_address: IpAddr) -> Option<InterfaceRates> {
+        let elapsed = self
+            .last_snapshot
+            .duration_since(self.previous_snapshot?)
+            .ok()?;
+        let current = self.interface_stats.iter().find(|s| s.ip_address == ip_address)?;
+        let previous = self
+            .previous_interface_stats
+            .iter()
+            .find(|s| s.ip_address == ip_address)?;
+        Some(current.rates_since(previous, elapsed))
+    }
     
     /// Returns the relative weight of the interface with the given IP address as compared to all other interfaces. Returns a float between 0 and 1.
     pub fn get_interface_weight(&self, ip_address: IpAddr) -> Option<f32> {
@@ -102,12 +140,69 @@ impl SourceState {
     }
 }
 
+/// Number of recent fetches kept in `AcquisitionMetrics`.
+pub const ACQUISITION_HISTORY_LEN: usize = 10;
+
+/// How long a source's recent fetches took and how many nodes they returned, oldest first.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AcquisitionMetrics {
+    pub fetch_durations: VecDeque<Duration>,
+    pub node_counts: VecDeque<usize>,
+}
+
+impl AcquisitionMetrics {
+    pub fn record(&mut self, duration: Duration, node_count: usize) {
+        if self.fetch_durations.len() == ACQUISITION_HISTORY_LEN {
+            self.fetch_durations.pop_front();
+        }
+        if self.node_counts.len() == ACQUISITION_HISTORY_LEN {
+            self.node_counts.pop_front();
+        }
+        self.fetch_durations.push_back(duration);
+        self.node_counts.push_back(node_count);
+    }
+
+    pub fn last_fetch(&self) -> Option<Duration> {
+        self.fetch_durations.back().copied()
+    }
+
+    /// Mean duration of the recorded fetches, `None` before the first one.
+    pub fn average_fetch(&self) -> Option<Duration> {
+        let count = self.fetch_durations.len() as u32;
+        (count > 0).then(|| self.fetch_durations.iter().sum::<Duration>() / count)
+    }
+
+    /// Change in node count between the oldest and the newest recorded fetch.
+    pub fn node_count_trend(&self) -> i64 {
+        match (self.node_counts.front(), self.node_counts.back()) {
+            (Some(&first), Some(&last)) => last as i64 - first as i64,
+            _ => 0,
+        }
+    }
+}
+
+/// Current serialized `TopologyStore` schema. Bump this and add a step to `TopologyStore::migrate`
+/// whenever `TopologyStore`, `SourceState` or `Partition` change shape.
+pub const STORE_SCHEMA_VERSION: u32 = 3;
+
 /// Storage for all known sources. Manages merging topologies from sources.
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct TopologyStore {
+    /// Schema version of the serialized form. Payloads written before versioning existed read as 0.
+    #[serde(default)]
+    version: u32,
     sources: HashMap<SourceId, SourceState>,
 }
 
+impl Default for TopologyStore {
+    fn default() -> Self {
+        Self {
+            version: STORE_SCHEMA_VERSION,
+            sources: HashMap::new(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Error)]
 pub enum StoreError {
     #[error("Source not found: {0}")]
@@ -116,10 +211,35 @@ pub enum StoreError {
     SourceAlreadyInDesiredState(SourceId, bool),
 }
 
+#[derive(Debug, Error)]
+pub enum StoreLoadError {
+    #[error("Invalid store JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Store schema version {0} is newer than supported version {STORE_SCHEMA_VERSION}")]
+    UnsupportedVersion(u32),
+}
+
+#[derive(Debug, Error)]
+pub enum ExportError {
+    #[error("Failed to merge topology: {0}")]
+    Federation(#[from] FederationError),
+    #[error("Failed to serialize topology: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
 pub struct MergeConfig {
     federator: Option<Box<dyn ProtocolFederator>>,
     disabled_sources: HashSet<SourceId>,
+    /// Leave out the partitions of sources that aren't `Connected`; they stay in the store
     connected_only: bool,
+    /// Drop networks with at most one attached router (leaf/stub networks) from the merged view
+    hide_leaf_networks: bool,
+    /// Skip fusion and show every source's nodes separately, for debugging the merge
+    unfused: bool,
+    /// Operator-assigned OSPF areas per merged node, applied after fusion
+    area_overrides: HashMap<Uuid, Vec<Ipv4Addr>>,
+    /// IS-IS pseudonode networks whose prefix couldn't be resolved are kept or collapsed
+    unresolved_prefix_policy: UnresolvedPrefixPolicy,
 }
 
 impl Default for MergeConfig {
@@ -128,9 +248,61 @@ impl Default for MergeConfig {
             federator: Some(Box::new(OspfFederator::new())),
             disabled_sources: Default::default(),
             connected_only: false,
+            hide_leaf_networks: false,
+            unfused: false,
+            area_overrides: HashMap::new(),
+            unresolved_prefix_policy: UnresolvedPrefixPolicy::default(),
         }
     }
 }
+
+/// Side information about a merged view, for display next to the graph.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MergeStats {
+    pub hidden_leaf_networks: usize,
+    /// Unresolved IS-IS pseudonode networks replaced by direct adjacencies
+    pub collapsed_unresolved_networks: usize,
+    /// Sources left out because `connected_only` is set and they aren't `Connected`
+    pub hidden_lost_sources: usize,
+}
+
+/// How each node of the merged view came about, see `TopologyStore::build_merged_view_traced`.
+/// Serialized as the audit trail behind the "Export merge trace" button.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MergeTrace {
+    /// One entry per merged node, sorted by key
+    pub nodes: Vec<MergeTraceEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MergeTraceEntry {
+    pub node: Uuid,
+    /// Router id or prefix the facets were grouped by
+    pub key: String,
+    /// Sources that reported the node, in precedence order. The first one wins where they disagree
+    pub sources: Vec<SourceId>,
+    /// Facets of several sources were merged by the federator
+    pub fused: bool,
+    /// Why the federator refused to merge, the first source's facet was kept as is
+    pub rejected: Option<String>,
+}
+
+impl MergeTrace {
+    fn record(&mut self, node: &Node, key: String, facets: &[Node], fused: bool, rejected: Option<String>) {
+        self.nodes.push(MergeTraceEntry {
+            node: node.id,
+            key,
+            sources: facets.iter().filter_map(|facet| facet.source_id.clone()).collect(),
+            fused: fused && facets.len() > 1,
+            rejected,
+        });
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
 pub enum AvailableFederators {
     Ospf(OspfFederator)
 }
@@ -164,6 +336,10 @@ impl MergeConfig {
             federator,
             disabled_sources: enabled_sources,
             connected_only,
+            hide_leaf_networks: false,
+            unfused: false,
+            area_overrides: HashMap::new(),
+            unresolved_prefix_policy: UnresolvedPrefixPolicy::default(),
         }
     }
     pub fn get_federator(&self) -> Option<&dyn ProtocolFederator> {
@@ -211,9 +387,93 @@ impl MergeConfig {
     pub fn is_source_enabled(&self, source: &SourceId) -> bool {
         !self.disabled_sources.contains(source)
     }
+    pub fn hide_leaf_networks(&self) -> bool {
+        self.hide_leaf_networks
+    }
+    pub fn connected_only(&self) -> bool {
+        self.connected_only
+    }
+    pub fn set_connected_only(&mut self, connected_only: bool) {
+        self.connected_only = connected_only;
+    }
+    pub fn set_hide_leaf_networks(&mut self, hide: bool) {
+        self.hide_leaf_networks = hide;
+    }
+    pub fn unfused(&self) -> bool {
+        self.unfused
+    }
+    pub fn set_unfused(&mut self, unfused: bool) {
+        self.unfused = unfused;
+    }
+    pub fn unresolved_prefix_policy(&self) -> UnresolvedPrefixPolicy {
+        self.unresolved_prefix_policy
+    }
+    pub fn set_unresolved_prefix_policy(&mut self, policy: UnresolvedPrefixPolicy) {
+        self.unresolved_prefix_policy = policy;
+    }
+    pub fn area_override(&self, node: &Uuid) -> Option<&[Ipv4Addr]> {
+        self.area_overrides.get(node).map(Vec::as_slice)
+    }
+    pub fn area_overrides(&self) -> &HashMap<Uuid, Vec<Ipv4Addr>> {
+        &self.area_overrides
+    }
+    /// An empty `areas` removes the override.
+    pub fn set_area_override(&mut self, node: Uuid, areas: Vec<Ipv4Addr>) {
+        if areas.is_empty() {
+            self.area_overrides.remove(&node);
+        } else {
+            self.area_overrides.insert(node, areas);
+        }
+    }
 }
 
 impl TopologyStore {
+    /// Deserialize a store, upgrading older schema versions to `STORE_SCHEMA_VERSION` first.
+    pub fn from_json(json: &str) -> Result<Self, StoreLoadError> {
+        let value: serde_json::Value = serde_json::from_str(json)?;
+        let value = Self::migrate(value)?;
+        Ok(serde_json::from_value(value)?)
+    }
+
+    #[allow(dead_code)]
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    #[allow(dead_code)]
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Upgrade a serialized store one version at a time until it matches `STORE_SCHEMA_VERSION`.
+    fn migrate(mut value: serde_json::Value) -> Result<serde_json::Value, StoreLoadError> {
+        let mut version = value
+            .get("version")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as u32;
+        if version > STORE_SCHEMA_VERSION {
+            return Err(StoreLoadError::UnsupportedVersion(version));
+        }
+
+        while version < STORE_SCHEMA_VERSION {
+            match version {
+                // 0 -> 1: unversioned payloads already have the version 1 shape
+                0 => {}
+                // 1 -> 2: `SourceState` gained previous interface stats, which default to empty
+                1 => {}
+                // 2 -> 3: `SourceState` gained acquisition metrics, which default to empty
+                2 => {}
+                _ => unreachable!("missing store migration from version {}", version),
+            }
+            version += 1;
+        }
+
+        if let Some(obj) = value.as_object_mut() {
+            obj.insert("version".to_string(), serde_json::Value::from(version));
+        }
+        Ok(value)
+    }
+
     pub fn sources_iter(&self) -> impl Iterator<Item = (&SourceId, &SourceState)> {
         self.sources.iter()
     }
@@ -248,6 +508,10 @@ impl TopologyStore {
             Some(state) => {
                 state.partition = part;
                 state.health = SourceHealth::Connected;
+                state.previous_interface_stats =
+                    std::mem::replace(&mut state.interface_stats, source_if_stats);
+                state.previous_snapshot = Some(state.last_snapshot);
+                state.parse_warnings.clear();
                 state.last_snapshot = timestamp;
                 state.last_connected = timestamp;
                 state.last_status_change = timestamp; // optional: only if you want “Connected” flips to count
@@ -259,6 +523,36 @@ impl TopologyStore {
         }
     }
 
+    /// Replace the partition like `replace_partition`, keeping the records skipped while parsing it.
+    pub fn replace_partition_with_warnings(
+        &mut self,
+        src_id: &SourceId,
+        nodes: Vec<Node>,
+        source_if_stats: Vec<InterfaceStats>,
+        warnings: Vec<ParseWarning>,
+        timestamp: SystemTime,
+    ) {
+        if !warnings.is_empty() {
+            eprintln!(
+                "[store] {} record(s) from {} were skipped as malformed",
+                warnings.len(),
+                src_id
+            );
+        }
+        self.replace_partition(src_id, nodes, source_if_stats, timestamp);
+        if let Some(state) = self.sources.get_mut(src_id) {
+            state.parse_warnings = warnings;
+        }
+    }
+
+    /// Record how long the last fetch of a source took, alongside its current node count.
+    pub fn record_fetch(&mut self, src_id: &SourceId, duration: Duration) {
+        if let Some(state) = self.sources.get_mut(src_id) {
+            let node_count = state.partition.nodes.len();
+            state.acquisition.record(duration, node_count);
+        }
+    }
+
     /// Mark a source as lost.
     #[allow(unused)]
     pub fn mark_lost(&mut self, src_id: &SourceId, timestamp: SystemTime) {
@@ -276,23 +570,295 @@ impl TopologyStore {
                     last_snapshot: timestamp,
                     last_connected: timestamp,
                     last_status_change: timestamp,
-                    interface_stats: Vec::new()
+                    interface_stats: Vec::new(),
+                    previous_interface_stats: Vec::new(),
+                    previous_snapshot: None,
+                    parse_warnings: Vec::new(),
+                    acquisition: AcquisitionMetrics::default(),
                 },
             );
         }
     }
 
+    /// Prefixes advertised as networks by both OSPF and IS-IS sources.
+    ///
+    /// Federation is protocol-specific, so such prefixes show up as two separate network nodes.
+    /// This only reports the overlap, it doesn't merge anything.
+    pub fn cross_protocol_prefix_conflicts(&self) -> Vec<IpNetwork> {
+        let unresolved = placeholder_network_prefix();
+        let mut seen_by: HashMap<IpNetwork, (bool, bool)> = HashMap::new();
+
+        for state in self.sources.values() {
+            for node in state.partition.nodes.values() {
+                let NodeInfo::Network(net) = &node.info else {
+                    continue;
+                };
+                if net.ip_address == unresolved {
+                    continue;
+                }
+                let entry = seen_by.entry(net.ip_address).or_default();
+                match &net.protocol_data {
+                    Some(ProtocolData::Ospf(_)) => entry.0 = true,
+                    Some(ProtocolData::IsIs(_)) => entry.1 = true,
+                    // Only OSPF synthesizes stub networks without protocol data
+                    None if net.is_synthetic => entry.0 = true,
+                    _ => {}
+                }
+            }
+        }
+
+        let mut conflicts: Vec<IpNetwork> = seen_by
+            .into_iter()
+            .filter(|(_, (ospf, isis))| *ospf && *isis)
+            .map(|(prefix, _)| prefix)
+            .collect();
+        conflicts.sort();
+        conflicts
+    }
+
+    /// Pairs of distinct network prefixes from different sources where the first contains the
+    /// second (e.g. `10.0.0.0/24` and `10.0.0.0/25`), sorted.
+    ///
+    /// Fusion keys on exact prefix equality, so these stay separate nodes. They usually point at
+    /// a mask mismatch or an aggregation boundary. IPv4 and IPv6 prefixes never overlap.
+    pub fn overlapping_prefixes(&self) -> Vec<(IpNetwork, IpNetwork)> {
+        let unresolved = placeholder_network_prefix();
+        let mut seen_by: HashMap<IpNetwork, HashSet<&SourceId>> = HashMap::new();
+        for (src_id, state) in &self.sources {
+            for node in state.partition.nodes.values() {
+                let NodeInfo::Network(net) = &node.info else {
+                    continue;
+                };
+                if net.ip_address != unresolved {
+                    seen_by.entry(net.ip_address).or_default().insert(src_id);
+                }
+            }
+        }
+
+        let mut overlaps = Vec::new();
+        for (outer, outer_sources) in &seen_by {
+            for (inner, inner_sources) in &seen_by {
+                let same_family = outer.is_ipv4() == inner.is_ipv4();
+                if !same_family
+                    || outer.prefix() >= inner.prefix()
+                    || !outer.contains(inner.network())
+                {
+                    continue;
+                }
+                // Overlaps only ever seen by one and the same source are its own business
+                let across_sources = outer_sources
+                    .iter()
+                    .any(|a| inner_sources.iter().any(|b| a != b));
+                if across_sources {
+                    overlaps.push((*outer, *inner));
+                }
+            }
+        }
+        overlaps.sort();
+        overlaps
+    }
+
+    /// Links of fused OSPF routers whose metric differs between sources, sorted by router and link.
+    ///
+    /// `merge_routers` keeps the metric of the facet with the highest precedence, so each conflict
+    /// pairs that winning report with one it overrode.
+    pub fn router_metric_conflicts(&self) -> Vec<MetricConflict> {
+        let mut routers_by_rid: HashMap<&RouterId, Vec<Node>> = HashMap::new();
+        for state in self.sources.values() {
+            for node in state.partition.nodes.values() {
+                if let NodeInfo::Router(r) = &node.info {
+                    routers_by_rid.entry(&r.id).or_default().push(node.clone());
+                }
+            }
+        }
+
+        let link_metrics = |node: &Node| match &node.info {
+            NodeInfo::Router(r) => match &r.protocol_data {
+                Some(ProtocolData::Ospf(data)) => match &data.payload {
+                    OspfPayload::Router(rp) => Some(rp.link_metrics.clone()),
+                    _ => None,
+                },
+                _ => None,
+            },
+            NodeInfo::Network(_) | NodeInfo::Area(_) => None,
+        };
+
+        let mut conflicts = Vec::new();
+        for (router, mut facets) in routers_by_rid {
+            if facets.len() < 2 {
+                continue;
+            }
+            self.sort_facets_by_precedence(&mut facets);
+            // First report of each link, i.e. the one fusion keeps
+            let mut kept: HashMap<Ipv4Addr, (Option<SourceId>, u16)> = HashMap::new();
+            for facet in &facets {
+                let Some(metrics) = link_metrics(facet) else {
+                    continue;
+                };
+                for (link, metric) in metrics {
+                    let (kept_source, kept_metric) = kept
+                        .entry(link)
+                        .or_insert_with(|| (facet.source_id.clone(), metric))
+                        .clone();
+                    if kept_metric != metric {
+                        conflicts.push(MetricConflict {
+                            router: router.clone(),
+                            link,
+                            kept: (kept_source, kept_metric),
+                            overridden: (facet.source_id.clone(), metric),
+                        });
+                    }
+                }
+            }
+        }
+        conflicts.sort_by_key(|c| (c.router.to_string(), c.link, c.overridden.0.as_ref().map(|s| s.to_string())));
+        conflicts
+    }
+
+    /// Best guess at the type of every OSPF area the sources reported, from the LSAs seen in it:
+    /// - the backbone is always `Normal`
+    /// - otherwise a Router-LSA with the E bit set in its options makes it `Normal`, since
+    ///   routers in stub areas and NSSAs clear it
+    /// - any Type-7 LSA makes the area an `Nssa`
+    /// - with the E bit clear on every Router-LSA, Type-3 summaries other than the default route
+    ///   make it a `Stub`, the default route alone `TotallyStubby`. No summaries at all is `Stub`.
+    ///
+    /// An NSSA without any Type-7 LSA looks like a stub area. Areas without a Router-LSA are left
+    /// out. Fused routers keep one area's LSA, so an ABR only counts in that area.
+    pub fn classify_areas(&self) -> HashMap<Ipv4Addr, AreaType> {
+        #[derive(Default)]
+        struct Evidence {
+            routers: usize,
+            external_capable: bool,
+            type_7: bool,
+            default_summary: bool,
+            other_summaries: bool,
+        }
+
+        const E_BIT: u8 = 0x02;
+        let mut areas: HashMap<Ipv4Addr, Evidence> = HashMap::new();
+        for state in self.sources.values() {
+            for node in state.partition.nodes.values() {
+                let protocol_data = match &node.info {
+                    NodeInfo::Router(router) => router.protocol_data.as_ref(),
+                    NodeInfo::Network(network) => network.protocol_data.as_ref(),
+                    NodeInfo::Area(_) => None,
+                };
+                let Some(ProtocolData::Ospf(data)) = protocol_data else {
+                    continue;
+                };
+                let evidence = areas.entry(data.area_id).or_default();
+                match &*data.advertisement {
+                    OspfLinkStateAdvertisement::RouterLinks(_) => {
+                        evidence.routers += 1;
+                        // Options is the third byte of the LSA header
+                        evidence.external_capable |=
+                            data.raw_lsa_bytes.get(2).is_some_and(|options| options & E_BIT != 0);
+                    }
+                    OspfLinkStateAdvertisement::NSSAASExternal(_) => evidence.type_7 = true,
+                    OspfLinkStateAdvertisement::SummaryLinkIpNetwork(_) => {
+                        if data.link_state_id.is_unspecified() {
+                            evidence.default_summary = true;
+                        } else {
+                            evidence.other_summaries = true;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        areas
+            .into_iter()
+            .filter(|(_, evidence)| evidence.routers > 0)
+            .map(|(area_id, evidence)| {
+                let area_type = if area_id.is_unspecified() || evidence.external_capable {
+                    AreaType::Normal
+                } else if evidence.type_7 {
+                    AreaType::Nssa
+                } else if evidence.default_summary && !evidence.other_summaries {
+                    AreaType::TotallyStubby
+                } else {
+                    AreaType::Stub
+                };
+                (area_id, area_type)
+            })
+            .collect()
+    }
+
+    /// Merged view serialized as JSON, for consumers outside the GUI.
+    #[allow(unused)]
+    pub fn export_topology_json(&self, config: &MergeConfig) -> Result<String, ExportError> {
+        let nodes = self.build_merged_view_with(config)?;
+        Ok(serde_json::to_string_pretty(&nodes)?)
+    }
+
     pub fn build_merged_view_with(
         &self,
         config: &MergeConfig,
     ) -> Result<Vec<Node>, FederationError> {
+        self.build_merged_view_with_stats(config)
+            .map(|(nodes, _)| nodes)
+    }
+
+    /// Like `build_merged_view_with`, also reporting what the config filtered out.
+    ///
+    /// With `unfused` set, the view is the plain union of the partitions instead: no router
+    /// fusion and no network merging, each node scoped to and labeled with its source.
+    pub fn build_merged_view_with_stats(
+        &self,
+        config: &MergeConfig,
+    ) -> Result<(Vec<Node>, MergeStats), FederationError> {
+        self.merge_sources(config, None)
+    }
+
+    /// Like `build_merged_view_with`, also recording which sources every node came from and
+    /// whether their facets were fused.
+    pub fn build_merged_view_traced(
+        &self,
+        config: &MergeConfig,
+    ) -> Result<(Vec<Node>, MergeTrace), FederationError> {
+        let mut trace = MergeTrace::default();
+        let (nodes, _) = self.merge_sources(config, Some(&mut trace))?;
+        trace.nodes.sort_by(|a, b| a.key.cmp(&b.key).then(a.node.cmp(&b.node)));
+        Ok((nodes, trace))
+    }
+
+    fn merge_sources(
+        &self,
+        config: &MergeConfig,
+        mut trace: Option<&mut MergeTrace>,
+    ) -> Result<(Vec<Node>, MergeStats), FederationError> {
         let mut routers_by_rid: HashMap<RouterId, Vec<Node>> = HashMap::new();
-        let mut networks_by_prefix: HashMap<IpNetwork, Vec<Node>> = HashMap::new();
+        // Keyed by node id rather than prefix: ids follow the prefix, except for networks that
+        // share one but must stay apart (kept OSPF summaries, unresolved IS-IS pseudonodes)
+        let mut networks_by_id: HashMap<Uuid, Vec<Node>> = HashMap::new();
+        let mut stats = MergeStats::default();
+        let mut unfused = Vec::new();
 
         for (src_id, state) in &self.sources {
-            if (config.connected_only && state.health != SourceHealth::Connected)
-                || !config.is_source_enabled(src_id)
-            {
+            if !config.is_source_enabled(src_id) {
+                continue;
+            }
+            if config.connected_only && state.health != SourceHealth::Connected {
+                stats.hidden_lost_sources += 1;
+                continue;
+            }
+
+            if config.unfused {
+                for node in state.partition.nodes.values() {
+                    let key = match &node.info {
+                        NodeInfo::Router(router) => router.id.to_string(),
+                        NodeInfo::Network(network) => network.ip_address.to_string(),
+                        NodeInfo::Area(area) => area.name.clone(),
+                    };
+                    let name = node.label.clone().unwrap_or_else(|| key.clone());
+                    let scoped = node.clone().into_source_scoped(format!("{} @ {}", name, src_id));
+                    if let Some(trace) = trace.as_deref_mut() {
+                        trace.record(&scoped, key, std::slice::from_ref(node), false, None);
+                    }
+                    unfused.push(scoped);
+                }
                 continue;
             }
 
@@ -304,61 +870,123 @@ impl TopologyStore {
                             .or_default()
                             .push(node.clone());
                     }
-                    NodeInfo::Network(net) => {
-                        networks_by_prefix
-                            .entry(net.ip_address)
-                            .or_default()
-                            .push(node.clone());
+                    NodeInfo::Network(_) => {
+                        networks_by_id.entry(node.id).or_default().push(node.clone());
                     }
+                    // Only built by the graph's collapsed view, sources never report one
+                    NodeInfo::Area(_) => {}
                 }
             }
         }
 
+        if config.unfused {
+            return Ok((unfused, stats));
+        }
+
+        for facets in routers_by_rid.values_mut() {
+            self.sort_facets_by_precedence(facets);
+        }
+        for facets in networks_by_id.values_mut() {
+            self.sort_facets_by_precedence(facets);
+        }
+
         let mut out = Vec::new();
 
         let federator = config.get_federator();
 
         // Routers
-        for (_rid, facets) in routers_by_rid {
-            if let Some(f) = federator {
-                match f.can_merge_router_facets(&facets) {
-                    Ok(()) => {
-                        out.push(f.merge_routers(&facets));
-                        continue;
-                    }
-                    Err(_e) => {
-                        // Fallback: select a representative facet
-                        out.push(Self::select_best_router(&facets));
-                        continue;
-                    }
-                }
+        for (rid, facets) in routers_by_rid {
+            let (node, fused, rejected) = match federator {
+                Some(f) => match f.can_merge_router_facets(&facets) {
+                    Ok(()) => (f.merge_routers(&facets), true, None),
+                    // Fallback: select a representative facet
+                    Err(e) => (Self::select_best_router(&facets), false, Some(e.to_string())),
+                },
+                None => (Self::select_best_router(&facets), false, None),
+            };
+            if let Some(trace) = trace.as_deref_mut() {
+                trace.record(&node, rid.to_string(), &facets, fused, rejected);
             }
-            out.push(Self::select_best_router(&facets));
+            out.push(node);
         }
 
         // Networks
-        for (_prefix, facets) in networks_by_prefix {
-            if let Some(f) = federator {
-                match f.can_merge_network_facets(&facets) {
-                    Ok(()) => {
-                        out.push(f.merge_networks(&facets));
-                        continue; // IMPORTANT: prevent double insert
-                    }
-                    Err(_e) => {
-                        // Fallback if federation not applicable (stub synthetic, mixed protocol, etc.)
-                        out.push(Self::select_best_network(&facets));
-                        continue;
-                    }
-                }
+        for facets in networks_by_id.into_values() {
+            let (node, fused, rejected) = match federator {
+                Some(f) => match f.can_merge_network_facets(&facets) {
+                    Ok(()) => (f.merge_networks(&facets), true, None),
+                    // Fallback if federation not applicable (stub synthetic, mixed protocol, etc.)
+                    Err(e) => (Self::select_best_network(&facets), false, Some(e.to_string())),
+                },
+                None => (Self::select_best_network(&facets), false, None),
+            };
+            if let Some(trace) = trace.as_deref_mut() {
+                let prefix = match &node.info {
+                    NodeInfo::Network(net) => net.ip_address.to_string(),
+                    NodeInfo::Router(router) => router.id.to_string(),
+                    NodeInfo::Area(area) => area.name.clone(),
+                };
+                trace.record(&node, prefix, &facets, fused, rejected);
             }
-            out.push(Self::select_best_network(&facets));
+            out.push(node);
         }
 
-        Ok(out)
+        // Overrides key merged nodes, so they survive re-polls and reconcile
+        for node in &mut out {
+            if let Some(areas) = config.area_overrides.get(&node.id)
+                && !override_node_areas(node, areas)
+            {
+                debug_log!("[store] Area override for {} ignored, not an OSPF area member", node.id);
+            }
+        }
+
+        // Collapse after federation, so the routers' adjacencies from every source are rewritten
+        if config.unresolved_prefix_policy == UnresolvedPrefixPolicy::Collapse {
+            stats.collapsed_unresolved_networks = collapse_unresolved_networks(&mut out);
+        }
+
+        if config.hide_leaf_networks {
+            // Filter after federation, since attachments are unioned across facets
+            let before = out.len();
+            out.retain(|node| {
+                !matches!(&node.info, NodeInfo::Network(net) if net.attached_routers.len() <= 1)
+            });
+            stats.hidden_leaf_networks = before - out.len();
+        }
+
+        if stats.collapsed_unresolved_networks + stats.hidden_leaf_networks > 0
+            && let Some(trace) = trace
+        {
+            let kept: HashSet<Uuid> = out.iter().map(|node| node.id).collect();
+            trace.nodes.retain(|entry| kept.contains(&entry.node));
+        }
+
+        Ok((out, stats))
+    }
+
+    /// Order facets of one router/network so that the most trustworthy comes first.
+    /// Federators and the `select_best_*` fallbacks rely on this when facets disagree.
+    ///
+    /// Precedence, highest first:
+    /// 1. facets from `Connected` sources before `Lost` ones,
+    /// 2. newer `last_snapshot` before older,
+    /// 3. source id, so the result never depends on `HashMap` iteration order.
+    ///
+    /// Facets without a known source sort last.
+    fn sort_facets_by_precedence(&self, facets: &mut [Node]) {
+        facets.sort_by_cached_key(|node| {
+            let state = node.source_id.as_ref().and_then(|id| self.sources.get(id));
+            (
+                state.is_none(),
+                state.is_none_or(|s| s.health != SourceHealth::Connected),
+                std::cmp::Reverse(state.map(|s| s.last_snapshot)),
+                node.source_id.as_ref().map(|id| id.to_string()),
+            )
+        });
     }
 
     fn select_best_router(facets: &[Node]) -> Node {
-        // For now return the first router
+        // Facets are already in precedence order
         facets[0].clone()
     }
 
@@ -367,6 +995,56 @@ impl TopologyStore {
     }
 }
 
+/// Two sources reporting different metrics for the same link of a router, see
+/// `TopologyStore::router_metric_conflicts`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetricConflict {
+    pub router: RouterId,
+    /// Link data of the Router-LSA entry (local interface address, or mask for stubs)
+    pub link: Ipv4Addr,
+    /// Source and metric fusion kept
+    pub kept: (Option<SourceId>, u16),
+    /// Source and metric it dropped
+    pub overridden: (Option<SourceId>, u16),
+}
+
+impl std::fmt::Display for MetricConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let source = |s: &Option<SourceId>| s.as_ref().map_or("unknown source".to_string(), |s| s.to_string());
+        write!(
+            f,
+            "{} link {}: {} from {}, {} from {}",
+            self.router,
+            self.link,
+            self.kept.1,
+            source(&self.kept.0),
+            self.overridden.1,
+            source(&self.overridden.0)
+        )
+    }
+}
+
+/// OSPF area type as inferred by `TopologyStore::classify_areas`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AreaType {
+    Normal,
+    Stub,
+    TotallyStubby,
+    Nssa,
+}
+
+impl AreaType {
+    pub fn name(self) -> &'static str {
+        match self {
+            AreaType::Normal => "normal",
+            AreaType::Stub => "stub",
+            AreaType::TotallyStubby => "totally stubby",
+            AreaType::Nssa => "NSSA",
+        }
+    }
+}
+
+#[cfg(test)]
 mod tests {
     #[allow(unused_imports)]
     use std::net::Ipv4Addr;
@@ -432,6 +1110,291 @@ mod tests {
         // Nodes after merging
     }
 
+    #[test]
+    fn test_hide_leaf_networks() {
+        let json = include_str!("../../test_data/test_store.json");
+        let store = TopologyStore::from_json(json).unwrap();
+        let mut config = MergeConfig::default();
+        let (full, stats) = store.build_merged_view_with_stats(&config).unwrap();
+        assert_eq!(stats.hidden_leaf_networks, 0);
+
+        let leaves = full
+            .iter()
+            .filter(|n| matches!(&n.info, NodeInfo::Network(net) if net.attached_routers.len() <= 1))
+            .count();
+        config.set_hide_leaf_networks(true);
+        let (filtered, stats) = store.build_merged_view_with_stats(&config).unwrap();
+        assert_eq!(stats.hidden_leaf_networks, leaves);
+        assert_eq!(filtered.len(), full.len() - leaves);
+    }
+
+    #[test]
+    fn test_connected_only_hides_lost_sources() {
+        let json = include_str!("../../test_data/test_store.json");
+        let mut store = TopologyStore::from_json(json).unwrap();
+        let lost = SourceId::Ipv4(Ipv4Addr::new(10, 0, 56, 6));
+        let lost_at = store.sources[&lost].last_snapshot;
+        store.mark_lost(&lost, lost_at);
+
+        let mut config = MergeConfig::default();
+        let (full, stats) = store.build_merged_view_with_stats(&config).unwrap();
+        assert_eq!(stats.hidden_lost_sources, 0);
+
+        config.set_connected_only(true);
+        let (current, stats) = store.build_merged_view_with_stats(&config).unwrap();
+        assert_eq!(stats.hidden_lost_sources, 1);
+        assert!(current.len() < full.len());
+        assert!(current.iter().all(|n| n.source_id.as_ref() != Some(&lost)));
+        // The partition is only hidden, not dropped
+        assert!(!store.sources[&lost].partition.nodes.is_empty());
+    }
+
+    #[test]
+    fn test_store_schema_migration() {
+        // The fixture predates schema versioning
+        let json = include_str!("../../test_data/test_store.json");
+        let store = TopologyStore::from_json(json).unwrap();
+        assert_eq!(store.version(), STORE_SCHEMA_VERSION);
+        assert_eq!(store.sources.len(), 2);
+
+        let round_trip = TopologyStore::from_json(&store.to_json().unwrap()).unwrap();
+        assert_eq!(round_trip.version(), STORE_SCHEMA_VERSION);
+        assert_eq!(round_trip.sources.len(), store.sources.len());
+
+        let future = format!(r#"{{"version": {}, "sources": {{}}}}"#, STORE_SCHEMA_VERSION + 1);
+        assert!(matches!(
+            TopologyStore::from_json(&future),
+            Err(StoreLoadError::UnsupportedVersion(_))
+        ));
+    }
+
+    #[test]
+    fn test_acquisition_metrics() {
+        let mut metrics = AcquisitionMetrics::default();
+        assert_eq!(metrics.average_fetch(), None);
+        assert_eq!(metrics.node_count_trend(), 0);
+
+        for i in 0..ACQUISITION_HISTORY_LEN + 2 {
+            metrics.record(Duration::from_millis(100 * (i as u64 + 1)), 20 - i);
+        }
+        assert_eq!(metrics.fetch_durations.len(), ACQUISITION_HISTORY_LEN);
+        assert_eq!(metrics.last_fetch(), Some(Duration::from_millis(1200)));
+        // Fetches 3..=12 are kept, 300ms to 1200ms
+        assert_eq!(metrics.average_fetch(), Some(Duration::from_millis(750)));
+        assert_eq!(metrics.node_count_trend(), -9);
+    }
+
+    #[test]
+    fn test_unfused_view_keeps_every_facet() {
+        let json = include_str!("../../test_data/test_store.json");
+        let store = TopologyStore::from_json(json).unwrap();
+        let mut config = MergeConfig::default();
+        let fused = store.build_merged_view_with(&config).unwrap();
+
+        config.set_unfused(true);
+        let unfused = store.build_merged_view_with(&config).unwrap();
+        let partition_total: usize = store.sources.values().map(|s| s.partition.nodes.len()).sum();
+        assert_eq!(unfused.len(), partition_total);
+        assert!(unfused.len() > fused.len());
+
+        let ids: HashSet<Uuid> = unfused.iter().map(|n| n.id).collect();
+        assert_eq!(ids.len(), unfused.len());
+        for node in &unfused {
+            let source = node.source_id.as_ref().unwrap();
+            assert!(node.source_scoped);
+            assert!(node.label.as_ref().unwrap().ends_with(&format!("@ {}", source)));
+        }
+    }
+
+    #[test]
+    fn test_merge_trace() {
+        let json = include_str!("../../test_data/test_store.json");
+        let store = TopologyStore::from_json(json).unwrap();
+        let (nodes, trace) = store.build_merged_view_traced(&MergeConfig::default()).unwrap();
+        let untraced = store.build_merged_view_with(&MergeConfig::default()).unwrap();
+        assert_eq!(nodes.len(), untraced.len());
+        assert_eq!(trace.nodes.len(), nodes.len());
+
+        let traced: HashSet<Uuid> = trace.nodes.iter().map(|entry| entry.node).collect();
+        assert!(nodes.iter().all(|node| traced.contains(&node.id)));
+        for entry in &trace.nodes {
+            assert!(!entry.sources.is_empty());
+            assert!(!entry.fused || entry.sources.len() > 1);
+            assert!(!(entry.fused && entry.rejected.is_some()));
+            // With the OSPF federator, facets of several sources are either fused or refused
+            if entry.sources.len() > 1 {
+                assert!(entry.fused || entry.rejected.is_some(), "{}", entry.key);
+            }
+        }
+        // Both sources report some of the same nodes
+        assert!(trace.nodes.iter().any(|entry| entry.sources.len() > 1));
+        assert!(trace.to_json().unwrap().contains("\"sources\""));
+    }
+
+    #[test]
+    fn test_router_facet_precedence() {
+        use crate::network::node::OspfPayload;
+
+        /// Overwrite one link metric of an OSPF router node, returning the link and its old metric.
+        fn set_first_link_metric(node: &mut Node, metric: u16) -> (Ipv4Addr, u16) {
+            let NodeInfo::Router(router) = &mut node.info else {
+                panic!("expected router");
+            };
+            let Some(ProtocolData::Ospf(data)) = &mut router.protocol_data else {
+                panic!("expected OSPF data");
+            };
+            let OspfPayload::Router(payload) = &mut data.payload else {
+                panic!("expected router payload");
+            };
+            let (link, value) = payload.link_metrics.iter_mut().next().unwrap();
+            (*link, std::mem::replace(value, metric))
+        }
+
+        let json = include_str!("../../test_data/test_store.json");
+        let mut store = TopologyStore::from_json(json).unwrap();
+        let older = SourceId::Ipv4(Ipv4Addr::new(172, 21, 0, 1));
+        let newer = SourceId::Ipv4(Ipv4Addr::new(10, 0, 56, 6));
+        assert!(store.sources[&newer].last_snapshot > store.sources[&older].last_snapshot);
+
+        // Let the newer source also report a router of the older one, with a conflicting metric
+        let (router_uuid, mut facet) = store.sources[&older]
+            .partition
+            .nodes
+            .iter()
+            .find(|(_, n)| matches!(n.info, NodeInfo::Router(_)))
+            .map(|(id, n)| (*id, n.clone()))
+            .unwrap();
+        let (link, original) = set_first_link_metric(&mut facet, 99);
+        facet.source_id = Some(newer.clone());
+        store
+            .sources
+            .get_mut(&newer)
+            .unwrap()
+            .partition
+            .nodes
+            .insert(router_uuid, facet);
+
+        let metric_in_view = |store: &TopologyStore| {
+            let view = store.build_merged_view_with(&MergeConfig::default()).unwrap();
+            let node = view.iter().find(|n| n.id == router_uuid).unwrap();
+            match &node.info {
+                NodeInfo::Router(r) => match &r.protocol_data {
+                    Some(ProtocolData::Ospf(data)) => match &data.payload {
+                        OspfPayload::Router(payload) => payload.link_metrics[&link],
+                        _ => panic!("expected router payload"),
+                    },
+                    _ => panic!("expected OSPF data"),
+                },
+                _ => panic!("expected router"),
+            }
+        };
+        assert_eq!(metric_in_view(&store), 99);
+        let NodeInfo::Router(router) = &store.sources[&newer].partition.nodes[&router_uuid].info else {
+            panic!("expected router");
+        };
+        assert!(store.router_metric_conflicts().contains(&MetricConflict {
+            router: router.id.clone(),
+            link,
+            kept: (Some(newer.clone()), 99),
+            overridden: (Some(older.clone()), original),
+        }));
+
+        // A Connected source outranks a newer Lost one
+        let lost_at = store.sources[&newer].last_snapshot;
+        store.mark_lost(&newer, lost_at);
+        assert_eq!(metric_in_view(&store), original);
+    }
+
+    #[test]
+    fn test_cross_protocol_prefix_conflicts() {
+        use crate::parsers::isis_parser::{
+            frr_json_lsp::JsonLsp, hostname::HostnameMap, protocol::JsonIsisProtocol,
+        };
+        use crate::topology::protocol::RoutingProtocol;
+
+        let json = include_str!("../../test_data/test_store.json");
+        let mut store = TopologyStore::from_json(json).unwrap();
+        assert!(store.cross_protocol_prefix_conflicts().is_empty());
+
+        let json_lsp: JsonLsp = serde_json::from_value(serde_json::json!({
+            "lsp": { "id": "r1.5a-00", "own": "*", "ownLSP": true },
+            "pduLen": 51,
+            "seqNumber": "0x00000001",
+            "chksum": "0x462b",
+            "holdtime": 1058,
+            "attPOl": "0/0/0",
+            "extReach": [{ "mtId": "Extended", "id": "0000.0000.0001.00", "metric": 0 }]
+        }))
+        .unwrap();
+        let hostname_map = HostnameMap::build_map_from_lines(
+            include_str!("../../test_data/isis_hostname_map_input.txt").lines(),
+        );
+        let lsp = json_lsp.try_into_lsp(1, &hostname_map).unwrap();
+        let proto = JsonIsisProtocol::new(hostname_map);
+        let mut isis_net = proto.item_to_node(lsp).unwrap().unwrap();
+        let prefix: IpNetwork = "172.21.14.0/24".parse().unwrap();
+        if let NodeInfo::Network(net) = &mut isis_net.info {
+            net.ip_address = prefix;
+        }
+
+        let isis_source = SourceId::Ipv4(Ipv4Addr::new(192, 0, 2, 1));
+        store.replace_partition(&isis_source, vec![isis_net], Vec::new(), SystemTime::now());
+
+        assert_eq!(store.cross_protocol_prefix_conflicts(), vec![prefix]);
+    }
+
+    #[test]
+    fn test_overlapping_prefixes() {
+        let json = include_str!("../../test_data/test_store.json");
+        let mut store = TopologyStore::from_json(json).unwrap();
+        let baseline = store.overlapping_prefixes();
+
+        let network = |prefix: &str| {
+            Node::new(
+                NodeInfo::Network(crate::network::node::Network {
+                    ip_address: prefix.parse().unwrap(),
+                    protocol_data: None,
+                    attached_routers: vec![],
+                    is_synthetic: true,
+                }),
+                None,
+            )
+        };
+        let now = SystemTime::now();
+        let a = SourceId::Ipv4(Ipv4Addr::new(192, 0, 2, 1));
+        let b = SourceId::Ipv4(Ipv4Addr::new(192, 0, 2, 2));
+        store.replace_partition(
+            &a,
+            vec![
+                network("198.51.100.0/24"),
+                // Overlaps only within this source are not reported
+                network("198.51.100.128/25"),
+                network("2001:db8::/32"),
+                network("203.0.113.0/24"),
+            ],
+            Vec::new(),
+            now,
+        );
+        store.replace_partition(&b, vec![network("198.51.100.0/25")], Vec::new(), now);
+        store.replace_partition(
+            &SourceId::Ipv4(Ipv4Addr::new(192, 0, 2, 3)),
+            vec![network("203.0.113.0/24"), network("::/0")],
+            Vec::new(),
+            now,
+        );
+
+        let overlaps: Vec<_> = store
+            .overlapping_prefixes()
+            .into_iter()
+            .filter(|pair| !baseline.contains(pair))
+            .collect();
+        let pair = |outer: &str, inner: &str| (outer.parse().unwrap(), inner.parse().unwrap());
+        assert_eq!(
+            overlaps,
+            vec![pair("198.51.100.0/24", "198.51.100.0/25"), pair("::/0", "2001:db8::/32")]
+        );
+    }
+
     #[test]
     fn test_store_merging_logic() {
         let json = include_str!("../../test_data/test_store.json");
@@ -465,4 +1428,311 @@ mod tests {
             assert!(merged_uuids.contains(&expected.to_string()))
         }
     }
+
+    /// Node for a synthetic LSA of `ls_type`, built through the same parsing path as polled rows.
+    fn ospf_node(
+        area: Ipv4Addr,
+        ls_type: u8,
+        link_state_id: Ipv4Addr,
+        router_id: Ipv4Addr,
+        body: &[u8],
+    ) -> Node {
+        use crate::parsers::ospf_parser::{lsa::OspfLsdbEntry, source::OspfRawRow};
+
+        let row = OspfRawRow::synthetic(area, ls_type, link_state_id, router_id, body);
+        OspfLsdbEntry::try_from(row).unwrap().try_into().unwrap()
+    }
+
+    fn ospf_node_with_options(
+        area: Ipv4Addr,
+        options: u8,
+        ls_type: u8,
+        link_state_id: Ipv4Addr,
+        router_id: Ipv4Addr,
+        body: &[u8],
+    ) -> Node {
+        use crate::parsers::ospf_parser::{lsa::OspfLsdbEntry, source::OspfRawRow};
+
+        let row =
+            OspfRawRow::synthetic_with_options(area, options, ls_type, link_state_id, router_id, body);
+        OspfLsdbEntry::try_from(row).unwrap().try_into().unwrap()
+    }
+
+    /// Router-LSA with `stub_links` stub links and no flags set.
+    fn ospf_router(area: Ipv4Addr, router_id: Ipv4Addr, stub_links: u8) -> Node {
+        let mut body = Vec::new();
+        body.extend_from_slice(&0u16.to_be_bytes()); // flags
+        body.extend_from_slice(&(stub_links as u16).to_be_bytes());
+        for i in 0..stub_links {
+            // Stub: link id = network, link data = mask
+            body.extend_from_slice(&[192, 168, i, 0, 255, 255, 255, 0, 3, 0]);
+            body.extend_from_slice(&1u16.to_be_bytes());
+        }
+        ospf_node(area, 1, router_id, router_id, &body)
+    }
+
+    /// Network-LSA for `dr_interface`/24 originated by `attached[0]`.
+    fn ospf_network(area: Ipv4Addr, dr_interface: Ipv4Addr, attached: &[Ipv4Addr]) -> Node {
+        let mut body = vec![255, 255, 255, 0];
+        for router in attached {
+            body.extend_from_slice(&router.octets());
+        }
+        ospf_node(area, 2, dr_interface, attached[0], &body)
+    }
+
+    /// Summary-LSA (Type-3) for `network`/24 originated by `abr`.
+    fn ospf_summary(area: Ipv4Addr, network: Ipv4Addr, abr: Ipv4Addr, metric: u8) -> Node {
+        ospf_node(area, 3, network, abr, &[255, 255, 255, 0, 0, 0, 0, metric])
+    }
+
+    fn router_payload(node: &Node) -> &crate::network::node::OspfRouterPayload {
+        use crate::network::node::OspfPayload;
+
+        match &node.info {
+            NodeInfo::Router(r) => match &r.protocol_data {
+                Some(ProtocolData::Ospf(data)) => match &data.payload {
+                    OspfPayload::Router(payload) => payload,
+                    _ => panic!("expected router payload"),
+                },
+                _ => panic!("expected OSPF data"),
+            },
+            _ => panic!("expected router"),
+        }
+    }
+
+    #[test]
+    fn test_merge_fuses_router_across_areas() {
+        let backbone = Ipv4Addr::new(0, 0, 0, 0);
+        let area_1 = Ipv4Addr::new(0, 0, 0, 1);
+        let abr = Ipv4Addr::new(10, 0, 0, 1);
+        let now = SystemTime::now();
+
+        let mut store = TopologyStore::default();
+        store.replace_partition(
+            &SourceId::Ipv4(Ipv4Addr::new(10, 0, 0, 1)),
+            vec![ospf_router(backbone, abr, 1)],
+            Vec::new(),
+            now,
+        );
+        store.replace_partition(
+            &SourceId::Ipv4(Ipv4Addr::new(10, 0, 0, 2)),
+            vec![ospf_router(area_1, abr, 2)],
+            Vec::new(),
+            now,
+        );
+
+        let view = store.build_merged_view_with(&MergeConfig::default()).unwrap();
+        assert_eq!(view.len(), 1);
+        let payload = router_payload(&view[0]);
+        assert!(payload.is_abr);
+        assert_eq!(payload.stub_link_count, 3);
+
+        let mut per_area: Vec<_> = payload
+            .per_area_facets
+            .iter()
+            .map(|f| (f.area_id, f.stub_link_count))
+            .collect();
+        per_area.sort();
+        assert_eq!(per_area, vec![(backbone, 1), (area_1, 2)]);
+    }
+
+    #[test]
+    fn test_area_override_survives_repoll() {
+        let backbone = Ipv4Addr::new(0, 0, 0, 0);
+        let area_1 = Ipv4Addr::new(0, 0, 0, 1);
+        let abr = Ipv4Addr::new(10, 0, 0, 1);
+        let src = SourceId::Ipv4(abr);
+
+        let mut store = TopologyStore::default();
+        store.replace_partition(&src, vec![ospf_router(backbone, abr, 1)], Vec::new(), SystemTime::now());
+        let mut config = MergeConfig::default();
+        let view = store.build_merged_view_with(&config).unwrap();
+        assert!(!router_payload(&view[0]).is_abr);
+
+        config.set_area_override(view[0].id, vec![backbone, area_1]);
+        store.replace_partition(&src, vec![ospf_router(backbone, abr, 1)], Vec::new(), SystemTime::now());
+        let view = store.build_merged_view_with(&config).unwrap();
+        let payload = router_payload(&view[0]);
+        assert!(payload.is_abr);
+        let mut per_area: Vec<_> = payload
+            .per_area_facets
+            .iter()
+            .map(|f| (f.area_id, f.stub_link_count))
+            .collect();
+        per_area.sort();
+        assert_eq!(per_area, vec![(backbone, 1), (area_1, 0)]);
+
+        config.set_area_override(view[0].id, Vec::new());
+        let view = store.build_merged_view_with(&config).unwrap();
+        assert!(!router_payload(&view[0]).is_abr);
+    }
+
+    #[test]
+    fn test_merge_fuses_summary_and_detailed_network() {
+        use crate::network::node::OspfPayload;
+
+        let backbone = Ipv4Addr::new(0, 0, 0, 0);
+        let area_1 = Ipv4Addr::new(0, 0, 0, 1);
+        let (r1, r2) = (Ipv4Addr::new(10, 0, 0, 1), Ipv4Addr::new(10, 0, 0, 2));
+        let (abr_a, abr_b) = (Ipv4Addr::new(10, 0, 0, 3), Ipv4Addr::new(10, 0, 0, 4));
+        let network = Ipv4Addr::new(172, 21, 14, 0);
+        let now = SystemTime::now();
+
+        let mut store = TopologyStore::default();
+        store.replace_partition(
+            &SourceId::Ipv4(r1),
+            vec![ospf_network(area_1, Ipv4Addr::new(172, 21, 14, 1), &[r1, r2])],
+            Vec::new(),
+            now,
+        );
+        store.replace_partition(
+            &SourceId::Ipv4(abr_a),
+            vec![ospf_summary(backbone, network, abr_a, 20)],
+            Vec::new(),
+            now,
+        );
+        store.replace_partition(
+            &SourceId::Ipv4(abr_b),
+            vec![ospf_summary(backbone, network, abr_b, 30)],
+            Vec::new(),
+            now,
+        );
+
+        let view = store.build_merged_view_with(&MergeConfig::default()).unwrap();
+        assert_eq!(view.len(), 1);
+        let NodeInfo::Network(net) = &view[0].info else {
+            panic!("expected network");
+        };
+        assert_eq!(net.ip_address, "172.21.14.0/24".parse::<IpNetwork>().unwrap());
+
+        let mut attached: Vec<_> = net.attached_routers.iter().map(|r| r.to_string()).collect();
+        attached.sort();
+        assert_eq!(attached, vec![r1.to_string(), r2.to_string()]);
+
+        let Some(ProtocolData::Ospf(data)) = &net.protocol_data else {
+            panic!("expected OSPF data");
+        };
+        let OspfPayload::Network(payload) = &data.payload else {
+            panic!("expected network payload");
+        };
+        let mut summaries: Vec<_> = payload
+            .summaries
+            .iter()
+            .map(|s| (s.origin_abr.to_string(), s.metric))
+            .collect();
+        summaries.sort();
+        assert_eq!(summaries, vec![(abr_a.to_string(), 20), (abr_b.to_string(), 30)]);
+    }
+
+    #[test]
+    fn test_merge_keeps_kept_summaries_apart() {
+        use crate::topology::ospf_protocol::summary_network_id;
+
+        let backbone = Ipv4Addr::new(0, 0, 0, 0);
+        let area_1 = Ipv4Addr::new(0, 0, 0, 1);
+        let (r1, r2) = (Ipv4Addr::new(10, 0, 0, 1), Ipv4Addr::new(10, 0, 0, 2));
+        let (abr_a, abr_b) = (Ipv4Addr::new(10, 0, 0, 3), Ipv4Addr::new(10, 0, 0, 4));
+        let prefix: IpNetwork = "172.21.14.0/24".parse().unwrap();
+        let now = SystemTime::now();
+        // As a source with KeepSummaries reports them
+        let kept_summary = |abr, metric| {
+            let mut node = ospf_summary(backbone, Ipv4Addr::new(172, 21, 14, 0), abr, metric);
+            node.id = summary_network_id(prefix);
+            node
+        };
+
+        let mut store = TopologyStore::default();
+        store.replace_partition(
+            &SourceId::Ipv4(r1),
+            vec![ospf_network(area_1, Ipv4Addr::new(172, 21, 14, 1), &[r1, r2])],
+            Vec::new(),
+            now,
+        );
+        store.replace_partition(&SourceId::Ipv4(abr_a), vec![kept_summary(abr_a, 20)], Vec::new(), now);
+        store.replace_partition(&SourceId::Ipv4(abr_b), vec![kept_summary(abr_b, 30)], Vec::new(), now);
+
+        // The detailed network and one summary node fused from both ABRs
+        let view = store.build_merged_view_with(&MergeConfig::default()).unwrap();
+        assert_eq!(view.len(), 2);
+        let summary = view.iter().find(|n| n.id == summary_network_id(prefix)).unwrap();
+        let NodeInfo::Network(net) = &summary.info else {
+            panic!("expected network");
+        };
+        assert_eq!(net.ip_address, prefix);
+        assert!(view.iter().any(|n| n.id != summary.id));
+    }
+
+    #[test]
+    fn test_merge_passes_through_unique_nodes() {
+        let area = Ipv4Addr::new(0, 0, 0, 0);
+        let (r1, r2) = (Ipv4Addr::new(10, 0, 0, 1), Ipv4Addr::new(10, 0, 0, 2));
+        let now = SystemTime::now();
+
+        let mut store = TopologyStore::default();
+        let nodes = vec![
+            ospf_router(area, r1, 1),
+            ospf_router(area, r2, 2),
+            ospf_network(area, Ipv4Addr::new(172, 21, 14, 1), &[r1, r2]),
+        ];
+        store.replace_partition(&SourceId::Ipv4(r1), nodes.clone(), Vec::new(), now);
+
+        let view = store.build_merged_view_with(&MergeConfig::default()).unwrap();
+        assert_eq!(view.len(), nodes.len());
+        for node in &nodes {
+            let merged = view.iter().find(|n| n.id == node.id).unwrap();
+            assert_eq!(merged.source_id, Some(SourceId::Ipv4(r1)));
+            if let NodeInfo::Router(_) = &node.info {
+                let (before, after) = (router_payload(node), router_payload(merged));
+                assert!(!after.is_abr);
+                assert_eq!(after.stub_link_count, before.stub_link_count);
+                assert_eq!(after.per_area_facets.len(), 1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_classify_areas() {
+        let now = SystemTime::now();
+        let area = |n| Ipv4Addr::new(0, 0, 0, n);
+        let router = |n| Ipv4Addr::new(10, 0, 0, n);
+        // Router-LSA without links and with the E bit clear
+        let stub_router = |area_id, router_id| {
+            ospf_node_with_options(area_id, 0x00, 1, router_id, router_id, &[0, 0, 0, 0])
+        };
+        let default_summary = |area_id, abr| {
+            ospf_node(area_id, 3, Ipv4Addr::UNSPECIFIED, abr, &[0, 0, 0, 0, 0, 0, 0, 1])
+        };
+        // Type-7 for 172.16.0.0/16 with metric 20
+        let type_7 = |area_id, asbr| {
+            let mut body = vec![255, 255, 0, 0, 0, 0, 0, 20];
+            body.extend_from_slice(&[0; 8]); // forwarding address, route tag
+            ospf_node(area_id, 7, Ipv4Addr::new(172, 16, 0, 0), asbr, &body)
+        };
+
+        let sources = [
+            vec![ospf_router(area(0), router(1), 0)],
+            vec![ospf_router(area(1), router(2), 1)],
+            vec![
+                stub_router(area(2), router(3)),
+                default_summary(area(2), router(1)),
+                ospf_summary(area(2), Ipv4Addr::new(10, 9, 0, 0), router(1), 5),
+            ],
+            vec![stub_router(area(3), router(4)), default_summary(area(3), router(1))],
+            vec![stub_router(area(4), router(5)), type_7(area(4), router(5))],
+            // No Router-LSA, nothing to go on
+            vec![ospf_summary(area(5), Ipv4Addr::new(10, 8, 0, 0), router(1), 5)],
+        ];
+        let mut store = TopologyStore::default();
+        for (i, nodes) in sources.into_iter().enumerate() {
+            store.replace_partition(&SourceId::Ipv4(router(100 + i as u8)), nodes, Vec::new(), now);
+        }
+
+        let types = store.classify_areas();
+        assert_eq!(types.get(&area(0)), Some(&AreaType::Normal));
+        assert_eq!(types.get(&area(1)), Some(&AreaType::Normal));
+        assert_eq!(types.get(&area(2)), Some(&AreaType::Stub));
+        assert_eq!(types.get(&area(3)), Some(&AreaType::TotallyStubby));
+        assert_eq!(types.get(&area(4)), Some(&AreaType::Nssa));
+        assert_eq!(types.get(&area(5)), None);
+    }
 }